@@ -29,6 +29,7 @@ final class CanvasView: NSView, NSTextViewDelegate {
     private let document = CoreDocument()
     private var cachedItems: [FfiItem] = []
     private var cachedArrowRendersById: [UInt64: FfiArrowRender] = [:]
+    private var cachedLayerClips: [UInt32: CGRect] = [:]
     private var activeStroke: FfiStroke?
     private var activeShape: FfiShape?
 
@@ -151,7 +152,7 @@ final class CanvasView: NSView, NSTextViewDelegate {
 
                 // While drawing arrows, update endpoint attachments live so the arrow
                 // visually “sticks” as the cursor moves over shapes.
-                if activeShape?.kind == .arrow || activeShape?.kind == .curvedArrow {
+                if activeShape?.kind == .arrow || activeShape?.kind == .curvedArrow || activeShape?.kind == .sCurve {
                     if let hit = hitTestClosedShape(at: p) {
                         activeShape?.endAttachId = hit.shape.id
                         activeShape?.endAttachUv = attachmentUv(point: p, in: hit.rect)
@@ -185,6 +186,7 @@ final class CanvasView: NSView, NSTextViewDelegate {
             // Pen: allow a single-click dot.
             if selectedTool == .pen {
                 var stroke = document.beginStroke(color: penColor.asFfiColor(), width: Float(penWidth), start: p.asFfiPoint())
+                stroke.createdMs = nowMs()
                 stroke.points.append(p.asFfiPoint())
                 commit(stroke: stroke)
             }
@@ -200,7 +202,7 @@ final class CanvasView: NSView, NSTextViewDelegate {
         if var shape = activeShape {
             shape.end = p.asFfiPoint()
 
-            if shape.kind == .arrow || shape.kind == .curvedArrow {
+            if shape.kind == .arrow || shape.kind == .curvedArrow || shape.kind == .sCurve {
                 // Finalize endpoint attachments when the arrow is released.
                 if let hit = hitTestClosedShape(at: p) {
                     shape.endAttachId = hit.shape.id
@@ -222,8 +224,32 @@ final class CanvasView: NSView, NSTextViewDelegate {
         ctx.setShouldAntialias(true)
         ctx.setAllowsAntialiasing(true)
 
+        // Fills outline polygons from the core (nonzero), e.g. for tapered lines.
+        func fillOutline(_ polygons: [[FfiPoint]], color: CGColor) {
+            ctx.beginPath()
+            for polygon in polygons {
+                guard let first = polygon.first else { continue }
+                ctx.move(to: first.asCGPoint())
+                for point in polygon.dropFirst() {
+                    ctx.addLine(to: point.asCGPoint())
+                }
+                ctx.closePath()
+            }
+            ctx.setFillColor(color)
+            ctx.fillPath(using: .winding)
+        }
+
         func draw(stroke: FfiStroke) {
             guard let first = stroke.points.first else { return }
+            ctx.saveGState()
+            defer { ctx.restoreGState() }
+            ctx.setBlendMode(stroke.blendMode.asCGBlendMode())
+            if stroke.line.taperStart > 0 || stroke.line.taperEnd > 0 {
+                // Core Graphics can't vary line width; fill the core's outline instead.
+                fillOutline(strokeOutline(points: stroke.points, width: stroke.width, line: stroke.line),
+                            color: stroke.color.asNSColor().cgColor)
+                return
+            }
             ctx.beginPath()
             ctx.move(to: first.asCGPoint())
             for point in stroke.points.dropFirst() {
@@ -231,17 +257,29 @@ final class CanvasView: NSView, NSTextViewDelegate {
             }
             ctx.setStrokeColor(stroke.color.asNSColor().cgColor)
             ctx.setLineWidth(CGFloat(stroke.width))
-            ctx.setLineCap(.round)
-            ctx.setLineJoin(.round)
+            ctx.setLineCap(stroke.line.cap.asCGLineCap())
+            ctx.setLineJoin(stroke.line.join.asCGLineJoin())
+            ctx.setMiterLimit(4) // matches the core outline and SVG export
             ctx.strokePath()
         }
 
         func draw(shape: FfiShape) {
+            ctx.saveGState()
+            defer { ctx.restoreGState() }
+            ctx.setBlendMode(shape.blendMode.asCGBlendMode())
+            if shape.rotation != 0, isClosedShape(shape.kind) || shape.kind == .arc {
+                // Closed shapes and arcs are stored unrotated; rotate about the rect center.
+                let rect = rectFromPoints(a: shape.start.asCGPoint(), b: shape.end.asCGPoint())
+                ctx.translateBy(x: rect.midX, y: rect.midY)
+                ctx.rotate(by: CGFloat(shape.rotation))
+                ctx.translateBy(x: -rect.midX, y: -rect.midY)
+            }
             let strokeColor = shape.style.strokeColor.asNSColor().cgColor
             ctx.setStrokeColor(strokeColor)
             ctx.setLineWidth(CGFloat(shape.style.strokeWidth))
-            ctx.setLineCap(.round)
-            ctx.setLineJoin(.round)
+            ctx.setLineCap(shape.style.line.cap.asCGLineCap())
+            ctx.setLineJoin(shape.style.line.join.asCGLineJoin())
+            ctx.setMiterLimit(4)
 
             func fillAndHatch(path: CGPath) {
                 guard shape.style.fillEnabled else { return }
@@ -285,9 +323,23 @@ final class CanvasView: NSView, NSTextViewDelegate {
                 ctx.strokeEllipse(in: rect)
                 drawShapeTextIfNeeded(shape, in: rect, clipPath: path)
 
-            case .arrow:
+            case .arc:
+                let rect = rectFromPoints(a: shape.start.asCGPoint(), b: shape.end.asCGPoint())
+                ctx.addPath(ellipseArcPath(in: rect, angles: shape.angles, pie: false))
+                ctx.strokePath()
+
+            case .pie:
+                let rect = rectFromPoints(a: shape.start.asCGPoint(), b: shape.end.asCGPoint())
+                let path = ellipseArcPath(in: rect, angles: shape.angles, pie: true)
+                fillAndHatch(path: path)
+                ctx.addPath(path)
+                ctx.strokePath()
+                drawShapeTextIfNeeded(shape, in: rect, clipPath: path)
+
+            case .arrow, .sCurve:
                 if let render = cachedArrowRendersById[shape.id] {
                     drawArrowFromRender(ctx: ctx, render: render, fillColor: strokeColor)
+                    drawArrowLabelIfNeeded(shape, render: render)
                 } else {
                     // During drag, the arrow isn't committed into the document yet.
                     let (start, end) = resolvedArrowEndpoints(for: shape)
@@ -297,6 +349,7 @@ final class CanvasView: NSView, NSTextViewDelegate {
             case .curvedArrow:
                 if let render = cachedArrowRendersById[shape.id] {
                     drawArrowFromRender(ctx: ctx, render: render, fillColor: strokeColor)
+                    drawArrowLabelIfNeeded(shape, render: render)
                 } else {
                     // Lightweight preview while dragging (routing is owned by Rust for committed shapes).
                     let (start, end) = resolvedArrowEndpoints(for: shape)
@@ -304,6 +357,7 @@ final class CanvasView: NSView, NSTextViewDelegate {
                         ctx: ctx,
                         start: start,
                         end: end,
+                        curvature: shape.style.curvature,
                         lineWidth: CGFloat(shape.style.strokeWidth),
                         strokeColor: strokeColor
                     )
@@ -315,11 +369,31 @@ final class CanvasView: NSView, NSTextViewDelegate {
             }
         }
 
+        // Halo casings go under all ink, so they never cover another annotation.
+        for halo in document.itemHalos() {
+            ctx.saveGState()
+            if let layerId = halo.layerId, let clip = cachedLayerClips[layerId] {
+                ctx.clip(to: clip)
+            }
+            fillOutline(halo.polygons, color: halo.color.asNSColor().cgColor)
+            ctx.restoreGState()
+        }
+
         for item in cachedItems {
+            let layerId: UInt32?
+            switch item {
+            case .stroke(let s): layerId = s.layerId
+            case .shape(let sh): layerId = sh.layerId
+            }
+            ctx.saveGState()
+            if let layerId, let clip = cachedLayerClips[layerId] {
+                ctx.clip(to: clip)
+            }
             switch item {
             case .stroke(let s): draw(stroke: s)
             case .shape(let sh): draw(shape: sh)
             }
+            ctx.restoreGState()
         }
         if let s = activeStroke {
             draw(stroke: s)
@@ -338,26 +412,26 @@ final class CanvasView: NSView, NSTextViewDelegate {
 
     func undo() {
         _ = commitTextEditingIfNeeded()
-        guard document.undo() else { return }
+        guard (try? document.undo()) != nil else { return }
         refreshItems()
         setNeedsDisplay(bounds)
     }
 
     func redo() {
         _ = commitTextEditingIfNeeded()
-        guard document.redo() else { return }
+        guard (try? document.redo()) != nil else { return }
         refreshItems()
         setNeedsDisplay(bounds)
     }
 
     private func commit(stroke: FfiStroke) {
-        document.commitStroke(stroke: stroke)
+        try? document.commitStroke(stroke: stroke)
         refreshItems()
         setNeedsDisplay(bounds)
     }
 
     private func commit(shape: FfiShape) {
-        document.commitShape(shape: shape)
+        try? document.commitShape(shape: shape)
         refreshItems()
         setNeedsDisplay(bounds)
     }
@@ -377,6 +451,17 @@ final class CanvasView: NSView, NSTextViewDelegate {
             cachedArrowRendersById[render.shapeId] = render
         }
 
+        cachedLayerClips.removeAll(keepingCapacity: true)
+        for layer in document.layers() {
+            guard let clip = layer.clip else { continue }
+            cachedLayerClips[layer.id] = CGRect(
+                x: CGFloat(clip.minX),
+                y: CGFloat(clip.minY),
+                width: CGFloat(clip.maxX - clip.minX),
+                height: CGFloat(clip.maxY - clip.minY)
+            )
+        }
+
         if let selectedShapeId, !cachedItems.contains(where: { item in
             if case .shape(let sh) = item { return sh.id == selectedShapeId }
             return false
@@ -392,6 +477,7 @@ final class CanvasView: NSView, NSTextViewDelegate {
         switch selectedTool {
         case .pen:
             activeStroke = document.beginStroke(color: penColor.asFfiColor(), width: Float(penWidth), start: start.asFfiPoint())
+            activeStroke?.createdMs = nowMs()
         case .eraser:
             break
         case .rectangle:
@@ -413,6 +499,12 @@ final class CanvasView: NSView, NSTextViewDelegate {
                 activeShape?.startAttachUv = attachmentUv(point: start, in: hit.rect)
             }
         }
+        activeShape?.createdMs = nowMs()
+    }
+
+    // Milliseconds since the Unix epoch, stamped on new ink for time-range review.
+    private func nowMs() -> UInt64 {
+        UInt64(Date().timeIntervalSince1970 * 1000)
     }
 
     private func resolvedArrowEndpoints(for shape: FfiShape) -> (start: CGPoint, end: CGPoint) {
@@ -474,11 +566,11 @@ final class CanvasView: NSView, NSTextViewDelegate {
         }
 
         switch target.shape.kind {
-        case .ellipse:
+        case .ellipse, .pie:
             return intersectEllipse(rect: rect, center: center, dx: dx, dy: dy)
         case .rectangle, .roundedRectangle:
             return intersectRect(rect: rect, center: center, dx: dx, dy: dy)
-        case .arrow, .curvedArrow:
+        case .arrow, .curvedArrow, .sCurve, .arc:
             return center
         }
     }
@@ -503,11 +595,11 @@ final class CanvasView: NSView, NSTextViewDelegate {
         }
 
         switch target.shape.kind {
-        case .ellipse:
+        case .ellipse, .pie:
             return intersectEllipse(rect: rect, center: center, dx: dx, dy: dy)
         case .rectangle, .roundedRectangle:
             return intersectRect(rect: rect, center: center, dx: dx, dy: dy)
-        case .arrow, .curvedArrow:
+        case .arrow, .curvedArrow, .sCurve, .arc:
             return center
         }
     }
@@ -551,9 +643,9 @@ final class CanvasView: NSView, NSTextViewDelegate {
 
     private func isClosedShape(_ kind: FfiShapeKind) -> Bool {
         switch kind {
-        case .rectangle, .roundedRectangle, .ellipse:
+        case .rectangle, .roundedRectangle, .ellipse, .pie:
             return true
-        case .arrow, .curvedArrow:
+        case .arrow, .curvedArrow, .sCurve, .arc:
             return false
         }
     }
@@ -568,11 +660,44 @@ final class CanvasView: NSView, NSTextViewDelegate {
             return (rect, roundedRectPath(in: rect, radius: radius))
         case .ellipse:
             return (rect, CGPath(ellipseIn: rect, transform: nil))
-        case .arrow, .curvedArrow:
+        case .arc:
+            return (rect, ellipseArcPath(in: rect, angles: shape.angles, pie: false))
+        case .pie:
+            return (rect, ellipseArcPath(in: rect, angles: shape.angles, pie: true))
+        case .arrow, .curvedArrow, .sCurve:
             return (rect, nil)
         }
     }
 
+    // Same sampling as the core's outline: rays from the center at evenly
+    // spaced angles, clockwise (y-down) from `angles.start`.
+    private func ellipseArcPath(in rect: CGRect, angles: FfiArcAngles?, pie: Bool) -> CGPath {
+        let center = CGPoint(x: rect.midX, y: rect.midY)
+        let start = CGFloat(angles?.start ?? 0)
+        var sweep = CGFloat(angles.map { $0.end - $0.start } ?? 0)
+            .truncatingRemainder(dividingBy: 2 * .pi)
+        if sweep < 0 { sweep += 2 * .pi }
+        if sweep <= 1e-6 { sweep = 2 * .pi }
+        let full = sweep >= 2 * .pi - 1e-6
+        let segments = max(2, Int((64 * sweep / (2 * .pi)).rounded(.up)))
+        let path = CGMutablePath()
+        if pie && !full { path.move(to: center) }
+        for i in 0...segments {
+            let angle = start + sweep * CGFloat(i) / CGFloat(segments)
+            let (rx, ry) = (rect.width / 2, rect.height / 2)
+            let k = hypot(cos(angle) * ry, sin(angle) * rx)
+            let t = k > 1e-6 ? rx * ry / k : 0
+            let point = CGPoint(x: center.x + cos(angle) * t, y: center.y + sin(angle) * t)
+            if i == 0 && !(pie && !full) {
+                path.move(to: point)
+            } else {
+                path.addLine(to: point)
+            }
+        }
+        if pie || full { path.closeSubpath() }
+        return path
+    }
+
     private func hitTestShape(at point: CGPoint) -> ShapeHit? {
         // Iterate in reverse so the most recently-added items win.
         for item in cachedItems.reversed() {
@@ -653,7 +778,7 @@ final class CanvasView: NSView, NSTextViewDelegate {
         if let h { shape.textAlignH = h }
         if let v { shape.textAlignV = v }
 
-        document.commitShape(shape: shape)
+        try? document.commitShape(shape: shape)
         refreshItems()
         setNeedsDisplay(bounds)
     }
@@ -831,7 +956,7 @@ final class CanvasView: NSView, NSTextViewDelegate {
         }
 
         shape.text = textView.string
-        document.commitShape(shape: shape)
+        try? document.commitShape(shape: shape)
         refreshItems()
         endTextEditing()
         setNeedsDisplay(bounds)
@@ -908,6 +1033,36 @@ final class CanvasView: NSView, NSTextViewDelegate {
         cgContext.restoreGState()
     }
 
+    // Arrow text is centered on the anchor the core picked clear of shape labels.
+    private func drawArrowLabelIfNeeded(_ shape: FfiShape, render: FfiArrowRender) {
+        guard let anchor = render.label?.asCGPoint() else { return }
+        let text = shape.text.trimmingCharacters(in: .whitespacesAndNewlines)
+        guard !text.isEmpty else { return }
+
+        let paragraph = NSMutableParagraphStyle()
+        paragraph.alignment = .center
+
+        let attrs: [NSAttributedString.Key: Any] = [
+            .font: NSFont.systemFont(ofSize: 15, weight: .medium),
+            .foregroundColor: NSColor.labelColor,
+            .paragraphStyle: paragraph,
+        ]
+
+        let nsText = text as NSString
+        let size = nsText.boundingRect(
+            with: CGSize(width: CGFloat.greatestFiniteMagnitude, height: .greatestFiniteMagnitude),
+            options: [.usesLineFragmentOrigin, .usesFontLeading],
+            attributes: attrs
+        ).size
+        let drawRect = CGRect(
+            x: anchor.x - ceil(size.width) / 2,
+            y: anchor.y - ceil(size.height) / 2,
+            width: ceil(size.width),
+            height: ceil(size.height)
+        )
+        nsText.draw(with: drawRect, options: [.usesLineFragmentOrigin, .usesFontLeading], attributes: attrs)
+    }
+
     private func drawSelectionOutline(for shape: FfiShape, ctx: CGContext) {
         let (rect, path) = shapePathAndRect(for: shape)
         ctx.saveGState()
@@ -931,7 +1086,13 @@ final class CanvasView: NSView, NSTextViewDelegate {
             fillEnabled: shapeFillEnabled,
             fillColor: shapeFillColor.asFfiColor(),
             hatchEnabled: shapeHatchEnabled,
-            cornerRadius: Float(shapeCornerRadius)
+            cornerRadius: Float(shapeCornerRadius),
+            strokeColorRef: nil,
+            fillColorRef: nil,
+            line: FfiLineStyle(cap: .round, join: .round, taperStart: 0, taperEnd: 0),
+            cornerRadii: nil,
+            curvature: nil,
+            headNotch: nil
         )
     }
 
@@ -985,44 +1146,78 @@ final class CanvasView: NSView, NSTextViewDelegate {
 
     private func drawArrowFromRender(ctx: CGContext, render: FfiArrowRender, fillColor: CGColor) {
         let start = render.start.asCGPoint()
-        let end = render.end.asCGPoint()
+        // The shaft stops at the head's base so thick strokes stay under the head.
+        let shaftEnd = render.shaftEnd.asCGPoint()
 
         ctx.saveGState()
         ctx.setStrokeColor(fillColor)
-        ctx.setLineCap(.round)
-        ctx.setLineJoin(.round)
+        ctx.setLineCap(render.style.line.cap.asCGLineCap())
+        ctx.setLineJoin(render.style.line.join.asCGLineJoin())
 
         ctx.beginPath()
         ctx.move(to: start)
-        switch render.path.kind {
+        switch render.shaft.kind {
         case .line:
-            ctx.addLine(to: end)
+            ctx.addLine(to: shaftEnd)
         case .quadratic:
-            guard let c1 = render.path.c1?.asCGPoint() else { break }
-            ctx.addQuadCurve(to: end, control: c1)
+            guard let c1 = render.shaft.c1?.asCGPoint() else { break }
+            ctx.addQuadCurve(to: shaftEnd, control: c1)
         case .cubic:
-            guard let c1 = render.path.c1?.asCGPoint(), let c2 = render.path.c2?.asCGPoint() else { break }
-            ctx.addCurve(to: end, control1: c1, control2: c2)
+            guard let c1 = render.shaft.c1?.asCGPoint(), let c2 = render.shaft.c2?.asCGPoint() else { break }
+            ctx.addCurve(to: shaftEnd, control1: c1, control2: c2)
         }
         ctx.strokePath()
         ctx.restoreGState()
 
-        // Arrowhead
-        let left = render.headLeft.asCGPoint()
-        let right = render.headRight.asCGPoint()
-
+        // Arrowhead, as the core outlines it.
         ctx.saveGState()
         ctx.setFillColor(fillColor)
         ctx.beginPath()
-        ctx.move(to: end)
-        ctx.addLine(to: left)
-        ctx.addLine(to: right)
+        ctx.addLines(between: render.headPolygon.map { $0.asCGPoint() })
         ctx.closePath()
-        ctx.fillPath()
+        switch render.headFillRule {
+        case .nonZero:
+            ctx.fillPath(using: .winding)
+        case .evenOdd:
+            ctx.fillPath(using: .evenOdd)
+        }
         ctx.restoreGState()
+
+        if let tail = render.tail {
+            drawArrowTail(ctx: ctx, tail: tail, lineWidth: CGFloat(render.style.strokeWidth), color: fillColor)
+        }
     }
 
-    private func quadControlSimple(start: CGPoint, end: CGPoint) -> CGPoint {
+    // Tail decorations (ER/UML notation) are outlines in the arrow's color.
+    private func drawArrowTail(ctx: CGContext, tail: FfiTailRender, lineWidth: CGFloat, color: CGColor) {
+        ctx.saveGState()
+        ctx.setStrokeColor(color)
+        ctx.setLineWidth(lineWidth)
+        ctx.setLineCap(.round)
+        ctx.setLineJoin(.round)
+        ctx.beginPath()
+        switch tail {
+        case let .circle(center, radius):
+            let c = center.asCGPoint()
+            let r = CGFloat(radius)
+            ctx.addEllipse(in: CGRect(x: c.x - r, y: c.y - r, width: r * 2, height: r * 2))
+        case let .diamond(points):
+            guard let first = points.first else { break }
+            ctx.move(to: first.asCGPoint())
+            for p in points.dropFirst() {
+                ctx.addLine(to: p.asCGPoint())
+            }
+            ctx.closePath()
+        case let .bar(a, b):
+            ctx.move(to: a.asCGPoint())
+            ctx.addLine(to: b.asCGPoint())
+        }
+        ctx.strokePath()
+        ctx.restoreGState()
+    }
+
+    // Mirrors the core's `quad_control`: an explicit curvature fixes the bow.
+    private func quadControlSimple(start: CGPoint, end: CGPoint, curvature: Float?) -> CGPoint {
         let mid = CGPoint(x: (start.x + end.x) / 2, y: (start.y + end.y) / 2)
         let dx = end.x - start.x
         let dy = end.y - start.y
@@ -1033,6 +1228,10 @@ final class CanvasView: NSView, NSTextViewDelegate {
         let ux = dx / len
         let uy = dy / len
         let perp = CGPoint(x: -uy, y: ux)
+        if let curvature {
+            let offset = len * CGFloat(curvature)
+            return CGPoint(x: mid.x + perp.x * offset, y: mid.y + perp.y * offset)
+        }
         let magnitude = min(160, max(18, len * 0.22))
         let sign: CGFloat = (dx * dy >= 0) ? 1 : -1
         return CGPoint(x: mid.x + perp.x * magnitude * sign, y: mid.y + perp.y * magnitude * sign)
@@ -1042,6 +1241,7 @@ final class CanvasView: NSView, NSTextViewDelegate {
         ctx: CGContext,
         start: CGPoint,
         end: CGPoint,
+        curvature: Float?,
         lineWidth: CGFloat,
         strokeColor: CGColor
     ) {
@@ -1050,7 +1250,7 @@ final class CanvasView: NSView, NSTextViewDelegate {
         let len = sqrt(dx * dx + dy * dy)
         guard len > 0.5 else { return }
 
-        let control = quadControlSimple(start: start, end: end)
+        let control = quadControlSimple(start: start, end: end, curvature: curvature)
 
         ctx.saveGState()
         ctx.setStrokeColor(strokeColor)
@@ -1224,6 +1424,37 @@ private extension FfiColorRgba8 {
     }
 }
 
+private extension FfiBlendMode {
+    func asCGBlendMode() -> CGBlendMode {
+        switch self {
+        case .normal: return .normal
+        case .multiply: return .multiply
+        case .screen: return .screen
+        case .overlay: return .overlay
+        }
+    }
+}
+
+private extension FfiLineCap {
+    func asCGLineCap() -> CGLineCap {
+        switch self {
+        case .butt: return .butt
+        case .round: return .round
+        case .square: return .square
+        }
+    }
+}
+
+private extension FfiLineJoin {
+    func asCGLineJoin() -> CGLineJoin {
+        switch self {
+        case .miter: return .miter
+        case .round: return .round
+        case .bevel: return .bevel
+        }
+    }
+}
+
 private extension FfiTextAlignH {
     func asNSTextAlignment() -> NSTextAlignment {
         switch self {
@@ -0,0 +1,13 @@
+{
+  "targets": [
+    {
+      "target_name": "overlay_scribe",
+      "sources": ["src/addon.c"],
+      "include_dirs": ["../../core/overlay_scribe_ffi/include"],
+      "libraries": ["<(module_root_dir)/../../target/release/liboverlay_scribe_ffi.a"],
+      "conditions": [
+        ["OS=='linux'", { "libraries": ["-lpthread", "-ldl", "-lm"] }]
+      ]
+    }
+  ]
+}
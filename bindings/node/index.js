@@ -0,0 +1,6 @@
+'use strict';
+
+// Node bindings for the OverlayScribe core. See README.md for building.
+const { Document } = require('./build/Release/overlay_scribe.node');
+
+module.exports = { Document };
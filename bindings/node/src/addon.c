@@ -0,0 +1,327 @@
+// N-API addon over the core's C ABI (include/overlay_scribe.h).
+//
+// Exposes a `Document` class, re-exported by index.js.
+// Every function validates its arguments and throws instead of crashing.
+
+#include <node_api.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "overlay_scribe.h"
+
+#define CHECK(env, call)                                        \
+  do {                                                          \
+    if ((call) != napi_ok) {                                    \
+      napi_throw_error((env), NULL, "N-API call failed: " #call); \
+      return NULL;                                              \
+    }                                                           \
+  } while (0)
+
+static const char *status_code(OscStatus status) {
+  switch (status) {
+    case OscStatus_Ok: return "OK";
+    case OscStatus_InvalidArgument: return "INVALID_ARGUMENT";
+    case OscStatus_CannotUndo: return "CANNOT_UNDO";
+    case OscStatus_CannotRedo: return "CANNOT_REDO";
+    case OscStatus_ParseError: return "PARSE_ERROR";
+    case OscStatus_SerializationError: return "SERIALIZATION_ERROR";
+    case OscStatus_InvalidPattern: return "INVALID_PATTERN";
+    case OscStatus_Cancelled: return "CANCELLED";
+    case OscStatus_LimitExceeded: return "LIMIT_EXCEEDED";
+    case OscStatus_InvalidInput: return "INVALID_INPUT";
+  }
+  return "UNKNOWN";
+}
+
+// Throws for a non-OK status; returns true if it did.
+static bool throw_status(napi_env env, OscStatus status) {
+  if (status == OscStatus_Ok) {
+    return false;
+  }
+  napi_throw_error(env, status_code(status), status_code(status));
+  return true;
+}
+
+static void finalize_document(napi_env env, void *data, void *hint) {
+  (void)env;
+  (void)hint;
+  osc_document_free((CoreDocument *)data);
+}
+
+// Unwraps `this` and reads up to `*argc` arguments into `argv`.
+static CoreDocument *unwrap(napi_env env, napi_callback_info info, size_t *argc,
+                            napi_value *argv) {
+  napi_value this_arg;
+  void *doc = NULL;
+  if (napi_get_cb_info(env, info, argc, argv, &this_arg, NULL) != napi_ok ||
+      napi_unwrap(env, this_arg, &doc) != napi_ok || doc == NULL) {
+    napi_throw_error(env, NULL, "not a Document");
+    return NULL;
+  }
+  return (CoreDocument *)doc;
+}
+
+// Converts a string returned by the C ABI into a JS string and frees it.
+static napi_value take_string(napi_env env, char *s, const char *what) {
+  if (s == NULL) {
+    napi_throw_error(env, NULL, what);
+    return NULL;
+  }
+  napi_value out;
+  napi_status status = napi_create_string_utf8(env, s, NAPI_AUTO_LENGTH, &out);
+  osc_string_free(s);
+  CHECK(env, status);
+  return out;
+}
+
+static napi_value document_new(napi_env env, napi_callback_info info) {
+  napi_value this_arg;
+  CHECK(env, napi_get_cb_info(env, info, NULL, NULL, &this_arg, NULL));
+  CoreDocument *doc = osc_document_new();
+  if (napi_wrap(env, this_arg, doc, finalize_document, NULL, NULL) != napi_ok) {
+    osc_document_free(doc);
+    napi_throw_error(env, NULL, "could not wrap document");
+    return NULL;
+  }
+  return this_arg;
+}
+
+static napi_value document_load_json(napi_env env, napi_callback_info info) {
+  size_t argc = 1;
+  napi_value argv[1];
+  CoreDocument *doc = unwrap(env, info, &argc, argv);
+  if (doc == NULL) {
+    return NULL;
+  }
+  size_t len = 0;
+  if (argc < 1 || napi_get_value_string_utf8(env, argv[0], NULL, 0, &len) != napi_ok) {
+    napi_throw_type_error(env, NULL, "loadJson expects a string");
+    return NULL;
+  }
+  char *json = malloc(len + 1);
+  if (json == NULL) {
+    napi_throw_error(env, NULL, "out of memory");
+    return NULL;
+  }
+  napi_get_value_string_utf8(env, argv[0], json, len + 1, &len);
+  OscStatus status = osc_document_load_json(doc, json);
+  free(json);
+  throw_status(env, status);
+  return NULL;
+}
+
+static napi_value document_to_json(napi_env env, napi_callback_info info) {
+  size_t argc = 0;
+  CoreDocument *doc = unwrap(env, info, &argc, NULL);
+  if (doc == NULL) {
+    return NULL;
+  }
+  return take_string(env, osc_document_to_json(doc), "could not serialize document");
+}
+
+static napi_value document_export_svg(napi_env env, napi_callback_info info) {
+  size_t argc = 0;
+  CoreDocument *doc = unwrap(env, info, &argc, NULL);
+  if (doc == NULL) {
+    return NULL;
+  }
+  return take_string(env, osc_document_export_svg(doc), "could not export SVG");
+}
+
+static napi_value document_item_count(napi_env env, napi_callback_info info) {
+  size_t argc = 0;
+  CoreDocument *doc = unwrap(env, info, &argc, NULL);
+  if (doc == NULL) {
+    return NULL;
+  }
+  napi_value out;
+  CHECK(env, napi_create_double(env, (double)osc_document_item_count(doc), &out));
+  return out;
+}
+
+static napi_value document_undo(napi_env env, napi_callback_info info) {
+  size_t argc = 0;
+  CoreDocument *doc = unwrap(env, info, &argc, NULL);
+  if (doc != NULL) {
+    throw_status(env, osc_document_undo(doc));
+  }
+  return NULL;
+}
+
+static napi_value document_redo(napi_env env, napi_callback_info info) {
+  size_t argc = 0;
+  CoreDocument *doc = unwrap(env, info, &argc, NULL);
+  if (doc != NULL) {
+    throw_status(env, osc_document_redo(doc));
+  }
+  return NULL;
+}
+
+static napi_value document_clear(napi_env env, napi_callback_info info) {
+  size_t argc = 0;
+  CoreDocument *doc = unwrap(env, info, &argc, NULL);
+  if (doc != NULL) {
+    osc_document_clear(doc);
+  }
+  return NULL;
+}
+
+// renderTile(col, row, size) -> Uint8Array of size * size * 4 RGBA bytes.
+static napi_value document_render_tile(napi_env env, napi_callback_info info) {
+  size_t argc = 3;
+  napi_value argv[3];
+  CoreDocument *doc = unwrap(env, info, &argc, argv);
+  if (doc == NULL) {
+    return NULL;
+  }
+  int32_t col, row;
+  uint32_t size;
+  if (argc < 3 || napi_get_value_int32(env, argv[0], &col) != napi_ok ||
+      napi_get_value_int32(env, argv[1], &row) != napi_ok ||
+      napi_get_value_uint32(env, argv[2], &size) != napi_ok || size == 0 ||
+      size > 4096) {
+    napi_throw_type_error(env, NULL, "renderTile expects (col, row, size) with 0 < size <= 4096");
+    return NULL;
+  }
+  size_t len = (size_t)size * size * 4;
+  void *data = NULL;
+  napi_value buffer, array;
+  CHECK(env, napi_create_arraybuffer(env, len, &data, &buffer));
+  if (throw_status(env, osc_document_render_tile(doc, col, row, size, data, len))) {
+    return NULL;
+  }
+  CHECK(env, napi_create_typedarray(env, napi_uint8_array, len, buffer, 0, &array));
+  return array;
+}
+
+// Sets `obj[name]` to `{ x, y }`; returns false (with an exception pending)
+// on failure.
+static bool set_point(napi_env env, napi_value obj, const char *name, OscPoint p) {
+  napi_value point, x, y;
+  return napi_create_object(env, &point) == napi_ok &&
+         napi_create_double(env, p.x, &x) == napi_ok &&
+         napi_create_double(env, p.y, &y) == napi_ok &&
+         napi_set_named_property(env, point, "x", x) == napi_ok &&
+         napi_set_named_property(env, point, "y", y) == napi_ok &&
+         napi_set_named_property(env, obj, name, point) == napi_ok;
+}
+
+// { kind, start, end } plus the control points the kind uses: `control`
+// for quadratics, `c1` and `c2` for cubics.
+static napi_value make_path(napi_env env, const OscPath *path) {
+  static const char *kinds[] = {"line", "quadratic", "cubic"};
+  napi_value out, kind;
+  CHECK(env, napi_create_object(env, &out));
+  CHECK(env, napi_create_string_utf8(env, kinds[path->kind], NAPI_AUTO_LENGTH, &kind));
+  CHECK(env, napi_set_named_property(env, out, "kind", kind));
+  bool ok = set_point(env, out, "start", path->start) && set_point(env, out, "end", path->end);
+  if (path->kind == OscPathKind_Quadratic) {
+    ok = ok && set_point(env, out, "control", path->c1);
+  } else if (path->kind == OscPathKind_Cubic) {
+    ok = ok && set_point(env, out, "c1", path->c1) && set_point(env, out, "c2", path->c2);
+  }
+  if (!ok) {
+    napi_throw_error(env, NULL, "could not build arrow path");
+    return NULL;
+  }
+  return out;
+}
+
+static napi_value make_arrow(napi_env env, const OscArrowRender *r) {
+  napi_value out, value, head, color;
+  CHECK(env, napi_create_object(env, &out));
+  CHECK(env, napi_create_double(env, (double)r->shape_id, &value));
+  CHECK(env, napi_set_named_property(env, out, "shapeId", value));
+
+  const uint8_t rgba[4] = {r->stroke_color.r, r->stroke_color.g, r->stroke_color.b,
+                           r->stroke_color.a};
+  const char *channels[4] = {"r", "g", "b", "a"};
+  CHECK(env, napi_create_object(env, &color));
+  for (size_t i = 0; i < 4; i++) {
+    CHECK(env, napi_create_uint32(env, rgba[i], &value));
+    CHECK(env, napi_set_named_property(env, color, channels[i], value));
+  }
+  CHECK(env, napi_set_named_property(env, out, "strokeColor", color));
+  CHECK(env, napi_create_double(env, r->stroke_width, &value));
+  CHECK(env, napi_set_named_property(env, out, "strokeWidth", value));
+
+  if ((value = make_path(env, &r->path)) == NULL) {
+    return NULL;
+  }
+  CHECK(env, napi_set_named_property(env, out, "path", value));
+  if ((value = make_path(env, &r->shaft)) == NULL) {
+    return NULL;
+  }
+  CHECK(env, napi_set_named_property(env, out, "shaft", value));
+
+  uint32_t head_len = r->head_len < 4 ? r->head_len : 4;
+  CHECK(env, napi_create_array_with_length(env, head_len, &head));
+  for (uint32_t i = 0; i < head_len; i++) {
+    napi_value point, x, y;
+    CHECK(env, napi_create_object(env, &point));
+    CHECK(env, napi_create_double(env, r->head[i].x, &x));
+    CHECK(env, napi_create_double(env, r->head[i].y, &y));
+    CHECK(env, napi_set_named_property(env, point, "x", x));
+    CHECK(env, napi_set_named_property(env, point, "y", y));
+    CHECK(env, napi_set_element(env, head, i, point));
+  }
+  CHECK(env, napi_set_named_property(env, out, "head", head));
+  CHECK(env, napi_create_string_utf8(env, r->head_even_odd ? "evenodd" : "nonzero",
+                                     NAPI_AUTO_LENGTH, &value));
+  CHECK(env, napi_set_named_property(env, out, "headFillRule", value));
+  return out;
+}
+
+// renderArrows() -> [{ shapeId, strokeColor, strokeWidth, path, shaft, head,
+// headFillRule }], one per arrow, routed by the core.
+static napi_value document_render_arrows(napi_env env, napi_callback_info info) {
+  size_t argc = 0;
+  CoreDocument *doc = unwrap(env, info, &argc, NULL);
+  if (doc == NULL) {
+    return NULL;
+  }
+  uintptr_t count = 0;
+  OscStatus status = osc_document_render_arrows(doc, NULL, 0, &count);
+  OscArrowRender *renders = NULL;
+  if (count > 0) {
+    renders = calloc(count, sizeof(OscArrowRender));
+    if (renders == NULL) {
+      napi_throw_error(env, NULL, "out of memory");
+      return NULL;
+    }
+    status = osc_document_render_arrows(doc, renders, count, &count);
+  }
+  napi_value out = NULL;
+  if (!throw_status(env, status) && napi_create_array_with_length(env, count, &out) == napi_ok) {
+    for (uintptr_t i = 0; i < count; i++) {
+      napi_value arrow = make_arrow(env, &renders[i]);
+      if (arrow == NULL || napi_set_element(env, out, (uint32_t)i, arrow) != napi_ok) {
+        out = NULL;
+        break;
+      }
+    }
+  }
+  free(renders);
+  return out;
+}
+
+static napi_value init(napi_env env, napi_value exports) {
+  napi_property_descriptor methods[] = {
+      {"loadJson", NULL, document_load_json, NULL, NULL, NULL, napi_default, NULL},
+      {"toJson", NULL, document_to_json, NULL, NULL, NULL, napi_default, NULL},
+      {"exportSvg", NULL, document_export_svg, NULL, NULL, NULL, napi_default, NULL},
+      {"itemCount", NULL, document_item_count, NULL, NULL, NULL, napi_default, NULL},
+      {"undo", NULL, document_undo, NULL, NULL, NULL, napi_default, NULL},
+      {"redo", NULL, document_redo, NULL, NULL, NULL, napi_default, NULL},
+      {"clear", NULL, document_clear, NULL, NULL, NULL, napi_default, NULL},
+      {"renderTile", NULL, document_render_tile, NULL, NULL, NULL, napi_default, NULL},
+      {"renderArrows", NULL, document_render_arrows, NULL, NULL, NULL, napi_default, NULL},
+  };
+  napi_value cls;
+  CHECK(env, napi_define_class(env, "Document", NAPI_AUTO_LENGTH, document_new, NULL,
+                               sizeof(methods) / sizeof(methods[0]), methods, &cls));
+  CHECK(env, napi_set_named_property(env, exports, "Document", cls));
+  return exports;
+}
+
+NAPI_MODULE(NODE_GYP_MODULE_NAME, init)
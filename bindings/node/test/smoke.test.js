@@ -0,0 +1,94 @@
+'use strict';
+
+const test = require('node:test');
+const assert = require('node:assert');
+const { Document } = require('..');
+
+const STROKE_DOC = JSON.stringify({
+  version: 2,
+  items: [
+    {
+      type: 'stroke',
+      data: {
+        id: 1,
+        color: { r: 255, g: 0, b: 0, a: 255 },
+        width: 4,
+        points: [{ x: 2, y: 8 }, { x: 30, y: 8 }],
+      },
+    },
+  ],
+});
+
+const CURVED_ARROW_DOC = JSON.stringify({
+  version: 2,
+  items: [
+    {
+      type: 'shape',
+      data: {
+        id: 7,
+        kind: 'curved_arrow',
+        style: {
+          stroke_color: { r: 0, g: 0, b: 255, a: 255 },
+          stroke_width: 3,
+          fill_enabled: false,
+          fill_color: { r: 0, g: 0, b: 0, a: 0 },
+          hatch_enabled: false,
+          corner_radius: 0,
+        },
+        start: { x: 0, y: 100 },
+        end: { x: 200, y: 100 },
+      },
+    },
+  ],
+});
+
+test('loads, saves and exports', () => {
+  const doc = new Document();
+  doc.loadJson(STROKE_DOC);
+  assert.strictEqual(doc.itemCount(), 1);
+  assert.match(doc.exportSvg(), /<svg/);
+
+  const copy = new Document();
+  copy.loadJson(doc.toJson());
+  assert.strictEqual(copy.itemCount(), 1);
+});
+
+test('renders tiles as RGBA bytes', () => {
+  const doc = new Document();
+  doc.loadJson(STROKE_DOC);
+  const tile = doc.renderTile(0, 0, 32);
+  assert.strictEqual(tile.length, 32 * 32 * 4);
+  const i = (8 * 32 + 16) * 4;
+  assert.deepStrictEqual([...tile.subarray(i, i + 4)], [255, 0, 0, 255]);
+});
+
+test('routes curved arrows', () => {
+  const doc = new Document();
+  doc.loadJson(CURVED_ARROW_DOC);
+  const [arrow, ...rest] = doc.renderArrows();
+  assert.strictEqual(rest.length, 0);
+  assert.strictEqual(arrow.shapeId, 7);
+  assert.deepStrictEqual(arrow.strokeColor, { r: 0, g: 0, b: 255, a: 255 });
+
+  // A free curved arrow bows off the straight line, halfway along.
+  const { path, shaft, head } = arrow;
+  assert.strictEqual(path.kind, 'quadratic');
+  assert.deepStrictEqual([path.start, path.end], [{ x: 0, y: 100 }, { x: 200, y: 100 }]);
+  assert.ok(Math.abs(path.control.x - 100) < 1e-3);
+  assert.ok(path.control.y > 120);
+
+  // The shaft stops short of the tip, where the head takes over.
+  assert.deepStrictEqual(shaft.start, path.start);
+  assert.ok(shaft.end.x < path.end.x);
+  assert.strictEqual(head.length, 3);
+  assert.deepStrictEqual(head[0], path.end);
+  assert.strictEqual(arrow.headFillRule, 'nonzero');
+
+  assert.deepStrictEqual(new Document().renderArrows(), []);
+});
+
+test('reports failures as errors with codes', () => {
+  const doc = new Document();
+  assert.throws(() => doc.undo(), { code: 'CANNOT_UNDO' });
+  assert.throws(() => doc.loadJson('not json'), { code: 'PARSE_ERROR' });
+});
@@ -0,0 +1,7 @@
+"""Python bindings for the OverlayScribe core.
+
+The `overlay_scribe_ffi` module and the native library next to it are
+generated by `scripts/gen-python-bindings.sh`; run it before importing.
+"""
+
+from .overlay_scribe_ffi import *  # noqa: F401,F403
@@ -0,0 +1,44 @@
+import unittest
+
+import overlay_scribe as osc
+
+
+class SmokeTest(unittest.TestCase):
+    def test_round_trip_and_export(self):
+        doc = osc.CoreDocument()
+        stroke = osc.FfiStroke(
+            id=1,
+            color=osc.FfiColorRgba8(r=255, g=0, b=0, a=255),
+            width=2.0,
+            points=[osc.FfiPoint(x=0.0, y=0.0), osc.FfiPoint(x=10.0, y=10.0)],
+            line=osc.FfiLineStyle(
+                cap=osc.FfiLineCap.ROUND,
+                join=osc.FfiLineJoin.ROUND,
+                taper_start=0.0,
+                taper_end=0.0,
+            ),
+            blend_mode=osc.FfiBlendMode.NORMAL,
+            color_ref=None,
+            layer_id=None,
+            tags=[],
+            link=None,
+            created_ms=None,
+        )
+        ids = doc.insert_items([osc.FfiItem.STROKE(stroke)])
+        self.assertEqual(len(ids), 1)
+
+        copy = osc.CoreDocument()
+        copy.load_json(doc.to_json())
+        self.assertEqual(len(copy.items()), 1)
+        self.assertIn("<svg", copy.export_svg(None))
+
+    def test_errors_are_exceptions(self):
+        doc = osc.CoreDocument()
+        with self.assertRaises(osc.FfiError.CannotUndo):
+            doc.undo()
+        with self.assertRaises(osc.FfiError.ParseError):
+            doc.load_json("not json")
+
+
+if __name__ == "__main__":
+    unittest.main()
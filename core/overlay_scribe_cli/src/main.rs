@@ -0,0 +1,223 @@
+//! `overlay-scribe`: headless conversion, validation, stats and diffing of
+//! documents, for support and batch jobs.
+
+mod png;
+
+use overlay_scribe_core::compare::diff;
+use overlay_scribe_core::export::default_region;
+use overlay_scribe_core::geometry::Rect;
+use overlay_scribe_core::raster::render_tiles;
+use overlay_scribe_core::validate::{repair, validate};
+use overlay_scribe_core::{Document, Item, Store, SvgOptions};
+use std::path::Path;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: overlay-scribe <command> [args]
+
+commands:
+  convert <input> <output> [--crop <padding>]
+                                     convert by extension: .json .bin .svg .png;
+                                     --crop trims images to the content plus padding
+  validate <input> [--repair <out>]  report problems, optionally writing a fixed copy
+  stats <input>                      print item counts, bounds and sizes
+  diff <old> <new>                   list added, removed and changed items
+
+Exit status: 0 on success, 1 if problems or differences were found, 2 on error.";
+
+// Tile edge used when rasterizing PNG exports, and the largest image edge.
+const PNG_TILE: u32 = 256;
+const PNG_MAX_EDGE: u32 = 16_384;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args.as_slice() {
+        ["convert", input, output] => convert(input, output, None),
+        ["convert", input, output, "--crop", padding] => match padding.parse::<f32>() {
+            Ok(padding) if padding.is_finite() => convert(input, output, Some(padding)),
+            _ => Err(format!("--crop: '{padding}' is not a number")),
+        },
+        ["validate", input] => check(input, None),
+        ["validate", input, "--repair", output] => check(input, Some(output)),
+        ["stats", input] => stats(input),
+        ["diff", old, new] => compare(old, new),
+        ["help" | "--help" | "-h"] => {
+            println!("{USAGE}");
+            Ok(ExitCode::SUCCESS)
+        }
+        _ => Err(USAGE.to_string()),
+    };
+    result.unwrap_or_else(|message| {
+        eprintln!("{message}");
+        ExitCode::from(2)
+    })
+}
+
+fn extension(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase()
+}
+
+// Reads a `.json` or `.bin` document. `.bin` is what `CoreDocument::to_bytes`
+// writes: UTF-8 compact JSON.
+fn load(path: &str) -> Result<Document, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("{path}: {e}"))?;
+    let json = String::from_utf8(bytes).map_err(|e| format!("{path}: {e}"))?;
+    Store::from_json(&json).map_err(|e| format!("{path}: {e}"))
+}
+
+fn store_for(doc: Document) -> Store {
+    let mut store = Store::new();
+    store.load_document(doc);
+    store
+}
+
+// `region` limits image formats to part of the document; `None` exports the
+// canvas, or the content when the canvas has no size.
+fn save(store: &Store, path: &str, region: Option<Rect>) -> Result<(), String> {
+    let bytes = match extension(path).as_str() {
+        "json" => store.to_json().map_err(|e| e.to_string())?.into_bytes(),
+        "bin" => store
+            .to_json_compact()
+            .map_err(|e| e.to_string())?
+            .into_bytes(),
+        "svg" => store.export_svg(&SvgOptions { region }).into_bytes(),
+        "png" => render_png(&store.document(), region)?,
+        other => return Err(format!("{path}: unsupported output format '.{other}'")),
+    };
+    std::fs::write(path, bytes).map_err(|e| format!("{path}: {e}"))
+}
+
+fn render_png(doc: &Document, region: Option<Rect>) -> Result<Vec<u8>, String> {
+    let region = region
+        .or_else(|| default_region(doc))
+        .ok_or("nothing to render: the document is empty")?;
+    let (x0, y0) = (region.min_x.floor(), region.min_y.floor());
+    let width = (region.max_x.ceil() - x0).max(1.0) as u32;
+    let height = (region.max_y.ceil() - y0).max(1.0) as u32;
+    if width > PNG_MAX_EDGE || height > PNG_MAX_EDGE {
+        return Err(format!(
+            "image would be {width}x{height}; the limit is {PNG_MAX_EDGE} per side"
+        ));
+    }
+    let area = Rect {
+        min_x: x0,
+        min_y: y0,
+        max_x: x0 + width as f32,
+        max_y: y0 + height as f32,
+    };
+    let mut rgba = vec![0u8; width as usize * height as usize * 4];
+    for tile in render_tiles(doc, PNG_TILE, &[area]) {
+        let size = tile.size as i64;
+        for ty in 0..size {
+            let y = tile.row as i64 * size + ty - y0 as i64;
+            if !(0..height as i64).contains(&y) {
+                continue;
+            }
+            for tx in 0..size {
+                let x = tile.col as i64 * size + tx - x0 as i64;
+                if !(0..width as i64).contains(&x) {
+                    continue;
+                }
+                let src = ((ty * size + tx) * 4) as usize;
+                let dst = ((y * width as i64 + x) * 4) as usize;
+                rgba[dst..dst + 4].copy_from_slice(&tile.rgba[src..src + 4]);
+            }
+        }
+    }
+    Ok(png::encode_rgba(width, height, &rgba))
+}
+
+fn convert(input: &str, output: &str, crop: Option<f32>) -> Result<ExitCode, String> {
+    let store = store_for(load(input)?);
+    let region = match crop {
+        Some(padding) => Some(
+            store
+                .export_bounds(padding)
+                .ok_or("nothing to crop to: the document is empty")?,
+        ),
+        None => None,
+    };
+    save(&store, output, region)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn check(input: &str, repaired: Option<&str>) -> Result<ExitCode, String> {
+    let mut doc = load(input)?;
+    let problems = validate(&doc);
+    for problem in &problems {
+        println!("{problem:?}");
+    }
+    let Some(output) = repaired else {
+        println!("{} problem(s)", problems.len());
+        return Ok(if problems.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::from(1)
+        });
+    };
+    let fixes = repair(&mut doc);
+    save(&store_for(doc), output, None)?;
+    println!(
+        "{} problem(s), {fixes} fix(es) written to {output}",
+        problems.len()
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+fn stats(input: &str) -> Result<ExitCode, String> {
+    let bytes = std::fs::metadata(input)
+        .map_err(|e| format!("{input}: {e}"))?
+        .len();
+    let doc = load(input)?;
+    let strokes = doc
+        .items
+        .iter()
+        .filter(|i| matches!(i, Item::Stroke(_)))
+        .count();
+    let points: usize = doc
+        .items
+        .iter()
+        .map(|i| match i {
+            Item::Stroke(s) => s.points.len(),
+            Item::Shape(_) => 0,
+        })
+        .sum();
+    println!("file:      {bytes} bytes, version {}", doc.version);
+    println!("items:     {}", doc.items.len());
+    println!("strokes:   {strokes} ({points} points)");
+    println!("shapes:    {}", doc.items.len() - strokes);
+    println!("layers:    {}", doc.meta.layers.len());
+    println!("revisions: {}", doc.meta.revisions.len());
+    match default_region(&doc) {
+        Some(r) => println!("bounds:    {} {} {} {}", r.min_x, r.min_y, r.max_x, r.max_y),
+        None => println!("bounds:    none"),
+    }
+    let store = store_for(doc);
+    let tags = store.all_tags();
+    if tags.is_empty() {
+        println!("tags:      none");
+    } else {
+        println!("tags:      {}", tags.join(", "));
+    }
+    println!("memory:    {} bytes", store.memory_footprint().total());
+    Ok(ExitCode::SUCCESS)
+}
+
+fn compare(old: &str, new: &str) -> Result<ExitCode, String> {
+    let d = diff(&load(old)?, &load(new)?);
+    let list = |ids: &[u64]| ids.iter().map(u64::to_string).collect::<Vec<_>>().join(" ");
+    println!("added:    {}", list(&d.added));
+    println!("removed:  {}", list(&d.removed));
+    println!("changed:  {}", list(&d.changed));
+    println!("reordered: {}", d.reordered);
+    println!("meta changed: {}", d.meta_changed);
+    Ok(if d.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
+    })
+}
@@ -0,0 +1,373 @@
+use crate::geometry::{closest_point_on_segment, item_bounds, rect_for_shape, Rect};
+use crate::model::{ColorRgba8, Item, Point, Shape, Stroke};
+use crate::render::render_arrows;
+use crate::store::{drawn_bounds, Document};
+
+/// WCAG 2.1 minimum contrast for graphical objects (SC 1.4.11).
+pub const WCAG_NON_TEXT_CONTRAST: f32 = 3.0;
+
+/// WCAG 2.1 AA minimum contrast for normal-size text (SC 1.4.3).
+pub const WCAG_AA_TEXT_CONTRAST: f32 = 4.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorRole {
+    Stroke,
+    Fill,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContrastIssue {
+    pub item_id: u64,
+    pub role: ColorRole,
+    pub color: ColorRgba8,
+    // Contrast of the composited color against the background.
+    pub ratio: f32,
+    // Closest color of the same hue and alpha that meets the threshold.
+    pub suggested: ColorRgba8,
+}
+
+/// Lists every stroke/fill color whose contrast against `background` is below `min_ratio`.
+///
+/// Translucent colors are evaluated as they appear composited over the background.
+pub fn contrast_report(
+    doc: &Document,
+    background: ColorRgba8,
+    min_ratio: f32,
+) -> Vec<ContrastIssue> {
+    let background = ColorRgba8 {
+        a: 255,
+        ..background
+    };
+    let mut out = Vec::new();
+    let mut check = |item_id: u64, role: ColorRole, color: ColorRgba8| {
+        let ratio = color.composite_over(background).contrast_ratio(background);
+        if ratio < min_ratio {
+            out.push(ContrastIssue {
+                item_id,
+                role,
+                color,
+                ratio,
+                suggested: adjust_for_contrast(color, background, min_ratio),
+            });
+        }
+    };
+    for item in &doc.items {
+        match item {
+            Item::Stroke(s) => check(s.id, ColorRole::Stroke, s.color),
+            Item::Shape(sh) => {
+                check(sh.id, ColorRole::Stroke, sh.style.stroke_color);
+                if sh.style.fill_enabled {
+                    check(sh.id, ColorRole::Fill, sh.style.fill_color);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Applies every suggestion from [`contrast_report`] in place and returns how
+/// many colors changed. Adjusted colors drop their palette reference.
+pub fn fix_contrast(doc: &mut Document, background: ColorRgba8, min_ratio: f32) -> usize {
+    let issues = contrast_report(doc, background, min_ratio);
+    for issue in &issues {
+        let Some(item) = doc.items.iter_mut().find(|it| it.id() == issue.item_id) else {
+            continue;
+        };
+        match (item, issue.role) {
+            (Item::Stroke(s), _) => {
+                s.color = issue.suggested;
+                s.color_ref = None;
+            }
+            (Item::Shape(sh), ColorRole::Stroke) => {
+                sh.style.stroke_color = issue.suggested;
+                sh.style.stroke_color_ref = None;
+            }
+            (Item::Shape(sh), ColorRole::Fill) => {
+                sh.style.fill_color = issue.suggested;
+                sh.style.fill_color_ref = None;
+            }
+        }
+    }
+    issues.len()
+}
+
+/// Moves `color`'s lightness away from the background until the composited
+/// contrast reaches `min_ratio`, keeping hue and alpha. Returns the extreme
+/// (black or white) when the threshold is unreachable.
+pub fn adjust_for_contrast(
+    color: ColorRgba8,
+    background: ColorRgba8,
+    min_ratio: f32,
+) -> ColorRgba8 {
+    use crate::color::{hsl_to_rgb, rgb_to_hsl};
+
+    let ratio_of = |c: ColorRgba8| c.composite_over(background).contrast_ratio(background);
+    if ratio_of(color) >= min_ratio {
+        return color;
+    }
+
+    let (h, s, l) = rgb_to_hsl(color);
+    let darken = background.relative_luminance() > 0.18;
+    let target = if darken { 0.0 } else { 1.0 };
+    let extreme = hsl_to_rgb(h, s, target, color.a);
+    if ratio_of(extreme) < min_ratio {
+        return extreme;
+    }
+
+    // Bisect on lightness between the original and the extreme.
+    let (mut ok, mut bad) = (target, l);
+    for _ in 0..24 {
+        let mid = (ok + bad) * 0.5;
+        if ratio_of(hsl_to_rgb(h, s, mid, color.a)) >= min_ratio {
+            ok = mid;
+        } else {
+            bad = mid;
+        }
+    }
+    hsl_to_rgb(h, s, ok, color.a)
+}
+
+/// A group of items drawn close together.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemCluster {
+    // Drawn bounds of all members.
+    pub bounds: Rect,
+    // Member ids in z-order.
+    pub ids: Vec<u64>,
+}
+
+/// Groups `items` so that any two whose drawn bounds come within `gap` of
+/// each other (directly or through other members) share a cluster, e.g. to
+/// export each annotation group as its own image. Clusters are ordered by
+/// their lowest member in z-order; items that draw nothing are skipped.
+pub fn cluster_items(items: &[Item], gap: f32) -> Vec<ItemCluster> {
+    let arrows = render_arrows(items);
+    let half = gap.max(0.0) * 0.5;
+    let bounds: Vec<(u64, Rect)> = items
+        .iter()
+        .filter_map(|item| Some((item.id(), drawn_bounds(item, &arrows)?)))
+        .collect();
+
+    // Union-find over items, joined when their grown bounds touch.
+    let mut parent: Vec<usize> = (0..bounds.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for i in 0..bounds.len() {
+        let a = bounds[i].1.inflate(half, half);
+        for (j, (_, b)) in bounds.iter().enumerate().skip(i + 1) {
+            if a.intersects(b.inflate(half, half)) {
+                let (ri, rj) = (root(&mut parent, i), root(&mut parent, j));
+                parent[ri.max(rj)] = ri.min(rj);
+            }
+        }
+    }
+
+    let mut clusters: Vec<(usize, ItemCluster)> = Vec::new();
+    for (i, (id, rect)) in bounds.iter().enumerate() {
+        let r = root(&mut parent, i);
+        match clusters.iter_mut().find(|(root, _)| *root == r) {
+            Some((_, cluster)) => {
+                cluster.bounds = cluster.bounds.union(*rect);
+                cluster.ids.push(*id);
+            }
+            None => clusters.push((
+                r,
+                ItemCluster {
+                    bounds: *rect,
+                    ids: vec![*id],
+                },
+            )),
+        }
+    }
+    clusters.into_iter().map(|(_, cluster)| cluster).collect()
+}
+
+/// An item that nearly repeats an earlier one in z-order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NearDuplicate {
+    pub original: u64,
+    // The later copy, which cleanup removes.
+    pub duplicate: u64,
+}
+
+/// Items that are near-identical overlapping copies of an earlier item, as
+/// left by double taps and repeated pastes: same kind, colors and text, and
+/// geometry (every point, and the stroke width) within `tolerance` document
+/// units. Each copy is paired with the earliest item it repeats.
+pub fn find_near_duplicates(doc: &Document, tolerance: f32) -> Vec<NearDuplicate> {
+    let tolerance = tolerance.max(0.0);
+    let bounds: Vec<Option<Rect>> = doc.items.iter().map(item_bounds).collect();
+    let mut out: Vec<NearDuplicate> = Vec::new();
+    for (j, later) in doc.items.iter().enumerate() {
+        let Some(bj) = bounds[j] else { continue };
+        let original = doc.items[..j].iter().zip(&bounds).find(|(earlier, bi)| {
+            !out.iter().any(|d| d.duplicate == earlier.id())
+                && bi.is_some_and(|bi| bi.inflate(tolerance, tolerance).intersects(bj))
+                && nearly_equal(earlier, later, tolerance)
+        });
+        if let Some((earlier, _)) = original {
+            out.push(NearDuplicate {
+                original: earlier.id(),
+                duplicate: later.id(),
+            });
+        }
+    }
+    out
+}
+
+fn nearly_equal(a: &Item, b: &Item, tolerance: f32) -> bool {
+    match (a, b) {
+        (Item::Stroke(a), Item::Stroke(b)) => strokes_match(a, b, tolerance),
+        (Item::Shape(a), Item::Shape(b)) => shapes_match(a, b, tolerance),
+        _ => false,
+    }
+}
+
+fn strokes_match(a: &Stroke, b: &Stroke, tolerance: f32) -> bool {
+    a.color == b.color
+        && a.blend_mode == b.blend_mode
+        && (a.width - b.width).abs() <= tolerance
+        && covers(&a.points, &b.points, tolerance)
+        && covers(&b.points, &a.points, tolerance)
+}
+
+fn shapes_match(a: &Shape, b: &Shape, tolerance: f32) -> bool {
+    let near = |p: Point, q: Point| (p.x - q.x).hypot(p.y - q.y) <= tolerance;
+    // How far the farthest corner moves for the rotation difference.
+    let reach = {
+        let r = rect_for_shape(a);
+        r.width().hypot(r.height()) * 0.5
+    };
+    a.kind == b.kind
+        && a.text == b.text
+        && a.style.stroke_color == b.style.stroke_color
+        && a.style.fill_enabled == b.style.fill_enabled
+        && (!a.style.fill_enabled || a.style.fill_color == b.style.fill_color)
+        && (a.style.stroke_width - b.style.stroke_width).abs() <= tolerance
+        && (a.start_attach_id, a.end_attach_id) == (b.start_attach_id, b.end_attach_id)
+        && near(a.start, b.start)
+        && near(a.end, b.end)
+        && (a.rotation - b.rotation).abs() * reach <= tolerance
+}
+
+// Whether every point of `points` lies within `tolerance` of the polyline
+// `line`.
+fn covers(points: &[Point], line: &[Point], tolerance: f32) -> bool {
+    let distance = |p: Point| match line {
+        [] => f32::INFINITY,
+        [only] => (p.x - only.x).hypot(p.y - only.y),
+        _ => line
+            .windows(2)
+            .map(|w| {
+                let c = closest_point_on_segment(p, w[0], w[1]);
+                (p.x - c.x).hypot(p.y - c.y)
+            })
+            .fold(f32::INFINITY, f32::min),
+    };
+    points.iter().all(|p| distance(*p) <= tolerance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Point;
+    use crate::store::Store;
+    use crate::test_util::black;
+
+    #[test]
+    fn flags_and_fixes_low_contrast_strokes() {
+        let white = ColorRgba8 {
+            r: 255,
+            g: 255,
+            b: 255,
+            a: 255,
+        };
+        let pale_yellow = ColorRgba8 {
+            r: 255,
+            g: 240,
+            b: 120,
+            a: 255,
+        };
+        let mut store = Store::new();
+        let s = store.begin_stroke(pale_yellow, 3.0, Point { x: 0.0, y: 0.0 });
+        store.commit_stroke(s).unwrap();
+
+        let mut doc = store.document();
+        let report = contrast_report(&doc, white, WCAG_NON_TEXT_CONTRAST);
+        assert_eq!(report.len(), 1);
+        assert!(report[0].ratio < WCAG_NON_TEXT_CONTRAST);
+
+        assert_eq!(fix_contrast(&mut doc, white, WCAG_NON_TEXT_CONTRAST), 1);
+        assert!(contrast_report(&doc, white, WCAG_NON_TEXT_CONTRAST).is_empty());
+    }
+
+    #[test]
+    fn clusters_items_within_the_gap() {
+        let black = black();
+        let mut store = Store::new();
+        let mut ids = Vec::new();
+        // Two strokes 8 apart (edge to edge, with width 2), one far away,
+        // and a fourth linking back to the first only through the second.
+        for (x0, x1) in [(0.0, 10.0), (20.0, 30.0), (500.0, 510.0), (40.0, 50.0)] {
+            let mut s = store.begin_stroke(black, 2.0, Point { x: x0, y: 0.0 });
+            s.points.push(Point { x: x1, y: 0.0 });
+            ids.push(s.id);
+            store.commit_stroke(s).unwrap();
+        }
+
+        let clusters = cluster_items(store.items(), 8.0);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].ids, [ids[0], ids[1], ids[3]]);
+        assert_eq!(clusters[0].bounds.min_x, -1.0);
+        assert_eq!(clusters[0].bounds.max_x, 51.0);
+        assert_eq!(clusters[1].ids, [ids[2]]);
+        assert_eq!(cluster_items(store.items(), 7.0).len(), 4);
+    }
+
+    #[test]
+    fn finds_overlapping_near_copies() {
+        let black = black();
+        let red = ColorRgba8 { r: 255, ..black };
+        let mut store = Store::new();
+        let mut add = |color, points: &[(f32, f32)]| {
+            let mut s = store.begin_stroke(color, 2.0, Point { x: 0.0, y: 0.0 });
+            s.points = points.iter().map(|&(x, y)| Point { x, y }).collect();
+            let id = s.id;
+            store.commit_stroke(s).unwrap();
+            id
+        };
+        let first = add(black, &[(0.0, 0.0), (50.0, 0.0), (100.0, 0.0)]);
+        // A double tap: fewer points, half a unit off.
+        let copy = add(black, &[(0.5, 0.5), (100.0, 0.5)]);
+        let third = add(black, &[(0.0, 0.0), (100.0, 0.0)]);
+        add(red, &[(0.0, 0.0), (100.0, 0.0)]);
+        add(black, &[(0.0, 0.0), (100.0, 20.0)]);
+
+        let found = find_near_duplicates(&store.document(), 1.0);
+        assert_eq!(
+            found,
+            [
+                NearDuplicate {
+                    original: first,
+                    duplicate: copy
+                },
+                NearDuplicate {
+                    original: first,
+                    duplicate: third
+                },
+            ]
+        );
+        assert_eq!(find_near_duplicates(&store.document(), 0.1).len(), 1);
+
+        let copies: Vec<u64> = found.iter().map(|d| d.duplicate).collect();
+        assert!(store.remove_items(&copies));
+        assert!(find_near_duplicates(&store.document(), 1.0).is_empty());
+        store.undo().unwrap();
+        assert_eq!(store.items().len(), 5);
+    }
+}
@@ -0,0 +1,210 @@
+//! Tweening between two versions of a document.
+
+use crate::model::{ArcAngles, ColorRgba8, CornerRadii, Item, Point, Shape, ShapeStyle, Stroke};
+use crate::store::Document;
+use std::collections::HashMap;
+use std::f32::consts::{PI, TAU};
+
+/// The document `t` of the way from `a` (t = 0) to `b` (t = 1).
+///
+/// Items are matched by id. Matched items interpolate geometry, widths,
+/// colors and rotation; discrete fields (kind, text, attachments, ...) switch
+/// from `a` to `b` at the halfway point. Items only in `a` fade out and items
+/// only in `b` fade in. Document-level state is taken from `b`.
+pub fn interpolate(a: &Document, b: &Document, t: f32) -> Document {
+    let t = t.clamp(0.0, 1.0);
+    let from: HashMap<u64, &Item> = a.items.iter().map(|i| (i.id(), i)).collect();
+    let mut items: Vec<Item> = b
+        .items
+        .iter()
+        .map(|item| match from.get(&item.id()) {
+            Some(old) => interpolate_item(old, item, t),
+            None => fade(item, t),
+        })
+        .collect();
+    let to: HashMap<u64, &Item> = b.items.iter().map(|i| (i.id(), i)).collect();
+    items.extend(
+        a.items
+            .iter()
+            .filter(|item| !to.contains_key(&item.id()))
+            .map(|item| fade(item, 1.0 - t)),
+    );
+    Document {
+        version: b.version,
+        preview: None,
+        items,
+        meta: b.meta.clone(),
+    }
+}
+
+fn interpolate_item(a: &Item, b: &Item, t: f32) -> Item {
+    match (a, b) {
+        (Item::Stroke(sa), Item::Stroke(sb)) => Item::Stroke(interpolate_stroke(sa, sb, t)),
+        (Item::Shape(sa), Item::Shape(sb)) => Item::Shape(interpolate_shape(sa, sb, t)),
+        // The item changed type: nothing to tween.
+        _ => if t < 0.5 { a } else { b }.clone(),
+    }
+}
+
+fn interpolate_stroke(a: &Stroke, b: &Stroke, t: f32) -> Stroke {
+    let n = a.points.len().max(b.points.len());
+    let (pa, pb) = (resample(&a.points, n), resample(&b.points, n));
+    let base = if t < 0.5 { a } else { b };
+    Stroke {
+        color: a.color.lerp(b.color, t),
+        width: lerp(a.width, b.width, t),
+        points: pa
+            .iter()
+            .zip(&pb)
+            .map(|(p, q)| lerp_point(*p, *q, t))
+            .collect(),
+        ..base.clone()
+    }
+}
+
+fn interpolate_shape(a: &Shape, b: &Shape, t: f32) -> Shape {
+    let base = if t < 0.5 { a } else { b };
+    let mut delta = (b.rotation - a.rotation) % TAU;
+    if delta > PI {
+        delta -= TAU;
+    } else if delta < -PI {
+        delta += TAU;
+    }
+    Shape {
+        start: lerp_point(a.start, b.start, t),
+        end: lerp_point(a.end, b.end, t),
+        rotation: a.rotation + delta * t,
+        style: ShapeStyle {
+            stroke_color: a.style.stroke_color.lerp(b.style.stroke_color, t),
+            stroke_width: lerp(a.style.stroke_width, b.style.stroke_width, t),
+            fill_color: a.style.fill_color.lerp(b.style.fill_color, t),
+            corner_radius: lerp(a.style.corner_radius, b.style.corner_radius, t),
+            corner_radii: match (a.style.corner_radii, b.style.corner_radii) {
+                (None, None) => None,
+                _ => {
+                    let (ra, rb) = (a.style.radii().to_array(), b.style.radii().to_array());
+                    let [top_left, top_right, bottom_right, bottom_left] =
+                        [0, 1, 2, 3].map(|i| lerp(ra[i], rb[i], t));
+                    Some(CornerRadii {
+                        top_left,
+                        top_right,
+                        bottom_right,
+                        bottom_left,
+                    })
+                }
+            },
+            curvature: match (a.style.curvature, b.style.curvature) {
+                (None, None) => None,
+                _ => Some(lerp(a.style.curvature(), b.style.curvature(), t)),
+            },
+            ..base.style
+        },
+        angles: match (a.angles, b.angles) {
+            (None, None) => None,
+            (from, to) => {
+                let (from, to) = (
+                    from.unwrap_or(ArcAngles::FULL),
+                    to.unwrap_or(ArcAngles::FULL),
+                );
+                Some(ArcAngles {
+                    start: lerp(from.start, to.start, t),
+                    end: lerp(from.end, to.end, t),
+                })
+            }
+        },
+        ..base.clone()
+    }
+}
+
+// Scales the item's alpha by `opacity`.
+fn fade(item: &Item, opacity: f32) -> Item {
+    let scale = |c: ColorRgba8| ColorRgba8 {
+        a: (c.a as f32 * opacity).round() as u8,
+        ..c
+    };
+    let mut item = item.clone();
+    match &mut item {
+        Item::Stroke(s) => s.color = scale(s.color),
+        Item::Shape(sh) => {
+            sh.style.stroke_color = scale(sh.style.stroke_color);
+            sh.style.fill_color = scale(sh.style.fill_color);
+        }
+    }
+    item
+}
+
+// Resamples a polyline to `n` points evenly spaced by index.
+fn resample(points: &[Point], n: usize) -> Vec<Point> {
+    if points.len() == n || points.is_empty() {
+        return points.to_vec();
+    }
+    if points.len() == 1 || n == 1 {
+        return vec![points[0]; n];
+    }
+    let last = (points.len() - 1) as f32;
+    (0..n)
+        .map(|i| {
+            let f = i as f32 / (n - 1) as f32 * last;
+            let k = (f.floor() as usize).min(points.len() - 2);
+            lerp_point(points[k], points[k + 1], f - k as f32)
+        })
+        .collect()
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn lerp_point(a: Point, b: Point, t: f32) -> Point {
+    Point {
+        x: lerp(a.x, b.x, t),
+        y: lerp(a.y, b.y, t),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ShapeKind;
+    use crate::store::Store;
+    use crate::test_util::{black, style};
+
+    #[test]
+    fn interpolates_matched_items_and_fades_the_rest() {
+        let mut store = Store::new();
+        let black = black();
+        let style = style();
+        let mut rect = store.begin_shape(ShapeKind::Rectangle, style, Point { x: 0.0, y: 0.0 });
+        rect.end = Point { x: 10.0, y: 10.0 };
+        let rect_id = rect.id;
+        store.commit_shape(rect).unwrap();
+        let gone = store.begin_stroke(black, 2.0, Point { x: 0.0, y: 0.0 });
+        store.commit_stroke(gone).unwrap();
+        let a = store.document();
+
+        store.nudge(&[rect_id], 100.0, 0.0, 0);
+        let mut s = store.begin_stroke(black, 2.0, Point { x: 5.0, y: 5.0 });
+        s.points.push(Point { x: 6.0, y: 5.0 });
+        let added = s.id;
+        store.commit_stroke(s).unwrap();
+        store.erase_at(Point { x: 0.0, y: 0.0 }, 0.5);
+        let b = store.document();
+
+        let mid = interpolate(&a, &b, 0.25);
+        let Item::Shape(sh) = &mid.items[0] else {
+            panic!("expected the rectangle first");
+        };
+        assert_eq!(sh.start, Point { x: 25.0, y: 0.0 });
+        assert_eq!(sh.end, Point { x: 35.0, y: 10.0 });
+        let Item::Stroke(new) = &mid.items[1] else {
+            panic!("expected the added stroke");
+        };
+        assert_eq!((new.id, new.color.a), (added, 64));
+        let Item::Stroke(old) = &mid.items[2] else {
+            panic!("expected the removed stroke last");
+        };
+        assert_eq!(old.color.a, 191);
+
+        assert_eq!(interpolate(&a, &b, 1.0).items[..2], b.items[..]);
+    }
+}
@@ -0,0 +1,217 @@
+//! Compact encodings used when serializing documents.
+
+use crate::model::Point;
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Grid that compact stroke points are quantized to, in document units.
+pub const POINT_STEP: f32 = 1.0 / 64.0;
+
+/// Stroke points as quantized deltas: `delta` holds `x, y` of the first point
+/// in steps, then per-point differences. Quantizing absolute positions keeps
+/// the running sums exact, so error never exceeds half a step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct CompactPoints {
+    step: f32,
+    delta: Vec<i64>,
+}
+
+impl CompactPoints {
+    /// Encodes `points`, or `None` if any coordinate is not finite.
+    pub(crate) fn encode(points: &[Point], step: f32) -> Option<Self> {
+        let mut delta = Vec::with_capacity(points.len() * 2);
+        let (mut px, mut py) = (0i64, 0i64);
+        for p in points {
+            if !(p.x.is_finite() && p.y.is_finite()) {
+                return None;
+            }
+            let x = (p.x as f64 / step as f64).round() as i64;
+            let y = (p.y as f64 / step as f64).round() as i64;
+            delta.push(x - px);
+            delta.push(y - py);
+            (px, py) = (x, y);
+        }
+        Some(Self { step, delta })
+    }
+
+    /// Decodes the points, refusing a malformed encoding instead of
+    /// producing garbage or overflowing.
+    fn decode(&self) -> Result<Vec<Point>, &'static str> {
+        if !self.delta.len().is_multiple_of(2) {
+            return Err("compact points need an even number of deltas");
+        }
+        if !(self.step.is_finite() && self.step > 0.0) {
+            return Err("compact points need a finite positive step");
+        }
+        let (mut x, mut y) = (0i64, 0i64);
+        self.delta
+            .chunks_exact(2)
+            .map(|d| {
+                x = x.checked_add(d[0]).ok_or("compact point out of range")?;
+                y = y.checked_add(d[1]).ok_or("compact point out of range")?;
+                let p = Point {
+                    x: (x as f64 * self.step as f64) as f32,
+                    y: (y as f64 * self.step as f64) as f32,
+                };
+                if !(p.x.is_finite() && p.y.is_finite()) {
+                    return Err("compact point out of range");
+                }
+                Ok(p)
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PointsRepr {
+    Plain(Vec<Point>),
+    Compact(CompactPoints),
+}
+
+/// Reads stroke points in either the plain or the compact form.
+pub(crate) fn deserialize_points<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Point>, D::Error> {
+    match PointsRepr::deserialize(d)? {
+        PointsRepr::Plain(points) => Ok(points),
+        PointsRepr::Compact(compact) => compact.decode().map_err(serde::de::Error::custom),
+    }
+}
+
+/// Standard base64 (RFC 4648, padded) for binary fields, e.g.
+/// `#[serde(with = "crate::codec::base64")]`.
+pub(crate) mod base64 {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    pub(crate) fn encode(bytes: &[u8]) -> String {
+        let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+        for chunk in bytes.chunks(3) {
+            let b = [
+                chunk[0],
+                *chunk.get(1).unwrap_or(&0),
+                *chunk.get(2).unwrap_or(&0),
+            ];
+            let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+                } else {
+                    out.push('=');
+                }
+            }
+        }
+        out
+    }
+
+    /// `None` if `text` is not valid padded base64.
+    pub(crate) fn decode(text: &str) -> Option<Vec<u8>> {
+        let text = text.as_bytes();
+        if !text.len().is_multiple_of(4) {
+            return None;
+        }
+        let mut out = Vec::with_capacity(text.len() / 4 * 3);
+        for (index, chunk) in text.chunks(4).enumerate() {
+            let last = index + 1 == text.len() / 4;
+            let pad = chunk.iter().rev().take_while(|c| **c == b'=').count();
+            if pad > 2 || (pad > 0 && !last) {
+                return None;
+            }
+            let mut n = 0u32;
+            for c in &chunk[..4 - pad] {
+                let v = ALPHABET.iter().position(|a| a == c)? as u32;
+                n = n << 6 | v;
+            }
+            n <<= 6 * pad as u32;
+            out.extend_from_slice(&n.to_be_bytes()[1..4 - pad]);
+        }
+        Some(out)
+    }
+
+    pub(crate) fn serialize<S: Serializer>(bytes: &[u8], s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&encode(bytes))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(d)?;
+        decode(&text).ok_or_else(|| D::Error::custom("invalid base64"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Item;
+    use crate::store::{Document, Store};
+    use crate::test_util::black;
+
+    #[test]
+    fn base64_round_trips_and_rejects_garbage() {
+        assert_eq!(base64::encode(b"Man"), "TWFu");
+        assert_eq!(base64::encode(b"Ma"), "TWE=");
+        assert_eq!(base64::encode(b"M"), "TQ==");
+        for len in 0..8 {
+            let bytes: Vec<u8> = (0..len).map(|i: u8| i.wrapping_mul(37) ^ 0xc8).collect();
+            assert_eq!(base64::decode(&base64::encode(&bytes)), Some(bytes));
+        }
+        assert_eq!(base64::decode("TQ="), None);
+        assert_eq!(base64::decode("T=Q="), None);
+        assert_eq!(base64::decode("TQ==TWFu"), None);
+        assert_eq!(base64::decode("TW-u"), None);
+    }
+
+    #[test]
+    fn compact_points_round_trip_within_half_a_step() {
+        let mut store = Store::new();
+        let c = black();
+        let mut s = store.begin_stroke(c, 2.0, Point { x: -3.3, y: 1000.7 });
+        s.points.extend((0..500).map(|i| Point {
+            x: (i as f32 * 0.37).sin() * 40.0 + i as f32,
+            y: 1000.0 + (i as f32 * 0.11).cos() * 25.0,
+        }));
+        let original = s.points.clone();
+        store.commit_stroke(s).unwrap();
+
+        let plain = store.to_json().unwrap();
+        let compact = store.to_json_compact().unwrap();
+        assert!(compact.len() * 2 < plain.len());
+
+        let doc = Store::from_json(&compact).unwrap();
+        assert_eq!(doc.version, Document::COMPACT_VERSION);
+        let Item::Stroke(loaded) = &doc.items[0] else {
+            panic!("expected a stroke");
+        };
+        assert_eq!(loaded.points.len(), original.len());
+        for (a, b) in loaded.points.iter().zip(&original) {
+            assert!((a.x - b.x).abs() <= POINT_STEP * 0.5 + 1e-4);
+            assert!((a.y - b.y).abs() <= POINT_STEP * 0.5 + 1e-4);
+        }
+        // Plain documents still load.
+        let Item::Stroke(again) = &Store::from_json(&plain).unwrap().items[0] else {
+            panic!("expected a stroke");
+        };
+        assert_eq!(again.points, original);
+    }
+
+    #[test]
+    fn malformed_compact_points_are_refused() {
+        #[derive(Debug, Deserialize)]
+        struct Points {
+            #[serde(deserialize_with = "deserialize_points")]
+            points: Vec<Point>,
+        }
+        let parse = |step: &str, delta: &str| {
+            let json = format!(r#"{{"points":{{"step":{step},"delta":[{delta}]}}}}"#);
+            serde_json::from_str::<Points>(&json)
+        };
+        assert_eq!(
+            parse("0.5", "2,4,1,-1").unwrap().points,
+            [Point { x: 1.0, y: 2.0 }, Point { x: 1.5, y: 1.5 }]
+        );
+        assert!(parse("0.5", "2,4,1").is_err());
+        assert!(parse("0", "2,4").is_err());
+        assert!(parse("-0.5", "2,4").is_err());
+        assert!(parse("1e39", "2,4").is_err());
+        assert!(parse("0.5", &format!("{},0,1,0", i64::MAX)).is_err());
+        assert!(parse("1e30", &format!("{},0", i64::MAX)).is_err());
+    }
+}
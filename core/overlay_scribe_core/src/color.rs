@@ -0,0 +1,325 @@
+use crate::model::ColorRgba8;
+
+impl ColorRgba8 {
+    /// Per-channel linear blend from `self` (t = 0) to `other` (t = 1).
+    pub fn lerp(self, other: ColorRgba8, t: f32) -> ColorRgba8 {
+        let mix = |a: u8, b: u8| {
+            (a as f32 + (b as f32 - a as f32) * t)
+                .round()
+                .clamp(0.0, 255.0) as u8
+        };
+        ColorRgba8 {
+            r: mix(self.r, other.r),
+            g: mix(self.g, other.g),
+            b: mix(self.b, other.b),
+            a: mix(self.a, other.a),
+        }
+    }
+
+    /// WCAG relative luminance of the color (alpha ignored), in `[0, 1]`.
+    pub fn relative_luminance(self) -> f32 {
+        fn channel(v: u8) -> f32 {
+            let c = v as f32 / 255.0;
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        }
+        0.2126 * channel(self.r) + 0.7152 * channel(self.g) + 0.0722 * channel(self.b)
+    }
+
+    /// WCAG contrast ratio between two opaque colors, in `[1, 21]`.
+    pub fn contrast_ratio(self, other: ColorRgba8) -> f32 {
+        let a = self.relative_luminance();
+        let b = other.relative_luminance();
+        let (hi, lo) = if a >= b { (a, b) } else { (b, a) };
+        (hi + 0.05) / (lo + 0.05)
+    }
+
+    /// Opaque black or white, whichever contrasts more with `self`, at
+    /// `self`'s alpha. Used for casings under ink.
+    pub fn contrasting(self) -> ColorRgba8 {
+        let black = ColorRgba8 {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: self.a,
+        };
+        let white = ColorRgba8 {
+            r: 255,
+            g: 255,
+            b: 255,
+            a: self.a,
+        };
+        if self.contrast_ratio(white) >= self.contrast_ratio(black) {
+            white
+        } else {
+            black
+        }
+    }
+
+    /// The opaque color seen when `self` is drawn over an opaque `background`.
+    pub fn composite_over(self, background: ColorRgba8) -> ColorRgba8 {
+        let a = self.a as f32 / 255.0;
+        let mix = |fg: u8, bg: u8| (fg as f32 * a + bg as f32 * (1.0 - a)).round() as u8;
+        ColorRgba8 {
+            r: mix(self.r, background.r),
+            g: mix(self.g, background.g),
+            b: mix(self.b, background.b),
+            a: 255,
+        }
+    }
+
+    /// Mirrors HSL lightness while keeping hue, saturation and alpha, so dark
+    /// ink becomes light ink of the same hue (and vice versa).
+    pub fn invert_lightness(self) -> ColorRgba8 {
+        let (h, s, l) = rgb_to_hsl(self);
+        hsl_to_rgb(h, s, 1.0 - l, self.a)
+    }
+
+    /// Parses `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa` (the `#` is
+    /// optional, digits are case-insensitive). Missing alpha is opaque.
+    pub fn from_hex(text: &str) -> Option<ColorRgba8> {
+        let digits = text.trim();
+        let digits = digits.strip_prefix('#').unwrap_or(digits);
+        if !digits.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize, len: usize| {
+            let v = u8::from_str_radix(digits.get(i * len..(i + 1) * len)?, 16).ok()?;
+            Some(if len == 1 { v * 17 } else { v })
+        };
+        let len = match digits.len() {
+            3 | 4 => 1,
+            6 | 8 => 2,
+            _ => return None,
+        };
+        Some(ColorRgba8 {
+            r: channel(0, len)?,
+            g: channel(1, len)?,
+            b: channel(2, len)?,
+            a: if digits.len() / len == 4 {
+                channel(3, len)?
+            } else {
+                255
+            },
+        })
+    }
+
+    /// Lowercase `#rrggbb`, or `#rrggbbaa` when not opaque.
+    pub fn to_hex(self) -> String {
+        let mut hex = format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b);
+        if self.a < 255 {
+            hex.push_str(&format!("{:02x}", self.a));
+        }
+        hex
+    }
+
+    /// (hue in degrees `[0, 360)`, saturation `[0, 1]`, lightness `[0, 1]`);
+    /// alpha is ignored.
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        rgb_to_hsl(self)
+    }
+
+    /// The color at `h` degrees, saturation `s` and lightness `l` (both
+    /// clamped to `[0, 1]`), with alpha `a`.
+    pub fn from_hsl(h: f32, s: f32, l: f32, a: u8) -> ColorRgba8 {
+        hsl_to_rgb(h, s, l, a)
+    }
+
+    /// (hue in degrees `[0, 360)`, saturation `[0, 1]`, value `[0, 1]`), as
+    /// color pickers show it; alpha is ignored.
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let (h, s, l) = rgb_to_hsl(self);
+        let v = l + s * l.min(1.0 - l);
+        let sv = if v > 0.0 { 2.0 * (1.0 - l / v) } else { 0.0 };
+        (h, sv.clamp(0.0, 1.0), v.clamp(0.0, 1.0))
+    }
+
+    /// The color at `h` degrees, saturation `s` and value `v` (both clamped
+    /// to `[0, 1]`), with alpha `a`.
+    pub fn from_hsv(h: f32, s: f32, v: f32, a: u8) -> ColorRgba8 {
+        let (s, v) = (s.clamp(0.0, 1.0), v.clamp(0.0, 1.0));
+        let l = v * (1.0 - s * 0.5);
+        let sl = if l > 0.0 && l < 1.0 {
+            (v - l) / l.min(1.0 - l)
+        } else {
+            0.0
+        };
+        hsl_to_rgb(h, sl, l, a)
+    }
+
+    /// Raises HSL lightness by `amount` (`0.1` is ten points), keeping hue,
+    /// saturation and alpha.
+    pub fn lighten(self, amount: f32) -> ColorRgba8 {
+        let (h, s, l) = rgb_to_hsl(self);
+        hsl_to_rgb(h, s, l + amount, self.a)
+    }
+
+    /// Lowers HSL lightness by `amount`; see [`ColorRgba8::lighten`].
+    pub fn darken(self, amount: f32) -> ColorRgba8 {
+        self.lighten(-amount)
+    }
+
+    /// The channels multiplied by alpha, as premultiplied surfaces store them.
+    pub fn premultiplied(self) -> ColorRgba8 {
+        let scale = |v: u8| ((v as u32 * self.a as u32 + 127) / 255) as u8;
+        ColorRgba8 {
+            r: scale(self.r),
+            g: scale(self.g),
+            b: scale(self.b),
+            a: self.a,
+        }
+    }
+
+    /// Undoes [`ColorRgba8::premultiplied`], e.g. for a pixel read back by
+    /// an eyedropper. Fully transparent pixels come back transparent black.
+    pub fn unpremultiplied(self) -> ColorRgba8 {
+        if self.a == 0 {
+            return ColorRgba8 {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 0,
+            };
+        }
+        let scale = |v: u8| ((v as u32 * 255 + self.a as u32 / 2) / self.a as u32).min(255) as u8;
+        ColorRgba8 {
+            r: scale(self.r),
+            g: scale(self.g),
+            b: scale(self.b),
+            a: self.a,
+        }
+    }
+}
+
+/// A document-wide color substitution, e.g. for re-theming an overlay drawn
+/// on a light screenshot so it stays readable on a dark background.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ThemeMap {
+    /// Explicit substitutions matched on RGB; alpha of the original is kept.
+    /// Colors without an entry are left untouched.
+    Table(Vec<(ColorRgba8, ColorRgba8)>),
+    /// Built-in dark/light transform, see [`ColorRgba8::invert_lightness`].
+    InvertLightness,
+}
+
+impl ThemeMap {
+    pub fn map(&self, color: ColorRgba8) -> ColorRgba8 {
+        match self {
+            ThemeMap::Table(entries) => entries
+                .iter()
+                .find(|(from, _)| from.r == color.r && from.g == color.g && from.b == color.b)
+                .map(|(_, to)| ColorRgba8 { a: color.a, ..*to })
+                .unwrap_or(color),
+            ThemeMap::InvertLightness => color.invert_lightness(),
+        }
+    }
+}
+
+// Returns (hue in degrees [0, 360), saturation [0, 1], lightness [0, 1]).
+pub(crate) fn rgb_to_hsl(c: ColorRgba8) -> (f32, f32, f32) {
+    let r = c.r as f32 / 255.0;
+    let g = c.g as f32 / 255.0;
+    let b = c.b as f32 / 255.0;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) * 0.5;
+    let d = max - min;
+    if d <= f32::EPSILON {
+        return (0.0, 0.0, l);
+    }
+    let s = d / (1.0 - (2.0 * l - 1.0).abs());
+    let h = if max == r {
+        60.0 * ((g - b) / d).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / d + 2.0)
+    } else {
+        60.0 * ((r - g) / d + 4.0)
+    };
+    (h, s.clamp(0.0, 1.0), l)
+}
+
+pub(crate) fn hsl_to_rgb(h: f32, s: f32, l: f32, a: u8) -> ColorRgba8 {
+    let s = s.clamp(0.0, 1.0);
+    let l = l.clamp(0.0, 1.0);
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let hp = h.rem_euclid(360.0) / 60.0;
+    let x = c * (1.0 - (hp.rem_euclid(2.0) - 1.0).abs());
+    let (r1, g1, b1) = match hp as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = l - c * 0.5;
+    let to_u8 = |v: f32| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    ColorRgba8 {
+        r: to_u8(r1),
+        g: to_u8(g1),
+        b: to_u8(b1),
+        a,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_hsl_hsv_and_premultiplication_round_trip() {
+        let orange = ColorRgba8 {
+            r: 255,
+            g: 136,
+            b: 0,
+            a: 255,
+        };
+        assert_eq!(ColorRgba8::from_hex("#FF8800"), Some(orange));
+        assert_eq!(ColorRgba8::from_hex("f80"), Some(orange));
+        assert_eq!(
+            ColorRgba8::from_hex("#ff880080"),
+            Some(ColorRgba8 { a: 128, ..orange })
+        );
+        assert_eq!(
+            ColorRgba8::from_hex("#ff88"),
+            Some(ColorRgba8 {
+                a: 136,
+                r: 255,
+                g: 255,
+                b: 136
+            })
+        );
+        assert_eq!(ColorRgba8::from_hex("#ff88g0"), None);
+        assert_eq!(
+            ColorRgba8::from_hex("#ff8"),
+            Some(ColorRgba8 {
+                r: 255,
+                g: 255,
+                b: 136,
+                a: 255
+            })
+        );
+        assert_eq!(ColorRgba8::from_hex("#ff88000"), None);
+        assert_eq!(orange.to_hex(), "#ff8800");
+        assert_eq!(ColorRgba8 { a: 128, ..orange }.to_hex(), "#ff880080");
+
+        let (h, s, v) = orange.to_hsv();
+        assert!((h - 32.0).abs() < 0.1 && (s - 1.0).abs() < 1e-4 && (v - 1.0).abs() < 1e-4);
+        assert_eq!(ColorRgba8::from_hsv(h, s, v, 255), orange);
+        let (h, s, l) = orange.to_hsl();
+        assert_eq!(ColorRgba8::from_hsl(h, s, l, 255), orange);
+        assert_eq!(orange.lighten(1.0).to_hex(), "#ffffff");
+        assert!((orange.darken(0.25).to_hsl().2 - (l - 0.25)).abs() < 0.01);
+
+        let half = ColorRgba8 { a: 128, ..orange };
+        let pre = half.premultiplied();
+        assert_eq!((pre.r, pre.g, pre.b), (128, 68, 0));
+        // Premultiplying drops precision, so the round trip is within one.
+        let back = pre.unpremultiplied();
+        assert_eq!((back.r, back.b, back.a), (255, 0, 128));
+        assert!(back.g.abs_diff(136) <= 1);
+    }
+}
@@ -0,0 +1,226 @@
+//! Comparison of two document versions: item diffs and onion skins.
+
+use crate::model::{ColorRgba8, Item};
+use crate::render::{render_arrows, ArrowRender};
+use crate::store::Document;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OnionSkinOptions {
+    // Color the older version is blended toward, and by how much (0..=1).
+    pub tint: ColorRgba8,
+    pub tint_amount: f32,
+    // Alpha multiplier for the older version.
+    pub ghost_opacity: f32,
+    // Leave out older items identical in the newer version.
+    pub hide_unchanged: bool,
+}
+
+impl Default for OnionSkinOptions {
+    fn default() -> Self {
+        Self {
+            tint: ColorRgba8 {
+                r: 255,
+                g: 64,
+                b: 64,
+                a: 255,
+            },
+            tint_amount: 0.6,
+            ghost_opacity: 0.35,
+            hide_unchanged: true,
+        }
+    }
+}
+
+/// Render primitives for an older version ghosted under a newer one. Draw
+/// the ghost layer first. Arrows are routed within their own version, so
+/// each layer's arrows resolve attachments against that version's items.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnionSkin {
+    pub ghost: Vec<Item>,
+    pub ghost_arrows: Vec<ArrowRender>,
+    pub current: Vec<Item>,
+    pub current_arrows: Vec<ArrowRender>,
+}
+
+pub fn onion_skin(older: &Document, newer: &Document, options: &OnionSkinOptions) -> OnionSkin {
+    let newer_by_id: HashMap<u64, &Item> = newer.items.iter().map(|i| (i.id(), i)).collect();
+    let shown = |item: &&Item| !options.hide_unchanged || newer_by_id.get(&item.id()) != Some(item);
+    let ghost_ids: HashSet<u64> = older.items.iter().filter(shown).map(Item::id).collect();
+
+    let paint = |c: ColorRgba8| {
+        let tinted = c.lerp(options.tint, options.tint_amount.clamp(0.0, 1.0));
+        ColorRgba8 {
+            a: (c.a as f32 * options.ghost_opacity.clamp(0.0, 1.0)).round() as u8,
+            ..tinted
+        }
+    };
+    let ghost = older
+        .items
+        .iter()
+        .filter(|item| ghost_ids.contains(&item.id()))
+        .map(|item| {
+            let mut item = item.clone();
+            match &mut item {
+                Item::Stroke(s) => s.color = paint(s.color),
+                Item::Shape(sh) => {
+                    sh.style.stroke_color = paint(sh.style.stroke_color);
+                    sh.style.fill_color = paint(sh.style.fill_color);
+                }
+            }
+            item
+        })
+        .collect();
+    let ghost_arrows = render_arrows(&older.items)
+        .into_iter()
+        .filter(|r| ghost_ids.contains(&r.shape_id))
+        .map(|mut r| {
+            r.style.stroke_color = paint(r.style.stroke_color);
+            r.style.fill_color = paint(r.style.fill_color);
+            r
+        })
+        .collect();
+    OnionSkin {
+        ghost,
+        ghost_arrows,
+        current: newer.items.clone(),
+        current_arrows: render_arrows(&newer.items),
+    }
+}
+
+/// Item-level differences between two documents, by id. Each list is in
+/// the order the items appear in their document.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocumentDiff {
+    pub added: Vec<u64>,
+    pub removed: Vec<u64>,
+    pub changed: Vec<u64>,
+    // Same items, different stacking order.
+    pub reordered: bool,
+    // Anything outside the item list (palette, layers, canvas, ...).
+    pub meta_changed: bool,
+}
+
+impl DocumentDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+pub fn diff(older: &Document, newer: &Document) -> DocumentDiff {
+    let older_by_id: HashMap<u64, &Item> = older.items.iter().map(|i| (i.id(), i)).collect();
+    let newer_ids: HashSet<u64> = newer.items.iter().map(Item::id).collect();
+    let mut out = DocumentDiff::default();
+    for item in &newer.items {
+        match older_by_id.get(&item.id()) {
+            None => out.added.push(item.id()),
+            Some(old) if *old != item => out.changed.push(item.id()),
+            Some(_) => {}
+        }
+    }
+    out.removed = older
+        .items
+        .iter()
+        .map(Item::id)
+        .filter(|id| !newer_ids.contains(id))
+        .collect();
+    let kept_old = older
+        .items
+        .iter()
+        .map(Item::id)
+        .filter(|id| newer_ids.contains(id));
+    let kept_new = newer
+        .items
+        .iter()
+        .map(Item::id)
+        .filter(|id| older_by_id.contains_key(id));
+    out.reordered = !kept_old.eq(kept_new);
+    out.meta_changed = older.meta != newer.meta;
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Point;
+    use crate::store::Store;
+    use crate::test_util::black;
+
+    #[test]
+    fn ghosts_changed_items_with_tint() {
+        let mut store = Store::new();
+        let black = black();
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            let s = store.begin_stroke(black, 2.0, Point { x: 0.0, y: 0.0 });
+            ids.push(s.id);
+            store.commit_stroke(s).unwrap();
+        }
+        let older = store.document();
+        store.nudge(&ids[..1], 10.0, 0.0, 0);
+        let newer = store.document();
+
+        let options = OnionSkinOptions {
+            tint: ColorRgba8 {
+                r: 255,
+                g: 0,
+                b: 0,
+                a: 255,
+            },
+            tint_amount: 1.0,
+            ghost_opacity: 0.5,
+            hide_unchanged: true,
+        };
+        let skin = onion_skin(&older, &newer, &options);
+        assert_eq!(skin.ghost.len(), 1);
+        let Item::Stroke(g) = &skin.ghost[0] else {
+            panic!("expected a stroke");
+        };
+        assert_eq!(g.id, ids[0]);
+        assert_eq!(
+            g.color,
+            ColorRgba8 {
+                r: 255,
+                g: 0,
+                b: 0,
+                a: 128
+            }
+        );
+        assert_eq!(skin.current, newer.items);
+
+        let all = OnionSkinOptions {
+            hide_unchanged: false,
+            ..options
+        };
+        assert_eq!(onion_skin(&older, &newer, &all).ghost.len(), 2);
+    }
+
+    #[test]
+    fn diff_reports_added_removed_changed_and_order() {
+        let mut store = Store::new();
+        let black = black();
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            let s = store.begin_stroke(black, 2.0, Point { x: 0.0, y: 0.0 });
+            ids.push(s.id);
+            store.commit_stroke(s).unwrap();
+        }
+        let older = store.document();
+        assert!(diff(&older, &older).is_empty());
+
+        store.translate(&ids[1..2], 5.0, 0.0);
+        store.bring_to_front(&ids[1..2]);
+        let s = store.begin_stroke(black, 2.0, Point { x: 0.0, y: 0.0 });
+        let added = s.id;
+        store.commit_stroke(s).unwrap();
+        let mut newer = store.document();
+        newer.items.retain(|i| i.id() != ids[0]);
+
+        let d = diff(&older, &newer);
+        assert_eq!(d.added, [added]);
+        assert_eq!(d.removed, [ids[0]]);
+        assert_eq!(d.changed, [ids[1]]);
+        assert!(d.reordered);
+        assert!(!d.meta_changed);
+    }
+}
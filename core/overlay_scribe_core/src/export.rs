@@ -0,0 +1,714 @@
+//! Vector export of a document.
+//!
+//! The SVG output mirrors what the shells draw: round caps and joins, routed
+//! arrows with filled heads and tail decorations, 45-degree hatching, blend
+//! modes, layer clips, halo casings and sketched outlines. Output is deterministic for a given
+//! document so exports can be diffed.
+
+use crate::codec::base64;
+use crate::geometry::{ellipse_point_at, is_boxed_shape, is_closed_shape, rect_for_shape, Rect};
+use crate::halo::item_halos;
+use crate::model::{
+    ArcAngles, BlendMode, ColorRgba8, CornerRadii, ImageSource, Item, LineCap, LineJoin, LineStyle,
+    Point, Shape, ShapeKind, Stroke, TextAlignH, TextAlignV,
+};
+use crate::outline::stroke_outline;
+use crate::render::{
+    render_arrows, ArrowPath, ArrowRender, FillRule, TailRender, TEXT_FONT_SIZE, TEXT_INSET,
+    TEXT_LINE_HEIGHT,
+};
+use crate::replay::{replay_schedule, ReplayOptions, ReplayStep};
+use crate::sketch::{item_sketches, ItemSketch};
+use crate::store::{tight_bounds, Document};
+use std::fmt::Write;
+
+const HATCH_SPACING: f32 = 10.0;
+// Polyline resolution for tapered curved arrows, which are exported as outlines.
+const OUTLINE_SEGMENTS: usize = 24;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SvgOptions {
+    // Region of the document to export. Defaults to the canvas when it has a
+    // size, otherwise to the drawn bounds of all items.
+    pub region: Option<Rect>,
+}
+
+/// The region [`to_svg`] exports when no explicit region is given: the
+/// canvas when it has a size, else the background image, else the drawn
+/// bounds of all items.
+pub fn default_region(doc: &Document) -> Option<Rect> {
+    let page = page_origin(doc);
+    if let Some(canvas) = &doc.meta.canvas {
+        if let (Some(w), Some(h)) = (canvas.width, canvas.height) {
+            return Some(Rect {
+                min_x: page.x,
+                min_y: page.y,
+                max_x: page.x + w,
+                max_y: page.y + h,
+            });
+        }
+    }
+    if let Some(image) = &doc.meta.background_image {
+        return Some(image.rect_at(page));
+    }
+    tight_bounds(&doc.items, &doc.items)
+}
+
+fn page_origin(doc: &Document) -> Point {
+    doc.meta.page_origin.unwrap_or(Point { x: 0.0, y: 0.0 })
+}
+
+/// Renders `doc` as a standalone SVG document.
+pub fn to_svg(doc: &Document, options: &SvgOptions) -> String {
+    to_svg_with_progress(doc, options, |_, _| true).unwrap_or_default()
+}
+
+/// Like [`to_svg`], calling `progress(done, total)` after each item. Returns
+/// `None` as soon as `progress` returns false.
+pub fn to_svg_with_progress(
+    doc: &Document,
+    options: &SvgOptions,
+    progress: impl FnMut(usize, usize) -> bool,
+) -> Option<String> {
+    write_svg(doc, options, &[], progress)
+}
+
+/// Renders `doc` as an SVG that replays the drawing: each item (and its
+/// halo) appears at its turn in [`replay_schedule`], using SMIL `<set>` so
+/// browsers play it without script.
+pub fn to_animated_svg(doc: &Document, options: &SvgOptions, replay: &ReplayOptions) -> String {
+    let steps = replay_schedule(doc, replay);
+    write_svg(doc, options, &steps, |_, _| true).unwrap_or_default()
+}
+
+// The SVG of `doc`; items with an entry in `reveal` stay hidden until its start.
+fn write_svg(
+    doc: &Document,
+    options: &SvgOptions,
+    reveal: &[ReplayStep],
+    mut progress: impl FnMut(usize, usize) -> bool,
+) -> Option<String> {
+    let region = options
+        .region
+        .or_else(|| default_region(doc))
+        .unwrap_or(Rect {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: 0.0,
+            max_y: 0.0,
+        });
+    let (w, h) = (region.width().max(0.0), region.height().max(0.0));
+    // The viewBox stays in document units; width and height carry the
+    // physical size so the file prints at the intended scale.
+    let units = doc.meta.units;
+    let size = |v: f32| {
+        if units.is_default() {
+            v.to_string()
+        } else {
+            format!("{}{}", units.to_unit(v, units.unit), units.unit.suffix())
+        }
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+        size(w),
+        size(h),
+        region.min_x,
+        region.min_y,
+        w,
+        h
+    );
+    if let Some(bg) = doc.meta.canvas.and_then(|c| c.background) {
+        let _ = writeln!(
+            out,
+            r#"<rect x="{}" y="{}" width="{}" height="{}"{}/>"#,
+            region.min_x,
+            region.min_y,
+            w,
+            h,
+            paint("fill", bg)
+        );
+    }
+
+    if let Some(image) = &doc.meta.background_image {
+        let href = match &image.source {
+            ImageSource::Embedded { mime_type, data } => {
+                format!("data:{};base64,{}", escape(mime_type), base64::encode(data))
+            }
+            ImageSource::Uri { uri } => escape(uri),
+        };
+        let page = page_origin(doc);
+        let _ = writeln!(
+            out,
+            r#"<image x="{}" y="{}" width="{}" height="{}" href="{href}"/>"#,
+            page.x, page.y, image.width, image.height
+        );
+    }
+
+    for layer in &doc.meta.layers {
+        let Some(c) = layer.clip else { continue };
+        let _ = writeln!(
+            out,
+            r#"<defs><clipPath id="clip-layer-{}"><rect x="{}" y="{}" width="{}" height="{}"/></clipPath></defs>"#,
+            layer.id,
+            c.min_x,
+            c.min_y,
+            c.width(),
+            c.height()
+        );
+    }
+
+    let arrows = render_arrows(&doc.items);
+    let sketches = doc
+        .meta
+        .sketch
+        .map(|s| item_sketches(&doc.items, &arrows, &s))
+        .unwrap_or_default();
+    let sketch_of = |id: u64| sketches.iter().find(|s| s.item_id == id);
+    let clip_of = |layer_id: Option<u32>| {
+        layer_id.filter(|id| {
+            doc.meta
+                .layers
+                .iter()
+                .any(|l| l.id == *id && l.clip.is_some())
+        })
+    };
+    // Casings go under all ink, so they never cover another annotation.
+    if let Some(halo) = &doc.meta.halo {
+        out.push_str("<g id=\"halo\">\n");
+        for h in item_halos(&doc.items, &arrows, halo) {
+            let clip = clip_of(h.layer_id)
+                .map(|id| format!(r#" clip-path="url(#clip-layer-{id})""#))
+                .unwrap_or_default();
+            let hidden = open_reveal(&mut out, reveal, h.item_id);
+            let _ = writeln!(
+                out,
+                r#"<path d="{}"{}{clip}/>"#,
+                polygons_path(&h.polygons),
+                paint("fill", h.color)
+            );
+            if hidden {
+                out.push_str("</g>\n");
+            }
+        }
+        out.push_str("</g>\n");
+    }
+
+    let total = doc.items.len();
+    for (done, item) in doc.items.iter().enumerate() {
+        if !progress(done, total) {
+            return None;
+        }
+        let clip = clip_of(item.layer_id());
+        if let Some(id) = clip {
+            let _ = writeln!(out, r#"<g clip-path="url(#clip-layer-{id})">"#);
+        }
+        let hidden = open_reveal(&mut out, reveal, item.id());
+        if let Some(link) = item.link() {
+            let _ = writeln!(out, r#"<a href="{}">"#, escape(link));
+        }
+        match item {
+            Item::Stroke(s) => write_stroke(&mut out, s),
+            Item::Shape(sh) if is_boxed_shape(sh.kind) => {
+                write_closed_shape(&mut out, sh, sketch_of(sh.id))
+            }
+            Item::Shape(sh) => {
+                if let Some(r) = arrows.iter().find(|r| r.shape_id == sh.id) {
+                    write_arrow(&mut out, r, sketch_of(sh.id));
+                    write_arrow_label(&mut out, sh, r);
+                }
+            }
+        }
+        if item.link().is_some() {
+            out.push_str("</a>\n");
+        }
+        if hidden {
+            out.push_str("</g>\n");
+        }
+        if clip.is_some() {
+            out.push_str("</g>\n");
+        }
+    }
+    if !progress(total, total) {
+        return None;
+    }
+    out.push_str("</svg>\n");
+    Some(out)
+}
+
+// Opens a group that stays hidden until item `id`'s replay step starts.
+// Returns whether a group was opened.
+fn open_reveal(out: &mut String, reveal: &[ReplayStep], id: u64) -> bool {
+    let Some(step) = reveal.iter().find(|s| s.item_id == id) else {
+        return false;
+    };
+    let _ = writeln!(
+        out,
+        r#"<g visibility="hidden"><set attributeName="visibility" to="visible" begin="{}s" fill="freeze"/>"#,
+        step.start
+    );
+    true
+}
+
+fn write_stroke(out: &mut String, s: &Stroke) {
+    let Some(first) = s.points.first() else {
+        return;
+    };
+    if s.line.is_tapered() || (s.points.len() == 1 && s.line.cap != LineCap::Round) {
+        write_outline(
+            out,
+            &s.points,
+            s.width,
+            &s.line,
+            s.color,
+            &blend(s.blend_mode),
+        );
+        return;
+    }
+    if s.points.len() == 1 {
+        let _ = writeln!(
+            out,
+            r#"<circle cx="{}" cy="{}" r="{}"{}{}/>"#,
+            first.x,
+            first.y,
+            s.width * 0.5,
+            paint("fill", s.color),
+            blend(s.blend_mode)
+        );
+        return;
+    }
+    let points: Vec<String> = s
+        .points
+        .iter()
+        .map(|p| format!("{},{}", p.x, p.y))
+        .collect();
+    let _ = writeln!(
+        out,
+        r#"<polyline points="{}" fill="none"{} stroke-width="{}"{}{}/>"#,
+        points.join(" "),
+        paint("stroke", s.color),
+        s.width,
+        line_attrs(&s.line),
+        blend(s.blend_mode)
+    );
+}
+
+// ` stroke-linecap=".." stroke-linejoin=".."` for `line`. The miter limit is
+// left at SVG's default, which `outline::MITER_LIMIT` matches.
+fn line_attrs(line: &LineStyle) -> String {
+    let cap = match line.cap {
+        LineCap::Butt => "butt",
+        LineCap::Round => "round",
+        LineCap::Square => "square",
+    };
+    let join = match line.join {
+        LineJoin::Miter => "miter",
+        LineJoin::Round => "round",
+        LineJoin::Bevel => "bevel",
+    };
+    format!(r#" stroke-linecap="{cap}" stroke-linejoin="{join}""#)
+}
+
+// Tapered lines have no SVG stroke equivalent, so their outline is filled.
+fn write_outline(
+    out: &mut String,
+    points: &[Point],
+    width: f32,
+    line: &LineStyle,
+    color: ColorRgba8,
+    attrs: &str,
+) {
+    let d = polygons_path(&stroke_outline(points, width, line));
+    if d.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, r#"<path d="{d}"{}{attrs}/>"#, paint("fill", color));
+}
+
+// Path data filling `polygons` together (nonzero rule).
+fn polygons_path(polygons: &[Vec<Point>]) -> String {
+    let mut d = String::new();
+    for poly in polygons {
+        for (i, p) in poly.iter().enumerate() {
+            let _ = write!(d, "{}{} {} ", if i == 0 { "M" } else { "L" }, p.x, p.y);
+        }
+        d.push('Z');
+    }
+    d
+}
+
+// Also draws arcs, which get no fill or text. `sketch`, when given, replaces
+// the exact outline (not the fill).
+fn write_closed_shape(out: &mut String, sh: &Shape, sketch: Option<&ItemSketch>) {
+    let r = rect_for_shape(sh);
+    let c = r.center();
+    let mut group = format!(r#"<g id="item-{}""#, sh.id);
+    if sh.rotation != 0.0 {
+        let _ = write!(
+            group,
+            r#" transform="rotate({} {} {})""#,
+            sh.rotation.to_degrees(),
+            c.x,
+            c.y
+        );
+    }
+    group.push_str(&blend(sh.blend_mode));
+    let _ = writeln!(out, "{group}>");
+
+    let geometry = match sh.kind {
+        ShapeKind::Ellipse => format!(
+            r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}""#,
+            c.x,
+            c.y,
+            r.width() * 0.5,
+            r.height() * 0.5
+        ),
+        ShapeKind::RoundedRectangle => {
+            let radii = sh.style.radii().fit(r.width(), r.height());
+            if radii.is_uniform() {
+                rect_element(r, radii.top_left)
+            } else {
+                format!(r#"<path d="{}""#, rounded_rect_path(r, radii))
+            }
+        }
+        ShapeKind::Arc | ShapeKind::Pie => format!(
+            r#"<path d="{}""#,
+            arc_path(
+                r,
+                sh.angles.unwrap_or(ArcAngles::FULL),
+                sh.kind == ShapeKind::Pie
+            )
+        ),
+        _ => rect_element(r, 0.0),
+    };
+    let closed = is_closed_shape(sh.kind);
+
+    if closed && sh.style.fill_enabled {
+        let _ = writeln!(out, "{geometry}{}/>", paint("fill", sh.style.fill_color));
+        if sh.style.hatch_enabled {
+            write_hatch(out, sh, &geometry);
+        }
+    }
+    if let Some(sketch) = sketch {
+        // Sketched lines are in document space; undo the group's rotation.
+        let unrotate = if sh.rotation != 0.0 {
+            format!(
+                r#" transform="rotate({} {} {})""#,
+                -sh.rotation.to_degrees(),
+                c.x,
+                c.y
+            )
+        } else {
+            String::new()
+        };
+        write_sketch(
+            out,
+            sketch,
+            sh.style.stroke_width,
+            &sh.style.line,
+            sh.style.stroke_color,
+            &unrotate,
+        );
+    } else {
+        let _ = writeln!(
+            out,
+            r#"{geometry} fill="none"{} stroke-width="{}"{}/>"#,
+            paint("stroke", sh.style.stroke_color),
+            sh.style.stroke_width,
+            line_attrs(&sh.style.line)
+        );
+    }
+    if closed {
+        write_text(out, sh, r);
+    }
+    out.push_str("</g>\n");
+}
+
+fn rect_element(r: Rect, radius: f32) -> String {
+    let mut el = format!(
+        r#"<rect x="{}" y="{}" width="{}" height="{}""#,
+        r.min_x,
+        r.min_y,
+        r.width(),
+        r.height()
+    );
+    if radius > 0.0 {
+        let _ = write!(el, r#" rx="{radius}""#);
+    }
+    el
+}
+
+// Path data of `r` with a circular arc of the fitted radius at each corner,
+// clockwise from the top-left.
+fn rounded_rect_path(r: Rect, radii: CornerRadii) -> String {
+    let [tl, tr, br, bl] = radii.to_array();
+    // (line end, arc radius, arc end) per side, clockwise from the top.
+    let sides = [
+        ((r.max_x - tr, r.min_y), tr, (r.max_x, r.min_y + tr)),
+        ((r.max_x, r.max_y - br), br, (r.max_x - br, r.max_y)),
+        ((r.min_x + bl, r.max_y), bl, (r.min_x, r.max_y - bl)),
+        ((r.min_x, r.min_y + tl), tl, (r.min_x + tl, r.min_y)),
+    ];
+    let mut d = format!("M{} {}", r.min_x + tl, r.min_y);
+    for ((lx, ly), radius, (ax, ay)) in sides {
+        let _ = write!(d, " L{lx} {ly}");
+        if radius > 0.0 {
+            let _ = write!(d, " A{radius} {radius} 0 0 1 {ax} {ay}");
+        }
+    }
+    d.push_str(" Z");
+    d
+}
+
+// Path data of the ellipse in `r` over `angles`; a `pie` runs from the center
+// out to the arc and back, unless it spans a full turn. A full turn is split
+// in two, since an SVG arc ending where it starts draws nothing.
+fn arc_path(r: Rect, angles: ArcAngles, pie: bool) -> String {
+    let (rx, ry) = (r.width() * 0.5, r.height() * 0.5);
+    let sweep = angles.sweep();
+    let full = sweep >= std::f32::consts::TAU;
+    let from = ellipse_point_at(r, angles.start);
+    let mut d = if pie && !full {
+        let c = r.center();
+        format!("M{} {} L{} {}", c.x, c.y, from.x, from.y)
+    } else {
+        format!("M{} {}", from.x, from.y)
+    };
+    let stops: &[f32] = if full { &[0.5, 1.0] } else { &[1.0] };
+    for share in stops {
+        let to = ellipse_point_at(r, angles.start + sweep * share);
+        let large = u8::from(!full && sweep > std::f32::consts::PI);
+        let _ = write!(d, " A{rx} {ry} 0 {large} 1 {} {}", to.x, to.y);
+    }
+    if pie || full {
+        d.push_str(" Z");
+    }
+    d
+}
+
+fn write_hatch(out: &mut String, sh: &Shape, geometry: &str) {
+    let hatch = ColorRgba8 {
+        a: (sh.style.stroke_color.a as f32 * 0.35).round() as u8,
+        ..sh.style.stroke_color
+    };
+    // The shells draw x - y = c lines every HATCH_SPACING along x, i.e. a
+    // perpendicular spacing of HATCH_SPACING / sqrt(2).
+    let spacing = HATCH_SPACING / std::f32::consts::SQRT_2;
+    let _ = writeln!(
+        out,
+        r#"<defs><pattern id="hatch-{}" patternUnits="userSpaceOnUse" width="{}" height="{}" patternTransform="rotate(45)"><line x1="0" y1="0" x2="0" y2="{}"{} stroke-width="{}"/></pattern></defs>"#,
+        sh.id,
+        spacing,
+        spacing,
+        spacing,
+        paint("stroke", hatch),
+        (sh.style.stroke_width * 0.6).max(1.0)
+    );
+    let _ = writeln!(out, r#"{geometry} fill="url(#hatch-{})"/>"#, sh.id);
+}
+
+fn write_text(out: &mut String, sh: &Shape, r: Rect) {
+    let text = sh.text.trim();
+    if text.is_empty() {
+        return;
+    }
+    let inset = r.inflate(-TEXT_INSET, -TEXT_INSET);
+    if inset.width() <= 6.0 || inset.height() <= 6.0 {
+        return;
+    }
+    let (x, anchor) = match sh.text_align_h {
+        TextAlignH::Left => (inset.min_x, "start"),
+        TextAlignH::Center => (inset.center().x, "middle"),
+        TextAlignH::Right => (inset.max_x, "end"),
+    };
+    let (y, baseline) = match sh.text_align_v {
+        TextAlignV::Top => (inset.min_y, "hanging"),
+        TextAlignV::Middle => (inset.center().y, "central"),
+        TextAlignV::Bottom => (inset.max_y, "text-after-edge"),
+    };
+    let _ = writeln!(
+        out,
+        r#"<text x="{x}" y="{y}" font-family="system-ui, sans-serif" font-size="{TEXT_FONT_SIZE}" font-weight="500" text-anchor="{anchor}" dominant-baseline="{baseline}">{}</text>"#,
+        escape(text)
+    );
+}
+
+// One centered line per line of text, around the routed label anchor.
+fn write_arrow_label(out: &mut String, sh: &Shape, r: &ArrowRender) {
+    let Some(center) = r.label else { return };
+    let lines: Vec<&str> = sh.text.trim().lines().collect();
+    let step = TEXT_FONT_SIZE * TEXT_LINE_HEIGHT;
+    let top = center.y - step * (lines.len() as f32 - 1.0) * 0.5;
+    for (i, line) in lines.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            r#"<text x="{}" y="{}" font-family="system-ui, sans-serif" font-size="{TEXT_FONT_SIZE}" font-weight="500" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+            center.x,
+            top + step * i as f32,
+            escape(line)
+        );
+    }
+}
+
+// Each sketched pass as an open path (filled outlines when tapered).
+fn write_sketch(
+    out: &mut String,
+    sketch: &ItemSketch,
+    width: f32,
+    line: &LineStyle,
+    color: ColorRgba8,
+    attrs: &str,
+) {
+    for pass in &sketch.lines {
+        if line.is_tapered() {
+            write_outline(out, pass, width, line, color, attrs);
+            continue;
+        }
+        let mut d = String::new();
+        for (i, p) in pass.iter().enumerate() {
+            let _ = write!(d, "{}{} {} ", if i == 0 { "M" } else { "L" }, p.x, p.y);
+        }
+        let _ = writeln!(
+            out,
+            r#"<path d="{}" fill="none"{} stroke-width="{width}"{}{attrs}/>"#,
+            d.trim_end(),
+            paint("stroke", color),
+            line_attrs(line)
+        );
+    }
+}
+
+fn write_arrow(out: &mut String, r: &ArrowRender, sketch: Option<&ItemSketch>) {
+    // The shaft stops at the head's base; the head covers the rest.
+    let (s, e) = (r.start, r.shaft_end);
+    let d = match r.shaft {
+        ArrowPath::Line => format!("M{} {} L{} {}", s.x, s.y, e.x, e.y),
+        ArrowPath::Quadratic { control } => {
+            format!(
+                "M{} {} Q{} {} {} {}",
+                s.x, s.y, control.x, control.y, e.x, e.y
+            )
+        }
+        ArrowPath::Cubic { c1, c2 } => format!(
+            "M{} {} C{} {} {} {} {} {}",
+            s.x, s.y, c1.x, c1.y, c2.x, c2.y, e.x, e.y
+        ),
+    };
+    let _ = writeln!(
+        out,
+        r#"<g id="item-{}"{}>"#,
+        r.shape_id,
+        blend(r.blend_mode)
+    );
+    if let Some(sketch) = sketch {
+        write_sketch(
+            out,
+            sketch,
+            r.style.stroke_width,
+            &r.style.line,
+            r.style.stroke_color,
+            "",
+        );
+    } else if r.style.line.is_tapered() {
+        let shaft = r.shaft.flatten(s, e, OUTLINE_SEGMENTS);
+        write_outline(
+            out,
+            &shaft,
+            r.style.stroke_width,
+            &r.style.line,
+            r.style.stroke_color,
+            "",
+        );
+    } else {
+        let _ = writeln!(
+            out,
+            r#"<path d="{d}" fill="none"{} stroke-width="{}"{}/>"#,
+            paint("stroke", r.style.stroke_color),
+            r.style.stroke_width,
+            line_attrs(&r.style.line)
+        );
+    }
+    let points: Vec<String> = r
+        .head_polygon()
+        .iter()
+        .map(|p| format!("{},{}", p.x, p.y))
+        .collect();
+    // Nonzero is the SVG default.
+    let rule = match r.head_fill_rule {
+        FillRule::NonZero => "",
+        FillRule::EvenOdd => r#" fill-rule="evenodd""#,
+    };
+    let _ = writeln!(
+        out,
+        r#"<polygon points="{}"{}{rule}/>"#,
+        points.join(" "),
+        paint("fill", r.style.stroke_color)
+    );
+    if let Some(tail) = r.tail {
+        write_tail(out, &tail, r);
+    }
+    out.push_str("</g>\n");
+}
+
+fn write_tail(out: &mut String, tail: &TailRender, r: &ArrowRender) {
+    let stroke = paint("stroke", r.style.stroke_color);
+    let width = r.style.stroke_width;
+    let _ = match *tail {
+        TailRender::Circle { center, radius } => writeln!(
+            out,
+            r#"<circle cx="{}" cy="{}" r="{radius}" fill="none"{stroke} stroke-width="{width}"/>"#,
+            center.x, center.y
+        ),
+        TailRender::Diamond {
+            points: [a, b, c, d],
+        } => writeln!(
+            out,
+            r#"<polygon points="{},{} {},{} {},{} {},{}" fill="none"{stroke} stroke-width="{width}" stroke-linejoin="round"/>"#,
+            a.x, a.y, b.x, b.y, c.x, c.y, d.x, d.y
+        ),
+        TailRender::Bar { a, b } => writeln!(
+            out,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}"{stroke} stroke-width="{width}" stroke-linecap="round"/>"#,
+            a.x, a.y, b.x, b.y
+        ),
+    };
+}
+
+// ` fill="rgb(..)"` plus an opacity attribute for translucent colors.
+fn paint(attr: &str, c: ColorRgba8) -> String {
+    let mut s = format!(r#" {attr}="rgb({},{},{})""#, c.r, c.g, c.b);
+    if c.a < 255 {
+        let _ = write!(
+            s,
+            r#" {attr}-opacity="{}""#,
+            (c.a as f32 / 255.0 * 1000.0).round() / 1000.0
+        );
+    }
+    s
+}
+
+fn blend(mode: BlendMode) -> String {
+    let css = match mode {
+        BlendMode::Normal => return String::new(),
+        BlendMode::Multiply => "multiply",
+        BlendMode::Screen => "screen",
+        BlendMode::Overlay => "overlay",
+    };
+    format!(r#" style="mix-blend-mode:{css}""#)
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
@@ -1,6 +1,7 @@
-use crate::model::{Item, Point, Shape, ShapeKind};
+use crate::model::{ArcAngles, CornerRadii, Item, Point, Shape, ShapeKind};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Rect {
     pub min_x: f32,
     pub min_y: f32,
@@ -58,6 +59,232 @@ impl Rect {
             max_y: self.max_y.max(other.max_y),
         }
     }
+
+    /// Whether the rects overlap; touching edges count as intersecting.
+    pub fn intersects(&self, other: Rect) -> bool {
+        self.min_x <= other.max_x
+            && other.min_x <= self.max_x
+            && self.min_y <= other.max_y
+            && other.min_y <= self.max_y
+    }
+}
+
+/// Overlap of two rects, or `None` if they are disjoint.
+pub fn rect_intersection(a: Rect, b: Rect) -> Option<Rect> {
+    if !a.intersects(b) {
+        return None;
+    }
+    Some(Rect {
+        min_x: a.min_x.max(b.min_x),
+        min_y: a.min_y.max(b.min_y),
+        max_x: a.max_x.min(b.max_x),
+        max_y: a.max_y.min(b.max_y),
+    })
+}
+
+/// Intersection point of segments `a1`-`a2` and `b1`-`b2`, if they cross or
+/// touch. Collinear overlapping segments return the overlap start closest to `a1`.
+pub fn segment_segment_intersection(a1: Point, a2: Point, b1: Point, b2: Point) -> Option<Point> {
+    let rx = a2.x - a1.x;
+    let ry = a2.y - a1.y;
+    let sx = b2.x - b1.x;
+    let sy = b2.y - b1.y;
+    let denom = rx * sy - ry * sx;
+    let qpx = b1.x - a1.x;
+    let qpy = b1.y - a1.y;
+    let eps = 1e-6;
+
+    if denom.abs() <= eps {
+        // Parallel; only collinear overlaps intersect.
+        if (qpx * ry - qpy * rx).abs() > eps {
+            return None;
+        }
+        let rr = rx * rx + ry * ry;
+        if rr <= eps {
+            return (closest_point_on_segment(a1, b1, b2) == a1).then_some(a1);
+        }
+        let t0 = (qpx * rx + qpy * ry) / rr;
+        let t1 = t0 + (sx * rx + sy * ry) / rr;
+        let (lo, hi) = if t0 <= t1 { (t0, t1) } else { (t1, t0) };
+        if hi < 0.0 || lo > 1.0 {
+            return None;
+        }
+        let t = lo.max(0.0);
+        return Some(Point {
+            x: a1.x + rx * t,
+            y: a1.y + ry * t,
+        });
+    }
+
+    let t = (qpx * sy - qpy * sx) / denom;
+    let u = (qpx * ry - qpy * rx) / denom;
+    if (-eps..=1.0 + eps).contains(&t) && (-eps..=1.0 + eps).contains(&u) {
+        Some(Point {
+            x: a1.x + rx * t,
+            y: a1.y + ry * t,
+        })
+    } else {
+        None
+    }
+}
+
+/// Whether segment `a`-`b` touches `rect` (including lying fully inside it).
+pub fn segment_intersects_rect(a: Point, b: Point, rect: Rect) -> bool {
+    if rect.contains(a) || rect.contains(b) {
+        return true;
+    }
+    if !Rect::from_points(a, b).intersects(rect) {
+        return false;
+    }
+    let tl = Point {
+        x: rect.min_x,
+        y: rect.min_y,
+    };
+    let tr = Point {
+        x: rect.max_x,
+        y: rect.min_y,
+    };
+    let br = Point {
+        x: rect.max_x,
+        y: rect.max_y,
+    };
+    let bl = Point {
+        x: rect.min_x,
+        y: rect.max_y,
+    };
+    [(tl, tr), (tr, br), (br, bl), (bl, tl)]
+        .iter()
+        .any(|(p, q)| segment_segment_intersection(a, b, *p, *q).is_some())
+}
+
+/// A 2D affine transform (2×3 matrix), using the CoreGraphics convention:
+/// `x' = a·x + c·y + tx`, `y' = b·x + d·y + ty`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform2D {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub tx: f32,
+    pub ty: f32,
+}
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform2D {
+    pub const IDENTITY: Self = Self {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        tx: 0.0,
+        ty: 0.0,
+    };
+
+    pub fn translate(dx: f32, dy: f32) -> Self {
+        Self {
+            tx: dx,
+            ty: dy,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn scale(sx: f32, sy: f32) -> Self {
+        Self {
+            a: sx,
+            d: sy,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Rotation by `angle` radians about the origin (clockwise on a y-down canvas).
+    pub fn rotate(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self {
+            a: cos,
+            b: sin,
+            c: -sin,
+            d: cos,
+            tx: 0.0,
+            ty: 0.0,
+        }
+    }
+
+    /// Scaling that keeps `pivot` fixed (e.g. a pinch gesture's focal point).
+    pub fn scale_about(sx: f32, sy: f32, pivot: Point) -> Self {
+        Self::translate(-pivot.x, -pivot.y)
+            .then(Self::scale(sx, sy))
+            .then(Self::translate(pivot.x, pivot.y))
+    }
+
+    pub fn rotate_about(angle: f32, pivot: Point) -> Self {
+        Self::translate(-pivot.x, -pivot.y)
+            .then(Self::rotate(angle))
+            .then(Self::translate(pivot.x, pivot.y))
+    }
+
+    /// The transform applying `self` first, then `next`.
+    pub fn then(self, next: Self) -> Self {
+        Self {
+            a: self.a * next.a + self.b * next.c,
+            b: self.a * next.b + self.b * next.d,
+            c: self.c * next.a + self.d * next.c,
+            d: self.c * next.b + self.d * next.d,
+            tx: self.tx * next.a + self.ty * next.c + next.tx,
+            ty: self.tx * next.b + self.ty * next.d + next.ty,
+        }
+    }
+
+    pub fn apply(&self, p: Point) -> Point {
+        Point {
+            x: self.a * p.x + self.c * p.y + self.tx,
+            y: self.b * p.x + self.d * p.y + self.ty,
+        }
+    }
+
+    /// Applies only the linear part (no translation), for direction vectors.
+    pub fn apply_vector(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.a * x + self.c * y, self.b * x + self.d * y)
+    }
+
+    pub fn determinant(&self) -> f32 {
+        self.a * self.d - self.b * self.c
+    }
+
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det.abs() <= f32::EPSILON || !det.is_finite() {
+            return None;
+        }
+        let inv = 1.0 / det;
+        Some(Self {
+            a: self.d * inv,
+            b: -self.b * inv,
+            c: -self.c * inv,
+            d: self.a * inv,
+            tx: (self.c * self.ty - self.d * self.tx) * inv,
+            ty: (self.b * self.tx - self.a * self.ty) * inv,
+        })
+    }
+
+    pub fn is_finite(&self) -> bool {
+        [self.a, self.b, self.c, self.d, self.tx, self.ty]
+            .iter()
+            .all(|v| v.is_finite())
+    }
+}
+
+/// Mirror direction for flip operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlipAxis {
+    /// Mirror left-right, about a vertical line.
+    Horizontal,
+    /// Mirror top-bottom, about a horizontal line.
+    Vertical,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -65,27 +292,113 @@ pub enum ClosedShapeKind {
     Rectangle,
     RoundedRectangle,
     Ellipse,
+    Pie,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct ClosedShapeHit {
     pub id: u64,
     pub kind: ClosedShapeKind,
+    // Unrotated rect; see `Shape::rotation`.
     pub rect: Rect,
+    pub rotation: f32,
+    // Fitted corner radii of a rounded rectangle; zero for other kinds.
+    pub radii: CornerRadii,
+    // Span of a pie; the full turn for other kinds.
+    pub angles: ArcAngles,
+}
+
+impl ClosedShapeHit {
+    /// Maps a canvas point into the shape's unrotated frame.
+    pub fn to_local(&self, p: Point) -> Point {
+        if self.rotation == 0.0 {
+            return p;
+        }
+        rotate_point(p, self.rect.center(), -self.rotation)
+    }
+
+    /// Maps a point in the shape's unrotated frame back onto the canvas.
+    pub fn to_world(&self, p: Point) -> Point {
+        if self.rotation == 0.0 {
+            return p;
+        }
+        rotate_point(p, self.rect.center(), self.rotation)
+    }
+
+    /// Whether `p` lies inside the (rotated) rect.
+    pub fn contains(&self, p: Point) -> bool {
+        self.rect.contains(self.to_local(p))
+    }
+
+    /// Axis-aligned bounds of the rotated rect.
+    pub fn bounds(&self) -> Rect {
+        rotated_rect_bounds(self.rect, self.rotation)
+    }
+}
+
+/// Rotates `p` about `pivot` by `angle` radians (clockwise on a y-down canvas).
+pub fn rotate_point(p: Point, pivot: Point, angle: f32) -> Point {
+    let (sin, cos) = angle.sin_cos();
+    let dx = p.x - pivot.x;
+    let dy = p.y - pivot.y;
+    Point {
+        x: pivot.x + dx * cos - dy * sin,
+        y: pivot.y + dx * sin + dy * cos,
+    }
+}
+
+/// Axis-aligned bounds of `rect` rotated by `angle` about its center.
+pub fn rotated_rect_bounds(rect: Rect, angle: f32) -> Rect {
+    if angle == 0.0 {
+        return rect;
+    }
+    let c = rect.center();
+    let corners = [
+        Point {
+            x: rect.min_x,
+            y: rect.min_y,
+        },
+        Point {
+            x: rect.max_x,
+            y: rect.min_y,
+        },
+        Point {
+            x: rect.max_x,
+            y: rect.max_y,
+        },
+        Point {
+            x: rect.min_x,
+            y: rect.max_y,
+        },
+    ]
+    .map(|p| rotate_point(p, c, angle));
+    corners[1..]
+        .iter()
+        .fold(Rect::from_points(corners[0], corners[0]), |r, p| {
+            r.union(Rect::from_points(*p, *p))
+        })
 }
 
 pub fn is_closed_shape(kind: ShapeKind) -> bool {
     matches!(
         kind,
-        ShapeKind::Rectangle | ShapeKind::RoundedRectangle | ShapeKind::Ellipse
+        ShapeKind::Rectangle | ShapeKind::RoundedRectangle | ShapeKind::Ellipse | ShapeKind::Pie
     )
 }
 
+/// Closed shapes and arcs: kinds laid out in the box between `start` and
+/// `end` and rotated about its center, as opposed to arrows running from
+/// `start` to `end`.
+pub fn is_boxed_shape(kind: ShapeKind) -> bool {
+    is_closed_shape(kind) || kind == ShapeKind::Arc
+}
+
 pub fn closed_shape_kind(kind: ShapeKind) -> Option<ClosedShapeKind> {
     match kind {
         ShapeKind::Rectangle => Some(ClosedShapeKind::Rectangle),
         ShapeKind::RoundedRectangle => Some(ClosedShapeKind::RoundedRectangle),
         ShapeKind::Ellipse => Some(ClosedShapeKind::Ellipse),
+        ShapeKind::Pie => Some(ClosedShapeKind::Pie),
         _ => None,
     }
 }
@@ -94,18 +407,704 @@ pub fn rect_for_shape(shape: &Shape) -> Rect {
     Rect::from_points(shape.start, shape.end)
 }
 
+/// Hit-testing and routing view of a closed shape; `None` for arrow kinds.
+pub fn closed_shape_hit(shape: &Shape) -> Option<ClosedShapeHit> {
+    let kind = closed_shape_kind(shape.kind)?;
+    let rect = rect_for_shape(shape);
+    let radii = if kind == ClosedShapeKind::RoundedRectangle {
+        shape.style.radii().fit(rect.width(), rect.height())
+    } else {
+        CornerRadii::default()
+    };
+    let angles = match kind {
+        ClosedShapeKind::Pie => shape.angles.unwrap_or(ArcAngles::FULL),
+        _ => ArcAngles::FULL,
+    };
+    Some(ClosedShapeHit {
+        id: shape.id,
+        kind,
+        rect,
+        rotation: shape.rotation,
+        radii,
+        angles,
+    })
+}
+
+/// Point where the ray from the center of `r` at `angle` (radians clockwise
+/// from +x) meets the inscribed ellipse.
+pub fn ellipse_point_at(r: Rect, angle: f32) -> Point {
+    let c = r.center();
+    let (a, b) = (r.width() * 0.5, r.height() * 0.5);
+    let (sin, cos) = angle.sin_cos();
+    let k = (cos * b).hypot(sin * a);
+    if k <= f32::EPSILON {
+        return c;
+    }
+    let t = a * b / k;
+    Point {
+        x: c.x + cos * t,
+        y: c.y + sin * t,
+    }
+}
+
+// Unrotated polyline along the ellipse in `r` over `angles`, both ends
+// included.
+fn arc_points(r: Rect, angles: ArcAngles, full_segments: usize) -> Vec<Point> {
+    let sweep = angles.sweep();
+    let n = ((full_segments as f32 * sweep / std::f32::consts::TAU).ceil() as usize).max(2);
+    (0..=n)
+        .map(|i| ellipse_point_at(r, angles.start + sweep * i as f32 / n as f32))
+        .collect()
+}
+
+/// Modifier keys held while dragging out a shape, see [`drag_corners`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShapeGesture {
+    // Shift: closed shapes stay square (circles for ellipses); arrows snap
+    // to multiples of 45 degrees.
+    pub constrain: bool,
+    // Alt/Option: the press point is the center instead of a corner (the
+    // midpoint for arrows).
+    pub from_center: bool,
+}
+
+/// Start and end corners of the box centered on `center` reaching
+/// `half_extent.x` and `half_extent.y` each way (signs are ignored).
+pub fn corners_from_center(center: Point, half_extent: Point) -> (Point, Point) {
+    let (hx, hy) = (half_extent.x.abs(), half_extent.y.abs());
+    (
+        Point {
+            x: center.x - hx,
+            y: center.y - hy,
+        },
+        Point {
+            x: center.x + hx,
+            y: center.y + hy,
+        },
+    )
+}
+
+/// `to` moved so the box from `anchor` to it is square: the shorter side
+/// grows to match the longer, keeping the drag direction.
+pub fn square_constrained(anchor: Point, to: Point) -> Point {
+    let (dx, dy) = (to.x - anchor.x, to.y - anchor.y);
+    let side = dx.abs().max(dy.abs());
+    Point {
+        x: anchor.x + side.copysign(dx),
+        y: anchor.y + side.copysign(dy),
+    }
+}
+
+/// `to` turned about `anchor` to the nearest multiple of 45 degrees,
+/// keeping its distance.
+pub fn octant_constrained(anchor: Point, to: Point) -> Point {
+    let (dx, dy) = (to.x - anchor.x, to.y - anchor.y);
+    let len = dx.hypot(dy);
+    if len <= f32::EPSILON {
+        return to;
+    }
+    let step = std::f32::consts::FRAC_PI_4;
+    let angle = (dy.atan2(dx) / step).round() * step;
+    let (sin, cos) = angle.sin_cos();
+    Point {
+        x: anchor.x + cos * len,
+        y: anchor.y + sin * len,
+    }
+}
+
+/// Start and end of a `kind` shape dragged from `press` to `pointer` with
+/// `gesture` held, so every shell applies the same modifier semantics.
+pub fn drag_corners(
+    kind: ShapeKind,
+    press: Point,
+    pointer: Point,
+    gesture: ShapeGesture,
+) -> (Point, Point) {
+    let end = match (gesture.constrain, is_boxed_shape(kind)) {
+        (false, _) => pointer,
+        (true, true) => square_constrained(press, pointer),
+        (true, false) => octant_constrained(press, pointer),
+    };
+    if !gesture.from_center {
+        return (press, end);
+    }
+    let start = Point {
+        x: 2.0 * press.x - end.x,
+        y: 2.0 * press.y - end.y,
+    };
+    (start, end)
+}
+
+/// Axis-aligned bounds of an item's raw geometry (stroke points or shape
+/// start/end), ignoring stroke width. `None` for a stroke without points.
+pub fn item_bounds(item: &Item) -> Option<Rect> {
+    match item {
+        Item::Stroke(s) => {
+            let first = *s.points.first()?;
+            Some(
+                s.points
+                    .iter()
+                    .fold(Rect::from_points(first, first), |r, p| {
+                        r.union(Rect::from_points(*p, *p))
+                    }),
+            )
+        }
+        Item::Shape(sh) => Some(if is_boxed_shape(sh.kind) {
+            rotated_rect_bounds(rect_for_shape(sh), sh.rotation)
+        } else {
+            rect_for_shape(sh)
+        }),
+    }
+}
+
+/// Union of [`item_bounds`] over `items`.
+pub fn union_bounds<'a>(items: impl IntoIterator<Item = &'a Item>) -> Option<Rect> {
+    items
+        .into_iter()
+        .filter_map(item_bounds)
+        .reduce(|a, b| a.union(b))
+}
+
 pub fn collect_closed_shapes(items: &[Item]) -> Vec<ClosedShapeHit> {
-    let mut out = Vec::new();
-    for it in items {
-        let Item::Shape(sh) = it else { continue };
-        let Some(kind) = closed_shape_kind(sh.kind) else {
-            continue;
+    items
+        .iter()
+        .filter_map(|it| match it {
+            Item::Shape(sh) => closed_shape_hit(sh),
+            Item::Stroke(_) => None,
+        })
+        .collect()
+}
+
+/// Outline of a boxed shape (see [`is_boxed_shape`]) as a polyline, in
+/// document space with the shape's rotation applied. Closed shapes repeat
+/// their first point at the end; an arc is left open. Rounded corners follow
+/// the shells' quadratic corner curves. Returns an empty vec for arrow kinds.
+pub fn shape_outline(shape: &Shape) -> Vec<Point> {
+    const ELLIPSE_SEGMENTS: usize = 64;
+    const CORNER_SEGMENTS: usize = 8;
+
+    let r = rect_for_shape(shape);
+    let mut pts: Vec<Point> = match shape.kind {
+        ShapeKind::Rectangle => vec![
+            Point {
+                x: r.min_x,
+                y: r.min_y,
+            },
+            Point {
+                x: r.max_x,
+                y: r.min_y,
+            },
+            Point {
+                x: r.max_x,
+                y: r.max_y,
+            },
+            Point {
+                x: r.min_x,
+                y: r.max_y,
+            },
+        ],
+        ShapeKind::RoundedRectangle => {
+            let rad = shape.style.radii().fit(r.width(), r.height());
+            let p = |x, y| Point { x, y };
+            // (corner, entry point, exit point) clockwise from the top-right.
+            let (tl, tr, br, bl) = (
+                rad.top_left,
+                rad.top_right,
+                rad.bottom_right,
+                rad.bottom_left,
+            );
+            let corners = [
+                (
+                    p(r.max_x, r.min_y),
+                    p(r.max_x - tr, r.min_y),
+                    p(r.max_x, r.min_y + tr),
+                ),
+                (
+                    p(r.max_x, r.max_y),
+                    p(r.max_x, r.max_y - br),
+                    p(r.max_x - br, r.max_y),
+                ),
+                (
+                    p(r.min_x, r.max_y),
+                    p(r.min_x + bl, r.max_y),
+                    p(r.min_x, r.max_y - bl),
+                ),
+                (
+                    p(r.min_x, r.min_y),
+                    p(r.min_x, r.min_y + tl),
+                    p(r.min_x + tl, r.min_y),
+                ),
+            ];
+            let mut out = Vec::new();
+            for (c, a, b) in corners {
+                for i in 0..=CORNER_SEGMENTS {
+                    let t = i as f32 / CORNER_SEGMENTS as f32;
+                    let mt = 1.0 - t;
+                    out.push(Point {
+                        x: mt * mt * a.x + 2.0 * mt * t * c.x + t * t * b.x,
+                        y: mt * mt * a.y + 2.0 * mt * t * c.y + t * t * b.y,
+                    });
+                }
+            }
+            out.dedup();
+            out
+        }
+        ShapeKind::Ellipse => {
+            let c = r.center();
+            let (rx, ry) = (r.width() * 0.5, r.height() * 0.5);
+            (0..ELLIPSE_SEGMENTS)
+                .map(|i| {
+                    let a = i as f32 / ELLIPSE_SEGMENTS as f32 * std::f32::consts::TAU;
+                    Point {
+                        x: c.x + rx * a.cos(),
+                        y: c.y + ry * a.sin(),
+                    }
+                })
+                .collect()
+        }
+        ShapeKind::Arc => arc_points(r, shape.angles.unwrap_or(ArcAngles::FULL), ELLIPSE_SEGMENTS),
+        ShapeKind::Pie => match shape.angles {
+            Some(angles) if angles.sweep() < std::f32::consts::TAU => {
+                let mut out = vec![r.center()];
+                out.extend(arc_points(r, angles, ELLIPSE_SEGMENTS));
+                out
+            }
+            _ => {
+                let mut out = arc_points(r, ArcAngles::FULL, ELLIPSE_SEGMENTS);
+                out.pop();
+                out
+            }
+        },
+        ShapeKind::Arrow | ShapeKind::CurvedArrow | ShapeKind::SCurve => return Vec::new(),
+    };
+    if shape.rotation != 0.0 {
+        let c = r.center();
+        for p in &mut pts {
+            *p = rotate_point(*p, c, shape.rotation);
+        }
+    }
+    if shape.kind != ShapeKind::Arc {
+        if let Some(first) = pts.first().copied() {
+            pts.push(first);
+        }
+    }
+    pts
+}
+
+/// Convex hull of `points` (Andrew's monotone chain), starting at the
+/// lowest-x point and without repeating it. Collinear boundary points are
+/// dropped; fewer than three distinct points are returned as-is (deduplicated).
+pub fn convex_hull(points: &[Point]) -> Vec<Point> {
+    let mut pts: Vec<Point> = points
+        .iter()
+        .copied()
+        .filter(|p| p.x.is_finite() && p.y.is_finite())
+        .collect();
+    pts.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    pts.dedup();
+    if pts.len() < 3 {
+        return pts;
+    }
+    let cross =
+        |o: Point, a: Point, b: Point| (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x);
+    let mut hull: Vec<Point> = Vec::with_capacity(pts.len() * 2);
+    for pass in 0..2 {
+        let start = hull.len();
+        let iter: Box<dyn Iterator<Item = &Point>> = if pass == 0 {
+            Box::new(pts.iter())
+        } else {
+            Box::new(pts.iter().rev())
+        };
+        for &p in iter {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        // The last point of each chain is the first of the next.
+        hull.pop();
+    }
+    hull
+}
+
+/// Closest point to `p` on segment `a`-`b`.
+pub fn closest_point_on_segment(p: Point, a: Point, b: Point) -> Point {
+    let abx = b.x - a.x;
+    let aby = b.y - a.y;
+    let len2 = abx * abx + aby * aby;
+    if len2 <= f32::EPSILON {
+        return a;
+    }
+    let t = (((p.x - a.x) * abx + (p.y - a.y) * aby) / len2).clamp(0.0, 1.0);
+    Point {
+        x: a.x + t * abx,
+        y: a.y + t * aby,
+    }
+}
+
+/// Closest point on the outline of a closed shape or arc to `p`, and the
+/// distance to it; `None` for arrow-like shapes, whose outline is a path
+/// (the render module's `closest_boundary_point` covers every kind).
+///
+/// Works from `p` inside or outside closed shapes and honors rotation and
+/// corner radius. Arcs and pies use their flattened [`shape_outline`].
+pub fn closest_outline_point(shape: &Shape, p: Point) -> Option<(Point, f32)> {
+    if matches!(shape.kind, ShapeKind::Arc | ShapeKind::Pie) {
+        return Some(closest_point_on_polyline(&shape_outline(shape), p));
+    }
+    let hit = closed_shape_hit(shape)?;
+    let local = hit.to_local(p);
+    let r = hit.rect;
+    let q = match hit.kind {
+        ClosedShapeKind::Rectangle | ClosedShapeKind::RoundedRectangle => {
+            closest_on_rounded_rect(r, hit.radii, local)
+        }
+        ClosedShapeKind::Ellipse | ClosedShapeKind::Pie => closest_on_ellipse(r, local),
+    };
+    let q = hit.to_world(q);
+    Some((q, (q.x - p.x).hypot(q.y - p.y)))
+}
+
+/// Closest point on the polyline `points` to `p`, and the distance to it;
+/// `p` at an infinite distance when `points` is empty.
+pub fn closest_point_on_polyline(points: &[Point], p: Point) -> (Point, f32) {
+    let dist = |q: Point| (q, (q.x - p.x).hypot(q.y - p.y));
+    match points {
+        [] => (p, f32::INFINITY),
+        [only] => dist(*only),
+        _ => points
+            .windows(2)
+            .map(|w| dist(closest_point_on_segment(p, w[0], w[1])))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((p, f32::INFINITY)),
+    }
+}
+
+// Closest point on the outline of `r` with fitted corner `radii`, from
+// inside or outside: the nearest of its four straight edges and four arcs.
+pub(crate) fn closest_on_rounded_rect(r: Rect, radii: CornerRadii, p: Point) -> Point {
+    let [tl, tr, br, bl] = radii.to_array();
+    let pt = |x, y| Point { x, y };
+    let edges = [
+        (pt(r.min_x + tl, r.min_y), pt(r.max_x - tr, r.min_y)),
+        (pt(r.max_x, r.min_y + tr), pt(r.max_x, r.max_y - br)),
+        (pt(r.max_x - br, r.max_y), pt(r.min_x + bl, r.max_y)),
+        (pt(r.min_x, r.max_y - bl), pt(r.min_x, r.min_y + tl)),
+    ];
+    // (arc center, radius, outward x and y signs of its quarter).
+    let arcs = [
+        (pt(r.min_x + tl, r.min_y + tl), tl, -1.0, -1.0),
+        (pt(r.max_x - tr, r.min_y + tr), tr, 1.0, -1.0),
+        (pt(r.max_x - br, r.max_y - br), br, 1.0, 1.0),
+        (pt(r.min_x + bl, r.max_y - bl), bl, -1.0, 1.0),
+    ];
+    let mut candidates: Vec<Point> = edges
+        .iter()
+        .map(|(a, b)| closest_point_on_segment(p, *a, *b))
+        .collect();
+    for (c, radius, sx, sy) in arcs {
+        // Clamped into the arc's quarter; arc ends are covered by the edges.
+        let dx = if (p.x - c.x) * sx > 0.0 {
+            p.x - c.x
+        } else {
+            0.0
+        };
+        let dy = if (p.y - c.y) * sy > 0.0 {
+            p.y - c.y
+        } else {
+            0.0
+        };
+        let len = dx.hypot(dy);
+        if radius > 0.0 && len > 1e-6 {
+            candidates.push(pt(c.x + dx / len * radius, c.y + dy / len * radius));
+        }
+    }
+    let d2 = |q: &Point| (q.x - p.x).powi(2) + (q.y - p.y).powi(2);
+    candidates
+        .into_iter()
+        .min_by(|a, b| d2(a).total_cmp(&d2(b)))
+        .unwrap_or(p)
+}
+
+fn closest_on_ellipse(r: Rect, p: Point) -> Point {
+    let c = r.center();
+    let a = r.width() * 0.5;
+    let b = r.height() * 0.5;
+    if a <= f32::EPSILON || b <= f32::EPSILON {
+        let (s, e) = if a <= f32::EPSILON {
+            (Point { x: c.x, y: r.min_y }, Point { x: c.x, y: r.max_y })
+        } else {
+            (Point { x: r.min_x, y: c.y }, Point { x: r.max_x, y: c.y })
         };
-        out.push(ClosedShapeHit {
-            id: sh.id,
+        return closest_point_on_segment(p, s, e);
+    }
+    let at = |t: f32| Point {
+        x: c.x + a * t.cos(),
+        y: c.y + b * t.sin(),
+    };
+    let d2 = |q: Point| (q.x - p.x).powi(2) + (q.y - p.y).powi(2);
+
+    // Coarse sampling of the parametric angle, then local refinement.
+    let coarse = 64;
+    let step = std::f32::consts::TAU / coarse as f32;
+    let mut best_t = 0.0;
+    let mut best = d2(at(0.0));
+    for i in 1..coarse {
+        let t = i as f32 * step;
+        let d = d2(at(t));
+        if d < best {
+            best = d;
+            best_t = t;
+        }
+    }
+    let fine = 32;
+    let mut t_out = best_t;
+    for i in 0..=fine {
+        let t = best_t - step + 2.0 * step * i as f32 / fine as f32;
+        let d = d2(at(t));
+        if d < best {
+            best = d;
+            t_out = t;
+        }
+    }
+    at(t_out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ShapeStyle;
+    use crate::test_util::style;
+
+    fn shape(kind: ShapeKind, start: Point, end: Point, corner_radius: f32) -> Shape {
+        Shape {
+            id: 1,
             kind,
-            rect: rect_for_shape(sh),
+            style: ShapeStyle {
+                stroke_width: 1.0,
+                corner_radius,
+                ..style()
+            },
+            start,
+            end,
+            start_attach_id: None,
+            end_attach_id: None,
+            start_attach_uv: None,
+            end_attach_uv: None,
+            tail: None,
+            angles: None,
+            ignore_obstacles: false,
+            route: None,
+            text: String::new(),
+            text_align_h: Default::default(),
+            text_align_v: Default::default(),
+            blend_mode: Default::default(),
+            rotation: 0.0,
+            layer_id: None,
+            tags: Vec::new(),
+            link: None,
+            created_ms: None,
+        }
+    }
+
+    #[test]
+    fn closest_boundary_point_for_closed_shapes() {
+        let a = Point { x: 0.0, y: 0.0 };
+        let b = Point { x: 100.0, y: 50.0 };
+
+        let rect = shape(ShapeKind::Rectangle, a, b, 0.0);
+        let (q, d) = closest_outline_point(&rect, Point { x: 50.0, y: 10.0 }).unwrap();
+        assert_eq!(q, Point { x: 50.0, y: 0.0 });
+        assert!((d - 10.0).abs() < 1e-5);
+
+        let rounded = shape(ShapeKind::RoundedRectangle, a, b, 10.0);
+        let (q, _) = closest_outline_point(&rounded, Point { x: -10.0, y: -10.0 }).unwrap();
+        let expected = 10.0 - 10.0 * std::f32::consts::FRAC_1_SQRT_2;
+        assert!((q.x - expected).abs() < 1e-4 && (q.y - expected).abs() < 1e-4);
+
+        let ellipse = shape(ShapeKind::Ellipse, a, b, 0.0);
+        let (q, d) = closest_outline_point(&ellipse, Point { x: 50.0, y: 70.0 }).unwrap();
+        assert!((q.x - 50.0).abs() < 0.1 && (q.y - 50.0).abs() < 0.1);
+        assert!((d - 20.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn rounded_rects_take_a_radius_per_corner() {
+        let mut sh = shape(
+            ShapeKind::RoundedRectangle,
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 100.0, y: 50.0 },
+            5.0,
+        );
+        sh.style.corner_radii = Some(CornerRadii {
+            top_left: 0.0,
+            top_right: 10.0,
+            bottom_right: 60.0,
+            bottom_left: 40.0,
         });
+        // The right-hand corners would overlap on the 50-unit side, so every
+        // radius shrinks by the same factor.
+        let fitted = sh.style.radii().fit(100.0, 50.0);
+        assert!((fitted.top_right + fitted.bottom_right - 50.0).abs() < 1e-4);
+        assert!((fitted.bottom_left - 40.0 * 50.0 / 70.0).abs() < 1e-4);
+        assert_eq!(
+            CornerRadii::uniform(80.0).fit(100.0, 50.0),
+            CornerRadii::uniform(25.0)
+        );
+
+        let outline = shape_outline(&sh);
+        assert!(outline.contains(&Point { x: 0.0, y: 0.0 }));
+        assert!(!outline.contains(&Point { x: 100.0, y: 0.0 }));
+
+        // Square top-left corner, rounded top-right one.
+        let (q, _) = closest_outline_point(&sh, Point { x: -5.0, y: -5.0 }).unwrap();
+        assert_eq!(q, Point { x: 0.0, y: 0.0 });
+        let (q, d) = closest_outline_point(&sh, Point { x: 105.0, y: -5.0 }).unwrap();
+        let on_arc = fitted.top_right * (1.0 - std::f32::consts::FRAC_1_SQRT_2);
+        assert!((q.x - (100.0 - on_arc)).abs() < 1e-4 && (q.y - on_arc).abs() < 1e-4);
+        assert!(d > 5.0 * std::f32::consts::SQRT_2);
+
+        // Arrows attached at the rounded corner end on its arc.
+        let hit = closed_shape_hit(&sh).unwrap();
+        let anchor = crate::render::anchor_point_uv(&hit, Point { x: 1.0, y: 0.0 });
+        let (_, gap) = closest_outline_point(&sh, anchor).unwrap();
+        assert!(gap < 1e-3 && anchor.x < 100.0);
+    }
+
+    #[test]
+    fn arcs_stay_open_and_pies_take_attachments() {
+        use std::f32::consts::FRAC_PI_2;
+        let quarter = ArcAngles {
+            start: 0.0,
+            end: FRAC_PI_2,
+        };
+        let (a, b) = (Point { x: 0.0, y: 0.0 }, Point { x: 100.0, y: 100.0 });
+        let mut arc = shape(ShapeKind::Arc, a, b, 0.0);
+        arc.angles = Some(quarter);
+        let mut pie = shape(ShapeKind::Pie, a, b, 0.0);
+        pie.angles = Some(quarter);
+
+        let outline = shape_outline(&arc);
+        let near = |p: Point, q: Point| (p.x - q.x).abs() < 1e-3 && (p.y - q.y).abs() < 1e-3;
+        assert!(near(outline[0], Point { x: 100.0, y: 50.0 }));
+        assert!(near(*outline.last().unwrap(), Point { x: 50.0, y: 100.0 }));
+        let outline = shape_outline(&pie);
+        assert_eq!(outline[0], Point { x: 50.0, y: 50.0 });
+        assert_eq!(outline.last(), Some(&Point { x: 50.0, y: 50.0 }));
+
+        // The pie's straight edge counts as boundary; the arc has none.
+        let (q, d) = closest_outline_point(&pie, Point { x: 75.0, y: 40.0 }).unwrap();
+        assert!(near(q, Point { x: 75.0, y: 50.0 }) && (d - 10.0).abs() < 1e-3);
+        let (_, d) = closest_outline_point(&arc, Point { x: 75.0, y: 40.0 }).unwrap();
+        assert!(d > 10.0);
+
+        assert!(closed_shape_hit(&arc).is_none());
+        let hit = closed_shape_hit(&pie).unwrap();
+        // Toward the missing part of the pie: the nearer end of the span.
+        let anchor = crate::render::anchor_point_uv(&hit, Point { x: 0.5, y: 0.0 });
+        assert!(near(anchor, Point { x: 100.0, y: 50.0 }));
+        let anchor = crate::render::anchor_point_uv(&hit, Point { x: 1.0, y: 1.0 });
+        let (_, gap) = closest_outline_point(&pie, anchor).unwrap();
+        assert!(gap < 0.5 && anchor.x > 50.0 && anchor.y > 50.0);
+    }
+
+    #[test]
+    fn drag_corners_apply_square_and_center_modifiers() {
+        let p = |x, y| Point { x, y };
+        let press = p(10.0, 10.0);
+        let square = ShapeGesture {
+            constrain: true,
+            from_center: false,
+        };
+        let centered = ShapeGesture {
+            constrain: false,
+            from_center: true,
+        };
+        assert_eq!(
+            drag_corners(ShapeKind::Rectangle, press, p(40.0, 0.0), square),
+            (press, p(40.0, -20.0))
+        );
+        assert_eq!(
+            drag_corners(ShapeKind::Ellipse, press, p(40.0, 20.0), centered),
+            (p(-20.0, 0.0), p(40.0, 20.0))
+        );
+        let (_, end) = drag_corners(ShapeKind::Arrow, press, p(50.0, 14.0), square);
+        assert!((end.y - 10.0).abs() < 1e-4 && (end.x - 50.2).abs() < 0.1);
+        assert_eq!(
+            drag_corners(
+                ShapeKind::Arrow,
+                press,
+                p(30.0, 0.0),
+                ShapeGesture::default()
+            ),
+            (press, p(30.0, 0.0))
+        );
+        assert_eq!(
+            corners_from_center(press, p(-5.0, 3.0)),
+            (p(5.0, 7.0), p(15.0, 13.0))
+        );
+    }
+
+    #[test]
+    fn convex_hull_drops_interior_points() {
+        let p = |x, y| Point { x, y };
+        let hull = convex_hull(&[
+            p(0.0, 0.0),
+            p(10.0, 0.0),
+            p(5.0, 5.0),
+            p(10.0, 10.0),
+            p(0.0, 10.0),
+            p(5.0, 0.0),
+        ]);
+        assert_eq!(hull.len(), 4);
+        assert!(!hull.contains(&p(5.0, 5.0)));
+        assert!(!hull.contains(&p(5.0, 0.0)));
+    }
+
+    #[test]
+    fn segment_and_rect_intersections() {
+        let r = Rect {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: 10.0,
+            max_y: 10.0,
+        };
+        let p = |x, y| Point { x, y };
+        assert!(segment_intersects_rect(p(-5.0, 5.0), p(15.0, 5.0), r));
+        assert!(segment_intersects_rect(p(2.0, 2.0), p(3.0, 3.0), r));
+        assert!(!segment_intersects_rect(p(-5.0, -1.0), p(15.0, -1.0), r));
+        assert!(!segment_intersects_rect(p(11.0, -5.0), p(20.0, 4.0), r));
+
+        assert_eq!(
+            segment_segment_intersection(p(0.0, 0.0), p(10.0, 10.0), p(0.0, 10.0), p(10.0, 0.0)),
+            Some(p(5.0, 5.0))
+        );
+        assert_eq!(
+            segment_segment_intersection(p(0.0, 0.0), p(10.0, 0.0), p(5.0, 0.0), p(20.0, 0.0)),
+            Some(p(5.0, 0.0))
+        );
+        assert_eq!(
+            segment_segment_intersection(p(0.0, 0.0), p(1.0, 0.0), p(0.0, 1.0), p(1.0, 1.0)),
+            None
+        );
+
+        let other = Rect {
+            min_x: 5.0,
+            min_y: 8.0,
+            max_x: 20.0,
+            max_y: 30.0,
+        };
+        assert!(r.intersects(other));
+        assert_eq!(
+            rect_intersection(r, other),
+            Some(Rect {
+                min_x: 5.0,
+                min_y: 8.0,
+                max_x: 10.0,
+                max_y: 10.0
+            })
+        );
     }
-    out
 }
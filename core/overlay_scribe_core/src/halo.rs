@@ -0,0 +1,136 @@
+//! Contrasting casings ("halos") drawn under annotation ink when a document
+//! sets [`Halo`]. Each casing is a set of fillable polygons shared by the SVG
+//! export, the rasterizer and the shells, so the casing looks the same
+//! everywhere. Fills, hatching and labels get no casing.
+
+use crate::geometry::{is_boxed_shape, shape_outline};
+use crate::model::{ColorRgba8, Halo, Item, LineStyle, Point};
+use crate::outline::stroke_outline;
+use crate::render::ArrowRender;
+
+// Segments used to flatten curved arrow paths.
+const ARROW_SEGMENTS: usize = 24;
+
+/// The casing under one item's ink.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemHalo {
+    pub item_id: u64,
+    pub layer_id: Option<u32>,
+    pub color: ColorRgba8,
+    // Nonzero-filled polygons, as from `stroke_outline`.
+    pub polygons: Vec<Vec<Point>>,
+}
+
+/// Casings for `items` in drawing order. Draw all of them before any ink, so
+/// a casing never covers another annotation. Items with nothing to case
+/// (arrows missing from `arrows`, empty strokes) are left out.
+pub fn item_halos(items: &[Item], arrows: &[ArrowRender], halo: &Halo) -> Vec<ItemHalo> {
+    let reach = if halo.width.is_finite() {
+        halo.width.max(0.0)
+    } else {
+        0.0
+    };
+    if reach == 0.0 {
+        return Vec::new();
+    }
+    let round = LineStyle::default();
+    let mut out = Vec::new();
+    for item in items {
+        let (ink, polygons) = match item {
+            Item::Stroke(s) => (
+                s.color,
+                stroke_outline(&s.points, s.width + 2.0 * reach, &s.line),
+            ),
+            Item::Shape(sh) if is_boxed_shape(sh.kind) => (
+                sh.style.stroke_color,
+                stroke_outline(
+                    &shape_outline(sh),
+                    sh.style.stroke_width + 2.0 * reach,
+                    &sh.style.line,
+                ),
+            ),
+            Item::Shape(sh) => {
+                let Some(r) = arrows.iter().find(|r| r.shape_id == sh.id) else {
+                    continue;
+                };
+                let width = r.style.stroke_width + 2.0 * reach;
+                let shaft = r.shaft.flatten(r.start, r.shaft_end, ARROW_SEGMENTS);
+                let mut polygons = stroke_outline(&shaft, width, &r.style.line);
+                let mut head = r.head_polygon();
+                head.push(r.end);
+                polygons.extend(stroke_outline(&head, 2.0 * reach, &round));
+                head.pop();
+                polygons.push(head);
+                if let Some(tail) = r.tail {
+                    polygons.extend(stroke_outline(&tail.outline(), width, &round));
+                }
+                (r.style.stroke_color, polygons)
+            }
+        };
+        if polygons.is_empty() {
+            continue;
+        }
+        out.push(ItemHalo {
+            item_id: item.id(),
+            layer_id: item.layer_id(),
+            color: halo.color.unwrap_or_else(|| ink.contrasting()),
+            polygons,
+        });
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::Store;
+
+    #[test]
+    fn halos_contrast_with_the_ink_and_reach_past_it() {
+        let dark = ColorRgba8 {
+            r: 20,
+            g: 20,
+            b: 60,
+            a: 200,
+        };
+        let light = ColorRgba8 {
+            r: 250,
+            g: 230,
+            b: 40,
+            a: 255,
+        };
+        let mut store = Store::new();
+        for color in [dark, light] {
+            let mut s = store.begin_stroke(color, 4.0, Point { x: 0.0, y: 0.0 });
+            s.points.push(Point { x: 100.0, y: 0.0 });
+            store.commit_stroke(s).unwrap();
+        }
+        let items = store.items();
+        let halo = Halo {
+            width: 3.0,
+            color: None,
+        };
+        let halos = item_halos(items, &[], &halo);
+        assert_eq!(halos.len(), 2);
+        assert_eq!(
+            (halos[0].color.r, halos[0].color.a),
+            (255, 200),
+            "dark ink gets a white casing"
+        );
+        assert_eq!(halos[1].color.r, 0, "light ink gets a black casing");
+        let reach = halos[0]
+            .polygons
+            .iter()
+            .flatten()
+            .map(|p| p.y.abs())
+            .fold(0.0, f32::max);
+        assert!((reach - 5.0).abs() < 1e-3);
+
+        let fixed = Halo {
+            color: Some(light),
+            ..halo
+        };
+        assert_eq!(item_halos(items, &[], &fixed)[0].color, light);
+        assert!(item_halos(items, &[], &Halo { width: 0.0, ..halo }).is_empty());
+    }
+}
@@ -1,10 +1,47 @@
+pub mod analysis;
+pub mod animate;
+mod codec;
+pub mod color;
+pub mod compare;
+pub mod export;
 pub mod geometry;
+pub mod halo;
+pub mod metrics;
 pub mod model;
+pub mod outline;
+pub mod raster;
 pub mod render;
+pub mod replay;
+pub mod search;
+pub mod sketch;
+pub mod snap;
 pub mod store;
+pub mod templates;
+#[cfg(test)]
+mod test_util;
+pub mod trace;
+pub mod units;
+pub mod validate;
 
+pub use codec::POINT_STEP;
+pub use color::ThemeMap;
+pub use export::SvgOptions;
 pub use model::{
-    ColorRgba8, Item, Point, Shape, ShapeKind, ShapeStyle, Stroke, TextAlignH, TextAlignV,
+    ArcAngles, ArrowRoute, ArrowTail, BackgroundImage, BackgroundPattern, BlendMode, Canvas,
+    ColorRgba8, CornerRadii, DocumentUnits, Frame, Guide, GuideOrientation, Halo, ImageSource,
+    Item, Layer, LengthUnit, LineCap, LineJoin, LineStyle, NamedView, PaletteColor, Point,
+    RecentStyles, Revision, Shape, ShapeDefaults, ShapeKind, ShapeStyle, Sketch, Slide, Stroke,
+    StylePreset, TextAlignH, TextAlignV, Tool, ToolProfile, WorldPoint,
 };
-pub use render::{ArrowPath, ArrowRender};
-pub use store::{Document, Store, StoreError};
+pub use render::{
+    ArrowPath, ArrowRender, EndpointAllowance, FillRule, ObstacleScope, RoutingConfig, TailRender,
+};
+pub use replay::ReplayOptions;
+pub use search::{FindOptions, TextMatches};
+pub use store::{
+    AlignEdge, ArrowEnd, AttachCandidate, BadgeOrder, CopiedStyle, Document, DocumentLimits,
+    DocumentMeta, DocumentPreview, HistoryStack, InvalidInput, InvariantViolation, Limit,
+    LoadProgress, MemoryFootprint, ReplaceScope, SpacingAxis, SplitAt, Store, StoreError,
+    CHUNK_SIZE, MAX_STROKE_WIDTH,
+};
+pub use templates::TemplateLibrary;
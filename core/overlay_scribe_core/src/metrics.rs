@@ -0,0 +1,143 @@
+//! Opt-in usage counters for the whole process, so product teams can see how
+//! the tools are used without instrumenting every shell.
+//!
+//! Nothing is counted until [`set_metrics_enabled`] turns collection on, and
+//! the counters never leave the process: shells read them with
+//! [`session_metrics`] and decide what, if anything, to do with them.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Counters since collection was enabled or last reset.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionMetrics {
+    // Edits by type; the parts of a multi-part edit count separately.
+    pub items_added: u64,
+    pub items_removed: u64,
+    pub items_replaced: u64,
+    pub item_lists_replaced: u64,
+    pub meta_edits: u64,
+    pub undos: u64,
+    pub redos: u64,
+    // Curved arrow routes, drag previews included, that found no
+    // obstacle-free path.
+    pub routing_fallbacks: u64,
+    // Serializations attempted, and the time spent on them.
+    pub saves: u64,
+    pub save_time: Duration,
+    pub loads: u64,
+    pub load_time: Duration,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static METRICS: Mutex<SessionMetrics> = Mutex::new(SessionMetrics {
+    items_added: 0,
+    items_removed: 0,
+    items_replaced: 0,
+    item_lists_replaced: 0,
+    meta_edits: 0,
+    undos: 0,
+    redos: 0,
+    routing_fallbacks: 0,
+    saves: 0,
+    save_time: Duration::ZERO,
+    loads: 0,
+    load_time: Duration::ZERO,
+});
+
+/// Turns collection on or off. Turning it off keeps the counts so far.
+pub fn set_metrics_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn metrics_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// The counters so far.
+pub fn session_metrics() -> SessionMetrics {
+    METRICS.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Zeroes every counter, e.g. when a new session starts.
+pub fn reset_session_metrics() {
+    *METRICS.lock().unwrap_or_else(|e| e.into_inner()) = SessionMetrics::default();
+}
+
+// Applies `f` to the counters when collection is on.
+pub(crate) fn count(f: impl FnOnce(&mut SessionMetrics)) {
+    if metrics_enabled() {
+        f(&mut METRICS.lock().unwrap_or_else(|e| e.into_inner()));
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Serialization {
+    Save,
+    Load,
+}
+
+// Counts one save or load, and its duration, when dropped.
+pub(crate) struct Timer {
+    kind: Serialization,
+    start: Option<Instant>,
+}
+
+impl Timer {
+    pub(crate) fn start(kind: Serialization) -> Self {
+        Self {
+            kind,
+            start: metrics_enabled().then(Instant::now),
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        let Some(start) = self.start else { return };
+        let elapsed = start.elapsed();
+        count(|m| match self.kind {
+            Serialization::Save => {
+                m.saves += 1;
+                m.save_time += elapsed;
+            }
+            Serialization::Load => {
+                m.loads += 1;
+                m.load_time += elapsed;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Point;
+    use crate::store::Store;
+    use crate::test_util::black;
+
+    // Other tests run alongside this one, so counts are checked as lower bounds.
+    #[test]
+    fn counts_edits_undo_and_serialization_while_enabled() {
+        set_metrics_enabled(true);
+        reset_session_metrics();
+        let mut store = Store::new();
+        let c = black();
+        let mut s = store.begin_stroke(c, 2.0, Point { x: 0.0, y: 0.0 });
+        s.points.push(Point { x: 10.0, y: 0.0 });
+        let id = s.id;
+        store.commit_stroke(s).unwrap();
+        store.remove_items(&[id]);
+        store.undo().unwrap();
+        let json = store.to_json().unwrap();
+        Store::from_json(&json).unwrap();
+        let m = session_metrics();
+        set_metrics_enabled(false);
+
+        assert!(m.items_added >= 1);
+        assert!(m.item_lists_replaced >= 1);
+        assert!(m.undos >= 1);
+        assert!(m.saves >= 1 && m.loads >= 1);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::geometry::Rect;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -14,12 +15,102 @@ pub struct Point {
     pub y: f32,
 }
 
+/// How an item composites onto whatever is already drawn beneath it.
+///
+/// Mirrors the separable blend modes shared by CoreGraphics, SVG/CSS
+/// `mix-blend-mode` and most raster pipelines, so every shell and exporter can
+/// map it one-to-one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LineCap {
+    Butt,
+    #[default]
+    Round,
+    Square,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LineJoin {
+    Miter,
+    #[default]
+    Round,
+    Bevel,
+}
+
+/// Cap, join and taper of a drawn line. The default is the round, untapered
+/// look every client drew before these were configurable.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub struct LineStyle {
+    #[serde(default)]
+    pub cap: LineCap,
+    #[serde(default)]
+    pub join: LineJoin,
+    // Share of an open line's length, from each end, over which its width
+    // ramps up from zero. 0 means no taper; if the two add up to more than 1
+    // they are scaled down to meet. Closed outlines are never tapered.
+    #[serde(default)]
+    pub taper_start: f32,
+    #[serde(default)]
+    pub taper_end: f32,
+}
+
+impl LineStyle {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn is_tapered(&self) -> bool {
+        self.taper_start > 0.0 || self.taper_end > 0.0
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Stroke {
     pub id: u64,
     pub color: ColorRgba8,
     pub width: f32,
+    // Plain `{x, y}` objects, or the quantized form `Store::to_json_compact` writes.
+    #[serde(deserialize_with = "crate::codec::deserialize_points")]
     pub points: Vec<Point>,
+
+    #[serde(default, skip_serializing_if = "LineStyle::is_default")]
+    pub line: LineStyle,
+
+    #[serde(default)]
+    pub blend_mode: BlendMode,
+
+    // Optional reference into the document palette. When set, `color` mirrors
+    // the palette entry and follows it when the entry is edited.
+    #[serde(default)]
+    pub color_ref: Option<u32>,
+
+    // Layer this item belongs to; `None` is the implicit base layer.
+    #[serde(default)]
+    pub layer_id: Option<u32>,
+
+    // Free-form labels such as "todo", kept sorted and unique.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    // URL or app deep link the item points to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+
+    // When drawing began, in milliseconds on the shell's clock; `None` for
+    // ink recorded before timestamps were kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_ms: Option<u64>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -30,8 +121,130 @@ pub enum ShapeKind {
     Ellipse,
     Arrow,
     CurvedArrow,
+    // An open curve along the ellipse in the shape's rect, over `angles`.
+    Arc,
+    // A closed wedge: the `Arc` plus the two radii back to the center.
+    Pie,
+    // Arrow-like connector bending into an S whose bow is fixed by
+    // `ShapeStyle::curvature`; it is not rerouted around obstacles.
+    SCurve,
 }
 
+/// The span of an arc or pie on the ellipse inscribed in the shape's rect,
+/// in radians clockwise from +x (y-down canvas) in the unrotated frame, as
+/// seen from the center. The arc runs clockwise from `start` to `end`; equal
+/// angles (mod a full turn) close the ellipse.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ArcAngles {
+    pub start: f32,
+    pub end: f32,
+}
+
+impl ArcAngles {
+    pub const FULL: Self = Self {
+        start: 0.0,
+        end: std::f32::consts::TAU,
+    };
+
+    /// Clockwise span from `start` to `end`, in `(0, TAU]`.
+    pub fn sweep(&self) -> f32 {
+        let sweep = (self.end - self.start).rem_euclid(std::f32::consts::TAU);
+        if sweep <= f32::EPSILON {
+            std::f32::consts::TAU
+        } else {
+            sweep
+        }
+    }
+
+    /// Whether the ray at `angle` from the center passes through the span.
+    pub fn contains(&self, angle: f32) -> bool {
+        (angle - self.start).rem_euclid(std::f32::consts::TAU) <= self.sweep()
+    }
+}
+
+/// A curved arrow's detour as picked by `Store::reroute_arrows`: the
+/// controls of its cubic in the frame of the arrow's chord, so the detour
+/// follows the ends as they move. `x` runs from the start (0) to the end (1)
+/// and `y` is the offset along the chord turned a quarter clockwise, both as
+/// fractions of the chord's length.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ArrowRoute {
+    pub c1: Point,
+    pub c2: Point,
+}
+
+impl ArrowRoute {
+    /// The route of the cubic with controls `c1` and `c2` from `start` to
+    /// `end`; `None` when the ends coincide.
+    pub fn from_controls(start: Point, end: Point, c1: Point, c2: Point) -> Option<Self> {
+        let (dx, dy) = (end.x - start.x, end.y - start.y);
+        let len2 = dx * dx + dy * dy;
+        if len2 <= f32::EPSILON {
+            return None;
+        }
+        let local = |p: Point| {
+            let (px, py) = (p.x - start.x, p.y - start.y);
+            Point {
+                x: (px * dx + py * dy) / len2,
+                y: (py * dx - px * dy) / len2,
+            }
+        };
+        Some(Self {
+            c1: local(c1),
+            c2: local(c2),
+        })
+    }
+
+    /// The cubic's controls between `start` and `end`.
+    pub fn controls(&self, start: Point, end: Point) -> (Point, Point) {
+        let (dx, dy) = (end.x - start.x, end.y - start.y);
+        let world = |p: Point| Point {
+            x: start.x + p.x * dx - p.y * dy,
+            y: start.y + p.x * dy + p.y * dx,
+        };
+        (world(self.c1), world(self.c2))
+    }
+
+    /// The same curve traveled from the other end.
+    pub fn reversed(self) -> Self {
+        let flip = |p: Point| Point {
+            x: 1.0 - p.x,
+            y: -p.y,
+        };
+        Self {
+            c1: flip(self.c2),
+            c2: flip(self.c1),
+        }
+    }
+
+    /// The curve reflected across its chord.
+    pub fn mirrored(self) -> Self {
+        let flip = |p: Point| Point { x: p.x, y: -p.y };
+        Self {
+            c1: flip(self.c1),
+            c2: flip(self.c2),
+        }
+    }
+
+    pub fn is_finite(&self) -> bool {
+        [self.c1, self.c2]
+            .iter()
+            .all(|p| p.x.is_finite() && p.y.is_finite())
+    }
+}
+
+/// Marker drawn at the start (non-head) end of an arrow, for ER and UML
+/// notations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArrowTail {
+    Circle,
+    Diamond,
+    Bar,
+}
+
+/// How a shape is drawn. Stored inline on every shape; `Store` keeps the
+/// distinct styles in a table of its own (see `Store::distinct_styles`).
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct ShapeStyle {
     pub stroke_color: ColorRgba8,
@@ -40,6 +253,430 @@ pub struct ShapeStyle {
     pub fill_color: ColorRgba8,
     pub hatch_enabled: bool,
     pub corner_radius: f32,
+
+    // Optional palette references; see `Stroke::color_ref`.
+    #[serde(default)]
+    pub stroke_color_ref: Option<u32>,
+
+    #[serde(default)]
+    pub fill_color_ref: Option<u32>,
+
+    #[serde(default, skip_serializing_if = "LineStyle::is_default")]
+    pub line: LineStyle,
+
+    // Per-corner radii of a rounded rectangle; `None` rounds every corner by
+    // `corner_radius`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corner_radii: Option<CornerRadii>,
+
+    // Bow of an `SCurve` or `CurvedArrow` as a signed share of its length.
+    // `None` is `DEFAULT_CURVATURE` for S-curves and the automatic bow for
+    // curved arrows. See `ShapeStyle::curvature`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub curvature: Option<f32>,
+
+    // Arrow-like shapes: depth of a notch cut into the back of the head, as
+    // a share of the head's length (drawn clamped to 0..=0.8). `None` is a
+    // plain triangle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head_notch: Option<f32>,
+}
+
+impl ShapeStyle {
+    /// Bow of an S-curve connector when the style doesn't set one.
+    pub const DEFAULT_CURVATURE: f32 = 0.25;
+
+    /// The radius of each corner, from `corner_radii` or else `corner_radius`.
+    pub fn radii(&self) -> CornerRadii {
+        self.corner_radii
+            .unwrap_or(CornerRadii::uniform(self.corner_radius))
+    }
+
+    /// Signed bow of an S-curve connector, as a share of the distance
+    /// between its ends. Positive values swing the first half to the right
+    /// of the start-to-end direction (as seen on screen) and the second half
+    /// to the left; negative values mirror that. The sign does
+    /// not depend on where the ends are, so dragging them keeps the bow.
+    ///
+    /// A curved arrow with an explicit `curvature` puts its control point
+    /// that share of its length to that side, instead of picking a side
+    /// from the ends' layout; obstacle detours still apply.
+    pub fn curvature(&self) -> f32 {
+        self.curvature.unwrap_or(Self::DEFAULT_CURVATURE)
+    }
+}
+
+/// Corner radii of a rounded rectangle in its unrotated frame, clockwise from
+/// the top-left like CSS `border-radius`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub struct CornerRadii {
+    pub top_left: f32,
+    pub top_right: f32,
+    pub bottom_right: f32,
+    pub bottom_left: f32,
+}
+
+impl CornerRadii {
+    pub fn uniform(radius: f32) -> Self {
+        Self {
+            top_left: radius,
+            top_right: radius,
+            bottom_right: radius,
+            bottom_left: radius,
+        }
+    }
+
+    pub fn is_uniform(&self) -> bool {
+        *self == Self::uniform(self.top_left)
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.to_array().iter().all(|r| r.is_finite())
+    }
+
+    /// Top-left, top-right, bottom-right, bottom-left.
+    pub fn to_array(self) -> [f32; 4] {
+        [
+            self.top_left,
+            self.top_right,
+            self.bottom_right,
+            self.bottom_left,
+        ]
+    }
+
+    /// The radii as drawn in a `width` by `height` box: negatives become 0,
+    /// and if two corners on one side would overlap, all radii shrink by the
+    /// same factor until they meet (the CSS rule). A single radius thus ends
+    /// up clamped to half the shorter side.
+    pub fn fit(self, width: f32, height: f32) -> Self {
+        let [tl, tr, br, bl] = self.to_array().map(|r| r.max(0.0));
+        let (w, h) = (width.abs(), height.abs());
+        let scale = [(w, tl + tr), (h, tr + br), (w, br + bl), (h, bl + tl)]
+            .iter()
+            .filter(|(_, sum)| *sum > 0.0)
+            .map(|(side, sum)| side / sum)
+            .fold(1.0f32, f32::min);
+        Self {
+            top_left: tl * scale,
+            top_right: tr * scale,
+            bottom_right: br * scale,
+            bottom_left: bl * scale,
+        }
+    }
+}
+
+/// A named, reusable style (e.g. "review red", "approved green").
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StylePreset {
+    pub name: String,
+    pub style: ShapeStyle,
+}
+
+/// What a new shape of `kind` starts with, saved with the document so the
+/// author's tool settings come back on reopen.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ShapeDefaults {
+    pub kind: ShapeKind,
+    pub style: ShapeStyle,
+    #[serde(default)]
+    pub text_align_h: TextAlignH,
+    #[serde(default)]
+    pub text_align_v: TextAlignV,
+    // Tail decoration for arrow kinds; ignored for closed shapes.
+    #[serde(default)]
+    pub tail: Option<ArrowTail>,
+}
+
+/// What a [`ToolProfile`] draws with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "tool", rename_all = "snake_case")]
+pub enum Tool {
+    Pen,
+    Marker,
+    Highlighter,
+    // `width` is the eraser diameter; color, line and blend are unused.
+    Eraser,
+    Shape { kind: ShapeKind },
+}
+
+/// A named tool setting such as "fine pen" or "yellow highlighter", kept
+/// with the document (or in the shell's settings via
+/// `Store::export_tool_profiles`) so it follows the file across devices.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolProfile {
+    pub name: String,
+    #[serde(flatten)]
+    pub tool: Tool,
+    pub color: ColorRgba8,
+    pub width: f32,
+    #[serde(default, skip_serializing_if = "LineStyle::is_default")]
+    pub line: LineStyle,
+    #[serde(default)]
+    pub blend_mode: BlendMode,
+}
+
+/// Colors and shape styles used most recently, newest first and without
+/// repeats, for a "recent colors" row.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecentStyles {
+    #[serde(default)]
+    pub colors: Vec<ColorRgba8>,
+    #[serde(default)]
+    pub styles: Vec<ShapeStyle>,
+}
+
+impl RecentStyles {
+    /// Entries kept in each list.
+    pub const LIMIT: usize = 12;
+}
+
+/// A named document-level color that styles can reference by `id`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaletteColor {
+    pub id: u32,
+    pub name: String,
+    pub color: ColorRgba8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuideOrientation {
+    // A line at constant y.
+    Horizontal,
+    // A line at constant x.
+    Vertical,
+}
+
+/// A document-level layout guide line (not an item). `position` is the y of
+/// a horizontal guide or the x of a vertical one.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Guide {
+    pub id: u32,
+    pub orientation: GuideOrientation,
+    pub position: f32,
+}
+
+/// A point in world space, in f64 so it stays exact far from the origin.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub struct WorldPoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// A document layer. Items opt in via `layer_id`; a layer's `clip` masks
+/// everything drawn on it to that rectangle (e.g. annotate only inside a
+/// dialog of the underlying screenshot).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Layer {
+    pub id: u32,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clip: Option<Rect>,
+}
+
+/// A named export region ("artboard") on the board.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Frame {
+    pub name: String,
+    pub rect: Rect,
+}
+
+/// A named viewport bookmark. `center` is in document units; `zoom` is the
+/// shell's scale factor (1.0 = 100%).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NamedView {
+    pub name: String,
+    pub center: Point,
+    pub zoom: f32,
+}
+
+/// A named snapshot of the document's items, stored as a delta against the
+/// previous revision (the first against an empty document).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Revision {
+    pub name: String,
+    // Items added or modified since the previous revision.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changed: Vec<Item>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<u64>,
+    // Z-order of every item in this revision, by id.
+    pub order: Vec<u64>,
+}
+
+/// One slide of a deck: a background and the items drawn over it. The
+/// current slide's content lives in the document's own items and background
+/// image, so its entry in the deck is left empty.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Slide {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background: Option<BackgroundImage>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<Item>,
+}
+
+/// Pattern drawn on the canvas background; `spacing` is in document units.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BackgroundPattern {
+    #[default]
+    None,
+    Grid {
+        spacing: f32,
+    },
+    Dots {
+        spacing: f32,
+    },
+    Lines {
+        spacing: f32,
+    },
+}
+
+/// The drawing surface, shared by exports and shells. A canvas without a
+/// size is unbounded; without a background it is transparent.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub struct Canvas {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background: Option<ColorRgba8>,
+    #[serde(default)]
+    pub pattern: BackgroundPattern,
+}
+
+/// Where a background image's pixels come from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ImageSource {
+    // The encoded file (PNG, JPEG, ...) itself, base64 in JSON, so the
+    // document is self-contained.
+    Embedded {
+        mime_type: String,
+        #[serde(with = "crate::codec::base64")]
+        data: Vec<u8>,
+    },
+    // A file path or URL the shell resolves; exports reference it as-is.
+    Uri {
+        uri: String,
+    },
+}
+
+/// The screenshot being annotated. It covers `width` by `height` document
+/// units from the page origin (see `DocumentMeta::page_origin`), which is
+/// (0, 0) unless the document was rebased.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackgroundImage {
+    pub source: ImageSource,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl BackgroundImage {
+    pub fn rect(&self) -> Rect {
+        self.rect_at(Point { x: 0.0, y: 0.0 })
+    }
+
+    /// The area the image covers with its top-left corner at `origin`.
+    pub fn rect_at(&self, origin: Point) -> Rect {
+        Rect {
+            min_x: origin.x,
+            min_y: origin.y,
+            max_x: origin.x + self.width,
+            max_y: origin.y + self.height,
+        }
+    }
+}
+
+/// A physical length unit for measurements and exports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LengthUnit {
+    #[default]
+    Px,
+    Pt,
+    Mm,
+}
+
+impl LengthUnit {
+    /// Length of one unit in inches (CSS pixels: 96 per inch; points: 72).
+    pub fn inches(self) -> f32 {
+        match self {
+            Self::Px => 1.0 / 96.0,
+            Self::Pt => 1.0 / 72.0,
+            Self::Mm => 1.0 / 25.4,
+        }
+    }
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Self::Px => "px",
+            Self::Pt => "pt",
+            Self::Mm => "mm",
+        }
+    }
+}
+
+/// How document coordinates map to physical lengths: one `unit` spans
+/// `scale` document units. The default is one CSS pixel per unit.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DocumentUnits {
+    #[serde(default)]
+    pub unit: LengthUnit,
+    #[serde(default = "default_unit_scale")]
+    pub scale: f32,
+}
+
+fn default_unit_scale() -> f32 {
+    1.0
+}
+
+impl Default for DocumentUnits {
+    fn default() -> Self {
+        Self {
+            unit: LengthUnit::Px,
+            scale: default_unit_scale(),
+        }
+    }
+}
+
+impl DocumentUnits {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// `length` in document units, expressed in `unit`.
+    pub fn to_unit(&self, length: f32, unit: LengthUnit) -> f32 {
+        length / self.scale * self.unit.inches() / unit.inches()
+    }
+
+    /// `value` in `unit`, expressed in document units.
+    pub fn from_unit(&self, value: f32, unit: LengthUnit) -> f32 {
+        value * unit.inches() / self.unit.inches() * self.scale
+    }
+}
+
+/// A contrasting casing drawn under every annotation's ink, so marks stay
+/// readable over busy screenshots. `width` is how far the casing reaches
+/// past the ink on each side.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Halo {
+    pub width: f32,
+    // Fixed casing color; `None` picks black or white per item, whichever
+    // contrasts more with its ink.
+    #[serde(default)]
+    pub color: Option<ColorRgba8>,
+}
+
+/// A hand-drawn look for shape outlines and arrows. `roughness` is how far,
+/// in document units, outline points wander from the exact geometry.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Sketch {
+    pub roughness: f32,
+    // Mixed with each item's id; changing it redraws every wobble.
+    #[serde(default)]
+    pub seed: u64,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -86,6 +723,25 @@ pub struct Shape {
     #[serde(default)]
     pub end_attach_uv: Option<Point>,
 
+    // Tail decoration of arrow-like shapes; ignored on closed shapes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tail: Option<ArrowTail>,
+
+    // Arrow-like shapes only: keep the default path even where it crosses
+    // other shapes, instead of detouring around them.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ignore_obstacles: bool,
+
+    // Curved arrows only: the detour kept by the last reroute, taken over
+    // fresh routing while it stays clear of obstacles.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route: Option<ArrowRoute>,
+
+    // Span of `Arc` and `Pie` shapes; `None` is the whole ellipse. Ignored
+    // on other kinds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub angles: Option<ArcAngles>,
+
     #[serde(default)]
     pub text: String,
 
@@ -94,6 +750,32 @@ pub struct Shape {
 
     #[serde(default)]
     pub text_align_v: TextAlignV,
+
+    #[serde(default)]
+    pub blend_mode: BlendMode,
+
+    // Rotation of closed shapes about their rect center, in radians (positive is
+    // clockwise on the y-down canvas). `start`/`end` and attachment UVs are in the
+    // unrotated frame. Arrow-like shapes keep 0 and store rotated endpoints instead.
+    #[serde(default)]
+    pub rotation: f32,
+
+    // Layer this item belongs to; `None` is the implicit base layer.
+    #[serde(default)]
+    pub layer_id: Option<u32>,
+
+    // Free-form labels such as "todo", kept sorted and unique.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    // URL or app deep link the item points to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+
+    // When drawing began, in milliseconds on the shell's clock; `None` for
+    // ink recorded before timestamps were kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_ms: Option<u64>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -102,3 +784,70 @@ pub enum Item {
     Stroke(Stroke),
     Shape(Shape),
 }
+
+impl Item {
+    pub fn id(&self) -> u64 {
+        match self {
+            Item::Stroke(s) => s.id,
+            Item::Shape(sh) => sh.id,
+        }
+    }
+
+    pub fn layer_id(&self) -> Option<u32> {
+        match self {
+            Item::Stroke(s) => s.layer_id,
+            Item::Shape(sh) => sh.layer_id,
+        }
+    }
+
+    pub fn tags(&self) -> &[String] {
+        match self {
+            Item::Stroke(s) => &s.tags,
+            Item::Shape(sh) => &sh.tags,
+        }
+    }
+
+    pub fn created_ms(&self) -> Option<u64> {
+        match self {
+            Item::Stroke(s) => s.created_ms,
+            Item::Shape(sh) => sh.created_ms,
+        }
+    }
+
+    pub fn link(&self) -> Option<&str> {
+        match self {
+            Item::Stroke(s) => s.link.as_deref(),
+            Item::Shape(sh) => sh.link.as_deref(),
+        }
+    }
+
+    pub fn set_link(&mut self, link: Option<String>) {
+        match self {
+            Item::Stroke(s) => s.link = link,
+            Item::Shape(sh) => sh.link = link,
+        }
+    }
+
+    pub fn tags_mut(&mut self) -> &mut Vec<String> {
+        match self {
+            Item::Stroke(s) => &mut s.tags,
+            Item::Shape(sh) => &mut sh.tags,
+        }
+    }
+
+    /// Moves the item's geometry. Attachments are left alone: attached arrow
+    /// ends keep resolving against their target.
+    pub fn translate(&mut self, dx: f32, dy: f32) {
+        let shift = |p: &mut Point| {
+            p.x += dx;
+            p.y += dy;
+        };
+        match self {
+            Item::Stroke(s) => s.points.iter_mut().for_each(shift),
+            Item::Shape(sh) => {
+                shift(&mut sh.start);
+                shift(&mut sh.end);
+            }
+        }
+    }
+}
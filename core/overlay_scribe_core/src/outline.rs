@@ -0,0 +1,313 @@
+//! Fillable outlines of lines drawn with a [`LineStyle`]: caps, joins and
+//! taper. Exports and shells that can't stroke a variable-width line fill
+//! these polygons instead, so tapered ink looks the same everywhere.
+
+use crate::model::{LineCap, LineJoin, LineStyle, Point};
+
+/// Longest miter, as a multiple of the half width, before it is beveled.
+/// Matches SVG's default `stroke-miterlimit`.
+pub const MITER_LIMIT: f32 = 4.0;
+
+// Segments used for round caps and joins.
+const ROUND_SEGMENTS: usize = 16;
+
+/// Polygons whose union is the ink of `points` stroked at `width` with
+/// `line`. Every polygon winds the same way (positive signed area in
+/// document coordinates), so they can be filled together with the nonzero
+/// rule. A polyline whose last point equals its first is treated as closed:
+/// it gets a join there instead of caps, and is never tapered.
+pub fn stroke_outline(points: &[Point], width: f32, line: &LineStyle) -> Vec<Vec<Point>> {
+    let half = width * 0.5;
+    let mut pts: Vec<Point> = Vec::with_capacity(points.len());
+    for p in points {
+        if pts.last() != Some(p) {
+            pts.push(*p);
+        }
+    }
+    if pts.is_empty() || !half.is_finite() || half <= 0.0 {
+        return Vec::new();
+    }
+    let mut out = Vec::new();
+    if pts.len() == 1 {
+        push_cap(&mut out, line.cap, pts[0], (1.0, 0.0), half);
+        return oriented(out);
+    }
+
+    let closed = pts.len() > 2 && pts[0] == pts[pts.len() - 1];
+    if closed {
+        pts.pop();
+    }
+    let widths = if closed || !line.is_tapered() {
+        vec![half; pts.len()]
+    } else {
+        split_for_taper(&mut pts, half, line)
+    };
+
+    let n = pts.len();
+    let segments = if closed { n } else { n - 1 };
+    let dir = |i: usize| unit(pts[i], pts[(i + 1) % n]);
+    for i in 0..segments {
+        let (a, b) = (pts[i], pts[(i + 1) % n]);
+        let (ha, hb) = (widths[i], widths[(i + 1) % n]);
+        if ha <= 0.0 && hb <= 0.0 {
+            continue;
+        }
+        let (nx, ny) = normal(dir(i));
+        out.push(vec![
+            offset(a, nx, ny, ha),
+            offset(b, nx, ny, hb),
+            offset(b, nx, ny, -hb),
+            offset(a, nx, ny, -ha),
+        ]);
+    }
+
+    let joints = if closed { 0..n } else { 1..n - 1 };
+    for v in joints {
+        let prev = dir((v + n - 1) % n);
+        push_join(&mut out, line.join, pts[v], prev, dir(v), widths[v]);
+    }
+    if !closed {
+        let (dx, dy) = dir(0);
+        push_cap(&mut out, line.cap, pts[0], (-dx, -dy), widths[0]);
+        push_cap(&mut out, line.cap, pts[n - 1], dir(n - 2), widths[n - 1]);
+    }
+    oriented(out)
+}
+
+// Inserts points where the taper ramps end, so width stays linear along
+// every segment, and returns the half width at each point.
+fn split_for_taper(pts: &mut Vec<Point>, half: f32, line: &LineStyle) -> Vec<f32> {
+    let mut ts = line.taper_start.clamp(0.0, 1.0);
+    let mut te = line.taper_end.clamp(0.0, 1.0);
+    if ts + te > 1.0 {
+        let scale = 1.0 / (ts + te);
+        ts *= scale;
+        te *= scale;
+    }
+    let total: f32 = pts.windows(2).map(|w| distance(w[0], w[1])).sum();
+    let breaks = [ts * total, total - te * total];
+
+    let mut split = vec![pts[0]];
+    let mut dist = vec![0.0];
+    let mut walked = 0.0;
+    for w in pts.windows(2) {
+        let len = distance(w[0], w[1]);
+        for at in breaks {
+            if at > walked && at < walked + len {
+                let t = (at - walked) / len;
+                split.push(Point {
+                    x: w[0].x + (w[1].x - w[0].x) * t,
+                    y: w[0].y + (w[1].y - w[0].y) * t,
+                });
+                dist.push(at);
+            }
+        }
+        walked += len;
+        split.push(w[1]);
+        dist.push(walked);
+    }
+    *pts = split;
+
+    dist.iter()
+        .map(|&d| {
+            let mut f: f32 = 1.0;
+            if ts > 0.0 {
+                f = f.min(d / (ts * total));
+            }
+            if te > 0.0 {
+                f = f.min((total - d) / (te * total));
+            }
+            half * f.clamp(0.0, 1.0)
+        })
+        .collect()
+}
+
+// `dir` points away from the line, out of the cap.
+fn push_cap(out: &mut Vec<Vec<Point>>, cap: LineCap, at: Point, dir: (f32, f32), half: f32) {
+    if half <= 0.0 {
+        return;
+    }
+    match cap {
+        LineCap::Butt => {}
+        LineCap::Round => out.push(circle(at, half)),
+        LineCap::Square => {
+            let (nx, ny) = normal(dir);
+            let far = offset(at, dir.0, dir.1, half);
+            out.push(vec![
+                offset(at, nx, ny, half),
+                offset(far, nx, ny, half),
+                offset(far, nx, ny, -half),
+                offset(at, nx, ny, -half),
+            ]);
+        }
+    }
+}
+
+fn push_join(
+    out: &mut Vec<Vec<Point>>,
+    join: LineJoin,
+    at: Point,
+    prev: (f32, f32),
+    next: (f32, f32),
+    half: f32,
+) {
+    let cross = prev.0 * next.1 - prev.1 * next.0;
+    if half <= 0.0 || cross.abs() <= 1e-6 && prev.0 * next.0 + prev.1 * next.1 > 0.0 {
+        return;
+    }
+    if join == LineJoin::Round {
+        out.push(circle(at, half));
+        return;
+    }
+    // The outer side of the turn is opposite the way the line bends.
+    let side = if cross > 0.0 { -1.0 } else { 1.0 };
+    let (pn, nn) = (normal(prev), normal(next));
+    let a = offset(at, pn.0, pn.1, side * half);
+    let b = offset(at, nn.0, nn.1, side * half);
+    let (mx, my) = (pn.0 + nn.0, pn.1 + nn.1);
+    let m_len = mx.hypot(my);
+    // cos of half the angle between the offset edges.
+    let cos_half = m_len * 0.5;
+    if join == LineJoin::Miter && m_len > 1e-6 && 1.0 / cos_half <= MITER_LIMIT {
+        let reach = side * half / cos_half;
+        let tip = offset(at, mx / m_len, my / m_len, reach);
+        out.push(vec![at, a, tip, b]);
+    } else {
+        out.push(vec![at, a, b]);
+    }
+}
+
+fn circle(center: Point, radius: f32) -> Vec<Point> {
+    (0..ROUND_SEGMENTS)
+        .map(|i| {
+            let a = i as f32 / ROUND_SEGMENTS as f32 * std::f32::consts::TAU;
+            Point {
+                x: center.x + radius * a.cos(),
+                y: center.y + radius * a.sin(),
+            }
+        })
+        .collect()
+}
+
+fn oriented(mut polygons: Vec<Vec<Point>>) -> Vec<Vec<Point>> {
+    for poly in &mut polygons {
+        let area: f32 = poly
+            .iter()
+            .zip(poly.iter().cycle().skip(1))
+            .map(|(a, b)| a.x * b.y - b.x * a.y)
+            .sum();
+        if area < 0.0 {
+            poly.reverse();
+        }
+    }
+    polygons
+}
+
+fn unit(a: Point, b: Point) -> (f32, f32) {
+    let len = distance(a, b);
+    ((b.x - a.x) / len, (b.y - a.y) / len)
+}
+
+fn normal((dx, dy): (f32, f32)) -> (f32, f32) {
+    (-dy, dx)
+}
+
+fn offset(p: Point, dx: f32, dy: f32, by: f32) -> Point {
+    Point {
+        x: p.x + dx * by,
+        y: p.y + dy * by,
+    }
+}
+
+fn distance(a: Point, b: Point) -> f32 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(polygons: &[Vec<Point>]) -> (f32, f32, f32, f32) {
+        polygons.iter().flatten().fold(
+            (
+                f32::INFINITY,
+                f32::INFINITY,
+                f32::NEG_INFINITY,
+                f32::NEG_INFINITY,
+            ),
+            |(x0, y0, x1, y1), p| (x0.min(p.x), y0.min(p.y), x1.max(p.x), y1.max(p.y)),
+        )
+    }
+
+    #[test]
+    fn caps_joins_and_taper_shape_the_outline() {
+        let line = [Point { x: 0.0, y: 0.0 }, Point { x: 100.0, y: 0.0 }];
+        let style = |cap| LineStyle {
+            cap,
+            ..LineStyle::default()
+        };
+        assert_eq!(
+            bounds(&stroke_outline(&line, 10.0, &style(LineCap::Butt))),
+            (0.0, -5.0, 100.0, 5.0)
+        );
+        assert_eq!(
+            bounds(&stroke_outline(&line, 10.0, &style(LineCap::Square))),
+            (-5.0, -5.0, 105.0, 5.0)
+        );
+
+        // A right-angle corner: the miter reaches the corner of the offset
+        // edges, a bevel cuts it off.
+        let corner = [
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 100.0, y: 0.0 },
+            Point { x: 100.0, y: 100.0 },
+        ];
+        let join = |join| LineStyle {
+            cap: LineCap::Butt,
+            join,
+            ..LineStyle::default()
+        };
+        let near_corner = |polygons: &[Vec<Point>]| {
+            polygons
+                .iter()
+                .flatten()
+                .any(|p| (p.x - 105.0).abs() < 1e-3 && (p.y + 5.0).abs() < 1e-3)
+        };
+        assert!(near_corner(&stroke_outline(
+            &corner,
+            10.0,
+            &join(LineJoin::Miter)
+        )));
+        assert!(!near_corner(&stroke_outline(
+            &corner,
+            10.0,
+            &join(LineJoin::Bevel)
+        )));
+
+        // Taper: zero width at the start, full width from 30% on.
+        let tapered = LineStyle {
+            cap: LineCap::Butt,
+            taper_start: 0.3,
+            ..LineStyle::default()
+        };
+        let outline = stroke_outline(&line, 10.0, &tapered);
+        let at = |x: f32| {
+            outline
+                .iter()
+                .flatten()
+                .filter(|p| (p.x - x).abs() < 1e-3)
+                .map(|p| p.y.abs())
+                .fold(0.0, f32::max)
+        };
+        assert_eq!(at(0.0), 0.0);
+        assert_eq!(at(30.0), 5.0);
+        assert!(outline.iter().all(|poly| {
+            let area: f32 = poly
+                .iter()
+                .zip(poly.iter().cycle().skip(1))
+                .map(|(a, b)| a.x * b.y - b.x * a.y)
+                .sum();
+            area >= 0.0
+        }));
+    }
+}
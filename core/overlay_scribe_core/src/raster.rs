@@ -0,0 +1,459 @@
+//! Software rasterization of a document into square tiles.
+//!
+//! One document unit is one pixel and tiles are aligned to the document
+//! origin, so tile `(col, row)` covers `[col * size, (col + 1) * size)` on x
+//! (likewise y). Strokes, fills, shape outlines (sketched when the document
+//! sets [`crate::model::Sketch`]) and arrows are drawn with
+//! anti-aliased coverage and source-over compositing, clipped by layer clips,
+//! over the document's halo casings when it sets one.
+//! Hatching, text and non-normal blend modes are left to the shells.
+
+use crate::geometry::{
+    closest_point_on_segment, is_boxed_shape, is_closed_shape, rect_intersection, shape_outline,
+    Rect,
+};
+use crate::halo::item_halos;
+use crate::model::{ColorRgba8, Item, LineStyle, Point};
+use crate::outline::stroke_outline;
+use crate::render::render_arrows;
+use crate::sketch::item_sketches;
+use crate::store::Document;
+
+// Segments used to flatten curved arrow paths.
+const ARROW_SEGMENTS: usize = 24;
+
+/// A rendered tile: `size * size` straight-alpha RGBA8 pixels, row-major.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tile {
+    pub col: i32,
+    pub row: i32,
+    pub size: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl Tile {
+    /// The document region this tile covers.
+    pub fn rect(&self) -> Rect {
+        let s = self.size as f32;
+        Rect {
+            min_x: self.col as f32 * s,
+            min_y: self.row as f32 * s,
+            max_x: (self.col + 1) as f32 * s,
+            max_y: (self.row + 1) as f32 * s,
+        }
+    }
+}
+
+enum Paint {
+    // Polyline with round caps and joins.
+    Line { points: Vec<Point>, half_width: f32 },
+    // Even-odd filled polygon.
+    Fill { points: Vec<Point> },
+    // Union of polygons, e.g. a `stroke_outline`.
+    Polygons { polygons: Vec<Vec<Point>> },
+}
+
+// Lines in the default round style are drawn directly; any other cap, join
+// or taper goes through the shared outline so it matches the SVG export.
+fn line_paint(points: Vec<Point>, width: f32, line: &LineStyle) -> Paint {
+    if line.is_default() {
+        Paint::Line {
+            points,
+            half_width: width * 0.5,
+        }
+    } else {
+        Paint::Polygons {
+            polygons: stroke_outline(&points, width, line),
+        }
+    }
+}
+
+struct Primitive {
+    paint: Paint,
+    color: ColorRgba8,
+    bounds: Rect,
+    clip: Option<Rect>,
+}
+
+/// Renders every tile touching one of `dirty_rects`, in row-major order.
+/// Tiles are fully redrawn, so the shell can replace them wholesale.
+pub fn render_tiles(doc: &Document, tile_size: u32, dirty_rects: &[Rect]) -> Vec<Tile> {
+    render_tiles_with_progress(doc, tile_size, dirty_rects, |_, _| true).unwrap_or_default()
+}
+
+/// Like [`render_tiles`], calling `progress(done, total)` before each tile and
+/// once at the end. Returns `None` as soon as `progress` returns false.
+pub fn render_tiles_with_progress(
+    doc: &Document,
+    tile_size: u32,
+    dirty_rects: &[Rect],
+    mut progress: impl FnMut(usize, usize) -> bool,
+) -> Option<Vec<Tile>> {
+    if tile_size == 0 {
+        return Some(Vec::new());
+    }
+    let primitives = primitives(doc);
+    let background = doc.meta.canvas.as_ref().and_then(|c| c.background);
+    let coords = dirty_tiles(tile_size, dirty_rects);
+    let total = coords.len();
+    let mut tiles = Vec::with_capacity(total);
+    for (done, (col, row)) in coords.into_iter().enumerate() {
+        if !progress(done, total) {
+            return None;
+        }
+        tiles.push(render_tile(col, row, tile_size, background, &primitives));
+    }
+    progress(total, total).then_some(tiles)
+}
+
+fn dirty_tiles(size: u32, rects: &[Rect]) -> Vec<(i32, i32)> {
+    let s = size as f32;
+    let mut tiles = Vec::new();
+    for r in rects {
+        let finite = [r.min_x, r.min_y, r.max_x, r.max_y]
+            .iter()
+            .all(|v| v.is_finite());
+        if !finite || r.max_x <= r.min_x || r.max_y <= r.min_y {
+            continue;
+        }
+        let (c0, c1) = ((r.min_x / s).floor() as i32, (r.max_x / s).ceil() as i32);
+        let (r0, r1) = ((r.min_y / s).floor() as i32, (r.max_y / s).ceil() as i32);
+        for row in r0..r1 {
+            for col in c0..c1 {
+                tiles.push((col, row));
+            }
+        }
+    }
+    tiles.sort_unstable_by_key(|&(col, row)| (row, col));
+    tiles.dedup();
+    tiles
+}
+
+fn primitives(doc: &Document) -> Vec<Primitive> {
+    let arrows = render_arrows(&doc.items);
+    let layer_clip = |layer_id: Option<u32>| {
+        layer_id.and_then(|id| {
+            doc.meta
+                .layers
+                .iter()
+                .find(|l| l.id == id)
+                .and_then(|l| l.clip)
+        })
+    };
+    let sketches = doc
+        .meta
+        .sketch
+        .map(|s| item_sketches(&doc.items, &arrows, &s))
+        .unwrap_or_default();
+    // A shape's outline (or arrow shaft): its sketched passes when there are
+    // any, else the exact line.
+    let ink_lines = |id: u64, exact: Vec<Point>| {
+        sketches
+            .iter()
+            .find(|s| s.item_id == id)
+            .map_or_else(|| vec![exact], |s| s.lines.clone())
+    };
+    let mut out = Vec::new();
+    // Casings go under all ink, so they never cover another annotation.
+    if let Some(halo) = &doc.meta.halo {
+        for h in item_halos(&doc.items, &arrows, halo) {
+            let paint = Paint::Polygons {
+                polygons: h.polygons,
+            };
+            if let Some(bounds) = paint_bounds(&paint) {
+                out.push(Primitive {
+                    bounds,
+                    paint,
+                    color: h.color,
+                    clip: layer_clip(h.layer_id),
+                });
+            }
+        }
+    }
+    for item in &doc.items {
+        let clip = layer_clip(item.layer_id());
+        let mut push = |paint: Paint, color: ColorRgba8| {
+            let Some(bounds) = paint_bounds(&paint) else {
+                return;
+            };
+            out.push(Primitive {
+                bounds,
+                paint,
+                color,
+                clip,
+            });
+        };
+        match item {
+            Item::Stroke(s) => push(line_paint(s.points.clone(), s.width, &s.line), s.color),
+            Item::Shape(sh) if is_boxed_shape(sh.kind) => {
+                let outline = shape_outline(sh);
+                if is_closed_shape(sh.kind) && sh.style.fill_enabled {
+                    push(
+                        Paint::Fill {
+                            points: outline.clone(),
+                        },
+                        sh.style.fill_color,
+                    );
+                }
+                for line in ink_lines(sh.id, outline) {
+                    push(
+                        line_paint(line, sh.style.stroke_width, &sh.style.line),
+                        sh.style.stroke_color,
+                    );
+                }
+            }
+            Item::Shape(sh) => {
+                let Some(r) = arrows.iter().find(|r| r.shape_id == sh.id) else {
+                    continue;
+                };
+                let shaft = r.shaft.flatten(r.start, r.shaft_end, ARROW_SEGMENTS);
+                for line in ink_lines(sh.id, shaft) {
+                    push(
+                        line_paint(line, r.style.stroke_width, &r.style.line),
+                        r.style.stroke_color,
+                    );
+                }
+                push(
+                    Paint::Fill {
+                        points: r.head_polygon(),
+                    },
+                    r.style.stroke_color,
+                );
+                if let Some(tail) = r.tail {
+                    push(
+                        Paint::Line {
+                            points: tail.outline(),
+                            half_width: r.style.stroke_width * 0.5,
+                        },
+                        r.style.stroke_color,
+                    );
+                }
+            }
+        }
+    }
+    out
+}
+
+fn paint_bounds(paint: &Paint) -> Option<Rect> {
+    match paint {
+        Paint::Line { points, half_width } => {
+            points_bounds(points).map(|b| b.inflate(*half_width + 1.0, *half_width + 1.0))
+        }
+        Paint::Fill { points } => points_bounds(points).map(|b| b.inflate(1.0, 1.0)),
+        Paint::Polygons { polygons } => polygons
+            .iter()
+            .filter_map(|p| points_bounds(p))
+            .reduce(|a, b| a.union(b))
+            .map(|b| b.inflate(1.0, 1.0)),
+    }
+}
+
+fn points_bounds(points: &[Point]) -> Option<Rect> {
+    let first = *points.first()?;
+    Some(points.iter().fold(Rect::from_points(first, first), |r, p| {
+        r.union(Rect::from_points(*p, *p))
+    }))
+}
+
+fn render_tile(
+    col: i32,
+    row: i32,
+    size: u32,
+    background: Option<ColorRgba8>,
+    primitives: &[Primitive],
+) -> Tile {
+    let n = size as usize;
+    // Premultiplied RGBA in 0..=1 while compositing.
+    let mut acc = vec![[0.0f32; 4]; n * n];
+    let tile = Tile {
+        col,
+        row,
+        size,
+        rgba: Vec::new(),
+    };
+    let area = tile.rect();
+    if let Some(bg) = background {
+        for px in &mut acc {
+            blend(px, bg, 1.0);
+        }
+    }
+    for prim in primitives {
+        let region = prim
+            .clip
+            .map_or(Some(prim.bounds), |c| rect_intersection(c, prim.bounds))
+            .and_then(|r| rect_intersection(r, area));
+        let Some(region) = region else { continue };
+        let x0 = (region.min_x - area.min_x).floor().max(0.0) as usize;
+        let y0 = (region.min_y - area.min_y).floor().max(0.0) as usize;
+        let x1 = ((region.max_x - area.min_x).ceil() as usize).min(n);
+        let y1 = ((region.max_y - area.min_y).ceil() as usize).min(n);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let p = Point {
+                    x: area.min_x + x as f32 + 0.5,
+                    y: area.min_y + y as f32 + 0.5,
+                };
+                if prim.clip.is_some_and(|c| !c.contains(p)) {
+                    continue;
+                }
+                let coverage = coverage(&prim.paint, p);
+                if coverage > 0.0 {
+                    blend(&mut acc[y * n + x], prim.color, coverage);
+                }
+            }
+        }
+    }
+    let rgba = acc
+        .iter()
+        .flat_map(|&[r, g, b, a]| {
+            let un = |c: f32| if a > 0.0 { c / a } else { 0.0 };
+            [un(r), un(g), un(b), a].map(|c| (c * 255.0).round().clamp(0.0, 255.0) as u8)
+        })
+        .collect();
+    Tile { rgba, ..tile }
+}
+
+fn blend(dst: &mut [f32; 4], color: ColorRgba8, coverage: f32) {
+    let a = color.a as f32 / 255.0 * coverage;
+    let src = [
+        color.r as f32 / 255.0 * a,
+        color.g as f32 / 255.0 * a,
+        color.b as f32 / 255.0 * a,
+        a,
+    ];
+    for (d, s) in dst.iter_mut().zip(src) {
+        *d = s + *d * (1.0 - a);
+    }
+}
+
+fn coverage(paint: &Paint, p: Point) -> f32 {
+    match paint {
+        Paint::Line { points, half_width } => {
+            let d = match points.as_slice() {
+                [] => return 0.0,
+                [only] => distance(p, *only),
+                _ => points
+                    .windows(2)
+                    .map(|w| distance(p, closest_point_on_segment(p, w[0], w[1])))
+                    .fold(f32::INFINITY, f32::min),
+            };
+            (half_width + 0.5 - d).clamp(0.0, 1.0)
+        }
+        Paint::Fill { points } => polygon_coverage(points, p),
+        // Coverage adds up across polygons so shared edges don't show seams.
+        Paint::Polygons { polygons } => polygons
+            .iter()
+            .map(|poly| polygon_coverage(poly, p))
+            .sum::<f32>()
+            .min(1.0),
+    }
+}
+
+// Even-odd coverage of `points` at `p`, anti-aliased over one pixel.
+fn polygon_coverage(points: &[Point], p: Point) -> f32 {
+    if points.len() < 3 {
+        return 0.0;
+    }
+    let edges = points.iter().zip(points.iter().cycle().skip(1));
+    let mut inside = false;
+    let mut d = f32::INFINITY;
+    for (a, b) in edges {
+        if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+        d = d.min(distance(p, closest_point_on_segment(p, *a, *b)));
+    }
+    if inside {
+        (0.5 + d).min(1.0)
+    } else {
+        (0.5 - d).max(0.0)
+    }
+}
+
+fn distance(a: Point, b: Point) -> f32 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ShapeKind, ShapeStyle};
+    use crate::store::Store;
+    use crate::test_util::style;
+
+    fn pixel(tile: &Tile, x: usize, y: usize) -> [u8; 4] {
+        let i = (y * tile.size as usize + x) * 4;
+        tile.rgba[i..i + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn renders_only_dirty_tiles_with_fills_and_strokes() {
+        let mut store = Store::new();
+        let red = ColorRgba8 {
+            r: 255,
+            g: 0,
+            b: 0,
+            a: 255,
+        };
+        let blue = ColorRgba8 {
+            r: 0,
+            g: 0,
+            b: 255,
+            a: 255,
+        };
+        let style = ShapeStyle {
+            stroke_color: red,
+            fill_enabled: true,
+            fill_color: blue,
+            ..style()
+        };
+        let mut rect = store.begin_shape(ShapeKind::Rectangle, style, Point { x: 4.0, y: 4.0 });
+        rect.end = Point { x: 28.0, y: 28.0 };
+        store.commit_shape(rect).unwrap();
+        let mut s = store.begin_stroke(red, 4.0, Point { x: 40.0, y: 8.0 });
+        s.points.push(Point { x: 60.0, y: 8.0 });
+        store.commit_stroke(s).unwrap();
+
+        let dirty = Rect {
+            min_x: 10.0,
+            min_y: 10.0,
+            max_x: 40.0,
+            max_y: 20.0,
+        };
+        let tiles = render_tiles(&store.document(), 32, &[dirty]);
+        let coords: Vec<(i32, i32)> = tiles.iter().map(|t| (t.col, t.row)).collect();
+        assert_eq!(coords, [(0, 0), (1, 0)]);
+
+        let first = &tiles[0];
+        assert_eq!(first.rgba.len(), 32 * 32 * 4);
+        assert_eq!(pixel(first, 16, 16), [0, 0, 255, 255]);
+        assert_eq!(pixel(first, 4, 16), [255, 0, 0, 255]);
+        assert_eq!(pixel(first, 1, 1)[3], 0);
+        // The stroke from x=40 to 60 at y=8 lands in the second tile.
+        assert_eq!(pixel(&tiles[1], 50 - 32, 8), [255, 0, 0, 255]);
+        assert_eq!(pixel(&tiles[1], 50 - 32, 20)[3], 0);
+    }
+
+    #[test]
+    fn progress_can_cancel_tile_rendering() {
+        let store = Store::new();
+        let dirty = Rect {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: 64.0,
+            max_y: 64.0,
+        };
+        let mut calls = Vec::new();
+        let tiles = render_tiles_with_progress(&store.document(), 32, &[dirty], |done, total| {
+            calls.push((done, total));
+            true
+        });
+        assert_eq!(tiles.map(|t| t.len()), Some(4));
+        assert_eq!(calls.first(), Some(&(0, 4)));
+        assert_eq!(calls.last(), Some(&(4, 4)));
+
+        let cancelled =
+            render_tiles_with_progress(&store.document(), 32, &[dirty], |done, _| done < 2);
+        assert!(cancelled.is_none());
+    }
+}
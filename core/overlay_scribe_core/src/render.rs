@@ -1,7 +1,14 @@
 use crate::geometry::{
-    collect_closed_shapes, is_closed_shape, ClosedShapeHit, ClosedShapeKind, Rect,
+    closest_outline_point, closest_point_on_segment, collect_closed_shapes, ellipse_point_at,
+    is_closed_shape, rect_for_shape, rotated_rect_bounds, ClosedShapeHit, ClosedShapeKind, Rect,
 };
-use crate::model::{Item, Point, Shape, ShapeKind, ShapeStyle};
+use crate::metrics;
+use crate::model::{
+    ArrowTail, BlendMode, Item, Point, Shape, ShapeKind, ShapeStyle, TextAlignH, TextAlignV,
+};
+use crate::trace::Span;
+use std::borrow::Cow;
+use std::collections::HashSet;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ArrowPath {
@@ -10,15 +17,498 @@ pub enum ArrowPath {
     Cubic { c1: Point, c2: Point },
 }
 
+impl ArrowPath {
+    /// Point at curve parameter `t` in `[0, 1]` between `start` and `end`.
+    pub fn point_at(&self, start: Point, end: Point, t: f32) -> Point {
+        match *self {
+            ArrowPath::Line => Point {
+                x: start.x + (end.x - start.x) * t,
+                y: start.y + (end.y - start.y) * t,
+            },
+            ArrowPath::Quadratic { control } => point_at_quadratic(start, control, end, t),
+            ArrowPath::Cubic { c1, c2 } => point_at_cubic(start, c1, c2, end, t),
+        }
+    }
+
+    /// The curve as a polyline of `segments` pieces (a line is always one).
+    pub fn flatten(&self, start: Point, end: Point, segments: usize) -> Vec<Point> {
+        if matches!(self, ArrowPath::Line) {
+            return vec![start, end];
+        }
+        let n = segments.max(1);
+        (0..=n)
+            .map(|i| self.point_at(start, end, i as f32 / n as f32))
+            .collect()
+    }
+
+    /// The part of the curve from `start` up to parameter `t`, as a path of
+    /// the same kind that ends at `point_at(start, end, t)` (the controls
+    /// don't depend on `end`).
+    pub fn trimmed(&self, start: Point, t: f32) -> ArrowPath {
+        let t = clamp01(t);
+        let lerp = |a: Point, b: Point| Point {
+            x: a.x + (b.x - a.x) * t,
+            y: a.y + (b.y - a.y) * t,
+        };
+        match *self {
+            ArrowPath::Line => ArrowPath::Line,
+            ArrowPath::Quadratic { control } => ArrowPath::Quadratic {
+                control: lerp(start, control),
+            },
+            ArrowPath::Cubic { c1, c2 } => {
+                let (a, b) = (lerp(start, c1), lerp(c1, c2));
+                ArrowPath::Cubic {
+                    c1: a,
+                    c2: lerp(a, b),
+                }
+            }
+        }
+    }
+
+    /// Exact bounds of the curve between `start` and `end`, including extrema.
+    pub fn bounds(&self, start: Point, end: Point) -> Rect {
+        let mut ts = vec![0.0, 1.0];
+        match *self {
+            ArrowPath::Line => {}
+            ArrowPath::Quadratic { control } => {
+                for (a, b, c) in [(start.x, control.x, end.x), (start.y, control.y, end.y)] {
+                    // B'(t) = 0 at t = (a - b) / (a - 2b + c).
+                    let denom = a - 2.0 * b + c;
+                    if denom.abs() > f32::EPSILON {
+                        ts.push((a - b) / denom);
+                    }
+                }
+            }
+            ArrowPath::Cubic { c1, c2 } => {
+                for (a, b, c, d) in [(start.x, c1.x, c2.x, end.x), (start.y, c1.y, c2.y, end.y)] {
+                    ts.extend(cubic_extrema(a, b, c, d));
+                }
+            }
+        }
+        ts.into_iter()
+            .filter(|t| (0.0..=1.0).contains(t))
+            .map(|t| self.point_at(start, end, t))
+            .fold(Rect::from_points(start, end), |r, p| {
+                r.union(Rect::from_points(p, p))
+            })
+    }
+}
+
+// Roots of the derivative of a 1D cubic Bezier.
+fn cubic_extrema(a: f32, b: f32, c: f32, d: f32) -> Vec<f32> {
+    // B'(t)/3 = qa t^2 + qb t + qc
+    let qa = -a + 3.0 * b - 3.0 * c + d;
+    let qb = 2.0 * (a - 2.0 * b + c);
+    let qc = b - a;
+    if qa.abs() <= f32::EPSILON {
+        if qb.abs() <= f32::EPSILON {
+            return Vec::new();
+        }
+        return vec![-qc / qb];
+    }
+    let disc = qb * qb - 4.0 * qa * qc;
+    if disc < 0.0 {
+        return Vec::new();
+    }
+    let sq = disc.sqrt();
+    vec![(-qb + sq) / (2.0 * qa), (-qb - sq) / (2.0 * qa)]
+}
+
+// Samples used to measure curves by arc length.
+const ARC_LENGTH_SAMPLES: usize = 64;
+
+// Cumulative length of `path` at evenly spaced parameters, from 0 at `start`.
+fn arc_length_table(path: &ArrowPath, start: Point, end: Point) -> Vec<f32> {
+    let points = path.flatten(start, end, ARC_LENGTH_SAMPLES);
+    let mut table = Vec::with_capacity(points.len());
+    let mut total = 0.0;
+    table.push(total);
+    for w in points.windows(2) {
+        total += hypot(w[1].x - w[0].x, w[1].y - w[0].y);
+        table.push(total);
+    }
+    table
+}
+
+// Curve parameter `distance` along `path`, clamped to its ends.
+fn param_at_length(path: &ArrowPath, start: Point, end: Point, distance: f32) -> f32 {
+    let table = arc_length_table(path, start, end);
+    let total = table.last().copied().unwrap_or(0.0);
+    if total <= f32::EPSILON || distance.is_nan() || distance <= 0.0 {
+        return 0.0;
+    }
+    if distance >= total {
+        return 1.0;
+    }
+    let i = table.partition_point(|&l| l < distance).max(1);
+    let (l0, l1) = (table[i - 1], table[i]);
+    let frac = if l1 > l0 {
+        (distance - l0) / (l1 - l0)
+    } else {
+        0.0
+    };
+    let n = (table.len() - 1) as f32;
+    ((i - 1) as f32 + frac) / n
+}
+
+/// Length of `path` from `start` to `end`, measured on a fine polyline (the
+/// error is well under a unit for arrow-sized curves).
+pub fn path_length(path: &ArrowPath, start: Point, end: Point) -> f32 {
+    if matches!(path, ArrowPath::Line) {
+        return hypot(end.x - start.x, end.y - start.y);
+    }
+    arc_length_table(path, start, end)
+        .last()
+        .copied()
+        .unwrap_or(0.0)
+}
+
+/// Point `distance` units along `path` from `start`, clamped to its ends,
+/// e.g. for dashes or labels spaced evenly along a curve.
+pub fn point_at_length(path: &ArrowPath, start: Point, end: Point, distance: f32) -> Point {
+    path.point_at(start, end, param_at_length(path, start, end, distance))
+}
+
+/// Unit direction of travel `distance` units along `path` from `start`,
+/// clamped to its ends. `None` for a path of zero length.
+pub fn tangent_at_length(
+    path: &ArrowPath,
+    start: Point,
+    end: Point,
+    distance: f32,
+) -> Option<(f32, f32)> {
+    let t = param_at_length(path, start, end, distance);
+    let (dx, dy) = match *path {
+        ArrowPath::Line => (end.x - start.x, end.y - start.y),
+        ArrowPath::Quadratic { control } => {
+            let mt = 1.0 - t;
+            (
+                2.0 * (mt * (control.x - start.x) + t * (end.x - control.x)),
+                2.0 * (mt * (control.y - start.y) + t * (end.y - control.y)),
+            )
+        }
+        ArrowPath::Cubic { c1, c2 } => {
+            let mt = 1.0 - t;
+            let (a, b, c) = (3.0 * mt * mt, 6.0 * mt * t, 3.0 * t * t);
+            (
+                a * (c1.x - start.x) + b * (c2.x - c1.x) + c * (end.x - c2.x),
+                a * (c1.y - start.y) + b * (c2.y - c1.y) + c * (end.y - c2.y),
+            )
+        }
+    };
+    // A control on an end stalls the curve there; look just past it.
+    vec_norm(dx, dy).or_else(|| {
+        let (a, b) = (clamp01(t - 1e-3), clamp01(t + 1e-3));
+        let (p, q) = (path.point_at(start, end, a), path.point_at(start, end, b));
+        vec_norm(q.x - p.x, q.y - p.y)
+    })
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct ArrowRender {
     pub shape_id: u64,
     pub style: ShapeStyle,
+    pub blend_mode: BlendMode,
     pub start: Point,
     pub end: Point,
     pub path: ArrowPath,
+    // The stroked part of `path`: it stops at the arrowhead's base, at
+    // `shaft_end`, so a thick stroke doesn't poke through the head.
+    pub shaft: ArrowPath,
+    pub shaft_end: Point,
     pub head_left: Point,
     pub head_right: Point,
+    // Point of the notch cut into the back of the head (see
+    // `ShapeStyle::head_notch`), on the axis between the tip and the base.
+    pub head_notch: Option<Point>,
+    // How to fill `head_polygon`, so every renderer draws the same head.
+    pub head_fill_rule: FillRule,
+    pub tail: Option<TailRender>,
+    // Center of the arrow's label (its `text`), moved along or off the path
+    // when the midpoint would cover a shape's label. `None` without text.
+    pub label: Option<Point>,
+}
+
+impl ArrowRender {
+    /// The filled arrowhead as a closed polygon: the tip (`end`), the left
+    /// barb, the notch if any, then the right barb.
+    pub fn head_polygon(&self) -> Vec<Point> {
+        let mut points = vec![self.end, self.head_left];
+        points.extend(self.head_notch);
+        points.push(self.head_right);
+        points
+    }
+}
+
+/// Fill rule of a polygon handed to renderers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillRule {
+    #[default]
+    NonZero,
+    EvenOdd,
+}
+
+/// Font size of shape and arrow labels, matching the shells.
+pub const TEXT_FONT_SIZE: f32 = 15.0;
+/// Inset of a closed shape's label from the shape's edges.
+pub const TEXT_INSET: f32 = 10.0;
+// Average glyph advance and line height as multiples of the font size, for
+// sizing labels without a font engine.
+const TEXT_ADVANCE: f32 = 0.55;
+pub(crate) const TEXT_LINE_HEIGHT: f32 = 1.25;
+// Clearance kept around arrow labels.
+const LABEL_GAP: f32 = 4.0;
+// Path parameters tried for an arrow label, in order of preference.
+const LABEL_POSITIONS: [f32; 5] = [0.5, 0.35, 0.65, 0.2, 0.8];
+
+/// Estimated width and height of `text` set at [`TEXT_FONT_SIZE`] without
+/// wrapping.
+pub fn label_size(text: &str) -> (f32, f32) {
+    let lines = text.lines().count().max(1);
+    let longest = text.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+    (
+        longest as f32 * TEXT_FONT_SIZE * TEXT_ADVANCE,
+        lines as f32 * TEXT_FONT_SIZE * TEXT_LINE_HEIGHT,
+    )
+}
+
+// Estimated box of a closed shape's label: wrapped to the inset rect and
+// aligned in it like the shells do.
+fn shape_text_box(shape: &Shape) -> Option<Rect> {
+    let text = shape.text.trim();
+    if !is_closed_shape(shape.kind) || text.is_empty() {
+        return None;
+    }
+    let inset = rect_for_shape(shape).inflate(-TEXT_INSET, -TEXT_INSET);
+    if inset.width() <= 6.0 || inset.height() <= 6.0 {
+        return None;
+    }
+    let rows: f32 = text
+        .lines()
+        .map(|l| (label_size(l).0 / inset.width()).ceil().max(1.0))
+        .sum();
+    let w = label_size(text).0.min(inset.width());
+    let h = (rows * TEXT_FONT_SIZE * TEXT_LINE_HEIGHT).min(inset.height());
+    let x = match shape.text_align_h {
+        TextAlignH::Left => inset.min_x,
+        TextAlignH::Center => inset.center().x - w * 0.5,
+        TextAlignH::Right => inset.max_x - w,
+    };
+    let y = match shape.text_align_v {
+        TextAlignV::Top => inset.min_y,
+        TextAlignV::Middle => inset.center().y - h * 0.5,
+        TextAlignV::Bottom => inset.max_y - h,
+    };
+    let text_box = Rect {
+        min_x: x,
+        min_y: y,
+        max_x: x + w,
+        max_y: y + h,
+    };
+    Some(if shape.rotation == 0.0 {
+        text_box
+    } else {
+        // Conservative: the whole text area, rotated.
+        rotated_rect_bounds(inset, shape.rotation)
+    })
+}
+
+// Picks where an arrow's label goes: the first spot along the path, then
+// beside it, whose box stays clear of `taken`; the midpoint if none is.
+fn place_label(
+    path: ArrowPath,
+    start: Point,
+    end: Point,
+    stroke_width: f32,
+    (w, h): (f32, f32),
+    taken: &[Rect],
+) -> Point {
+    let free = |c: Point| {
+        let label = Rect {
+            min_x: c.x - w * 0.5,
+            min_y: c.y - h * 0.5,
+            max_x: c.x + w * 0.5,
+            max_y: c.y + h * 0.5,
+        }
+        .inflate(LABEL_GAP, LABEL_GAP);
+        !taken.iter().any(|r| r.intersects(label))
+    };
+    let on_path = LABEL_POSITIONS.map(|t| path.point_at(start, end, t));
+    if let Some(c) = on_path.iter().copied().find(|c| free(*c)) {
+        return c;
+    }
+    for t in LABEL_POSITIONS {
+        let a = path.point_at(start, end, (t - 0.01).max(0.0));
+        let b = path.point_at(start, end, (t + 0.01).min(1.0));
+        let len = hypot(b.x - a.x, b.y - a.y);
+        if len <= f32::EPSILON {
+            continue;
+        }
+        let (nx, ny) = (-(b.y - a.y) / len, (b.x - a.x) / len);
+        // Far enough that the label's box clears the stroke.
+        let off = (nx.abs() * w + ny.abs() * h) * 0.5 + stroke_width * 0.5 + LABEL_GAP;
+        let p = path.point_at(start, end, t);
+        for side in [1.0, -1.0] {
+            let c = Point {
+                x: p.x + nx * off * side,
+                y: p.y + ny * off * side,
+            };
+            if free(c) {
+                return c;
+            }
+        }
+    }
+    on_path[0]
+}
+
+/// Geometry of an arrow's tail decoration, drawn as an outline in the
+/// arrow's stroke color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TailRender {
+    Circle { center: Point, radius: f32 },
+    // Corners in order: at the arrow start, left, far tip, right.
+    Diamond { points: [Point; 4] },
+    // A line across the arrow near its start.
+    Bar { a: Point, b: Point },
+}
+
+// Segments used when a tail circle is approximated by a polyline.
+const TAIL_CIRCLE_SEGMENTS: usize = 24;
+
+impl TailRender {
+    /// The decoration as a polyline (closed for circles and diamonds).
+    pub fn outline(&self) -> Vec<Point> {
+        match *self {
+            TailRender::Circle { center, radius } => (0..=TAIL_CIRCLE_SEGMENTS)
+                .map(|i| {
+                    let a = i as f32 / TAIL_CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+                    Point {
+                        x: center.x + radius * a.cos(),
+                        y: center.y + radius * a.sin(),
+                    }
+                })
+                .collect(),
+            TailRender::Diamond { points } => {
+                let mut out = points.to_vec();
+                out.push(points[0]);
+                out
+            }
+            TailRender::Bar { a, b } => vec![a, b],
+        }
+    }
+
+    pub fn bounds(&self) -> Rect {
+        match *self {
+            TailRender::Circle { center, radius } => Rect {
+                min_x: center.x - radius,
+                min_y: center.y - radius,
+                max_x: center.x + radius,
+                max_y: center.y + radius,
+            },
+            TailRender::Diamond { points } => Rect::from_points(points[0], points[2])
+                .union(Rect::from_points(points[1], points[3])),
+            TailRender::Bar { a, b } => Rect::from_points(a, b),
+        }
+    }
+}
+
+/// Knobs for arrow routing.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RoutingConfig {
+    // Snap routing inputs and samples to a 1/64 unit grid and break score
+    // ties by candidate order, so the same document picks the same path on
+    // every platform despite last-bit float differences (e.g. in `sin_cos`).
+    pub deterministic: bool,
+    // Which closed shapes each arrow routes around.
+    pub obstacles: ObstacleScope,
+    // Also steer curved arrows off the paths of arrows routed before them,
+    // in document order. Crossing another arrow only counts against a path
+    // after crossing a shape, so arrows still take the cleanest detour.
+    pub avoid_arrows: bool,
+    // How far into the shapes an arrow is attached to its path may run.
+    pub endpoint_allowance: EndpointAllowance,
+}
+
+/// Distance from each end of an arrow within which its path may pass
+/// through the shape that end is attached to (it has to get out somehow):
+/// `fraction` of the shape's shorter side plus the arrow's stroke width,
+/// clamped to `min..=max`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EndpointAllowance {
+    pub fraction: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Default for EndpointAllowance {
+    fn default() -> Self {
+        Self {
+            fraction: 0.12,
+            min: 6.0,
+            max: 48.0,
+        }
+    }
+}
+
+impl EndpointAllowance {
+    fn around(&self, target: &ClosedShapeHit, stroke_width: f32) -> f32 {
+        let side = target.rect.width().min(target.rect.height());
+        let allowance = side * self.fraction + stroke_width.max(0.0);
+        // `max` wins over a misconfigured `min`, and NaN falls back to `min`.
+        allowance.max(self.min).min(self.max.max(0.0))
+    }
+}
+
+/// Closed shapes an arrow treats as obstacles. Shapes an arrow is attached
+/// to always resolve its ends, whatever the scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ObstacleScope {
+    #[default]
+    All,
+    // Only shapes on the arrow's own layer.
+    SameLayer,
+    // Only shapes sharing at least one tag with the arrow; an untagged
+    // arrow routes around nothing.
+    SharedTag,
+}
+
+impl ObstacleScope {
+    fn includes(self, arrow: &Shape, other: &Shape) -> bool {
+        match self {
+            ObstacleScope::All => true,
+            ObstacleScope::SameLayer => arrow.layer_id == other.layer_id,
+            ObstacleScope::SharedTag => arrow.tags.iter().any(|t| other.tags.contains(t)),
+        }
+    }
+}
+
+// Fixed-point resolution used by deterministic routing.
+const DETERMINISTIC_GRID: f32 = 64.0;
+
+impl RoutingConfig {
+    fn quantize(&self, v: f32) -> f32 {
+        if self.deterministic {
+            (v * DETERMINISTIC_GRID).round() / DETERMINISTIC_GRID
+        } else {
+            v
+        }
+    }
+
+    fn quantize_point(&self, p: Point) -> Point {
+        Point {
+            x: self.quantize(p.x),
+            y: self.quantize(p.y),
+        }
+    }
+
+    fn quantize_rect(&self, r: Rect) -> Rect {
+        Rect {
+            min_x: self.quantize(r.min_x),
+            min_y: self.quantize(r.min_y),
+            max_x: self.quantize(r.max_x),
+            max_y: self.quantize(r.max_y),
+        }
+    }
 }
 
 fn clamp01(v: f32) -> f32 {
@@ -73,7 +563,7 @@ fn point_from_uv(rect: Rect, uv: Point) -> Point {
     }
 }
 
-fn anchor_point_uv(target: &ClosedShapeHit, uv: Point) -> Point {
+pub(crate) fn anchor_point_uv(target: &ClosedShapeHit, uv: Point) -> Point {
     let center = target.rect.center();
     let local = point_from_uv(target.rect, uv);
     let dx = local.x - center.x;
@@ -81,12 +571,118 @@ fn anchor_point_uv(target: &ClosedShapeHit, uv: Point) -> Point {
     if dx * dx + dy * dy <= 1e-6 {
         return center;
     }
+    target.to_world(boundary_toward(target, dx, dy))
+}
+
+// Boundary point in the target's unrotated frame along direction (dx, dy) from its center.
+fn boundary_toward(target: &ClosedShapeHit, dx: f32, dy: f32) -> Point {
     match target.kind {
         ClosedShapeKind::Ellipse => intersect_ellipse(target.rect, dx, dy),
-        ClosedShapeKind::Rectangle | ClosedShapeKind::RoundedRectangle => {
-            intersect_rect(target.rect, dx, dy)
+        ClosedShapeKind::Rectangle => intersect_rect(target.rect, dx, dy),
+        ClosedShapeKind::RoundedRectangle => intersect_rounded_rect(target, dx, dy),
+        ClosedShapeKind::Pie => intersect_pie(target, dx, dy),
+    }
+}
+
+// The rim where the ray falls inside the pie's span; otherwise the rim at
+// the nearer end of the span, since such rays only touch the apex.
+fn intersect_pie(target: &ClosedShapeHit, dx: f32, dy: f32) -> Point {
+    let angles = target.angles;
+    let angle = dy.atan2(dx);
+    if angles.contains(angle) {
+        return ellipse_point_at(target.rect, angle);
+    }
+    let past_end = (angle - angles.end).rem_euclid(std::f32::consts::TAU);
+    let before_start = (angles.start - angle).rem_euclid(std::f32::consts::TAU);
+    let edge = if past_end <= before_start {
+        angles.end
+    } else {
+        angles.start
+    };
+    ellipse_point_at(target.rect, edge)
+}
+
+// Like `intersect_rect`, except that a ray leaving through a rounded corner's
+// square ends on that corner's arc.
+fn intersect_rounded_rect(target: &ClosedShapeHit, dx: f32, dy: f32) -> Point {
+    let r = target.rect;
+    let hit = intersect_rect(r, dx, dy);
+    let [tl, tr, br, bl] = target.radii.to_array();
+    // (arc center x, y, radius, outward x and y signs of its quarter).
+    let corners = [
+        (r.min_x + tl, r.min_y + tl, tl, -1.0, -1.0),
+        (r.max_x - tr, r.min_y + tr, tr, 1.0, -1.0),
+        (r.max_x - br, r.max_y - br, br, 1.0, 1.0),
+        (r.min_x + bl, r.max_y - bl, bl, -1.0, 1.0),
+    ];
+    let Some((cx, cy, radius, _, _)) = corners.into_iter().find(|&(cx, cy, radius, sx, sy)| {
+        radius > 0.0 && (hit.x - cx) * sx > 0.0 && (hit.y - cy) * sy > 0.0
+    }) else {
+        return hit;
+    };
+    // Farther root of |center + t * d - arc center| = radius.
+    let c = r.center();
+    let (ox, oy) = (c.x - cx, c.y - cy);
+    let a = dx * dx + dy * dy;
+    let b = 2.0 * (ox * dx + oy * dy);
+    let k = ox * ox + oy * oy - radius * radius;
+    let disc = b * b - 4.0 * a * k;
+    if a <= f32::EPSILON || disc < 0.0 {
+        return hit;
+    }
+    let t = (-b + disc.sqrt()) / (2.0 * a);
+    Point {
+        x: c.x + dx * t,
+        y: c.y + dy * t,
+    }
+}
+
+// Boundary point of `target` on the ray from its center toward canvas point `toward`.
+fn anchor_point_toward(target: &ClosedShapeHit, toward: Point) -> Point {
+    let c = target.rect.center();
+    let local = target.to_local(toward);
+    target.to_world(boundary_toward(target, local.x - c.x, local.y - c.y))
+}
+
+fn head_length(stroke_width: f32) -> f32 {
+    (stroke_width * 4.0).max(10.0)
+}
+
+// Deepest notch, as a share of the head's length, that still leaves a head.
+const MAX_HEAD_NOTCH: f32 = 0.8;
+
+// Share of the head's length cut out of its back by `style.head_notch`.
+fn head_notch_share(style: &ShapeStyle) -> f32 {
+    style
+        .head_notch
+        .filter(|n| n.is_finite())
+        .map_or(0.0, |n| n.clamp(0.0, MAX_HEAD_NOTCH))
+}
+
+// Bisection steps locating the head's base on a curve.
+const HEAD_BASE_STEPS: usize = 24;
+
+// `path` from `start` up to where it is `reach` from `end` (the back of the
+// head), and that point. Arrows shorter than their head keep no shaft.
+fn trim_to_head(path: ArrowPath, start: Point, end: Point, reach: f32) -> (ArrowPath, Point) {
+    let from_end = |t: f32| {
+        let p = path.point_at(start, end, t);
+        hypot(p.x - end.x, p.y - end.y)
+    };
+    if from_end(0.0) <= reach {
+        return (path.trimmed(start, 0.0), start);
+    }
+    // The last point of the curve still `reach` away from the tip.
+    let (mut lo, mut hi) = (0.0, 1.0);
+    for _ in 0..HEAD_BASE_STEPS {
+        let mid = (lo + hi) * 0.5;
+        if from_end(mid) >= reach {
+            lo = mid;
+        } else {
+            hi = mid;
         }
     }
+    (path.trimmed(start, lo), path.point_at(start, end, lo))
 }
 
 fn compute_arrowhead(
@@ -98,7 +694,7 @@ fn compute_arrowhead(
     let Some((ux, uy)) = vec_norm(tangent_dx, tangent_dy) else {
         return (end, end);
     };
-    let head_length = (stroke_width * 4.0).max(10.0);
+    let head_length = head_length(stroke_width);
     let head_width = (stroke_width * 3.0).max(8.0);
     let base = Point {
         x: end.x - ux * head_length,
@@ -117,6 +713,41 @@ fn compute_arrowhead(
     (left, right)
 }
 
+// Tail decoration at `start`, extending along the unit direction the arrow
+// leaves in; sized from the stroke width like the arrowhead.
+fn compute_tail(
+    tail: ArrowTail,
+    start: Point,
+    tangent_dx: f32,
+    tangent_dy: f32,
+    stroke_width: f32,
+) -> Option<TailRender> {
+    let (ux, uy) = vec_norm(tangent_dx, tangent_dy)?;
+    let size = (stroke_width * 3.0).max(8.0);
+    let along = |d: f32, side: f32| Point {
+        x: start.x + ux * d - uy * side,
+        y: start.y + uy * d + ux * side,
+    };
+    Some(match tail {
+        ArrowTail::Circle => TailRender::Circle {
+            center: along(size * 0.5, 0.0),
+            radius: size * 0.5,
+        },
+        ArrowTail::Diamond => TailRender::Diamond {
+            points: [
+                start,
+                along(size * 0.8, size * 0.5),
+                along(size * 1.6, 0.0),
+                along(size * 0.8, -size * 0.5),
+            ],
+        },
+        ArrowTail::Bar => TailRender::Bar {
+            a: along(size * 0.5, size * 0.5),
+            b: along(size * 0.5, -size * 0.5),
+        },
+    })
+}
+
 fn point_at_quadratic(start: Point, control: Point, end: Point, t: f32) -> Point {
     let mt = 1.0 - t;
     let a = mt * mt;
@@ -174,47 +805,98 @@ fn cubic_controls_pull_toward_waypoint(
     (c1, c2)
 }
 
+// How hard the router searches. Full effort is what committed arrows get;
+// previews while dragging trade accuracy for speed.
+#[derive(Debug, Clone, Copy)]
+struct Effort {
+    // Samples taken along each candidate path.
+    steps: usize,
+    // Waypoints tried when the default curve hits an obstacle.
+    candidates: usize,
+}
+
+impl Effort {
+    const FULL: Effort = Effort {
+        steps: 800,
+        candidates: 24,
+    };
+    const MIN_STEPS: usize = 32;
+
+    // `quality` in [0, 1]; 1 is exactly `FULL`.
+    fn at(quality: f32) -> Effort {
+        let q = if quality.is_nan() {
+            0.0
+        } else {
+            quality.clamp(0.0, 1.0)
+        };
+        let span = (Self::FULL.steps - Self::MIN_STEPS) as f32;
+        Effort {
+            steps: Self::MIN_STEPS + (span * q).round() as usize,
+            candidates: (Self::FULL.candidates as f32 * q).round() as usize,
+        }
+    }
+}
+
+// Endpoint allowances of the shapes an arrow is attached to, by id.
+fn endpoint_allowances(
+    shape: &Shape,
+    attached_ids: &[u64],
+    obstacles: &[ClosedShapeHit],
+    config: &RoutingConfig,
+) -> Vec<(u64, f32)> {
+    obstacles
+        .iter()
+        .filter(|o| attached_ids.contains(&o.id))
+        .map(|o| {
+            let allowance = config
+                .endpoint_allowance
+                .around(o, shape.style.stroke_width);
+            (o.id, config.quantize(allowance))
+        })
+        .collect()
+}
+
 fn sample_inside_hits(
     start: Point,
     end: Point,
-    attached_ids: &[u64],
+    allowances: &[(u64, f32)],
     obstacles: &[ClosedShapeHit],
+    config: &RoutingConfig,
+    steps: usize,
     point_at: impl Fn(f32) -> Point,
 ) -> (Vec<(u64, i32)>, i32) {
-    let endpoint_allowance = 14.0;
-    let steps = 800;
     let margin = 18.0;
 
     let mut hits_by_id: Vec<(u64, i32)> = Vec::new();
     let mut total = 0;
 
-    let mut expanded: Vec<(u64, Rect)> = Vec::new();
+    let mut expanded: Vec<(&ClosedShapeHit, Rect)> = Vec::new();
     for o in obstacles {
-        expanded.push((o.id, o.rect.inflate(margin, margin)));
+        expanded.push((o, o.rect.inflate(margin, margin)));
     }
 
     for i in 0..=steps {
         let t = i as f32 / steps as f32;
         let p = point_at(t);
 
-        for (id, rect) in expanded.iter().copied() {
-            if attached_ids.contains(&id) {
+        for (ob, rect) in expanded.iter().copied() {
+            let id = ob.id;
+            if let Some(&(_, allowance)) = allowances.iter().find(|(k, _)| *k == id) {
                 let ds = hypot(p.x - start.x, p.y - start.y);
                 let de = hypot(p.x - end.x, p.y - end.y);
-                if ds <= endpoint_allowance || de <= endpoint_allowance {
+                if ds <= allowance || de <= allowance {
                     continue;
                 }
             }
 
-            if !rect.contains(p) {
+            // Containment is tested in the obstacle's own (unrotated) frame.
+            let local = config.quantize_point(ob.to_local(p));
+            if !rect.contains(local) {
                 continue;
             }
 
             // Use the original rect containment as our inside test.
-            let Some(ob) = obstacles.iter().find(|o| o.id == id) else {
-                continue;
-            };
-            if ob.rect.contains(p) {
+            if ob.rect.contains(local) {
                 total += 1;
                 if let Some((_k, v)) = hits_by_id.iter_mut().find(|(k, _)| *k == id) {
                     *v += 1;
@@ -228,7 +910,93 @@ fn sample_inside_hits(
     (hits_by_id, total)
 }
 
-fn waypoint_candidates(start: Point, end: Point, obstacles: &[ClosedShapeHit]) -> Vec<Point> {
+// Clearance kept around another arrow's stroke.
+const ARROW_CLEARANCE: f32 = 8.0;
+// Segments used to flatten a routed arrow into an obstacle.
+const ARROW_OBSTACLE_SEGMENTS: usize = 24;
+
+/// A routed arrow as a soft obstacle for the arrows routed after it.
+#[derive(Debug, Clone)]
+struct ArrowObstacle {
+    // The path's bounds inflated by `reach`, to skip most samples cheaply.
+    bounds: Rect,
+    points: Vec<Point>,
+    // Distance from the path within which a sample counts as crossing it.
+    reach: f32,
+}
+
+impl ArrowObstacle {
+    fn new(r: &ArrowRender) -> Self {
+        let reach = r.style.stroke_width.max(0.0) * 0.5 + ARROW_CLEARANCE;
+        Self {
+            bounds: r.path.bounds(r.start, r.end).inflate(reach, reach),
+            points: r.path.flatten(r.start, r.end, ARROW_OBSTACLE_SEGMENTS),
+            reach,
+        }
+    }
+
+    fn near(&self, p: Point) -> bool {
+        self.bounds.contains(p)
+            && self.points.windows(2).any(|w| {
+                let q = closest_point_on_segment(p, w[0], w[1]);
+                hypot(p.x - q.x, p.y - q.y) <= self.reach
+            })
+    }
+}
+
+// Samples of a path that run along or across one of `arrows`. Samples near
+// the path's own ends are skipped, so arrows sharing an anchor don't count.
+fn sample_arrow_hits(
+    start: Point,
+    end: Point,
+    arrows: &[ArrowObstacle],
+    steps: usize,
+    point_at: impl Fn(f32) -> Point,
+) -> i32 {
+    if arrows.is_empty() {
+        return 0;
+    }
+    let mut total = 0;
+    for i in 0..=steps {
+        let p = point_at(i as f32 / steps as f32);
+        for a in arrows {
+            let allowance = 14.0 + a.reach;
+            if hypot(p.x - start.x, p.y - start.y) <= allowance
+                || hypot(p.x - end.x, p.y - end.y) <= allowance
+            {
+                continue;
+            }
+            if a.near(p) {
+                total += 1;
+            }
+        }
+    }
+    total
+}
+
+// Detour waypoints bowing to either side of the chord, for arrows whose
+// default curve only crosses other arrows.
+fn bow_candidates(start: Point, end: Point) -> Vec<Point> {
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    let mid = Point {
+        x: (start.x + end.x) * 0.5,
+        y: (start.y + end.y) * 0.5,
+    };
+    [0.25, -0.25, 0.45, -0.45]
+        .into_iter()
+        .map(|k| Point {
+            x: mid.x - dy * k,
+            y: mid.y + dx * k,
+        })
+        .collect()
+}
+
+fn waypoint_candidates(
+    start: Point,
+    end: Point,
+    obstacles: &[ClosedShapeHit],
+    cap: usize,
+) -> Vec<Point> {
     let margin = 26.0;
     let mid = Point {
         x: (start.x + end.x) * 0.5,
@@ -240,7 +1008,7 @@ fn waypoint_candidates(start: Point, end: Point, obstacles: &[ClosedShapeHit]) -
 
     let mut points: Vec<Point> = Vec::new();
     for hit in &primary {
-        let r = hit.rect.inflate(margin, margin);
+        let r = hit.bounds().inflate(margin, margin);
         union = Some(union.map(|u| u.union(r)).unwrap_or(r));
 
         // Midpoints.
@@ -313,33 +1081,73 @@ fn waypoint_candidates(start: Point, end: Point, obstacles: &[ClosedShapeHit]) -
     points.retain(|p| {
         !obstacles
             .iter()
-            .any(|o| o.rect.inflate(margin, margin).contains(*p))
+            .any(|o| o.bounds().inflate(margin, margin).contains(*p))
     });
 
     // Dedup-ish and cap.
     let mut out: Vec<Point> = Vec::new();
     for p in points {
+        if out.len() >= cap {
+            break;
+        }
         if !out.iter().any(|q| hypot(q.x - p.x, q.y - p.y) < 3.0) {
             out.push(p);
-            if out.len() >= 24 {
-                break;
-            }
         }
     }
     out
 }
 
+// Starts from the default quadratic curve and detours through waypoints
+// only if that curve crosses an obstacle or one of `arrows`, unless `shape`
+// opts out of avoidance. `start` and `end` are its resolved ends.
+#[allow(clippy::too_many_arguments)]
 fn choose_curved_path(
     start: Point,
     end: Point,
-    quad_control: Point,
+    shape: &Shape,
     attached_ids: &[u64],
     obstacles: &[ClosedShapeHit],
+    arrows: &[ArrowObstacle],
+    config: &RoutingConfig,
+    effort: Effort,
+    mut debug: Option<&mut RoutingDebug>,
 ) -> ArrowPath {
-    let (hits_by_id, quad_hits) = sample_inside_hits(start, end, attached_ids, obstacles, |t| {
+    let quad_control = config.quantize_point(quad_control(start, end, shape.style.curvature));
+    if let Some(d) = debug.as_deref_mut() {
+        d.quad_control = Some(quad_control);
+    }
+    if shape.ignore_obstacles {
+        return ArrowPath::Quadratic {
+            control: quad_control,
+        };
+    }
+    let steps = effort.steps;
+    let allowances = endpoint_allowances(shape, attached_ids, obstacles, config);
+    // A kept detour stands while it stays clear, even if the default curve
+    // would now do.
+    if let Some(route) = shape.route {
+        let (c1, c2) = route.controls(start, end);
+        let (c1, c2) = (config.quantize_point(c1), config.quantize_point(c2));
+        let point_at = |t| point_at_cubic(start, c1, c2, end, t);
+        let (_, hits) =
+            sample_inside_hits(start, end, &allowances, obstacles, config, steps, point_at);
+        if hits == 0 && sample_arrow_hits(start, end, arrows, steps, point_at) == 0 {
+            return ArrowPath::Cubic { c1, c2 };
+        }
+    }
+    let (hits_by_id, quad_hits) =
+        sample_inside_hits(start, end, &allowances, obstacles, config, steps, |t| {
+            point_at_quadratic(start, quad_control, end, t)
+        });
+    let quad_arrow_hits = sample_arrow_hits(start, end, arrows, steps, |t| {
         point_at_quadratic(start, quad_control, end, t)
     });
-    if quad_hits == 0 {
+    if let Some(d) = debug.as_deref_mut() {
+        d.quad_hits = quad_hits;
+        d.quad_arrow_hits = quad_arrow_hits;
+        d.obstacle_hits = hits_by_id.clone();
+    }
+    if quad_hits == 0 && quad_arrow_hits == 0 {
         return ArrowPath::Quadratic {
             control: quad_control,
         };
@@ -356,39 +1164,61 @@ fn choose_curved_path(
         -hits
     });
 
-    let candidates = waypoint_candidates(start, end, &ordered);
-    let mut best: Option<(ArrowPath, i32, f32)> = None;
+    let mut candidates = waypoint_candidates(start, end, &ordered, effort.candidates);
+    if quad_arrow_hits > 0 {
+        candidates.extend(bow_candidates(start, end));
+    }
+    if let Some(d) = debug.as_deref_mut() {
+        d.candidate_waypoints = candidates.clone();
+    }
+    // Best so far by shape hits, then arrow hits, then length score.
+    let mut best: Option<(ArrowPath, (i32, i32), f32)> = None;
 
     for w in candidates {
+        let w = config.quantize_point(w);
         let pairs = [
             cubic_controls_through_midpoint(start, end, w),
             cubic_controls_pull_toward_waypoint(start, end, w),
         ];
         for (c1, c2) in pairs {
-            let (_, hits) = sample_inside_hits(start, end, attached_ids, obstacles, |t| {
-                point_at_cubic(start, c1, c2, end, t)
-            });
+            let point_at = |t| point_at_cubic(start, c1, c2, end, t);
+            let (_, hits) =
+                sample_inside_hits(start, end, &allowances, obstacles, config, steps, point_at);
+            let arrow_hits = sample_arrow_hits(start, end, arrows, steps, point_at);
+            if let Some(d) = debug.as_deref_mut() {
+                d.per_candidate_hits.push(RoutingCandidate {
+                    waypoint: w,
+                    path: ArrowPath::Cubic { c1, c2 },
+                    hits,
+                    arrow_hits,
+                });
+            }
 
             let length_score =
                 hypot(c1.x - start.x, c1.y - start.y) + hypot(c2.x - end.x, c2.y - end.y);
-            let score = length_score;
+            // Quantized scores make near-ties exact, so the earlier candidate wins.
+            let score = config.quantize(length_score);
+            let key = (hits, arrow_hits);
             match best {
-                None => best = Some((ArrowPath::Cubic { c1, c2 }, hits, score)),
-                Some((_, best_hits, best_score)) => {
-                    if hits < best_hits || (hits == best_hits && score < best_score) {
-                        best = Some((ArrowPath::Cubic { c1, c2 }, hits, score));
+                None => best = Some((ArrowPath::Cubic { c1, c2 }, key, score)),
+                Some((_, best_key, best_score)) => {
+                    if key < best_key || (key == best_key && score < best_score) {
+                        best = Some((ArrowPath::Cubic { c1, c2 }, key, score));
                     }
                 }
             }
 
-            if hits == 0 {
+            if key == (0, 0) {
                 return ArrowPath::Cubic { c1, c2 };
             }
         }
     }
 
-    if let Some((path, hits, _)) = best {
-        if hits < quad_hits {
+    if debug.is_none() {
+        metrics::count(|m| m.routing_fallbacks += 1);
+    }
+    if let Some((path, key, _)) = best {
+        if key < (quad_hits, quad_arrow_hits) {
             return path;
         }
     }
@@ -398,7 +1228,10 @@ fn choose_curved_path(
     }
 }
 
-fn quad_control_simple(start: Point, end: Point) -> Point {
+// Control of a curved arrow's default quadratic. An explicit `curvature`
+// (see `ShapeStyle::curvature`) sets the bow; otherwise it follows the
+// legacy automatic rule.
+fn quad_control(start: Point, end: Point, curvature: Option<f32>) -> Point {
     let mid = Point {
         x: (start.x + end.x) * 0.5,
         y: (start.y + end.y) * 0.5,
@@ -412,16 +1245,33 @@ fn quad_control_simple(start: Point, end: Point) -> Point {
     let ux = dx / len;
     let uy = dy / len;
     let perp = Point { x: -uy, y: ux };
-    let magnitude = (len * 0.22).clamp(18.0, 160.0);
-
-    // Legacy-ish sign rule.
-    let sign = if dx * dy >= 0.0 { 1.0 } else { -1.0 };
+    let (magnitude, sign) = match curvature {
+        Some(c) => (len * c, 1.0),
+        // Legacy-ish sign rule.
+        None => (
+            (len * 0.22).clamp(18.0, 160.0),
+            if dx * dy >= 0.0 { 1.0 } else { -1.0 },
+        ),
+    };
     Point {
         x: mid.x + perp.x * magnitude * sign,
         y: mid.y + perp.y * magnitude * sign,
     }
 }
 
+// Controls of an S-curve from `start` to `end`: a third of the way along,
+// pushed `curvature` times the length to one side, then to the other.
+fn s_curve_controls(start: Point, end: Point, curvature: f32) -> (Point, Point) {
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    // Perpendicular scaled by the length, so the offset is `curvature * len`.
+    let (px, py) = (-dy * curvature, dx * curvature);
+    let at = |t: f32, side: f32| Point {
+        x: start.x + dx * t + px * side,
+        y: start.y + dy * t + py * side,
+    };
+    (at(1.0 / 3.0, 1.0), at(2.0 / 3.0, -1.0))
+}
+
 fn resolve_endpoints(shape: &Shape, closed: &[ClosedShapeHit]) -> (Point, Point, Vec<u64>) {
     let mut start = shape.start;
     let mut end = shape.end;
@@ -433,15 +1283,7 @@ fn resolve_endpoints(shape: &Shape, closed: &[ClosedShapeHit]) -> (Point, Point,
             if let Some(uv) = shape.start_attach_uv {
                 start = anchor_point_uv(target, uv);
             } else {
-                let c = target.rect.center();
-                let dx = end.x - c.x;
-                let dy = end.y - c.y;
-                start = match target.kind {
-                    ClosedShapeKind::Ellipse => intersect_ellipse(target.rect, dx, dy),
-                    ClosedShapeKind::Rectangle | ClosedShapeKind::RoundedRectangle => {
-                        intersect_rect(target.rect, dx, dy)
-                    }
-                };
+                start = anchor_point_toward(target, end);
             }
         }
     }
@@ -454,15 +1296,7 @@ fn resolve_endpoints(shape: &Shape, closed: &[ClosedShapeHit]) -> (Point, Point,
             if let Some(uv) = shape.end_attach_uv {
                 end = anchor_point_uv(target, uv);
             } else {
-                let c = target.rect.center();
-                let dx = start.x - c.x;
-                let dy = start.y - c.y;
-                end = match target.kind {
-                    ClosedShapeKind::Ellipse => intersect_ellipse(target.rect, dx, dy),
-                    ClosedShapeKind::Rectangle | ClosedShapeKind::RoundedRectangle => {
-                        intersect_rect(target.rect, dx, dy)
-                    }
-                };
+                end = anchor_point_toward(target, start);
             }
         }
     }
@@ -471,52 +1305,366 @@ fn resolve_endpoints(shape: &Shape, closed: &[ClosedShapeHit]) -> (Point, Point,
 }
 
 pub fn render_arrows(items: &[Item]) -> Vec<ArrowRender> {
-    let closed = collect_closed_shapes(items);
+    render_arrows_with(items, &RoutingConfig::default())
+}
+
+/// [`render_arrows`] with explicit routing options.
+pub fn render_arrows_with(items: &[Item], config: &RoutingConfig) -> Vec<ArrowRender> {
+    route_arrows(items, config, |_| true)
+}
+
+/// Routes only the arrows listed in `ids` or attached to a shape listed
+/// there, e.g. while one shape is being dragged. Other items still count as
+/// obstacles, so each result matches what [`render_arrows_with`] returns.
+pub fn render_arrows_for(items: &[Item], ids: &[u64], config: &RoutingConfig) -> Vec<ArrowRender> {
+    route_arrows(items, config, |shape| {
+        [Some(shape.id), shape.start_attach_id, shape.end_attach_id]
+            .iter()
+            .flatten()
+            .any(|id| ids.contains(id))
+    })
+}
+
+fn route_arrows(
+    items: &[Item],
+    config: &RoutingConfig,
+    wanted: impl Fn(&Shape) -> bool,
+) -> Vec<ArrowRender> {
+    let mut span = Span::enter("render.route_arrows");
+    span.record("items", items.len() as u64);
+    let mut closed = collect_closed_shapes(items);
+    if config.deterministic {
+        for hit in &mut closed {
+            hit.rect = config.quantize_rect(hit.rect);
+        }
+    }
     let mut out = Vec::new();
+    let mut text_boxes: Option<Vec<Rect>> = None;
+    // Arrows avoiding each other depend on every arrow before them, so all
+    // of them are routed and only the wanted ones returned.
+    let mut routed: Vec<(&Shape, ArrowObstacle)> = Vec::new();
 
     for it in items {
         let Item::Shape(shape) = it else { continue };
-        if !matches!(shape.kind, ShapeKind::Arrow | ShapeKind::CurvedArrow) {
+        if !is_arrow_like(shape.kind) || !(config.avoid_arrows || wanted(shape)) {
             continue;
         }
-
-        let (start, end, attached_ids) = resolve_endpoints(shape, &closed);
-        let dx = end.x - start.x;
-        let dy = end.y - start.y;
-        let len = hypot(dx, dy);
-        if len <= 0.5 {
+        let Some(render) = route_arrow(
+            shape,
+            items,
+            &scoped_obstacles(shape, items, &closed, config.obstacles),
+            &scoped_arrows(shape, &routed, config.obstacles),
+            config,
+            Effort::FULL,
+            &mut text_boxes,
+        ) else {
             continue;
+        };
+        if config.avoid_arrows {
+            routed.push((shape, ArrowObstacle::new(&render)));
+        }
+        if wanted(shape) {
+            out.push(render);
         }
+    }
 
-        let path = match shape.kind {
-            ShapeKind::Arrow => ArrowPath::Line,
-            ShapeKind::CurvedArrow => {
-                let quad = quad_control_simple(start, end);
-                choose_curved_path(start, end, quad, &attached_ids, &closed)
-            }
-            _ => ArrowPath::Line,
-        };
+    span.record("arrows", out.len() as u64);
+    out
+}
 
-        // Compute tangent at end for arrowhead.
-        let (tx, ty) = match path {
-            ArrowPath::Line => (dx, dy),
-            ArrowPath::Quadratic { control } => (end.x - control.x, end.y - control.y),
-            ArrowPath::Cubic { c2, .. } => (end.x - c2.x, end.y - c2.y),
-        };
-        let (hl, hr) = compute_arrowhead(end, tx, ty, shape.style.stroke_width);
+// The part of `closed` that `shape` routes around under `scope`, plus the
+// shapes it is attached to.
+fn scoped_obstacles<'a>(
+    shape: &Shape,
+    items: &[Item],
+    closed: &'a [ClosedShapeHit],
+    scope: ObstacleScope,
+) -> Cow<'a, [ClosedShapeHit]> {
+    if scope == ObstacleScope::All {
+        return Cow::Borrowed(closed);
+    }
+    let in_scope: HashSet<u64> = items
+        .iter()
+        .filter_map(|it| match it {
+            Item::Shape(other) if scope.includes(shape, other) => Some(other.id),
+            _ => None,
+        })
+        .chain(shape.start_attach_id)
+        .chain(shape.end_attach_id)
+        .collect();
+    Cow::Owned(
+        closed
+            .iter()
+            .filter(|hit| in_scope.contains(&hit.id))
+            .copied()
+            .collect(),
+    )
+}
+
+// The arrows routed before `shape` that it steers clear of under `scope`.
+fn scoped_arrows(
+    shape: &Shape,
+    routed: &[(&Shape, ArrowObstacle)],
+    scope: ObstacleScope,
+) -> Vec<ArrowObstacle> {
+    routed
+        .iter()
+        .filter(|(other, _)| scope.includes(shape, other))
+        .map(|(_, a)| a.clone())
+        .collect()
+}
+
+// Routes one arrow-like `shape` around `closed` and, softly, `arrows`;
+// `items` supply the shape labels its own label keeps clear of. `None` for
+// a degenerate arrow.
+fn route_arrow(
+    shape: &Shape,
+    items: &[Item],
+    closed: &[ClosedShapeHit],
+    arrows: &[ArrowObstacle],
+    config: &RoutingConfig,
+    effort: Effort,
+    text_boxes: &mut Option<Vec<Rect>>,
+) -> Option<ArrowRender> {
+    let (start, end, attached_ids) = resolve_endpoints(shape, closed);
+    let (start, end) = (config.quantize_point(start), config.quantize_point(end));
+    let dx = end.x - start.x;
+    let dy = end.y - start.y;
+    let len = hypot(dx, dy);
+    if len <= 0.5 {
+        return None;
+    }
 
-        out.push(ArrowRender {
-            shape_id: shape.id,
-            style: shape.style,
+    let path = match shape.kind {
+        ShapeKind::Arrow => ArrowPath::Line,
+        ShapeKind::CurvedArrow => choose_curved_path(
             start,
             end,
-            path,
-            head_left: hl,
-            head_right: hr,
+            shape,
+            &attached_ids,
+            closed,
+            arrows,
+            config,
+            effort,
+            None,
+        ),
+        ShapeKind::SCurve => s_curve_path(shape, start, end, config),
+        _ => ArrowPath::Line,
+    };
+
+    // Compute tangent at end for arrowhead.
+    let (tx, ty) = match path {
+        ArrowPath::Line => (dx, dy),
+        ArrowPath::Quadratic { control } => (end.x - control.x, end.y - control.y),
+        ArrowPath::Cubic { c2, .. } => (end.x - c2.x, end.y - c2.y),
+    };
+    let (hl, hr) = compute_arrowhead(end, tx, ty, shape.style.stroke_width);
+    // The shaft runs into the notch, if any, so it meets the head's back.
+    let notch = head_notch_share(&shape.style);
+    let back = head_length(shape.style.stroke_width) * (1.0 - notch);
+    let head_notch = (notch > 0.0).then(|| {
+        let (ux, uy) = vec_norm(tx, ty).unwrap_or((0.0, 0.0));
+        Point {
+            x: end.x - ux * back,
+            y: end.y - uy * back,
+        }
+    });
+    let (shaft, shaft_end) = trim_to_head(path, start, end, back);
+    let (sx, sy) = match path {
+        ArrowPath::Line => (dx, dy),
+        ArrowPath::Quadratic { control } => (control.x - start.x, control.y - start.y),
+        ArrowPath::Cubic { c1, .. } => (c1.x - start.x, c1.y - start.y),
+    };
+    let tail = shape
+        .tail
+        .and_then(|t| compute_tail(t, start, sx, sy, shape.style.stroke_width));
+
+    let text = shape.text.trim();
+    let label = (!text.is_empty()).then(|| {
+        let taken = text_boxes.get_or_insert_with(|| {
+            items
+                .iter()
+                .filter_map(|it| match it {
+                    Item::Shape(sh) => shape_text_box(sh),
+                    Item::Stroke(_) => None,
+                })
+                .collect()
         });
+        let size = label_size(text);
+        place_label(path, start, end, shape.style.stroke_width, size, taken)
+    });
+
+    Some(ArrowRender {
+        shape_id: shape.id,
+        style: shape.style,
+        blend_mode: shape.blend_mode,
+        start,
+        end,
+        path,
+        shaft,
+        shaft_end,
+        head_left: hl,
+        head_right: hr,
+        head_notch,
+        head_fill_rule: FillRule::NonZero,
+        tail,
+        label,
+    })
+}
+
+// The S-curve of `shape` between its resolved ends; its bow comes from the
+// style alone, so obstacles don't move it.
+fn s_curve_path(shape: &Shape, start: Point, end: Point, config: &RoutingConfig) -> ArrowPath {
+    let (c1, c2) = s_curve_controls(start, end, shape.style.curvature());
+    ArrowPath::Cubic {
+        c1: config.quantize_point(c1),
+        c2: config.quantize_point(c2),
     }
+}
 
-    out
+/// Cheap route for an arrow-like `shape` that is still being dragged (not
+/// yet in the document), around the closed shapes in `obstacles`. `quality`
+/// in `[0, 1]` scales how finely paths are sampled and how many detour
+/// waypoints are tried; at 0 only the default curve is checked. At 1 the
+/// result is exactly what [`render_arrows`] returns once the shape is
+/// committed into `obstacles`. `None` if `shape` isn't arrow-like or is too
+/// short to draw.
+pub fn preview_arrow(shape: &Shape, obstacles: &[Item], quality: f32) -> Option<ArrowRender> {
+    if !is_arrow_like(shape.kind) {
+        return None;
+    }
+    let closed: Vec<ClosedShapeHit> = collect_closed_shapes(obstacles)
+        .into_iter()
+        .filter(|hit| hit.id != shape.id)
+        .collect();
+    route_arrow(
+        shape,
+        obstacles,
+        &closed,
+        &[],
+        &RoutingConfig::default(),
+        Effort::at(quality),
+        &mut None,
+    )
+}
+
+/// Why the router picked an arrow's path: the curved-arrow baseline, the
+/// waypoints it tried and how often each candidate path hit an obstacle.
+/// Candidates after the first obstacle-free one are not evaluated, nor is
+/// anything past an arrow's kept route (`Shape::route`) while it is clear.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoutingDebug {
+    pub start: Point,
+    pub end: Point,
+    // Control of the default quadratic curve; `None` for straight arrows.
+    pub quad_control: Option<Point>,
+    // Samples of the quadratic that fell inside obstacles, total and by id;
+    // not counted for arrows that ignore obstacles.
+    pub quad_hits: i32,
+    // Samples of the quadratic near earlier arrows, with `avoid_arrows`.
+    pub quad_arrow_hits: i32,
+    pub obstacle_hits: Vec<(u64, i32)>,
+    pub candidate_waypoints: Vec<Point>,
+    pub per_candidate_hits: Vec<RoutingCandidate>,
+    pub chosen: Option<ArrowPath>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoutingCandidate {
+    pub waypoint: Point,
+    pub path: ArrowPath,
+    pub hits: i32,
+    pub arrow_hits: i32,
+}
+
+/// Routes one arrow like [`render_arrows_with`], recording the router's
+/// intermediate choices. `None` if `arrow_shape_id` isn't an arrow.
+pub fn arrow_routing_debug(
+    items: &[Item],
+    arrow_shape_id: u64,
+    config: &RoutingConfig,
+) -> Option<RoutingDebug> {
+    let mut closed = collect_closed_shapes(items);
+    if config.deterministic {
+        for hit in &mut closed {
+            hit.rect = config.quantize_rect(hit.rect);
+        }
+    }
+    let shape = items.iter().find_map(|it| match it {
+        Item::Shape(sh) if sh.id == arrow_shape_id && is_arrow_like(sh.kind) => Some(sh),
+        _ => None,
+    })?;
+    let arrows = if config.avoid_arrows {
+        earlier_arrows(items, shape, &closed, config)
+    } else {
+        Vec::new()
+    };
+    let closed = scoped_obstacles(shape, items, &closed, config.obstacles);
+    let (start, end, attached_ids) = resolve_endpoints(shape, &closed);
+    let (start, end) = (config.quantize_point(start), config.quantize_point(end));
+    let mut debug = RoutingDebug {
+        start,
+        end,
+        quad_control: None,
+        quad_hits: 0,
+        quad_arrow_hits: 0,
+        obstacle_hits: Vec::new(),
+        candidate_waypoints: Vec::new(),
+        per_candidate_hits: Vec::new(),
+        chosen: None,
+    };
+    if hypot(end.x - start.x, end.y - start.y) <= 0.5 {
+        return Some(debug);
+    }
+    debug.chosen = Some(match shape.kind {
+        ShapeKind::CurvedArrow => choose_curved_path(
+            start,
+            end,
+            shape,
+            &attached_ids,
+            &closed,
+            &arrows,
+            config,
+            Effort::FULL,
+            Some(&mut debug),
+        ),
+        ShapeKind::SCurve => s_curve_path(shape, start, end, config),
+        _ => ArrowPath::Line,
+    });
+    Some(debug)
+}
+
+// Routes the arrows ahead of `shape` in `items`, as `route_arrows` would,
+// and returns the ones `shape` steers clear of.
+fn earlier_arrows(
+    items: &[Item],
+    shape: &Shape,
+    closed: &[ClosedShapeHit],
+    config: &RoutingConfig,
+) -> Vec<ArrowObstacle> {
+    let mut routed: Vec<(&Shape, ArrowObstacle)> = Vec::new();
+    for it in items {
+        let Item::Shape(other) = it else { continue };
+        if other.id == shape.id {
+            break;
+        }
+        if !is_arrow_like(other.kind) {
+            continue;
+        }
+        let render = route_arrow(
+            other,
+            items,
+            &scoped_obstacles(other, items, closed, config.obstacles),
+            &scoped_arrows(other, &routed, config.obstacles),
+            config,
+            Effort::FULL,
+            &mut None,
+        );
+        if let Some(render) = render {
+            routed.push((other, ArrowObstacle::new(&render)));
+        }
+    }
+    scoped_arrows(shape, &routed, config.obstacles)
 }
 
 pub fn arrow_obstacle_ids(items: &[Item], arrow_shape_id: u64) -> Vec<u64> {
@@ -527,7 +1675,7 @@ pub fn arrow_obstacle_ids(items: &[Item], arrow_shape_id: u64) -> Vec<u64> {
         if sh.id != arrow_shape_id {
             continue;
         }
-        if !matches!(sh.kind, ShapeKind::Arrow | ShapeKind::CurvedArrow) {
+        if !is_arrow_like(sh.kind) {
             return Vec::new();
         }
         let (_, _, attached_ids) = resolve_endpoints(sh, &closed);
@@ -542,10 +1690,553 @@ pub fn arrow_obstacle_ids(items: &[Item], arrow_shape_id: u64) -> Vec<u64> {
     Vec::new()
 }
 
+/// Closest point on a path (and its distance to `p`).
+pub fn closest_point_on_path(start: Point, path: ArrowPath, end: Point, p: Point) -> (Point, f32) {
+    if path == ArrowPath::Line {
+        let q = closest_point_on_segment(p, start, end);
+        return (q, hypot(q.x - p.x, q.y - p.y));
+    }
+    // Coarse sampling, then refine around the best sample.
+    let dist = |t: f32| {
+        let q = path.point_at(start, end, t);
+        (q, hypot(q.x - p.x, q.y - p.y))
+    };
+    let coarse = 64;
+    let mut best_t = 0.0;
+    let mut best = dist(0.0);
+    for i in 1..=coarse {
+        let t = i as f32 / coarse as f32;
+        let d = dist(t);
+        if d.1 < best.1 {
+            best = d;
+            best_t = t;
+        }
+    }
+    let step = 1.0 / coarse as f32;
+    let (lo, hi) = ((best_t - step).max(0.0), (best_t + step).min(1.0));
+    let fine = 32;
+    for i in 0..=fine {
+        let t = lo + (hi - lo) * i as f32 / fine as f32;
+        let d = dist(t);
+        if d.1 < best.1 {
+            best = d;
+        }
+    }
+    best
+}
+
+/// Closest point on a routed arrow's path.
+pub fn closest_point_on_arrow(render: &ArrowRender, p: Point) -> (Point, f32) {
+    closest_point_on_path(render.start, render.path, render.end, p)
+}
+
+/// Closest point on a shape's outline to `p`, and the distance to it, for
+/// every kind: closed shapes and arcs as in [`closest_outline_point`],
+/// arrow-like shapes along their unrouted path. For the routed path of a
+/// rendered arrow use [`closest_point_on_arrow`].
+pub fn closest_boundary_point(shape: &Shape, p: Point) -> (Point, f32) {
+    closest_outline_point(shape, p).unwrap_or_else(|| {
+        closest_point_on_path(shape.start, unrouted_arrow_path(shape), shape.end, p)
+    })
+}
+
+/// The path an arrow-like shape takes from its raw endpoints, before
+/// attachment resolution and obstacle routing.
+pub fn unrouted_arrow_path(shape: &Shape) -> ArrowPath {
+    match shape.kind {
+        ShapeKind::CurvedArrow => ArrowPath::Quadratic {
+            control: quad_control(shape.start, shape.end, shape.style.curvature),
+        },
+        ShapeKind::SCurve => {
+            let (c1, c2) = s_curve_controls(shape.start, shape.end, shape.style.curvature());
+            ArrowPath::Cubic { c1, c2 }
+        }
+        _ => ArrowPath::Line,
+    }
+}
+
 pub fn is_arrow_like(kind: ShapeKind) -> bool {
-    matches!(kind, ShapeKind::Arrow | ShapeKind::CurvedArrow)
+    matches!(
+        kind,
+        ShapeKind::Arrow | ShapeKind::CurvedArrow | ShapeKind::SCurve
+    )
 }
 
 pub fn is_closed(kind: ShapeKind) -> bool {
     is_closed_shape(kind)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::store::Store;
+    use crate::test_util::style;
+
+    #[test]
+    fn deterministic_routing_ignores_last_bit_noise() {
+        let mut store = Store::new();
+        let style = style();
+        let mut ids = Vec::new();
+        for (x0, y0, x1, y1) in [
+            (0.0, 0.0, 100.0, 100.0),
+            (300.0, 0.0, 400.0, 100.0),
+            (150.0, 20.0, 250.0, 80.0),
+        ] {
+            let mut sh = store.begin_shape(ShapeKind::Rectangle, style, Point { x: x0, y: y0 });
+            sh.end = Point { x: x1, y: y1 };
+            ids.push(sh.id);
+            store.commit_shape(sh).unwrap();
+        }
+        let mut arrow =
+            store.begin_shape(ShapeKind::CurvedArrow, style, Point { x: 50.0, y: 50.0 });
+        arrow.end = Point { x: 350.0, y: 50.0 };
+        arrow.start_attach_id = Some(ids[0]);
+        arrow.end_attach_id = Some(ids[1]);
+        store.commit_shape(arrow).unwrap();
+
+        let config = RoutingConfig {
+            deterministic: true,
+            ..Default::default()
+        };
+        let mut items = store.items().to_vec();
+        let a = render_arrows_with(&items, &config);
+        assert!(matches!(a[0].path, ArrowPath::Cubic { .. }));
+
+        // Nudge the obstacle by one ulp, far below the fixed-point resolution.
+        if let Item::Shape(sh) = &mut items[2] {
+            sh.start.x = f32::from_bits(sh.start.x.to_bits() + 1);
+        }
+        assert_eq!(render_arrows_with(&items, &config), a);
+
+        // The debug route explains the same choice.
+        let arrow_id = items[3].id();
+        let debug = arrow_routing_debug(&items, arrow_id, &config).unwrap();
+        assert_eq!(debug.chosen, Some(a[0].path));
+        assert!(debug.quad_hits > 0);
+        assert!(debug.obstacle_hits.iter().any(|(id, _)| *id == ids[2]));
+        assert!(!debug.candidate_waypoints.is_empty());
+        let last = debug.per_candidate_hits.last().unwrap();
+        assert_eq!((last.path, last.hits), (a[0].path, 0));
+        assert!(arrow_routing_debug(&items, ids[0], &config).is_none());
+
+        // Subset routing picks arrows by their own id or an attached shape's.
+        assert_eq!(render_arrows_for(&items, &[ids[0]], &config), a);
+        assert_eq!(render_arrows_for(&items, &[arrow_id], &config), a);
+        assert!(render_arrows_for(&items, &[ids[2]], &config).is_empty());
+
+        // Opting out of avoidance keeps the default curve through the obstacle.
+        assert!(store.set_ignore_obstacles(&[arrow_id], true));
+        let plain = render_arrows_with(store.items(), &config);
+        assert_eq!(
+            plain[0].path,
+            ArrowPath::Quadratic {
+                control: debug.quad_control.unwrap()
+            }
+        );
+    }
+
+    #[test]
+    fn obstacle_scope_skips_shapes_on_other_layers_or_tags() {
+        let mut store = Store::new();
+        let style = style();
+        // Two nodes with a backdrop box between them.
+        for (x0, x1) in [(0.0, 100.0), (300.0, 400.0), (150.0, 250.0)] {
+            let mut sh = store.begin_shape(ShapeKind::Rectangle, style, Point { x: x0, y: 0.0 });
+            sh.end = Point { x: x1, y: 100.0 };
+            store.commit_shape(sh).unwrap();
+        }
+        let ids: Vec<u64> = store.items().iter().map(Item::id).collect();
+        let mut arrow =
+            store.begin_shape(ShapeKind::CurvedArrow, style, Point { x: 50.0, y: 50.0 });
+        arrow.end = Point { x: 350.0, y: 50.0 };
+        arrow.start_attach_id = Some(ids[0]);
+        arrow.end_attach_id = Some(ids[1]);
+        store.commit_shape(arrow).unwrap();
+
+        let mut items = store.items().to_vec();
+        if let Item::Shape(backdrop) = &mut items[2] {
+            backdrop.layer_id = Some(7);
+            backdrop.tags = vec!["backdrop".to_string()];
+        }
+        if let Item::Shape(arrow) = &mut items[3] {
+            arrow.tags = vec!["flow".to_string()];
+        }
+        let route = |obstacles| {
+            let config = RoutingConfig {
+                obstacles,
+                ..Default::default()
+            };
+            render_arrows_with(&items, &config)[0]
+        };
+        let all = route(ObstacleScope::All);
+        assert!(matches!(all.path, ArrowPath::Cubic { .. }));
+        for scope in [ObstacleScope::SameLayer, ObstacleScope::SharedTag] {
+            // Still attached to the nodes, just no detour around the backdrop.
+            let scoped = route(scope);
+            assert!(matches!(scoped.path, ArrowPath::Quadratic { .. }));
+            assert_eq!((scoped.start, scoped.end), (all.start, all.end));
+        }
+    }
+
+    #[test]
+    fn avoiding_arrows_routes_later_arrows_off_earlier_ones() {
+        let mut store = Store::new();
+        let style = style();
+        // Two curved arrows over the same span bow the same way by default.
+        for _ in 0..2 {
+            let mut arrow =
+                store.begin_shape(ShapeKind::CurvedArrow, style, Point { x: 0.0, y: 0.0 });
+            arrow.end = Point { x: 300.0, y: 0.0 };
+            store.commit_shape(arrow).unwrap();
+        }
+        let items = store.items();
+        let second = items[1].id();
+        let plain = render_arrows(items);
+        assert_eq!(plain[0].path, plain[1].path);
+
+        let config = RoutingConfig {
+            avoid_arrows: true,
+            ..Default::default()
+        };
+        let avoided = render_arrows_with(items, &config);
+        assert_eq!(avoided[0], plain[0], "the first arrow has nothing to avoid");
+        assert_ne!(avoided[1].path, plain[1].path);
+        let debug = arrow_routing_debug(items, second, &config).unwrap();
+        assert!(debug.quad_arrow_hits > 0);
+        assert_eq!(debug.chosen, Some(avoided[1].path));
+        let obstacle = ArrowObstacle::new(&avoided[0]);
+        let hits = sample_arrow_hits(avoided[1].start, avoided[1].end, &[obstacle], 200, |t| {
+            avoided[1]
+                .path
+                .point_at(avoided[1].start, avoided[1].end, t)
+        });
+        assert_eq!(hits, 0);
+
+        // Routing a subset still sees the arrows before it.
+        assert_eq!(
+            render_arrows_for(items, &[second], &config),
+            vec![avoided[1]]
+        );
+    }
+
+    #[test]
+    fn endpoint_allowance_scales_with_the_attached_shape() {
+        let mut store = Store::new();
+        let style = style();
+        for (w, h) in [(20.0, 10.0), (200.0, 100.0), (2000.0, 1000.0)] {
+            let mut sh = store.begin_shape(ShapeKind::Rectangle, style, Point { x: 0.0, y: 0.0 });
+            sh.end = Point { x: w, y: h };
+            store.commit_shape(sh).unwrap();
+        }
+        let closed = collect_closed_shapes(store.items());
+        let allowance = EndpointAllowance::default();
+        let around: Vec<f32> = closed.iter().map(|o| allowance.around(o, 4.0)).collect();
+        assert_eq!(around[0], allowance.min, "tiny shapes get the floor");
+        assert!((around[1] - (100.0 * allowance.fraction + 4.0)).abs() < 1e-4);
+        assert_eq!(around[2], allowance.max, "huge shapes get the cap");
+
+        let fixed = EndpointAllowance {
+            fraction: 0.0,
+            min: 14.0,
+            max: 14.0,
+        };
+        assert!(closed.iter().all(|o| fixed.around(o, 4.0) == 14.0));
+    }
+
+    #[test]
+    fn tail_decorations_sit_at_the_start() {
+        let mut store = Store::new();
+        let style = style();
+        let mut arrow = store.begin_shape(ShapeKind::Arrow, style, Point { x: 0.0, y: 0.0 });
+        arrow.end = Point { x: 100.0, y: 0.0 };
+        let id = arrow.id;
+        store.commit_shape(arrow).unwrap();
+        assert_eq!(render_arrows(store.items())[0].tail, None);
+
+        assert!(store.set_arrow_tail(&[id], Some(ArrowTail::Circle)));
+        let tail = render_arrows(store.items())[0].tail;
+        let Some(TailRender::Circle { center, radius }) = tail else {
+            panic!("expected circle tail");
+        };
+        assert_eq!((center, radius), (Point { x: 4.0, y: 0.0 }, 4.0));
+
+        assert!(store.set_arrow_tail(&[id], Some(ArrowTail::Diamond)));
+        let tail = render_arrows(store.items())[0].tail.unwrap();
+        assert_eq!((tail.bounds().min_x, tail.bounds().max_x), (0.0, 12.8));
+
+        assert!(store.set_arrow_tail(&[id], Some(ArrowTail::Bar)));
+        let tail = render_arrows(store.items())[0].tail.unwrap();
+        assert_eq!(
+            tail,
+            TailRender::Bar {
+                a: Point { x: 4.0, y: 4.0 },
+                b: Point { x: 4.0, y: -4.0 },
+            }
+        );
+        assert!(store.export_svg(&Default::default()).contains("<line "));
+    }
+
+    #[test]
+    fn arrow_labels_step_off_shape_labels() {
+        let mut store = Store::new();
+        let style = style();
+        let mut arrow = store.begin_shape(ShapeKind::Arrow, style, Point { x: 0.0, y: 0.0 });
+        arrow.end = Point { x: 200.0, y: 0.0 };
+        store.commit_shape(arrow.clone()).unwrap();
+        assert_eq!(render_arrows(store.items())[0].label, None);
+
+        arrow.text = "Label".to_string();
+        store.commit_shape(arrow).unwrap();
+        assert_eq!(
+            render_arrows(store.items())[0].label,
+            Some(Point { x: 100.0, y: 0.0 })
+        );
+
+        // A labelled box straddling the middle of the arrow pushes the label
+        // toward the start, where it is clear.
+        let mut rect = store.begin_shape(ShapeKind::Rectangle, style, Point { x: 60.0, y: -20.0 });
+        rect.end = Point { x: 140.0, y: 20.0 };
+        rect.text = "Box".to_string();
+        store.commit_shape(rect).unwrap();
+        let label = render_arrows(store.items())[0].label.unwrap();
+        assert_eq!(label, Point { x: 40.0, y: 0.0 });
+        assert!(store
+            .export_svg(&Default::default())
+            .contains(">Label</text>"));
+    }
+
+    #[test]
+    fn arrow_preview_matches_the_final_route_at_full_quality() {
+        let mut store = Store::new();
+        let style = style();
+        let mut blocker =
+            store.begin_shape(ShapeKind::Rectangle, style, Point { x: 150.0, y: 0.0 });
+        blocker.end = Point { x: 250.0, y: 100.0 };
+        store.commit_shape(blocker).unwrap();
+        let mut arrow =
+            store.begin_shape(ShapeKind::CurvedArrow, style, Point { x: 100.0, y: 50.0 });
+        arrow.end = Point { x: 300.0, y: 50.0 };
+
+        let obstacles = store.items().to_vec();
+        let quick = preview_arrow(&arrow, &obstacles, 0.0).unwrap();
+        assert!(matches!(quick.path, ArrowPath::Quadratic { .. }));
+        let full = preview_arrow(&arrow, &obstacles, 1.0).unwrap();
+        assert!(matches!(full.path, ArrowPath::Cubic { .. }));
+
+        store.commit_shape(arrow.clone()).unwrap();
+        assert_eq!(render_arrows(store.items()), vec![full]);
+        let mut box_shape = arrow;
+        box_shape.kind = ShapeKind::Ellipse;
+        assert!(preview_arrow(&box_shape, &obstacles, 1.0).is_none());
+    }
+
+    #[test]
+    fn shafts_stop_at_the_arrowhead_base() {
+        let start = Point { x: 0.0, y: 0.0 };
+        let end = Point { x: 300.0, y: 40.0 };
+        let cubic = ArrowPath::Cubic {
+            c1: Point { x: 80.0, y: -120.0 },
+            c2: Point { x: 220.0, y: 160.0 },
+        };
+        let cut = cubic.point_at(start, end, 0.3);
+        let part = cubic.trimmed(start, 0.3);
+        for t in [0.25, 0.5, 1.0] {
+            let a = part.point_at(start, cut, t);
+            let b = cubic.point_at(start, end, 0.3 * t);
+            assert!(hypot(a.x - b.x, a.y - b.y) < 1e-3);
+        }
+
+        let mut store = Store::new();
+        let style = ShapeStyle {
+            stroke_width: 6.0,
+            ..style()
+        };
+        for kind in [ShapeKind::Arrow, ShapeKind::CurvedArrow] {
+            let mut arrow = store.begin_shape(kind, style, start);
+            arrow.end = end;
+            store.commit_shape(arrow).unwrap();
+        }
+        let mut tiny = store.begin_shape(ShapeKind::Arrow, style, start);
+        tiny.end = Point { x: 10.0, y: 0.0 };
+        store.commit_shape(tiny).unwrap();
+
+        let renders = render_arrows(store.items());
+        let reach = head_length(6.0);
+        for r in &renders[..2] {
+            let gap = hypot(r.end.x - r.shaft_end.x, r.end.y - r.shaft_end.y);
+            assert!((gap - reach).abs() < 0.01, "{gap}");
+        }
+        let base = Point {
+            x: (renders[0].head_left.x + renders[0].head_right.x) * 0.5,
+            y: (renders[0].head_left.y + renders[0].head_right.y) * 0.5,
+        };
+        assert!(
+            hypot(
+                base.x - renders[0].shaft_end.x,
+                base.y - renders[0].shaft_end.y
+            ) < 0.01
+        );
+        assert_eq!(renders[2].shaft_end, renders[2].start, "all head, no shaft");
+    }
+
+    #[test]
+    fn notched_heads_close_their_polygon_at_the_notch() {
+        let mut store = Store::new();
+        let style = ShapeStyle {
+            stroke_width: 4.0,
+            ..style()
+        };
+        let mut arrow = store.begin_shape(ShapeKind::Arrow, style, Point { x: 0.0, y: 0.0 });
+        arrow.end = Point { x: 200.0, y: 0.0 };
+        let id = arrow.id;
+        store.commit_shape(arrow).unwrap();
+
+        let plain = render_arrows(store.items())[0];
+        assert_eq!(
+            plain.head_polygon(),
+            vec![plain.end, plain.head_left, plain.head_right]
+        );
+        assert_eq!(plain.head_fill_rule, FillRule::NonZero);
+
+        assert!(store.set_head_notch(&[id], Some(0.25)));
+        assert!(!store.set_head_notch(&[id], Some(f32::NAN)));
+        let notched = render_arrows(store.items())[0];
+        let notch = notched.head_notch.unwrap();
+        let base_x = 200.0 - head_length(4.0);
+        assert!((notch.x - (base_x + head_length(4.0) * 0.25)).abs() < 1e-3);
+        assert_eq!(notch.y, 0.0);
+        assert_eq!(notched.head_polygon().len(), 4);
+        assert_eq!(notched.head_polygon()[2], notch);
+        // The shaft runs into the notch instead of stopping at the base.
+        assert!((notched.shaft_end.x - notch.x).abs() < 0.01);
+    }
+
+    #[test]
+    fn arc_length_helpers_walk_the_curve_evenly() {
+        let start = Point { x: 0.0, y: 0.0 };
+        let end = Point { x: 30.0, y: 40.0 };
+        let line = ArrowPath::Line;
+        assert_eq!(path_length(&line, start, end), 50.0);
+        let p = point_at_length(&line, start, end, 10.0);
+        assert!((p.x - 6.0).abs() < 1e-4 && (p.y - 8.0).abs() < 1e-4);
+        assert_eq!(point_at_length(&line, start, end, 99.0), end);
+        assert_eq!(point_at_length(&line, start, end, -1.0), start);
+        let (tx, ty) = tangent_at_length(&line, start, end, 25.0).unwrap();
+        assert!((tx - 0.6).abs() < 1e-4 && (ty - 0.8).abs() < 1e-4);
+        assert_eq!(tangent_at_length(&line, start, start, 0.0), None);
+
+        // A quarter circle of radius 100 as a cubic.
+        let k = 0.552_284_8 * 100.0;
+        let (start, end) = (Point { x: 100.0, y: 0.0 }, Point { x: 0.0, y: 100.0 });
+        let arc = ArrowPath::Cubic {
+            c1: Point { x: 100.0, y: k },
+            c2: Point { x: k, y: 100.0 },
+        };
+        let len = path_length(&arc, start, end);
+        assert!((len - 50.0 * std::f32::consts::PI).abs() < 0.1, "{len}");
+        let mid = point_at_length(&arc, start, end, len * 0.5);
+        let half = arc.point_at(start, end, 0.5);
+        assert!(hypot(mid.x - half.x, mid.y - half.y) < 0.05);
+        // Moving round the circle: the tangent is perpendicular to the radius.
+        let (tx, ty) = tangent_at_length(&arc, start, end, len * 0.3).unwrap();
+        let p = point_at_length(&arc, start, end, len * 0.3);
+        assert!((tx * p.x + ty * p.y).abs() / 100.0 < 0.01);
+
+        // A control on the end still gives a direction there.
+        let stalled = ArrowPath::Quadratic { control: end };
+        assert!(tangent_at_length(&stalled, start, end, 1e6).is_some());
+    }
+
+    #[test]
+    fn explicit_curvature_overrides_the_legacy_side() {
+        let mut store = Store::new();
+        let style = style();
+        let mut arrow = store.begin_shape(ShapeKind::CurvedArrow, style, Point { x: 0.0, y: 0.0 });
+        arrow.end = Point { x: 200.0, y: 10.0 };
+        let id = arrow.id;
+        store.commit_shape(arrow).unwrap();
+        let control = |items: &[Item]| match render_arrows(items)[0].path {
+            ArrowPath::Quadratic { control } => control,
+            other => panic!("expected a quadratic, got {other:?}"),
+        };
+        let with_end_y = |items: &[Item], y: f32| {
+            let mut items = items.to_vec();
+            if let Item::Shape(sh) = &mut items[0] {
+                sh.end.y = y;
+            }
+            items
+        };
+
+        // The legacy rule swaps sides as the end crosses the start's row.
+        let items = store.items().to_vec();
+        assert!(control(&with_end_y(&items, 10.0)).y > 10.0);
+        assert!(control(&with_end_y(&items, -10.0)).y < -10.0);
+
+        assert!(store.set_curvature(&[id], Some(-0.2)));
+        let items = store.items().to_vec();
+        for y in [10.0, -10.0] {
+            let q = control(&with_end_y(&items, y));
+            assert!(q.y < -30.0, "bow left the chosen side at y = {y}: {q:?}");
+        }
+
+        // Reversing keeps the bow where it was; the hit test follows it.
+        let before = control(store.items());
+        assert!(store.reverse_arrow(id));
+        let after = control(store.items());
+        assert!((before.x - after.x).abs() < 1e-3 && (before.y - after.y).abs() < 1e-3);
+        assert!(store.erase_at(Point { x: 100.0, y: -15.0 }, 2.0));
+        assert!(store.items().is_empty());
+    }
+
+    #[test]
+    fn s_curves_keep_their_bow_as_the_ends_move() {
+        let mut store = Store::new();
+        let style = style();
+        let mut blocker = store.begin_shape(ShapeKind::Rectangle, style, Point { x: 80.0, y: 0.0 });
+        blocker.end = Point { x: 120.0, y: 40.0 };
+        store.commit_shape(blocker).unwrap();
+        let mut curve = store.begin_shape(ShapeKind::SCurve, style, Point { x: 0.0, y: 0.0 });
+        curve.end = Point { x: 300.0, y: 0.0 };
+        let id = curve.id;
+        store.commit_shape(curve).unwrap();
+
+        // Right of travel first (down for a rightward curve), left after,
+        // even though the blocker sits on the first bow.
+        let first = render_arrows(store.items());
+        let ArrowPath::Cubic { c1, c2 } = first[0].path else {
+            panic!("expected a cubic");
+        };
+        assert_eq!(c1, Point { x: 100.0, y: 75.0 });
+        assert_eq!(c2, Point { x: 200.0, y: -75.0 });
+
+        // Dropping the end below the start flips the legacy `dx * dy` rule,
+        // but not the S-curve's bow.
+        let mut items = store.items().to_vec();
+        if let Item::Shape(sh) = &mut items[1] {
+            sh.end.y = 30.0;
+        }
+        let ArrowPath::Cubic { c1, .. } = render_arrows(&items)[0].path else {
+            panic!("expected a cubic");
+        };
+        assert!(c1.y > 30.0);
+
+        assert!(store.set_curvature(&[id], Some(-0.1)));
+        assert!(!store.set_curvature(&[id], Some(f32::NAN)));
+        let ArrowPath::Cubic { c1, .. } = render_arrows(store.items())[0].path else {
+            panic!("expected a cubic");
+        };
+        assert_eq!(c1, Point { x: 100.0, y: -30.0 });
+
+        // Mirroring flips the bow; reversing retraces the same curve.
+        assert!(store.flip(&[id], crate::geometry::FlipAxis::Vertical));
+        assert!(store.reverse_arrow(id));
+        let ArrowPath::Cubic { c1, c2 } = render_arrows(store.items())[0].path else {
+            panic!("expected a cubic");
+        };
+        assert_eq!(
+            (c1, c2),
+            (Point { x: 200.0, y: -30.0 }, Point { x: 100.0, y: 30.0 })
+        );
+    }
+}
@@ -0,0 +1,231 @@
+//! Replaying how a document was drawn, for tutorials that show annotations
+//! appearing one after another.
+//!
+//! The replay follows drawing order, by timestamp where items have one and
+//! by creation order (item ids only grow) otherwise, and paces each item by
+//! its length at [`ReplayOptions::speed`]. In frames strokes draw on progressively and
+//! shapes appear whole when their turn comes; the animated SVG
+//! ([`crate::export::to_animated_svg`]) shows every item whole at its turn.
+
+use crate::geometry::{is_boxed_shape, shape_outline};
+use crate::model::{Item, Point};
+use crate::store::Document;
+
+// Shortest time given to an item, so dots and tiny shapes still register.
+const MIN_DURATION: f32 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayOptions {
+    // Document units of ink drawn per second.
+    pub speed: f32,
+    // Seconds between one item finishing and the next starting.
+    pub pause: f32,
+}
+
+impl Default for ReplayOptions {
+    fn default() -> Self {
+        Self {
+            speed: 600.0,
+            pause: 0.25,
+        }
+    }
+}
+
+/// When one item is drawn, in seconds from the start of the replay.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayStep {
+    pub item_id: u64,
+    pub start: f32,
+    pub duration: f32,
+}
+
+/// The replay timeline of `doc`, in drawing order.
+pub fn replay_schedule(doc: &Document, options: &ReplayOptions) -> Vec<ReplayStep> {
+    let speed = if options.speed.is_finite() && options.speed > 0.0 {
+        options.speed
+    } else {
+        ReplayOptions::default().speed
+    };
+    let pause = if options.pause.is_finite() {
+        options.pause.max(0.0)
+    } else {
+        0.0
+    };
+    let mut order: Vec<&Item> = doc.items.iter().collect();
+    order.sort_by_key(|item| (item.created_ms(), item.id()));
+    let mut at = 0.0;
+    let mut steps = Vec::with_capacity(order.len());
+    for (i, item) in order.into_iter().enumerate() {
+        if i > 0 {
+            at += pause;
+        }
+        let duration = (ink_length(item) / speed).max(MIN_DURATION);
+        steps.push(ReplayStep {
+            item_id: item.id(),
+            start: at,
+            duration,
+        });
+        at += duration;
+    }
+    steps
+}
+
+/// Total length of the replay in seconds.
+pub fn replay_duration(steps: &[ReplayStep]) -> f32 {
+    steps
+        .iter()
+        .map(|s| s.start + s.duration)
+        .fold(0.0, f32::max)
+}
+
+/// `doc` as it looks `time` seconds into the replay: finished items whole,
+/// the stroke being drawn cut short, later items left out. Stacking order is
+/// kept.
+pub fn replay_frame(doc: &Document, steps: &[ReplayStep], time: f32) -> Document {
+    let mut items = Vec::with_capacity(doc.items.len());
+    for item in &doc.items {
+        let Some(step) = steps.iter().find(|s| s.item_id == item.id()) else {
+            continue;
+        };
+        if time < step.start {
+            continue;
+        }
+        let done = (time - step.start) / step.duration;
+        match item {
+            Item::Stroke(s) if done < 1.0 => {
+                let mut s = s.clone();
+                s.points = truncated(&s.points, done);
+                items.push(Item::Stroke(s));
+            }
+            _ => items.push(item.clone()),
+        }
+    }
+    Document {
+        version: doc.version,
+        preview: None,
+        items,
+        meta: doc.meta.clone(),
+    }
+}
+
+/// Calls `frame(time, document)` for every frame of the replay at `fps`,
+/// ending on the finished drawing. Stops early when `frame` returns false.
+/// Returns the number of frames produced.
+pub fn replay_frames(
+    doc: &Document,
+    options: &ReplayOptions,
+    fps: f32,
+    mut frame: impl FnMut(f32, &Document) -> bool,
+) -> usize {
+    if !(fps.is_finite() && fps > 0.0) {
+        return 0;
+    }
+    let steps = replay_schedule(doc, options);
+    let total = replay_duration(&steps);
+    let count = (total * fps).ceil() as usize;
+    for i in 0..=count {
+        let time = (i as f32 / fps).min(total);
+        if !frame(time, &replay_frame(doc, &steps, time)) {
+            return i + 1;
+        }
+    }
+    count + 1
+}
+
+fn ink_length(item: &Item) -> f32 {
+    match item {
+        Item::Stroke(s) => length(&s.points),
+        Item::Shape(sh) if is_boxed_shape(sh.kind) => length(&shape_outline(sh)),
+        Item::Shape(sh) => distance(sh.start, sh.end),
+    }
+}
+
+// The first `share` of the polyline by length; never empty for a non-empty line.
+fn truncated(points: &[Point], share: f32) -> Vec<Point> {
+    let Some(&first) = points.first() else {
+        return Vec::new();
+    };
+    let mut left = share.max(0.0) * length(points);
+    let mut out = vec![first];
+    for pair in points.windows(2) {
+        let step = distance(pair[0], pair[1]);
+        if left >= step {
+            out.push(pair[1]);
+            left -= step;
+            continue;
+        }
+        if left > 0.0 {
+            let t = left / step;
+            out.push(Point {
+                x: pair[0].x + (pair[1].x - pair[0].x) * t,
+                y: pair[0].y + (pair[1].y - pair[0].y) * t,
+            });
+        }
+        break;
+    }
+    out
+}
+
+fn distance(a: Point, b: Point) -> f32 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+fn length(points: &[Point]) -> f32 {
+    points.windows(2).map(|w| distance(w[0], w[1])).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{to_animated_svg, SvgOptions};
+    use crate::store::Store;
+    use crate::test_util::black;
+
+    #[test]
+    fn replays_items_in_drawing_order_at_ink_speed() {
+        let mut store = Store::new();
+        let c = black();
+        let mut ids = Vec::new();
+        for y in [0.0, 50.0] {
+            let mut s = store.begin_stroke(c, 2.0, Point { x: 0.0, y });
+            s.points.push(Point { x: 100.0, y });
+            ids.push(s.id);
+            store.commit_stroke(s).unwrap();
+        }
+        // Restack so drawing order differs from stacking order.
+        let mut doc = store.document();
+        doc.items.reverse();
+        let options = ReplayOptions {
+            speed: 100.0,
+            pause: 0.5,
+        };
+
+        let steps = replay_schedule(&doc, &options);
+        assert_eq!(steps.len(), 2);
+        assert_eq!((steps[0].item_id, steps[0].start), (ids[0], 0.0));
+        assert_eq!((steps[1].start, steps[1].duration), (1.5, 1.0));
+        assert_eq!(replay_duration(&steps), 2.5);
+
+        let half = replay_frame(&doc, &steps, 0.5);
+        assert_eq!(half.items.len(), 1);
+        let Item::Stroke(s) = &half.items[0] else {
+            panic!("expected stroke")
+        };
+        assert_eq!(s.points.last(), Some(&Point { x: 50.0, y: 0.0 }));
+        assert_eq!(replay_frame(&doc, &steps, 9.0).items, doc.items);
+
+        let mut times = Vec::new();
+        assert_eq!(
+            replay_frames(&doc, &options, 2.0, |t, _| {
+                times.push(t);
+                true
+            }),
+            6
+        );
+        assert_eq!(times.last(), Some(&2.5));
+
+        let svg = to_animated_svg(&doc, &SvgOptions::default(), &options);
+        assert!(svg.contains(r#"begin="1.5s""#));
+        assert_eq!(svg.matches("<set ").count(), 2);
+    }
+}
@@ -0,0 +1,161 @@
+//! Text search over item content.
+//!
+//! Queries are literal, or in wildcard mode may use `?` for any one
+//! character and `*` for any run of characters; `\` makes the next character
+//! literal. Matching only ever scans forward through the text, so its cost
+//! is bounded by the text length times the query length.
+
+use std::ops::Range;
+
+/// Matches per item: the item id and the byte ranges matched in its text.
+pub type TextMatches = Vec<(u64, Vec<Range<usize>>)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FindOptions {
+    pub case_insensitive: bool,
+    // Treat `?`, `*` and `\` in the query as wildcards and escapes.
+    pub wildcard: bool,
+}
+
+/// A compiled search query.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    // The runs between `*`s, none empty; `None` is a `?`.
+    segments: Vec<Vec<Option<char>>>,
+    case_insensitive: bool,
+}
+
+impl Pattern {
+    /// Compiles `query`. A wildcard query ending in a lone `\` is refused;
+    /// literal queries always compile. A query with nothing but `*`s, or
+    /// nothing at all, matches nothing.
+    pub fn new(query: &str, options: FindOptions) -> Result<Self, String> {
+        let mut segments = vec![Vec::new()];
+        let mut chars = query.chars();
+        while let Some(c) = chars.next() {
+            let segment = segments.last_mut().expect("never empty");
+            match c {
+                _ if !options.wildcard => segment.push(Some(c)),
+                '?' => segment.push(None),
+                '*' => segments.push(Vec::new()),
+                '\\' => match chars.next() {
+                    Some(escaped) => segment.push(Some(escaped)),
+                    None => return Err("dangling '\\' at the end of the query".to_string()),
+                },
+                _ => segment.push(Some(c)),
+            }
+        }
+        // Matches are kept as short as possible, so a `*` at either end (or
+        // doubled) adds nothing.
+        segments.retain(|s| !s.is_empty());
+        Ok(Self {
+            segments,
+            case_insensitive: options.case_insensitive,
+        })
+    }
+
+    /// Non-overlapping, non-empty matches in `text`, as byte ranges. Each `*`
+    /// matches as few characters as it can.
+    pub fn find_all(&self, text: &str) -> Vec<Range<usize>> {
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let byte_at = |i: usize| chars.get(i).map_or(text.len(), |(b, _)| *b);
+        let mut out = Vec::new();
+        let mut pos = 0;
+        while let Some((start, end)) = self.find_from(&chars, pos) {
+            out.push(byte_at(start)..byte_at(end));
+            pos = end;
+        }
+        out
+    }
+
+    // The leftmost match starting at or after `from`. Each segment after the
+    // first goes at its earliest place after the one before; if one has no
+    // place, no later start can find it either.
+    fn find_from(&self, text: &[(usize, char)], from: usize) -> Option<(usize, usize)> {
+        let (first, rest) = self.segments.split_first()?;
+        let start = self.find_segment(first, text, from)?;
+        let mut end = start + first.len();
+        for segment in rest {
+            end = self.find_segment(segment, text, end)? + segment.len();
+        }
+        Some((start, end))
+    }
+
+    // Where `segment` first occurs in `text` at or after `from`.
+    fn find_segment(
+        &self,
+        segment: &[Option<char>],
+        text: &[(usize, char)],
+        from: usize,
+    ) -> Option<usize> {
+        let last = text.len().checked_sub(segment.len())?;
+        (from..=last).find(|&at| {
+            segment
+                .iter()
+                .zip(&text[at..])
+                .all(|(want, (_, c))| want.is_none_or(|w| self.eq(*c, w)))
+        })
+    }
+
+    fn eq(&self, a: char, b: char) -> bool {
+        a == b || (self.case_insensitive && fold(a) == fold(b))
+    }
+}
+
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use super::*;
+
+    const WILDCARD: FindOptions = FindOptions {
+        case_insensitive: false,
+        wildcard: true,
+    };
+
+    fn find(query: &str, options: FindOptions, text: &str) -> Vec<Range<usize>> {
+        Pattern::new(query, options).unwrap().find_all(text)
+    }
+
+    #[test]
+    fn literal_wildcard_and_case_folding() {
+        let ci = FindOptions {
+            case_insensitive: true,
+            wildcard: false,
+        };
+        assert_eq!(find("todo", ci, "TODO: fix, todo"), vec![0..4, 11..15]);
+        assert!(find("todo", FindOptions::default(), "TODO").is_empty());
+        // Literal mode doesn't interpret wildcards.
+        assert_eq!(find("a*b", FindOptions::default(), "axb a*b"), vec![4..7]);
+        assert!(find("", FindOptions::default(), "text").is_empty());
+
+        assert_eq!(
+            find("#??", WILDCARD, "see #12 and #345"),
+            vec![4..7, 12..15]
+        );
+        assert_eq!(
+            find("fix*bug", WILDCARD, "fix: bug, fix bug"),
+            vec![0..8, 10..17]
+        );
+        // `*` takes as little as it can, and does nothing at the ends.
+        assert_eq!(find("a*b", WILDCARD, "a b b"), vec![0..3]);
+        assert_eq!(find("*ok*", WILDCARD, "not ok"), vec![4..6]);
+        assert!(find("**", WILDCARD, "anything").is_empty());
+        assert_eq!(find(r"\*\?", WILDCARD, "a*? b"), vec![1..3]);
+        assert!(Pattern::new("50\\", WILDCARD).is_err());
+        // Byte ranges stay on char boundaries.
+        assert_eq!(find("caf?", WILDCARD, "un café!"), vec![3..8]);
+    }
+
+    #[test]
+    fn wildcard_matching_stays_linear() {
+        let long = "a".repeat(100_000);
+        let query = "a*".repeat(50) + "b";
+        assert!(find(&query, WILDCARD, &long).is_empty());
+        assert_eq!(find("a*a", WILDCARD, &long).len(), 50_000);
+        assert!(find(&"?".repeat(100_001), WILDCARD, &long).is_empty());
+    }
+}
@@ -0,0 +1,190 @@
+//! Hand-drawn ("sketchy") outlines for shapes and arrow shafts when a
+//! document sets [`Sketch`]. Each outline is redrawn as two wobbly passes
+//! whose jitter is seeded by the item's id, so an item keeps its look across
+//! redraws, exports and platforms. Only drawing changes: hit-testing,
+//! snapping and routing keep using the exact geometry. Freehand strokes,
+//! fills, arrowheads and tails are drawn as usual.
+
+use crate::geometry::{is_boxed_shape, shape_outline};
+use crate::model::{Item, Point, Sketch};
+use crate::render::ArrowRender;
+
+// Segments used to flatten curved arrow paths.
+const ARROW_SEGMENTS: usize = 24;
+// Longest run between jittered points; longer edges are split first so they
+// bow instead of staying straight.
+const SKETCH_STEP: f32 = 24.0;
+// Overlapping passes drawn per outline.
+const PASSES: u64 = 2;
+// Jitter never exceeds this share of the outline's length, so small shapes
+// keep their form.
+const MAX_JITTER_SHARE: f32 = 0.05;
+
+/// The sketched lines replacing one item's outline (or arrow shaft).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemSketch {
+    pub item_id: u64,
+    // Open polylines, stroked with the item's stroke width and line style.
+    pub lines: Vec<Vec<Point>>,
+}
+
+/// Sketched outlines for the closed shapes and routed arrows in `items`, in
+/// drawing order. Arrows missing from `arrows` are left out.
+pub fn item_sketches(items: &[Item], arrows: &[ArrowRender], sketch: &Sketch) -> Vec<ItemSketch> {
+    let mut out = Vec::new();
+    for item in items {
+        let Item::Shape(sh) = item else { continue };
+        let outline = if is_boxed_shape(sh.kind) {
+            shape_outline(sh)
+        } else {
+            let Some(r) = arrows.iter().find(|r| r.shape_id == sh.id) else {
+                continue;
+            };
+            r.shaft.flatten(r.start, r.shaft_end, ARROW_SEGMENTS)
+        };
+        let lines = sketch_lines(&outline, sh.id, sketch);
+        if !lines.is_empty() {
+            out.push(ItemSketch {
+                item_id: sh.id,
+                lines,
+            });
+        }
+    }
+    out
+}
+
+/// `points` redrawn as overlapping wobbly passes, seeded by `item_id` and
+/// `sketch.seed`. Empty for fewer than two points or a roughness that is not
+/// positive.
+pub fn sketch_lines(points: &[Point], item_id: u64, sketch: &Sketch) -> Vec<Vec<Point>> {
+    if points.len() < 2 || !(sketch.roughness.is_finite() && sketch.roughness > 0.0) {
+        return Vec::new();
+    }
+    let length: f32 = points
+        .windows(2)
+        .map(|w| (w[1].x - w[0].x).hypot(w[1].y - w[0].y))
+        .sum();
+    let amount = sketch.roughness.min(length * MAX_JITTER_SHARE);
+    let dense = subdivide(points);
+    (0..PASSES)
+        .map(|pass| {
+            let mut jitter = Jitter::new(item_id, sketch.seed, pass);
+            dense
+                .iter()
+                .map(|p| Point {
+                    x: p.x + jitter.next() * amount,
+                    y: p.y + jitter.next() * amount,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// `points` with extra points on every edge longer than `SKETCH_STEP`.
+fn subdivide(points: &[Point]) -> Vec<Point> {
+    let mut out = vec![points[0]];
+    for w in points.windows(2) {
+        let (a, b) = (w[0], w[1]);
+        let n = ((b.x - a.x).hypot(b.y - a.y) / SKETCH_STEP).ceil().max(1.0) as usize;
+        for i in 1..=n {
+            let t = i as f32 / n as f32;
+            out.push(Point {
+                x: a.x + (b.x - a.x) * t,
+                y: a.y + (b.y - a.y) * t,
+            });
+        }
+    }
+    out
+}
+
+// SplitMix64: integer-only, so every platform draws the same wobble.
+struct Jitter(u64);
+
+impl Jitter {
+    const GOLDEN: u64 = 0x9E37_79B9_7F4A_7C15;
+
+    fn new(item_id: u64, seed: u64, pass: u64) -> Self {
+        Self(item_id ^ seed.rotate_left(32) ^ pass.wrapping_mul(Self::GOLDEN))
+    }
+
+    // Uniform in [-1, 1).
+    fn next(&mut self) -> f32 {
+        self.0 = self.0.wrapping_add(Self::GOLDEN);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{to_svg, SvgOptions};
+    use crate::model::ShapeKind;
+    use crate::render::render_arrows;
+    use crate::store::Store;
+    use crate::test_util::style;
+
+    #[test]
+    fn sketches_are_stable_per_item_and_leave_hit_testing_alone() {
+        let style = style();
+        let mut store = Store::new();
+        for (kind, x) in [(ShapeKind::Rectangle, 0.0), (ShapeKind::Rectangle, 200.0)] {
+            let mut sh = store.begin_shape(kind, style, Point { x, y: 0.0 });
+            sh.end = Point {
+                x: x + 100.0,
+                y: 100.0,
+            };
+            store.commit_shape(sh).unwrap();
+        }
+        let mut arrow = store.begin_shape(ShapeKind::Arrow, style, Point { x: 0.0, y: 150.0 });
+        arrow.end = Point { x: 300.0, y: 150.0 };
+        store.commit_shape(arrow).unwrap();
+
+        let sketch = Sketch {
+            roughness: 2.0,
+            seed: 0,
+        };
+        assert!(!store.set_sketch(Some(Sketch {
+            roughness: f32::NAN,
+            seed: 0,
+        })));
+        assert!(store.set_sketch(Some(sketch)));
+
+        let items = store.items();
+        let arrows = render_arrows(items);
+        let sketches = item_sketches(items, &arrows, &sketch);
+        assert_eq!(sketches.len(), 3);
+        assert_eq!(sketches, item_sketches(items, &arrows, &sketch));
+        assert_eq!(sketches[0].lines.len(), 2);
+        assert_ne!(sketches[0].lines[0], sketches[0].lines[1]);
+
+        // Same outline, different ids: different wobble, both within reach.
+        let moved: Vec<Point> = sketches[1].lines[0]
+            .iter()
+            .map(|p| Point {
+                x: p.x - 200.0,
+                y: p.y,
+            })
+            .collect();
+        assert_ne!(moved, sketches[0].lines[0]);
+        assert!(sketches[0].lines[0].iter().all(|p| {
+            let edge = p.x.abs().min((p.x - 100.0).abs());
+            let edge = edge.min(p.y.abs()).min((p.y - 100.0).abs());
+            edge <= 2.0 + 1e-4
+        }));
+
+        let reseeded = Sketch { seed: 7, ..sketch };
+        assert_ne!(item_sketches(items, &arrows, &reseeded), sketches);
+
+        let svg = to_svg(&store.document(), &SvgOptions::default());
+        assert!(svg.contains(r#"<path d="M"#));
+        assert!(!svg.contains("<rect "));
+
+        // The exact geometry still answers hit tests.
+        assert!(store.erase_at(Point { x: 50.0, y: 100.0 }, 0.5));
+        assert_eq!(store.items().len(), 2);
+    }
+}
@@ -0,0 +1,363 @@
+use crate::geometry::{item_bounds, union_bounds, Rect};
+pub use crate::model::GuideOrientation;
+use crate::model::{Guide, Item, Point};
+
+/// What a snapping pass is allowed to snap to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapConfig {
+    // Grid spacing in document units; `None` disables grid snapping.
+    pub grid: Option<f32>,
+    // Snap to other items' bounding-box edges and centers.
+    pub items: bool,
+    // Angle increment in radians (e.g. PI / 4) for constraining the segment
+    // from the anchor; `None` disables angle snapping.
+    pub angle_step: Option<f32>,
+    // Maximum distance (document units) a point may move when snapping.
+    pub threshold: f32,
+    // Items never snapped to, typically the ones being edited.
+    pub exclude_ids: Vec<u64>,
+    // Document guide lines to snap to; preferred over items and the grid.
+    pub guides: Vec<Guide>,
+}
+
+impl Default for SnapConfig {
+    fn default() -> Self {
+        Self {
+            grid: None,
+            items: true,
+            angle_step: None,
+            threshold: 6.0,
+            exclude_ids: Vec::new(),
+            guides: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapTarget {
+    Grid,
+    Guide(u32),
+    ItemEdge(u64),
+    ItemCenter(u64),
+}
+
+/// A snap along one axis: the coordinate the point was moved to and why.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapHit {
+    pub value: f32,
+    pub target: SnapTarget,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapResult {
+    pub point: Point,
+    // Vertical guide line (x = value), if the x coordinate snapped.
+    pub x: Option<SnapHit>,
+    // Horizontal guide line (y = value), if the y coordinate snapped.
+    pub y: Option<SnapHit>,
+    // Angle from the anchor (radians) when the angle constraint was applied.
+    pub angle: Option<f32>,
+}
+
+/// Snaps an in-progress point.
+///
+/// When `anchor` is given and angle snapping is enabled, the point is first
+/// projected onto the nearest allowed angle from the anchor; an angle snap
+/// takes precedence over grid/item snapping so the constraint holds. Otherwise
+/// x and y snap independently to the nearest candidate within the threshold,
+/// preferring document guides, then items, then the grid on ties.
+pub fn snap_point(
+    point: Point,
+    anchor: Option<Point>,
+    items: &[Item],
+    config: &SnapConfig,
+) -> SnapResult {
+    if let (Some(anchor), Some(step)) = (anchor, config.angle_step) {
+        if let Some((p, angle)) = snap_angle(point, anchor, step, config.threshold) {
+            return SnapResult {
+                point: p,
+                x: None,
+                y: None,
+                angle: Some(angle),
+            };
+        }
+    }
+
+    let mut xs: Vec<SnapHit> = Vec::new();
+    let mut ys: Vec<SnapHit> = Vec::new();
+    for guide in &config.guides {
+        let hit = SnapHit {
+            value: guide.position,
+            target: SnapTarget::Guide(guide.id),
+        };
+        match guide.orientation {
+            GuideOrientation::Vertical => xs.push(hit),
+            GuideOrientation::Horizontal => ys.push(hit),
+        }
+    }
+    if config.items {
+        for item in items {
+            if config.exclude_ids.contains(&item.id()) {
+                continue;
+            }
+            let Some(r) = item_bounds(item) else { continue };
+            let id = item.id();
+            let c = r.center();
+            xs.push(SnapHit {
+                value: r.min_x,
+                target: SnapTarget::ItemEdge(id),
+            });
+            xs.push(SnapHit {
+                value: c.x,
+                target: SnapTarget::ItemCenter(id),
+            });
+            xs.push(SnapHit {
+                value: r.max_x,
+                target: SnapTarget::ItemEdge(id),
+            });
+            ys.push(SnapHit {
+                value: r.min_y,
+                target: SnapTarget::ItemEdge(id),
+            });
+            ys.push(SnapHit {
+                value: c.y,
+                target: SnapTarget::ItemCenter(id),
+            });
+            ys.push(SnapHit {
+                value: r.max_y,
+                target: SnapTarget::ItemEdge(id),
+            });
+        }
+    }
+    if let Some(g) = config.grid.filter(|g| *g > 0.0) {
+        xs.push(SnapHit {
+            value: (point.x / g).round() * g,
+            target: SnapTarget::Grid,
+        });
+        ys.push(SnapHit {
+            value: (point.y / g).round() * g,
+            target: SnapTarget::Grid,
+        });
+    }
+
+    let x = nearest(point.x, &xs, config.threshold);
+    let y = nearest(point.y, &ys, config.threshold);
+    SnapResult {
+        point: Point {
+            x: x.map_or(point.x, |h| h.value),
+            y: y.map_or(point.y, |h| h.value),
+        },
+        x,
+        y,
+        angle: None,
+    }
+}
+
+/// A smart guide shown while dragging: the line lies at `position` (x for
+/// vertical, y for horizontal) and spans `start..end` along the other axis,
+/// covering both the moving selection and the item it aligns with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AlignmentGuide {
+    pub orientation: GuideOrientation,
+    pub position: f32,
+    pub start: f32,
+    pub end: f32,
+    pub item_id: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlignmentResult {
+    // The proposed delta, adjusted so matching edges/centers line up exactly.
+    pub delta: Point,
+    pub guides: Vec<AlignmentGuide>,
+}
+
+/// Computes smart alignment guides for moving `moving_ids` by `delta`.
+///
+/// The moving selection's left/center/right (and top/middle/bottom) are
+/// matched against every other item's; the closest match within `threshold`
+/// on each axis adjusts the delta, and every feature that then coincides is
+/// reported as a guide.
+pub fn alignment_guides(
+    items: &[Item],
+    moving_ids: &[u64],
+    delta: Point,
+    threshold: f32,
+) -> AlignmentResult {
+    let Some(moving) = union_bounds(items.iter().filter(|it| moving_ids.contains(&it.id()))) else {
+        return AlignmentResult {
+            delta,
+            guides: Vec::new(),
+        };
+    };
+    let others: Vec<(u64, Rect)> = items
+        .iter()
+        .filter(|it| !moving_ids.contains(&it.id()))
+        .filter_map(|it| item_bounds(it).map(|r| (it.id(), r)))
+        .collect();
+
+    let xs = |r: Rect| [r.min_x, (r.min_x + r.max_x) * 0.5, r.max_x];
+    let ys = |r: Rect| [r.min_y, (r.min_y + r.max_y) * 0.5, r.max_y];
+
+    let best_offset = |mine: [f32; 3], theirs: &dyn Fn(Rect) -> [f32; 3]| {
+        let mut best: Option<f32> = None;
+        for (_, r) in &others {
+            for a in mine {
+                for b in theirs(*r) {
+                    let d = b - a;
+                    if d.abs() <= threshold && best.is_none_or(|bd: f32| d.abs() < bd.abs()) {
+                        best = Some(d);
+                    }
+                }
+            }
+        }
+        best.unwrap_or(0.0)
+    };
+
+    let shifted = translate_rect(moving, delta);
+    let delta = Point {
+        x: delta.x + best_offset(xs(shifted), &xs),
+        y: delta.y + best_offset(ys(shifted), &ys),
+    };
+    let moved = translate_rect(moving, delta);
+
+    let mut guides = Vec::new();
+    for (id, r) in &others {
+        for a in xs(moved) {
+            if xs(*r).iter().any(|b| (b - a).abs() <= 0.5) {
+                guides.push(AlignmentGuide {
+                    orientation: GuideOrientation::Vertical,
+                    position: a,
+                    start: moved.min_y.min(r.min_y),
+                    end: moved.max_y.max(r.max_y),
+                    item_id: *id,
+                });
+            }
+        }
+        for a in ys(moved) {
+            if ys(*r).iter().any(|b| (b - a).abs() <= 0.5) {
+                guides.push(AlignmentGuide {
+                    orientation: GuideOrientation::Horizontal,
+                    position: a,
+                    start: moved.min_x.min(r.min_x),
+                    end: moved.max_x.max(r.max_x),
+                    item_id: *id,
+                });
+            }
+        }
+    }
+    AlignmentResult { delta, guides }
+}
+
+fn translate_rect(r: Rect, d: Point) -> Rect {
+    Rect {
+        min_x: r.min_x + d.x,
+        min_y: r.min_y + d.y,
+        max_x: r.max_x + d.x,
+        max_y: r.max_y + d.y,
+    }
+}
+
+// First candidate with the smallest distance, so earlier (guide, item) candidates win ties.
+fn nearest(v: f32, candidates: &[SnapHit], threshold: f32) -> Option<SnapHit> {
+    let mut best: Option<(SnapHit, f32)> = None;
+    for hit in candidates {
+        let d = (hit.value - v).abs();
+        if d > threshold {
+            continue;
+        }
+        if best.is_none_or(|(_, bd)| d < bd) {
+            best = Some((*hit, d));
+        }
+    }
+    best.map(|(h, _)| h)
+}
+
+fn snap_angle(point: Point, anchor: Point, step: f32, threshold: f32) -> Option<(Point, f32)> {
+    if step <= 0.0 || !step.is_finite() {
+        return None;
+    }
+    let dx = point.x - anchor.x;
+    let dy = point.y - anchor.y;
+    let len = dx.hypot(dy);
+    if len <= f32::EPSILON {
+        return None;
+    }
+    let angle = (dy.atan2(dx) / step).round() * step;
+    let (sin, cos) = angle.sin_cos();
+    // Project onto the snapped ray and only accept small perpendicular moves.
+    let along = dx * cos + dy * sin;
+    let snapped = Point {
+        x: anchor.x + cos * along,
+        y: anchor.y + sin * along,
+    };
+    let moved = (snapped.x - point.x).hypot(snapped.y - point.y);
+    if moved > threshold {
+        return None;
+    }
+    Some((snapped, angle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ShapeKind;
+    use crate::store::Store;
+    use crate::test_util::{black, style};
+    use std::f32::consts::PI;
+
+    #[test]
+    fn snaps_to_item_edge_grid_and_angle() {
+        let mut store = Store::new();
+        let style = style();
+        let mut sh = store.begin_shape(ShapeKind::Rectangle, style, Point { x: 100.0, y: 100.0 });
+        sh.end = Point { x: 200.0, y: 150.0 };
+        let id = sh.id;
+        store.commit_shape(sh).unwrap();
+
+        let config = SnapConfig {
+            grid: Some(10.0),
+            ..SnapConfig::default()
+        };
+        let r = snap_point(Point { x: 198.0, y: 53.0 }, None, store.items(), &config);
+        assert_eq!(r.point, Point { x: 200.0, y: 50.0 });
+        assert_eq!(r.x.unwrap().target, SnapTarget::ItemEdge(id));
+        assert_eq!(r.y.unwrap().target, SnapTarget::Grid);
+
+        let config = SnapConfig {
+            angle_step: Some(PI / 4.0),
+            ..SnapConfig::default()
+        };
+        let r = snap_point(
+            Point { x: 52.0, y: 49.0 },
+            Some(Point { x: 0.0, y: 0.0 }),
+            &[],
+            &config,
+        );
+        assert!((r.point.x - r.point.y).abs() < 1e-4);
+        assert!((r.angle.unwrap() - PI / 4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn alignment_adjusts_delta_and_reports_guides() {
+        let mut store = Store::new();
+        let c = black();
+        let mut a = store.begin_stroke(c, 1.0, Point { x: 0.0, y: 0.0 });
+        a.points.push(Point { x: 10.0, y: 10.0 });
+        store.commit_stroke(a).unwrap();
+        let mut b = store.begin_stroke(c, 1.0, Point { x: 100.0, y: 0.0 });
+        b.points.push(Point { x: 110.0, y: 10.0 });
+        let b_id = b.id;
+        store.commit_stroke(b).unwrap();
+
+        let r = store.alignment_guides(&[b_id], Point { x: 0.0, y: 1.0 }, 6.0);
+        assert_eq!(r.delta, Point { x: 0.0, y: 0.0 });
+        assert_eq!(
+            r.guides
+                .iter()
+                .filter(|g| g.orientation == GuideOrientation::Horizontal)
+                .count(),
+            3
+        );
+    }
+}
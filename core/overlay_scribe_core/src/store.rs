@@ -1,24 +1,222 @@
-use crate::model::{ColorRgba8, Item, Point, Shape, ShapeKind, ShapeStyle, Stroke};
+use crate::codec::{CompactPoints, POINT_STEP};
+use crate::color::ThemeMap;
+use crate::export::{to_svg, SvgOptions};
+use crate::geometry::{
+    closest_on_rounded_rect, closest_point_on_polyline, is_closed_shape, rect_for_shape,
+    rotate_point, shape_outline, Rect,
+};
+use crate::metrics::{self, Serialization, Timer};
+use crate::model::{
+    BackgroundImage, Canvas, ColorRgba8, DocumentUnits, Frame, Guide, Halo, Item, Layer, NamedView,
+    PaletteColor, Point, RecentStyles, Revision, Shape, ShapeDefaults, ShapeKind, ShapeStyle,
+    Sketch, Slide, Stroke, StylePreset, ToolProfile, WorldPoint,
+};
+use crate::raster::{render_tiles, Tile};
+use crate::render::unrouted_arrow_path;
+use crate::snap::AlignmentResult;
+use crate::trace::Span;
+use crate::units::document_radius;
 use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
 use thiserror::Error;
 
+mod arrange;
+mod background;
+mod badges;
+mod canvas;
+mod chunks;
+mod defaults;
+mod flatten;
+mod frames;
+mod guides;
+mod halo;
+mod insert;
+mod intern;
+mod invariants;
+mod join;
+mod layers;
+mod limits;
+mod links;
+mod live;
+mod memory;
+mod origin;
+mod palette;
+mod presets;
+mod preview;
+mod query;
+mod recent;
+mod revisions;
+mod sanitize;
+mod shapes;
+mod sketch;
+mod slides;
+mod split;
+mod stream;
+mod style;
+mod tags;
+mod text;
+mod tidy;
+mod timeline;
+mod tools;
+mod transform;
+mod units;
+mod views;
+
+pub use arrange::{AlignEdge, SpacingAxis};
+pub use badges::BadgeOrder;
+use chunks::ChunkIndex;
+pub use chunks::CHUNK_SIZE;
+use intern::StyleTable;
+pub use invariants::{HistoryStack, InvariantViolation};
+pub use limits::{DocumentLimits, Limit};
+pub use memory::MemoryFootprint;
+pub use preview::DocumentPreview;
+pub use query::AttachCandidate;
+pub(crate) use query::{drawn_bounds, tight_bounds};
+pub use sanitize::{InvalidInput, MAX_STROKE_WIDTH};
+pub use shapes::ArrowEnd;
+pub use split::SplitAt;
+pub use stream::LoadProgress;
+pub use style::CopiedStyle;
+pub use text::ReplaceScope;
+pub use tidy::{TIDY_ALIGN_TOLERANCE, TIDY_GRID, TIDY_STRAIGHTEN_DEGREES};
+pub use transform::NUDGE_COALESCE_WINDOW_MS;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Document {
     pub version: u32,
+
+    // Written ahead of `items` by `Store::to_json` so `Store::read_preview`
+    // can stop before the bulk of the file. Ignored when loading.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<DocumentPreview>,
+
     pub items: Vec<Item>,
+
+    #[serde(flatten)]
+    pub meta: DocumentMeta,
 }
 
 impl Document {
     pub const CURRENT_VERSION: u32 = 2;
+    /// Written by `Store::to_json_compact`; readers older than this version
+    /// can't parse its stroke points.
+    pub const COMPACT_VERSION: u32 = 3;
 
     pub fn empty() -> Self {
         Self {
             version: Self::CURRENT_VERSION,
+            preview: None,
             items: Vec::new(),
+            meta: DocumentMeta::default(),
+        }
+    }
+
+    /// Rewrites every item color and palette entry through `theme`.
+    pub fn remap_colors(&mut self, theme: &ThemeMap) {
+        for item in &mut self.items {
+            match item {
+                Item::Stroke(s) => s.color = theme.map(s.color),
+                Item::Shape(sh) => {
+                    sh.style.stroke_color = theme.map(sh.style.stroke_color);
+                    sh.style.fill_color = theme.map(sh.style.fill_color);
+                }
+            }
+        }
+        for entry in &mut self.meta.palette {
+            entry.color = theme.map(entry.color);
         }
     }
 }
 
+/// Document-level state that is not an item (palette, settings, ...).
+///
+/// Serialized inline with the document; every field is optional on disk so
+/// older files keep loading.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DocumentMeta {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub palette: Vec<PaletteColor>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub presets: Vec<StylePreset>,
+
+    // At most one entry per kind; see `Store::set_shape_defaults`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shape_defaults: Vec<ShapeDefaults>,
+
+    // Names are unique; see `Store::save_tool_profile`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_profiles: Vec<ToolProfile>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canvas: Option<Canvas>,
+
+    // The screenshot under the annotations; see `Store::set_background_image`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background_image: Option<BackgroundImage>,
+
+    // Local position of the page's top-left corner, where a sized canvas and
+    // the background image start; `None` is (0, 0). Moved along with the
+    // items by `Store::rebase_origin`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_origin: Option<Point>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub guides: Vec<Guide>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub frames: Vec<Frame>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub layers: Vec<Layer>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub views: Vec<NamedView>,
+
+    // World position of local (0, 0). Item, guide, frame and view coordinates
+    // are f32 offsets from it; see `Store::rebase_origin`.
+    #[serde(default, skip_serializing_if = "WorldPoint::is_zero")]
+    pub origin: WorldPoint,
+
+    // Named snapshots, oldest first; see `Store::save_version`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revisions: Vec<Revision>,
+
+    // Whether `Store::snap_point` also snaps to `guides`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub snap_to_guides: bool,
+
+    // Slide deck, in order; empty for a single-slide document. The current
+    // slide is shown through `items` and `background_image`; see
+    // `Store::go_to_slide`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slides: Vec<Slide>,
+    #[serde(default, skip_serializing_if = "is_first")]
+    pub current_slide: usize,
+
+    // Physical size of a document unit, for measurements and exports.
+    #[serde(default, skip_serializing_if = "DocumentUnits::is_default")]
+    pub units: DocumentUnits,
+
+    // Contrasting casing under the ink; see `Store::set_halo`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub halo: Option<Halo>,
+
+    // Hand-drawn outlines; see `Store::set_sketch`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sketch: Option<Sketch>,
+
+    // Recently used colors and styles, present only when the store keeps
+    // them with the document; see `Store::set_keep_recent`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recent: Option<RecentStyles>,
+}
+
+fn is_first(index: &usize) -> bool {
+    *index == 0
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct DocumentV1 {
     version: u32,
@@ -33,6 +231,12 @@ pub enum StoreError {
     CannotRedo,
     #[error("serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+    #[error("invalid search pattern: {0}")]
+    InvalidPattern(String),
+    #[error("document limit reached: {limit:?} is capped at {max}")]
+    LimitExceeded { limit: Limit, max: usize },
+    #[error("invalid input: {0:?}")]
+    InvalidInput(InvalidInput),
 }
 
 #[derive(Debug, Clone)]
@@ -44,21 +248,49 @@ enum Edit {
     },
     ReplaceItem {
         index: usize,
-        before: Item,
-        after: Item,
+        before: Box<Item>,
+        after: Box<Item>,
     },
     ReplaceAll {
         before: Vec<Item>,
         after: Vec<Item>,
     },
+    ReplaceMeta {
+        before: Box<DocumentMeta>,
+        after: Box<DocumentMeta>,
+    },
+    // Several edits recorded as a single undo step, applied in order.
+    Batch(Vec<Edit>),
 }
 
 #[derive(Debug, Default)]
 pub struct Store {
-    items: Vec<Item>,
+    // Shared with snapshots handed out by `Store::shared_items`; edits copy
+    // the list first if a snapshot still holds it.
+    items: Arc<Vec<Item>>,
+    meta: DocumentMeta,
     undo: Vec<Edit>,
     redo: Vec<Edit>,
     next_id: u64,
+    copied_style: Option<CopiedStyle>,
+    // Ids and timestamp of the last nudge, while its undo entry is still on top.
+    last_nudge: Option<(Vec<u64>, u64)>,
+    // Spatial index over `items`, built on first region query and dropped
+    // whenever the items change.
+    chunks: OnceLock<ChunkIndex>,
+    // Distinct shape styles and each shape's index into them; built and
+    // dropped alongside `chunks`.
+    styles: OnceLock<StyleTable>,
+    // Strokes being drawn point-by-point; see `Store::begin_live_stroke`.
+    live_strokes: Vec<Stroke>,
+    // Shapes being dragged out, with their press point; see
+    // `Store::begin_live_shape`.
+    live_shapes: Vec<(Point, Shape)>,
+    // Outside `meta` so undo never rewinds it; written into saved documents
+    // only when `keep_recent` is set. See `Store::recent_colors`.
+    recent: RecentStyles,
+    keep_recent: bool,
+    limits: DocumentLimits,
 }
 
 impl Store {
@@ -67,41 +299,102 @@ impl Store {
     }
 
     pub fn document(&self) -> Document {
+        let mut meta = self.meta.clone();
+        if self.keep_recent {
+            meta.recent = Some(self.recent.clone());
+        }
         Document {
             version: Document::CURRENT_VERSION,
-            items: self.items.clone(),
+            preview: None,
+            items: self.items.to_vec(),
+            meta,
         }
     }
 
     pub fn load_document(&mut self, doc: Document) {
-        self.items = doc.items;
+        self.items = Arc::new(doc.items);
+        self.chunks.take();
+        self.styles.take();
+        self.meta = doc.meta;
+        if let Some(recent) = self.meta.recent.take() {
+            self.recent = recent;
+            self.keep_recent = true;
+        }
+        self.last_nudge = None;
         self.undo.clear();
         self.redo.clear();
+        let slides = self.meta.slides.iter().flat_map(|slide| &slide.items);
         self.next_id = self
             .items
             .iter()
-            .map(|item| match item {
-                Item::Stroke(s) => s.id,
-                Item::Shape(sh) => sh.id,
-            })
+            .chain(slides)
+            .map(Item::id)
             .max()
             .unwrap_or(0)
             .saturating_add(1);
     }
 
+    /// Renders the document as SVG.
+    pub fn export_svg(&self, options: &SvgOptions) -> String {
+        to_svg(&self.document(), options)
+    }
+
+    /// Rasterizes the tiles touching `dirty_rects`; see [`render_tiles`].
+    pub fn render_tiles(&self, tile_size: u32, dirty_rects: &[Rect]) -> Vec<Tile> {
+        render_tiles(&self.document(), tile_size, dirty_rects)
+    }
+
     pub fn to_json(&self) -> Result<String, StoreError> {
-        Ok(serde_json::to_string(&self.document())?)
+        let mut span = Span::enter("store.to_json");
+        let _timer = Timer::start(Serialization::Save);
+        span.record("items", self.items.len() as u64);
+        let mut doc = self.document();
+        doc.preview = Some(self.preview());
+        let json = serde_json::to_string(&doc)?;
+        span.record("bytes", json.len() as u64);
+        Ok(json)
+    }
+
+    /// Like [`Store::to_json`], but stroke points are written as deltas
+    /// quantized to [`POINT_STEP`], which shrinks long ink sessions several
+    /// times over. Positions round-trip to within half a step.
+    pub fn to_json_compact(&self) -> Result<String, StoreError> {
+        let mut span = Span::enter("store.to_json_compact");
+        let _timer = Timer::start(Serialization::Save);
+        span.record("items", self.items.len() as u64);
+        let mut doc = self.document();
+        doc.version = Document::COMPACT_VERSION;
+        doc.preview = Some(self.preview());
+        let mut value = serde_json::to_value(&doc)?;
+        if let Some(items) = value.get_mut("items").and_then(|v| v.as_array_mut()) {
+            for (item, json) in doc.items.iter().zip(items) {
+                let Item::Stroke(s) = item else { continue };
+                let Some(compact) = CompactPoints::encode(&s.points, POINT_STEP) else {
+                    continue;
+                };
+                json["data"]["points"] = serde_json::to_value(compact)?;
+            }
+        }
+        let json = serde_json::to_string(&value)?;
+        span.record("bytes", json.len() as u64);
+        Ok(json)
     }
 
     pub fn from_json(json: &str) -> Result<Document, StoreError> {
+        let mut span = Span::enter("store.from_json");
+        let _timer = Timer::start(Serialization::Load);
+        span.record("bytes", json.len() as u64);
         let v2: Result<Document, serde_json::Error> = serde_json::from_str(json);
         if let Ok(doc) = v2 {
+            span.record("items", doc.items.len() as u64);
             return Ok(doc);
         }
         let v1: DocumentV1 = serde_json::from_str(json)?;
         Ok(Document {
             version: Document::CURRENT_VERSION,
+            preview: None,
             items: v1.strokes.into_iter().map(Item::Stroke).collect(),
+            meta: DocumentMeta::default(),
         })
     }
 
@@ -113,11 +406,27 @@ impl Store {
             color,
             width,
             points: vec![start],
+            line: Default::default(),
+            blend_mode: Default::default(),
+            color_ref: None,
+            layer_id: None,
+            tags: Vec::new(),
+            link: None,
+            created_ms: None,
         }
     }
 
-    pub fn commit_stroke(&mut self, stroke: Stroke) {
+    /// Adds `stroke` as one undo step. Fails with `InvalidInput` for a
+    /// stroke that is empty, non-finite or not positive in width, and with
+    /// `LimitExceeded` for one that would break the [`DocumentLimits`];
+    /// nothing is added then. Widths past [`MAX_STROKE_WIDTH`] are clamped.
+    pub fn commit_stroke(&mut self, mut stroke: Stroke) -> Result<(), StoreError> {
+        Self::sanitize_stroke(&mut stroke)?;
+        self.check_limits(&[stroke.points.len()], &[])?;
+        self.resolve_stroke_palette(&mut stroke);
+        self.note_recent_color(stroke.color);
         self.apply(Edit::AddItem(Item::Stroke(stroke)));
+        Ok(())
     }
 
     pub fn begin_shape(&mut self, kind: ShapeKind, style: ShapeStyle, start: Point) -> Shape {
@@ -133,13 +442,32 @@ impl Store {
             end_attach_id: None,
             start_attach_uv: None,
             end_attach_uv: None,
+            tail: None,
+            angles: None,
+            ignore_obstacles: false,
+            route: None,
             text: String::new(),
             text_align_h: Default::default(),
             text_align_v: Default::default(),
+            blend_mode: Default::default(),
+            rotation: 0.0,
+            layer_id: None,
+            tags: Vec::new(),
+            link: None,
+            created_ms: None,
         }
     }
 
-    pub fn commit_shape(&mut self, shape: Shape) {
+    /// Adds `shape`, or replaces the shape with its id, as one undo step.
+    /// Fails with `InvalidInput` for a non-finite shape or a new free arrow
+    /// with no length, and with `LimitExceeded` for a new shape past
+    /// [`DocumentLimits::max_items`]; the document is unchanged then.
+    pub fn commit_shape(&mut self, mut shape: Shape) -> Result<(), StoreError> {
+        let is_new = !self.contains(shape.id);
+        Self::sanitize_shape(&mut shape, is_new)?;
+        self.check_limits(&[0], &[shape.id])?;
+        self.resolve_style_palette(&mut shape.style);
+        self.note_recent_style(shape.style);
         // If a shape with this id already exists, treat this as an update.
         // This supports editing operations (e.g., text changes) without duplicating items.
         if let Some((index, before)) =
@@ -153,16 +481,17 @@ impl Store {
         {
             self.apply(Edit::ReplaceItem {
                 index,
-                before,
-                after: Item::Shape(shape),
+                before: Box::new(before),
+                after: Box::new(Item::Shape(shape)),
             });
         } else {
             self.apply(Edit::AddItem(Item::Shape(shape)));
         }
+        Ok(())
     }
 
     pub fn clear_all(&mut self) {
-        let before = self.items.clone();
+        let before = self.items.to_vec();
         self.apply(Edit::ReplaceAll {
             before,
             after: Vec::new(),
@@ -173,6 +502,17 @@ impl Store {
         &self.items
     }
 
+    /// The current items behind a shared handle, without copying them. Later
+    /// edits copy the list before changing it, so the handle keeps showing
+    /// the items as they were, e.g. for a background render.
+    pub fn shared_items(&self) -> Arc<Vec<Item>> {
+        Arc::clone(&self.items)
+    }
+
+    pub fn meta(&self) -> &DocumentMeta {
+        &self.meta
+    }
+
     pub fn can_undo(&self) -> bool {
         !self.undo.is_empty()
     }
@@ -182,29 +522,33 @@ impl Store {
     }
 
     pub fn undo(&mut self) -> Result<(), StoreError> {
+        self.last_nudge = None;
         let edit = self.undo.pop().ok_or(StoreError::CannotUndo)?;
         let inverse = self.unapply(&edit);
         self.redo.push(inverse);
+        metrics::count(|m| m.undos += 1);
         Ok(())
     }
 
     pub fn redo(&mut self) -> Result<(), StoreError> {
+        self.last_nudge = None;
         let edit = self.redo.pop().ok_or(StoreError::CannotRedo)?;
         let inverse = self.unapply(&edit);
         self.undo.push(inverse);
+        metrics::count(|m| m.redos += 1);
         Ok(())
     }
 
+    /// Erases every item within `radius` of `point`, as one undo step.
     pub fn erase_at(&mut self, point: Point, radius: f32) -> bool {
         if self.items.is_empty() {
             return false;
         }
 
-        let before = self.items.clone();
+        let before = self.items.to_vec();
         let r2 = radius * radius;
-        self.items
-            .retain(|item| !item_intersects_point(item, point, r2));
-        let after = self.items.clone();
+        let mut after = before.clone();
+        after.retain(|item| !item_intersects_point(item, point, r2));
 
         if before == after {
             return false;
@@ -213,30 +557,146 @@ impl Store {
         true
     }
 
+    /// Like [`Store::erase_at`] with `radius` in screen pixels at zoom
+    /// `view_scale`; see [`document_radius`].
+    pub fn erase_at_scaled(&mut self, point: Point, radius: f32, view_scale: f32) -> bool {
+        self.erase_at(point, document_radius(radius, Some(view_scale)))
+    }
+
+    /// Deletes the items in `ids` as one undo step, e.g. the copies found by
+    /// [`crate::analysis::find_near_duplicates`].
+    pub fn remove_items(&mut self, ids: &[u64]) -> bool {
+        let mut items = self.items.to_vec();
+        items.retain(|item| !ids.contains(&item.id()));
+        let edit = self.items_edit(items);
+        self.apply_all([edit])
+    }
+
+    /// Smart alignment guides for dragging `moving_ids` by `proposed_delta`;
+    /// see [`crate::snap::alignment_guides`].
+    pub fn alignment_guides(
+        &self,
+        moving_ids: &[u64],
+        proposed_delta: Point,
+        threshold: f32,
+    ) -> AlignmentResult {
+        crate::snap::alignment_guides(&self.items, moving_ids, proposed_delta, threshold)
+    }
+
+    /// Applies a theme transform to the whole document as one undo step.
+    pub fn remap_colors(&mut self, theme: &ThemeMap) -> bool {
+        let mut doc = self.document();
+        doc.remap_colors(theme);
+        let edits = [self.items_edit(doc.items), self.meta_edit(doc.meta)];
+        self.apply_all(edits)
+    }
+
+    /// Raises every low-contrast color to `min_ratio` against `background`
+    /// (see [`crate::analysis::fix_contrast`]) as one undo step. Returns the
+    /// number of colors adjusted.
+    pub fn fix_contrast(&mut self, background: ColorRgba8, min_ratio: f32) -> usize {
+        let mut doc = self.document();
+        let fixed = crate::analysis::fix_contrast(&mut doc, background, min_ratio);
+        let edit = self.items_edit(doc.items);
+        self.apply_all([edit]);
+        fixed
+    }
+
+    // Builds (but does not apply) an edit replacing the item list, or `None` if unchanged.
+    fn items_edit(&self, after: Vec<Item>) -> Option<Edit> {
+        if *self.items == after {
+            return None;
+        }
+        Some(Edit::ReplaceAll {
+            before: self.items.to_vec(),
+            after,
+        })
+    }
+
+    // Builds (but does not apply) an edit in which `f` has modified every item
+    // whose id is listed in `ids`, or `None` if nothing changed.
+    fn map_items_edit(&self, ids: &[u64], mut f: impl FnMut(&mut Item)) -> Option<Edit> {
+        let mut items = self.items.to_vec();
+        for item in &mut items {
+            if ids.contains(&item.id()) {
+                f(item);
+            }
+        }
+        self.items_edit(items)
+    }
+
+    // Builds (but does not apply) an edit replacing the document meta, or `None` if unchanged.
+    fn meta_edit(&self, after: DocumentMeta) -> Option<Edit> {
+        if self.meta == after {
+            return None;
+        }
+        Some(Edit::ReplaceMeta {
+            before: Box::new(self.meta.clone()),
+            after: Box::new(after),
+        })
+    }
+
+    // Applies the given edits as one undo step. Returns false if there was nothing to do.
+    fn apply_all(&mut self, edits: impl IntoIterator<Item = Option<Edit>>) -> bool {
+        let mut edits: Vec<Edit> = edits.into_iter().flatten().collect();
+        match edits.len() {
+            0 => false,
+            1 => {
+                self.apply(edits.remove(0));
+                true
+            }
+            _ => {
+                self.apply(Edit::Batch(edits));
+                true
+            }
+        }
+    }
+
     fn apply(&mut self, edit: Edit) {
+        let mut span = Span::enter("store.apply");
+        span.record("items", self.items.len() as u64);
+        span.record("undo_depth", self.undo.len() as u64);
+        self.last_nudge = None;
         self.redo.clear();
         self.apply_no_history(&edit);
+        metrics::count(|m| count_edit(m, &edit));
         self.undo.push(edit);
     }
 
+    // The item list for an in-place edit, copied first if a snapshot from
+    // `shared_items` still holds it.
+    fn items_mut(&mut self) -> &mut Vec<Item> {
+        Arc::make_mut(&mut self.items)
+    }
+
     fn apply_no_history(&mut self, edit: &Edit) {
+        self.chunks.take();
+        self.styles.take();
         match edit {
-            Edit::AddItem(item) => self.items.push(item.clone()),
+            Edit::AddItem(item) => self.items_mut().push(item.clone()),
             Edit::RemoveItem { index, .. } => {
                 if *index < self.items.len() {
-                    self.items.remove(*index);
+                    self.items_mut().remove(*index);
                 }
             }
             Edit::ReplaceItem { index, after, .. } => {
                 if *index < self.items.len() {
-                    self.items[*index] = after.clone();
+                    self.items_mut()[*index] = (**after).clone();
+                }
+            }
+            Edit::ReplaceAll { after, .. } => self.items = Arc::new(after.clone()),
+            Edit::ReplaceMeta { after, .. } => self.meta = (**after).clone(),
+            Edit::Batch(edits) => {
+                for edit in edits {
+                    self.apply_no_history(edit);
                 }
             }
-            Edit::ReplaceAll { after, .. } => self.items = after.clone(),
         }
     }
 
     fn unapply(&mut self, edit: &Edit) -> Edit {
+        self.chunks.take();
+        self.styles.take();
         match edit {
             Edit::AddItem(item) => {
                 let index = self
@@ -245,7 +705,7 @@ impl Store {
                     .position(|x| x == item)
                     .unwrap_or_else(|| self.items.len().saturating_sub(1));
                 if index < self.items.len() {
-                    self.items.remove(index);
+                    self.items_mut().remove(index);
                 }
                 Edit::RemoveItem {
                     index,
@@ -254,7 +714,7 @@ impl Store {
             }
             Edit::RemoveItem { index, item } => {
                 let insert_at = (*index).min(self.items.len());
-                self.items.insert(insert_at, item.clone());
+                self.items_mut().insert(insert_at, item.clone());
                 Edit::AddItem(item.clone())
             }
             Edit::ReplaceItem {
@@ -263,7 +723,7 @@ impl Store {
                 after,
             } => {
                 if *index < self.items.len() {
-                    self.items[*index] = before.clone();
+                    self.items_mut()[*index] = (**before).clone();
                 }
                 Edit::ReplaceItem {
                     index: *index,
@@ -272,12 +732,25 @@ impl Store {
                 }
             }
             Edit::ReplaceAll { before, after } => {
-                self.items = before.clone();
+                self.items = Arc::new(before.clone());
                 Edit::ReplaceAll {
                     before: after.clone(),
                     after: before.clone(),
                 }
             }
+            Edit::ReplaceMeta { before, after } => {
+                self.meta = (**before).clone();
+                Edit::ReplaceMeta {
+                    before: after.clone(),
+                    after: before.clone(),
+                }
+            }
+            Edit::Batch(edits) => {
+                // Unapply in reverse; the collected inverses are then already in the
+                // order that re-applies the original edits when unapplied in turn.
+                let inverses = edits.iter().rev().map(|e| self.unapply(e)).collect();
+                Edit::Batch(inverses)
+            }
         }
     }
 }
@@ -303,8 +776,24 @@ fn stroke_intersects_point(stroke: &Stroke, p: Point, r2: f32) -> bool {
 }
 
 fn shape_intersects_point(shape: &Shape, p: Point, r2: f32) -> bool {
+    if matches!(shape.kind, ShapeKind::Arc | ShapeKind::Pie) {
+        let (_, d) = closest_point_on_polyline(&shape_outline(shape), p);
+        return d * d <= r2;
+    }
+    // Closed shapes are tested in their unrotated frame.
+    let p = if shape.rotation != 0.0 && is_closed_shape(shape.kind) {
+        let c = rect_for_shape(shape).center();
+        rotate_point(p, c, -shape.rotation)
+    } else {
+        p
+    };
     match shape.kind {
-        ShapeKind::Rectangle | ShapeKind::RoundedRectangle => {
+        ShapeKind::RoundedRectangle => {
+            let r = rect_for_shape(shape);
+            let radii = shape.style.radii().fit(r.width(), r.height());
+            dist2(closest_on_rounded_rect(r, radii, p), p) <= r2
+        }
+        ShapeKind::Rectangle => {
             let (min_x, max_x) = if shape.start.x <= shape.end.x {
                 (shape.start.x, shape.end.x)
             } else {
@@ -351,54 +840,30 @@ fn shape_intersects_point(shape: &Shape, p: Point, r2: f32) -> bool {
             approx_dist * approx_dist <= r2
         }
         ShapeKind::Arrow => dist2_point_to_segment(p, shape.start, shape.end) <= r2,
-        ShapeKind::CurvedArrow => {
-            let control = control_point_for_curve(shape.start, shape.end);
-            let samples = approximate_quadratic(shape.start, control, shape.end, 16);
-            for w in samples.windows(2) {
-                if dist2_point_to_segment(p, w[0], w[1]) <= r2 {
-                    return true;
-                }
-            }
-            false
+        ShapeKind::CurvedArrow | ShapeKind::SCurve => {
+            let samples = unrouted_arrow_path(shape).flatten(shape.start, shape.end, 16);
+            samples
+                .windows(2)
+                .any(|w| dist2_point_to_segment(p, w[0], w[1]) <= r2)
         }
+        // Handled above.
+        ShapeKind::Arc | ShapeKind::Pie => false,
     }
 }
 
-fn control_point_for_curve(start: Point, end: Point) -> Point {
-    let mid = Point {
-        x: (start.x + end.x) * 0.5,
-        y: (start.y + end.y) * 0.5,
-    };
-    let dx = end.x - start.x;
-    let dy = end.y - start.y;
-    let len = (dx * dx + dy * dy).sqrt();
-    if len <= 0.5 {
-        return mid;
-    }
-    let ux = dx / len;
-    let uy = dy / len;
-    let perp_x = -uy;
-    let perp_y = ux;
-    let sign = if dx * dy >= 0.0 { 1.0 } else { -1.0 };
-    let magnitude = (len * 0.22).clamp(18.0, 160.0);
-    Point {
-        x: mid.x + perp_x * magnitude * sign,
-        y: mid.y + perp_y * magnitude * sign,
-    }
-}
-
-fn approximate_quadratic(start: Point, control: Point, end: Point, steps: usize) -> Vec<Point> {
-    let steps = steps.max(1);
-    let mut out = Vec::with_capacity(steps + 1);
-    for i in 0..=steps {
-        let t = i as f32 / steps as f32;
-        let u = 1.0 - t;
-        out.push(Point {
-            x: u * u * start.x + 2.0 * u * t * control.x + t * t * end.x,
-            y: u * u * start.y + 2.0 * u * t * control.y + t * t * end.y,
-        });
+fn count_edit(m: &mut metrics::SessionMetrics, edit: &Edit) {
+    match edit {
+        Edit::AddItem(_) => m.items_added += 1,
+        Edit::RemoveItem { .. } => m.items_removed += 1,
+        Edit::ReplaceItem { .. } => m.items_replaced += 1,
+        Edit::ReplaceAll { .. } => m.item_lists_replaced += 1,
+        Edit::ReplaceMeta { .. } => m.meta_edits += 1,
+        Edit::Batch(edits) => {
+            for edit in edits {
+                count_edit(m, edit);
+            }
+        }
     }
-    out
 }
 
 fn dist2(a: Point, b: Point) -> f32 {
@@ -428,6 +893,8 @@ fn dist2_point_to_segment(p: Point, a: Point, b: Point) -> f32 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::BlendMode;
+    use crate::test_util::style;
 
     fn red() -> ColorRgba8 {
         ColorRgba8 {
@@ -451,7 +918,7 @@ mod tests {
     fn undo_redo_add_item_roundtrip() {
         let mut store = Store::new();
         let s = store.begin_stroke(red(), 3.0, Point { x: 1.0, y: 2.0 });
-        store.commit_stroke(s.clone());
+        store.commit_stroke(s.clone()).unwrap();
         assert_eq!(store.items().len(), 1);
         assert!(store.can_undo());
 
@@ -483,7 +950,7 @@ mod tests {
                 x: i as f32,
                 y: 1.0,
             });
-            store.commit_stroke(s);
+            store.commit_stroke(s).unwrap();
         }
         assert_eq!(store.items().len(), 3);
         store.clear_all();
@@ -501,6 +968,13 @@ mod tests {
                 color: red(),
                 width: 4.0,
                 points: vec![Point { x: 1.0, y: 2.0 }],
+                line: Default::default(),
+                blend_mode: Default::default(),
+                color_ref: None,
+                layer_id: None,
+                tags: Vec::new(),
+                link: None,
+                created_ms: None,
             }],
         };
         let json = serde_json::to_string(&v1).unwrap();
@@ -508,6 +982,101 @@ mod tests {
         assert_eq!(doc.items.len(), 1);
     }
 
+    #[test]
+    fn blend_mode_defaults_to_normal_and_roundtrips() {
+        let legacy = r#"{"version":2,"items":[{"type":"stroke","data":{"id":1,"color":{"r":0,"g":0,"b":0,"a":255},"width":2.0,"points":[{"x":0.0,"y":0.0}]}}]}"#;
+        let doc = Store::from_json(legacy).unwrap();
+        match &doc.items[0] {
+            Item::Stroke(s) => assert_eq!(s.blend_mode, BlendMode::Normal),
+            _ => panic!("expected stroke"),
+        }
+
+        let mut store = Store::new();
+        let mut s = store.begin_stroke(red(), 12.0, Point { x: 0.0, y: 0.0 });
+        s.blend_mode = BlendMode::Multiply;
+        store.commit_stroke(s).unwrap();
+        let doc = Store::from_json(&store.to_json().unwrap()).unwrap();
+        match &doc.items[0] {
+            Item::Stroke(s) => assert_eq!(s.blend_mode, BlendMode::Multiply),
+            _ => panic!("expected stroke"),
+        }
+    }
+
+    #[test]
+    fn invert_lightness_theme_is_undoable() {
+        let mut store = Store::new();
+        let black = ColorRgba8 {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 200,
+        };
+        let s = store.begin_stroke(black, 2.0, Point { x: 0.0, y: 0.0 });
+        store.commit_stroke(s).unwrap();
+
+        assert!(store.remap_colors(&ThemeMap::InvertLightness));
+        match &store.items()[0] {
+            Item::Stroke(s) => assert_eq!(
+                s.color,
+                ColorRgba8 {
+                    r: 255,
+                    g: 255,
+                    b: 255,
+                    a: 200
+                }
+            ),
+            _ => panic!("expected stroke"),
+        }
+
+        store.undo().unwrap();
+        match &store.items()[0] {
+            Item::Stroke(s) => assert_eq!(s.color, black),
+            _ => panic!("expected stroke"),
+        }
+    }
+
+    #[test]
+    fn apply_preset_restyles_selection_in_one_undo_step() {
+        let mut store = Store::new();
+        let style = ShapeStyle {
+            stroke_color: red(),
+            stroke_width: 3.0,
+            fill_color: green_fill(),
+            ..style()
+        };
+        let sh = store.begin_shape(ShapeKind::Rectangle, style, Point { x: 0.0, y: 0.0 });
+        let shape_id = sh.id;
+        store.commit_shape(sh).unwrap();
+        let s = store.begin_stroke(red(), 2.0, Point { x: 0.0, y: 0.0 });
+        let stroke_id = s.id;
+        store.commit_stroke(s).unwrap();
+
+        let approved = ShapeStyle {
+            stroke_color: green_fill(),
+            stroke_width: 6.0,
+            fill_enabled: true,
+            ..style
+        };
+        store.save_preset("approved", approved);
+        assert!(!store.apply_preset("missing", &[shape_id]));
+        assert!(store.apply_preset("approved", &[shape_id, stroke_id]));
+
+        match (&store.items()[0], &store.items()[1]) {
+            (Item::Shape(sh), Item::Stroke(s)) => {
+                assert_eq!(sh.style, approved);
+                assert_eq!(s.width, 6.0);
+            }
+            _ => panic!("unexpected items"),
+        }
+
+        store.undo().unwrap();
+        match &store.items()[0] {
+            Item::Shape(sh) => assert_eq!(sh.style, style),
+            _ => panic!("expected shape"),
+        }
+        assert_eq!(store.presets().len(), 1);
+    }
+
     #[test]
     fn erase_removes_shape_and_is_undoable() {
         let mut store = Store::new();
@@ -516,12 +1085,12 @@ mod tests {
             stroke_width: 3.0,
             fill_enabled: true,
             fill_color: green_fill(),
-            hatch_enabled: false,
             corner_radius: 10.0,
+            ..style()
         };
         let mut sh = store.begin_shape(ShapeKind::Rectangle, style, Point { x: 10.0, y: 10.0 });
         sh.end = Point { x: 50.0, y: 50.0 };
-        store.commit_shape(sh);
+        store.commit_shape(sh).unwrap();
 
         assert_eq!(store.items().len(), 1);
         assert!(store.erase_at(Point { x: 10.0, y: 10.0 }, 10.0));
@@ -529,4 +1098,48 @@ mod tests {
         store.undo().unwrap();
         assert_eq!(store.items().len(), 1);
     }
+
+    #[test]
+    fn screen_radii_grow_in_the_document_when_zoomed_out() {
+        let mut store = Store::new();
+        let mut s = store.begin_stroke(red(), 2.0, Point { x: 0.0, y: 0.0 });
+        s.points.push(Point { x: 100.0, y: 0.0 });
+        store.commit_stroke(s).unwrap();
+
+        let near = Point { x: 50.0, y: 20.0 };
+        assert!(!store.erase_at_scaled(near, 10.0, 1.0));
+        assert!(
+            !store.erase_at_scaled(near, 10.0, 0.0),
+            "bad scales are ignored"
+        );
+        // At a quarter zoom, 10 screen pixels span 40 document units.
+        assert!(store.erase_at_scaled(near, 10.0, 0.25));
+        assert!(store.items().is_empty());
+    }
+
+    #[test]
+    fn shared_items_are_unaffected_by_later_edits() {
+        let mut store = Store::new();
+        let mut s = store.begin_stroke(red(), 2.0, Point { x: 0.0, y: 0.0 });
+        s.points.push(Point { x: 10.0, y: 0.0 });
+        let id = s.id;
+        store.commit_stroke(s).unwrap();
+
+        let snapshot = store.shared_items();
+        assert!(Arc::ptr_eq(&snapshot, &store.shared_items()));
+        let before = snapshot.to_vec();
+        assert!(store.translate(&[id], 5.0, 5.0));
+        let mut s = store.begin_stroke(red(), 2.0, Point { x: 0.0, y: 0.0 });
+        s.points.push(Point { x: 0.0, y: 10.0 });
+        store.commit_stroke(s).unwrap();
+        store.undo().unwrap();
+        assert_eq!(*snapshot, before);
+        assert_ne!(store.items(), before.as_slice());
+
+        // With no snapshot left, edits change the list in place.
+        drop(snapshot);
+        let held = Arc::as_ptr(&store.items);
+        store.redo().unwrap();
+        assert_eq!(Arc::as_ptr(&store.items), held);
+    }
 }
@@ -0,0 +1,226 @@
+use super::Store;
+use crate::geometry::{item_bounds, union_bounds, Rect, Transform2D};
+use crate::model::{Item, Point};
+
+/// Which edge or center line [`Store::align`] lines items up on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlignEdge {
+    Left,
+    CenterX,
+    Right,
+    Top,
+    CenterY,
+    Bottom,
+}
+
+/// Direction [`Store::equalize_spacing`] distributes items along.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpacingAxis {
+    Horizontal,
+    Vertical,
+}
+
+impl Store {
+    /// Moves each of `ids` so its bounds line up with the selection's bounds
+    /// on `edge`, as one undo step.
+    pub fn align(&mut self, ids: &[u64], edge: AlignEdge) -> bool {
+        let Some(target) = union_bounds(self.items.iter().filter(|it| ids.contains(&it.id())))
+        else {
+            return false;
+        };
+        let edit = self.map_items_edit(ids, |item| {
+            let Some(r) = item_bounds(item) else { return };
+            let (dx, dy) = match edge {
+                AlignEdge::Left => (target.min_x - r.min_x, 0.0),
+                AlignEdge::CenterX => (target.center().x - r.center().x, 0.0),
+                AlignEdge::Right => (target.max_x - r.max_x, 0.0),
+                AlignEdge::Top => (0.0, target.min_y - r.min_y),
+                AlignEdge::CenterY => (0.0, target.center().y - r.center().y),
+                AlignEdge::Bottom => (0.0, target.max_y - r.max_y),
+            };
+            item.translate(dx, dy);
+        });
+        self.apply_all([edit])
+    }
+
+    /// Moves `ids` along `axis` so the gaps between neighbouring bounds are
+    /// equal, keeping the first and last item in place. Needs at least three
+    /// items; one undo step.
+    pub fn equalize_spacing(&mut self, ids: &[u64], axis: SpacingAxis) -> bool {
+        // (start, size) on the axis.
+        let span = |r: &Rect| match axis {
+            SpacingAxis::Horizontal => (r.min_x, r.width()),
+            SpacingAxis::Vertical => (r.min_y, r.height()),
+        };
+        let mut spans: Vec<(u64, f32, f32)> = self
+            .items
+            .iter()
+            .filter(|it| ids.contains(&it.id()))
+            .filter_map(|it| {
+                let (start, size) = span(&item_bounds(it)?);
+                Some((it.id(), start, size))
+            })
+            .collect();
+        if spans.len() < 3 {
+            return false;
+        }
+        spans.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let (first, last) = (spans[0], spans[spans.len() - 1]);
+        let sizes: f32 = spans[1..spans.len() - 1].iter().map(|s| s.2).sum();
+        let gap = (last.1 - (first.1 + first.2) - sizes) / (spans.len() - 1) as f32;
+
+        let mut cursor = first.1 + first.2 + gap;
+        let mut shifts = Vec::with_capacity(spans.len() - 2);
+        for &(id, start, size) in &spans[1..spans.len() - 1] {
+            shifts.push((id, cursor - start));
+            cursor += size + gap;
+        }
+        let moved: Vec<u64> = shifts.iter().map(|(id, _)| *id).collect();
+        let edit = self.map_items_edit(&moved, |item| {
+            let Some(&(_, d)) = shifts.iter().find(|(id, _)| *id == item.id()) else {
+                return;
+            };
+            match axis {
+                SpacingAxis::Horizontal => item.translate(d, 0.0),
+                SpacingAxis::Vertical => item.translate(0.0, d),
+            }
+        });
+        self.apply_all([edit])
+    }
+
+    /// Scales and moves `ids` so their combined bounds fill `target`, as one
+    /// undo step. Fails for an empty or degenerate selection or target.
+    pub fn resize(&mut self, ids: &[u64], target: Rect) -> bool {
+        let Some(bounds) = union_bounds(self.items.iter().filter(|it| ids.contains(&it.id())))
+        else {
+            return false;
+        };
+        if bounds.width() <= 0.0 || bounds.height() <= 0.0 {
+            return false;
+        }
+        let origin = Point {
+            x: bounds.min_x,
+            y: bounds.min_y,
+        };
+        let transform = Transform2D::translate(-origin.x, -origin.y)
+            .then(Transform2D::scale(
+                target.width() / bounds.width(),
+                target.height() / bounds.height(),
+            ))
+            .then(Transform2D::translate(target.min_x, target.min_y));
+        self.transform_items(ids, transform)
+    }
+
+    /// Moves `ids` to the top of the z-order, keeping their relative order.
+    pub fn bring_to_front(&mut self, ids: &[u64]) -> bool {
+        let (mut rest, picked): (Vec<Item>, Vec<Item>) = self
+            .items
+            .iter()
+            .cloned()
+            .partition(|it| !ids.contains(&it.id()));
+        rest.extend(picked);
+        let edit = self.items_edit(rest);
+        self.apply_all([edit])
+    }
+
+    /// Moves `ids` to the bottom of the z-order, keeping their relative order.
+    pub fn send_to_back(&mut self, ids: &[u64]) -> bool {
+        let (mut picked, rest): (Vec<Item>, Vec<Item>) = self
+            .items
+            .iter()
+            .cloned()
+            .partition(|it| ids.contains(&it.id()));
+        picked.extend(rest);
+        let edit = self.items_edit(picked);
+        self.apply_all([edit])
+    }
+
+    /// Moves each of `ids` one step up, past the next unselected item.
+    pub fn bring_forward(&mut self, ids: &[u64]) -> bool {
+        let mut items = self.items.to_vec();
+        for i in (0..items.len().saturating_sub(1)).rev() {
+            if ids.contains(&items[i].id()) && !ids.contains(&items[i + 1].id()) {
+                items.swap(i, i + 1);
+            }
+        }
+        let edit = self.items_edit(items);
+        self.apply_all([edit])
+    }
+
+    /// Moves each of `ids` one step down, past the previous unselected item.
+    pub fn send_backward(&mut self, ids: &[u64]) -> bool {
+        let mut items = self.items.to_vec();
+        for i in 1..items.len() {
+            if ids.contains(&items[i].id()) && !ids.contains(&items[i - 1].id()) {
+                items.swap(i, i - 1);
+            }
+        }
+        let edit = self.items_edit(items);
+        self.apply_all([edit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::black;
+
+    fn stroke(store: &mut Store, x: f32, y: f32) -> u64 {
+        let c = black();
+        let mut s = store.begin_stroke(c, 2.0, Point { x, y });
+        s.points.push(Point {
+            x: x + 10.0,
+            y: y + 10.0,
+        });
+        let id = s.id;
+        store.commit_stroke(s).unwrap();
+        id
+    }
+
+    #[test]
+    fn select_align_resize_and_reorder() {
+        let mut store = Store::new();
+        let a = stroke(&mut store, 0.0, 0.0);
+        let b = stroke(&mut store, 30.0, 50.0);
+        let c = stroke(&mut store, 60.0, 20.0);
+
+        let marquee = Rect {
+            min_x: 5.0,
+            min_y: 5.0,
+            max_x: 45.0,
+            max_y: 65.0,
+        };
+        assert_eq!(store.select_in_rect(marquee, false), [a, b]);
+        assert_eq!(store.select_in_rect(marquee, true), [b]);
+
+        assert!(store.align(&[a, b], AlignEdge::Left));
+        assert_eq!(store.selection_bounds(&[b], false).unwrap().min_x, 0.0);
+        assert!(!store.align(&[a, b], AlignEdge::Left));
+
+        // a spans y 0..10, b 50..60 and c 20..30: c moves to the middle of the gap.
+        assert!(store.equalize_spacing(&[a, b, c], SpacingAxis::Vertical));
+        assert_eq!(store.selection_bounds(&[c], false).unwrap().min_y, 25.0);
+        assert!(!store.equalize_spacing(&[a, b, c], SpacingAxis::Vertical));
+        assert!(!store.equalize_spacing(&[a, b], SpacingAxis::Horizontal));
+
+        let target = Rect {
+            min_x: 100.0,
+            min_y: 100.0,
+            max_x: 120.0,
+            max_y: 140.0,
+        };
+        assert!(store.resize(&[c], target));
+        assert_eq!(store.selection_bounds(&[c], false), Some(target));
+
+        let order = |store: &Store| store.items().iter().map(Item::id).collect::<Vec<_>>();
+        assert!(store.bring_to_front(&[a]));
+        assert_eq!(order(&store), [b, c, a]);
+        assert!(store.send_backward(&[a]));
+        assert_eq!(order(&store), [b, a, c]);
+        assert!(store.send_to_back(&[c]));
+        assert_eq!(order(&store), [c, b, a]);
+        assert!(store.bring_forward(&[c, b]));
+        assert_eq!(order(&store), [a, c, b]);
+        assert!(!store.bring_forward(&[b]));
+    }
+}
@@ -0,0 +1,69 @@
+use super::Store;
+use crate::model::{BackgroundImage, ImageSource};
+
+impl Store {
+    pub fn background_image(&self) -> Option<&BackgroundImage> {
+        self.meta.background_image.as_ref()
+    }
+
+    /// Sets (or with `None` removes) the screenshot being annotated, as one
+    /// undo step. An image without a positive finite size, or embedded
+    /// without any bytes, is refused.
+    pub fn set_background_image(&mut self, image: Option<BackgroundImage>) -> bool {
+        if let Some(image) = &image {
+            let sized = [image.width, image.height]
+                .iter()
+                .all(|v| v.is_finite() && *v > 0.0);
+            let empty = match &image.source {
+                ImageSource::Embedded { data, .. } => data.is_empty(),
+                ImageSource::Uri { uri } => uri.is_empty(),
+            };
+            if !sized || empty {
+                return false;
+            }
+        }
+        let mut meta = self.meta.clone();
+        meta.background_image = image;
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::default_region;
+    use crate::SvgOptions;
+
+    #[test]
+    fn background_image_round_trips_and_exports_under_items() {
+        let mut store = Store::new();
+        let image = BackgroundImage {
+            source: ImageSource::Embedded {
+                mime_type: "image/png".to_string(),
+                data: b"\x89PNG".to_vec(),
+            },
+            width: 800.0,
+            height: 600.0,
+        };
+        assert!(!store.set_background_image(Some(BackgroundImage {
+            width: 0.0,
+            ..image.clone()
+        })));
+        assert!(store.set_background_image(Some(image.clone())));
+
+        let json = store.to_json().unwrap();
+        assert!(json.contains(r#""data":"iVBORw==""#));
+        let loaded = Store::from_json(&json).unwrap();
+        assert_eq!(loaded.meta.background_image.as_ref(), Some(&image));
+        assert_eq!(default_region(&loaded), Some(image.rect()));
+
+        let svg = store.export_svg(&SvgOptions::default());
+        assert!(svg.contains(
+            r#"<image x="0" y="0" width="800" height="600" href="data:image/png;base64,iVBORw=="/>"#
+        ));
+
+        store.undo().unwrap();
+        assert!(store.background_image().is_none());
+    }
+}
@@ -0,0 +1,131 @@
+use super::Store;
+use crate::geometry::{is_closed_shape, rect_for_shape};
+use crate::model::{Item, Shape};
+
+/// How [`Store::renumber_badges`] orders step markers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BadgeOrder {
+    // Oldest badge first.
+    Creation,
+    // Reading order: rows top to bottom, each row left to right.
+    Spatial,
+    // The listed ids first, then any other badges in creation order.
+    Manual(Vec<u64>),
+}
+
+/// A numbered badge is a closed shape whose label is just a positive number.
+fn is_badge(shape: &Shape) -> bool {
+    is_closed_shape(shape.kind) && shape.text.trim().parse::<u32>().is_ok_and(|n| n > 0)
+}
+
+impl Store {
+    /// Relabels every numbered badge 1, 2, 3, ... in `order`, as one undo step.
+    pub fn renumber_badges(&mut self, order: &BadgeOrder) -> bool {
+        let mut badges: Vec<&Shape> = self
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Shape(sh) if is_badge(sh) => Some(sh),
+                _ => None,
+            })
+            .collect();
+        match order {
+            BadgeOrder::Creation => badges.sort_by_key(|sh| sh.id),
+            BadgeOrder::Spatial => badges = reading_order(badges),
+            BadgeOrder::Manual(ids) => {
+                badges.sort_by_key(|sh| {
+                    let pos = ids.iter().position(|id| *id == sh.id);
+                    (pos.is_none(), pos, sh.id)
+                });
+            }
+        }
+        let numbers: Vec<(u64, String)> = badges
+            .iter()
+            .enumerate()
+            .map(|(i, sh)| (sh.id, (i + 1).to_string()))
+            .collect();
+        let ids: Vec<u64> = numbers.iter().map(|(id, _)| *id).collect();
+        let edit = self.map_items_edit(&ids, |item| {
+            if let Item::Shape(sh) = item {
+                if let Some((_, n)) = numbers.iter().find(|(id, _)| *id == sh.id) {
+                    sh.text.clone_from(n);
+                }
+            }
+        });
+        self.apply_all([edit])
+    }
+}
+
+// Groups badges into rows (a badge joins the current row when its center is
+// within half the row's first badge height), then sorts each row by x.
+fn reading_order(mut badges: Vec<&Shape>) -> Vec<&Shape> {
+    let center = |sh: &Shape| rect_for_shape(sh).center();
+    badges.sort_by(|a, b| center(a).y.total_cmp(&center(b).y));
+    let mut out = Vec::with_capacity(badges.len());
+    let mut row: Vec<&Shape> = Vec::new();
+    for sh in badges {
+        if let Some(first) = row.first() {
+            let band = rect_for_shape(first).height().abs() * 0.5;
+            if center(sh).y - center(first).y > band {
+                row.sort_by(|a, b| center(a).x.total_cmp(&center(b).x));
+                out.append(&mut row);
+            }
+        }
+        row.push(sh);
+    }
+    row.sort_by(|a, b| center(a).x.total_cmp(&center(b).x));
+    out.append(&mut row);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Point, ShapeKind, ShapeStyle};
+    use crate::test_util::style;
+
+    #[test]
+    fn renumbers_in_creation_spatial_and_manual_order() {
+        let mut store = Store::new();
+        let style = ShapeStyle {
+            fill_enabled: true,
+            ..style()
+        };
+        // Created right-to-left on one row (with a little jitter), then one below.
+        let mut ids = Vec::new();
+        for (x, y, label) in [
+            (200.0, 2.0, "7"),
+            (100.0, 0.0, "7"),
+            (0.0, 4.0, "2"),
+            (0.0, 100.0, "1"),
+        ] {
+            let mut sh = store.begin_shape(ShapeKind::Ellipse, style, Point { x, y });
+            sh.end = Point {
+                x: x + 20.0,
+                y: y + 20.0,
+            };
+            sh.text = label.to_string();
+            ids.push(sh.id);
+            store.commit_shape(sh).unwrap();
+        }
+        let mut note = store.begin_shape(ShapeKind::Rectangle, style, Point { x: 0.0, y: 0.0 });
+        note.text = "Check this".to_string();
+        store.commit_shape(note).unwrap();
+
+        let labels = |store: &Store| -> Vec<String> {
+            ids.iter()
+                .map(|id| match store.item(*id) {
+                    Some(Item::Shape(sh)) => sh.text.clone(),
+                    _ => String::new(),
+                })
+                .collect()
+        };
+        assert!(store.renumber_badges(&BadgeOrder::Creation));
+        assert_eq!(labels(&store), ["1", "2", "3", "4"]);
+        assert!(store.renumber_badges(&BadgeOrder::Spatial));
+        assert_eq!(labels(&store), ["3", "2", "1", "4"]);
+        assert!(store.renumber_badges(&BadgeOrder::Manual(vec![ids[3], ids[1]])));
+        assert_eq!(labels(&store), ["3", "2", "4", "1"]);
+        assert!(!store.renumber_badges(&BadgeOrder::Manual(vec![ids[3], ids[1]])));
+    }
+}
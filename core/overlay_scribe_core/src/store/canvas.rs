@@ -0,0 +1,47 @@
+use super::Store;
+use crate::model::Canvas;
+
+impl Store {
+    pub fn canvas(&self) -> Option<&Canvas> {
+        self.meta.canvas.as_ref()
+    }
+
+    /// Replaces the canvas definition (`None` for an unbounded, transparent
+    /// surface) as one undo step.
+    pub fn set_canvas(&mut self, canvas: Option<Canvas>) -> bool {
+        let mut meta = self.meta.clone();
+        meta.canvas = canvas;
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{BackgroundPattern, ColorRgba8};
+
+    #[test]
+    fn canvas_round_trips_and_undoes() {
+        let mut store = Store::new();
+        let canvas = Canvas {
+            width: Some(1920.0),
+            height: Some(1080.0),
+            background: Some(ColorRgba8 {
+                r: 255,
+                g: 255,
+                b: 255,
+                a: 255,
+            }),
+            pattern: BackgroundPattern::Dots { spacing: 16.0 },
+        };
+        assert!(store.set_canvas(Some(canvas)));
+        assert!(!store.set_canvas(Some(canvas)));
+
+        let loaded = Store::from_json(&store.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.meta.canvas, Some(canvas));
+
+        store.undo().unwrap();
+        assert!(store.canvas().is_none());
+    }
+}
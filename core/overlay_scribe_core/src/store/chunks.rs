@@ -0,0 +1,202 @@
+use super::query::drawn_bounds;
+use super::{Document, Store, StoreError};
+use crate::geometry::Rect;
+use crate::model::Item;
+use crate::render::render_arrows;
+use std::collections::HashMap;
+use std::mem::size_of;
+
+/// Side length of a spatial chunk, in document units.
+pub const CHUNK_SIZE: f32 = 512.0;
+
+/// Uniform-grid index from chunk coordinates to the items whose drawn bounds
+/// touch that chunk.
+#[derive(Debug, Default)]
+pub(super) struct ChunkIndex {
+    chunks: HashMap<(i32, i32), Vec<usize>>,
+    bounds: Vec<Option<Rect>>,
+}
+
+impl ChunkIndex {
+    fn build(items: &[Item]) -> Self {
+        let arrows = render_arrows(items);
+        let mut index = ChunkIndex {
+            chunks: HashMap::new(),
+            bounds: items.iter().map(|it| drawn_bounds(it, &arrows)).collect(),
+        };
+        for (i, bounds) in index.bounds.iter().enumerate() {
+            let Some(r) = bounds else { continue };
+            let (x0, y0, x1, y1) = chunk_range(*r);
+            for cy in y0..=y1 {
+                for cx in x0..=x1 {
+                    index.chunks.entry((cx, cy)).or_default().push(i);
+                }
+            }
+        }
+        index
+    }
+
+    pub(super) fn heap_bytes(&self) -> usize {
+        let keys = self.chunks.capacity() * size_of::<((i32, i32), Vec<usize>)>();
+        let ids: usize = self
+            .chunks
+            .values()
+            .map(|ids| ids.capacity() * size_of::<usize>())
+            .sum();
+        keys + ids + self.bounds.capacity() * size_of::<Option<Rect>>()
+    }
+
+    // Indices (in document order) of items whose bounds intersect `rect`.
+    fn query(&self, rect: Rect) -> Vec<usize> {
+        let (x0, y0, x1, y1) = chunk_range(rect);
+        let span = (x1 as i64 - x0 as i64 + 1).saturating_mul(y1 as i64 - y0 as i64 + 1);
+        let mut hits: Vec<usize> = if span > self.chunks.len() as i64 {
+            // Huge query rects: walking the occupied chunks is cheaper.
+            self.chunks
+                .iter()
+                .filter(|((cx, cy), _)| (x0..=x1).contains(cx) && (y0..=y1).contains(cy))
+                .flat_map(|(_, ids)| ids.iter().copied())
+                .collect()
+        } else {
+            (y0..=y1)
+                .flat_map(|cy| (x0..=x1).map(move |cx| (cx, cy)))
+                .filter_map(|key| self.chunks.get(&key))
+                .flat_map(|ids| ids.iter().copied())
+                .collect()
+        };
+        hits.sort_unstable();
+        hits.dedup();
+        hits.retain(|&i| self.bounds[i].is_some_and(|b| b.intersects(rect)));
+        hits
+    }
+}
+
+fn chunk_range(r: Rect) -> (i32, i32, i32, i32) {
+    // `as` saturates, so far-off or infinite rects clamp to the grid's edge.
+    let cell = |v: f32| (v / CHUNK_SIZE).floor() as i32;
+    (cell(r.min_x), cell(r.min_y), cell(r.max_x), cell(r.max_y))
+}
+
+impl Store {
+    fn chunk_index(&self) -> &ChunkIndex {
+        self.chunks.get_or_init(|| ChunkIndex::build(&self.items))
+    }
+
+    /// Items whose drawn bounds intersect `rect`, in document (z) order.
+    ///
+    /// Backed by a chunked spatial index, so viewport queries on large boards
+    /// only look at nearby items.
+    pub fn items_in_region(&self, rect: Rect) -> Vec<&Item> {
+        self.chunk_index()
+            .query(rect)
+            .into_iter()
+            .map(|i| &self.items[i])
+            .collect()
+    }
+
+    /// Copies of the items intersecting `rect`, in document (z) order, for
+    /// shells that page a large board in by region.
+    pub fn load_region(&self, rect: Rect) -> Vec<Item> {
+        self.items_in_region(rect).into_iter().cloned().collect()
+    }
+
+    /// Serializes only the items intersecting `rect` (plus all document-level
+    /// state); see [`Store::load_region`].
+    pub fn region_to_json(&self, rect: Rect) -> Result<String, StoreError> {
+        let doc = Document {
+            version: Document::CURRENT_VERSION,
+            preview: None,
+            items: self.load_region(rect),
+            meta: self.meta.clone(),
+        };
+        Ok(serde_json::to_string(&doc)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Point;
+    use crate::test_util::black;
+
+    #[test]
+    fn region_queries_follow_edits() {
+        let mut store = Store::new();
+        let c = black();
+        let mut ids = Vec::new();
+        for x in [0.0, 5000.0, -3000.0] {
+            let mut s = store.begin_stroke(c, 2.0, Point { x, y: 10.0 });
+            s.points.push(Point {
+                x: x + 20.0,
+                y: 30.0,
+            });
+            ids.push(s.id);
+            store.commit_stroke(s).unwrap();
+        }
+        let near_origin = Rect {
+            min_x: -100.0,
+            min_y: -100.0,
+            max_x: 100.0,
+            max_y: 100.0,
+        };
+        let found: Vec<u64> = store
+            .items_in_region(near_origin)
+            .iter()
+            .map(|it| it.id())
+            .collect();
+        assert_eq!(found, vec![ids[0]]);
+
+        assert!(store.translate(&[ids[1]], -5000.0, 0.0));
+        assert_eq!(store.items_in_region(near_origin).len(), 2);
+        store.undo().unwrap();
+        assert_eq!(store.items_in_region(near_origin).len(), 1);
+
+        let everything = Rect {
+            min_x: f32::NEG_INFINITY,
+            min_y: f32::NEG_INFINITY,
+            max_x: f32::INFINITY,
+            max_y: f32::INFINITY,
+        };
+        assert_eq!(store.items_in_region(everything).len(), 3);
+
+        let doc = Store::from_json(&store.region_to_json(near_origin).unwrap()).unwrap();
+        assert_eq!(doc.items.len(), 1);
+    }
+
+    #[test]
+    fn load_region_lists_each_item_once_in_z_order() {
+        let mut store = Store::new();
+        // Spans a dozen chunks, so the index lists it under each of them.
+        let mut long = store.begin_stroke(black(), 2.0, Point { x: -3000.0, y: 0.0 });
+        long.points.push(Point { x: 3000.0, y: 0.0 });
+        let long_id = long.id;
+        store.commit_stroke(long).unwrap();
+        let mut short = store.begin_stroke(black(), 2.0, Point { x: 600.0, y: -20.0 });
+        short.points.push(Point { x: 620.0, y: 20.0 });
+        let short_id = short.id;
+        store.commit_stroke(short).unwrap();
+        let far = store.begin_stroke(
+            black(),
+            2.0,
+            Point {
+                x: 9000.0,
+                y: 9000.0,
+            },
+        );
+        store.commit_stroke(far).unwrap();
+
+        let across = Rect {
+            min_x: -1000.0,
+            min_y: -50.0,
+            max_x: 1000.0,
+            max_y: 50.0,
+        };
+        let ids: Vec<u64> = store.load_region(across).iter().map(Item::id).collect();
+        assert_eq!(ids, vec![long_id, short_id]);
+
+        // Raising the long stroke changes the order, not the membership.
+        assert!(store.bring_to_front(&[long_id]));
+        let ids: Vec<u64> = store.load_region(across).iter().map(Item::id).collect();
+        assert_eq!(ids, vec![short_id, long_id]);
+    }
+}
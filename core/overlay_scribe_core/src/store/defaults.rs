@@ -0,0 +1,125 @@
+use super::Store;
+use crate::geometry::is_boxed_shape;
+use crate::model::{Point, Shape, ShapeDefaults, ShapeKind, ShapeStyle};
+
+impl Store {
+    pub fn shape_defaults(&self) -> &[ShapeDefaults] {
+        &self.meta.shape_defaults
+    }
+
+    pub fn shape_defaults_for(&self, kind: ShapeKind) -> Option<&ShapeDefaults> {
+        self.meta.shape_defaults.iter().find(|d| d.kind == kind)
+    }
+
+    /// Saves the defaults for `defaults.kind`, replacing earlier ones, as
+    /// one undo step. A stroke width that is negative or not finite is
+    /// refused.
+    pub fn set_shape_defaults(&mut self, defaults: ShapeDefaults) -> bool {
+        let width = defaults.style.stroke_width;
+        if !(width.is_finite() && width >= 0.0) {
+            return false;
+        }
+        let mut meta = self.meta.clone();
+        match meta
+            .shape_defaults
+            .iter_mut()
+            .find(|d| d.kind == defaults.kind)
+        {
+            Some(d) => *d = defaults,
+            None => meta.shape_defaults.push(defaults),
+        }
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+
+    pub fn remove_shape_defaults(&mut self, kind: ShapeKind) -> bool {
+        let mut meta = self.meta.clone();
+        meta.shape_defaults.retain(|d| d.kind != kind);
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+
+    /// Like [`Store::begin_shape`], starting from the saved defaults for
+    /// `kind` (style, text alignment and arrow tail), or from `fallback`
+    /// when none are saved.
+    pub fn begin_default_shape(
+        &mut self,
+        kind: ShapeKind,
+        fallback: ShapeStyle,
+        start: Point,
+    ) -> Shape {
+        let defaults = self.shape_defaults_for(kind).copied();
+        let style = defaults.map_or(fallback, |d| d.style);
+        let mut shape = self.begin_shape(kind, style, start);
+        if let Some(d) = defaults {
+            shape.text_align_h = d.text_align_h;
+            shape.text_align_v = d.text_align_v;
+            if !is_boxed_shape(kind) {
+                shape.tail = d.tail;
+            }
+        }
+        shape
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ArrowTail, ColorRgba8, TextAlignH, TextAlignV};
+    use crate::test_util::style;
+
+    #[test]
+    fn shape_defaults_round_trip_and_seed_new_shapes() {
+        let mut store = Store::new();
+        let style = |width| ShapeStyle {
+            stroke_color: ColorRgba8 {
+                r: 200,
+                g: 0,
+                b: 0,
+                a: 255,
+            },
+            stroke_width: width,
+            fill_color: ColorRgba8 {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 0,
+            },
+            ..style()
+        };
+        let arrow = ShapeDefaults {
+            kind: ShapeKind::Arrow,
+            style: style(5.0),
+            text_align_h: TextAlignH::Left,
+            text_align_v: TextAlignV::Top,
+            tail: Some(ArrowTail::Circle),
+        };
+        assert!(!store.set_shape_defaults(ShapeDefaults {
+            style: style(f32::NAN),
+            ..arrow
+        }));
+        assert!(store.set_shape_defaults(ShapeDefaults {
+            style: style(1.0),
+            ..arrow
+        }));
+        assert!(store.set_shape_defaults(arrow));
+        assert_eq!(store.shape_defaults(), &[arrow]);
+
+        let mut reloaded = Store::new();
+        reloaded.load_document(Store::from_json(&store.to_json().unwrap()).unwrap());
+        assert_eq!(reloaded.shape_defaults_for(ShapeKind::Arrow), Some(&arrow));
+        let origin = Point { x: 0.0, y: 0.0 };
+        let shape = reloaded.begin_default_shape(ShapeKind::Arrow, style(2.0), origin);
+        assert_eq!(
+            (shape.style.stroke_width, shape.text_align_h, shape.tail),
+            (5.0, TextAlignH::Left, Some(ArrowTail::Circle))
+        );
+        let rect = reloaded.begin_default_shape(ShapeKind::Rectangle, style(2.0), origin);
+        assert_eq!(rect.style.stroke_width, 2.0);
+
+        assert!(reloaded.remove_shape_defaults(ShapeKind::Arrow));
+        assert!(reloaded.shape_defaults().is_empty());
+        reloaded.undo().unwrap();
+        assert_eq!(reloaded.shape_defaults(), &[arrow]);
+    }
+}
@@ -0,0 +1,163 @@
+use super::Store;
+use crate::geometry::{is_boxed_shape, shape_outline};
+use crate::model::{Item, LineStyle, Point, Shape, Stroke};
+use crate::render::{is_arrow_like, render_arrows, ArrowRender};
+
+// Polyline resolution for curved arrows.
+const CURVE_SEGMENTS: usize = 32;
+
+impl Store {
+    /// Converts the given shapes into plain strokes, as one undo step, for
+    /// export to tools that only understand ink.
+    ///
+    /// Closed shapes become their outline and arrows their routed path plus a
+    /// closed head. Fills, hatching and text are not representable as ink and
+    /// are dropped. Arrows attached to a flattened shape keep their current
+    /// endpoints but lose the attachment. The first stroke of each shape keeps
+    /// the shape's id.
+    pub fn flatten(&mut self, ids: &[u64]) -> bool {
+        let arrows = render_arrows(&self.items);
+        let flattened: Vec<u64> = self
+            .items
+            .iter()
+            .filter_map(|it| match it {
+                Item::Shape(sh) if ids.contains(&sh.id) => Some(sh.id),
+                _ => None,
+            })
+            .collect();
+        if flattened.is_empty() {
+            return false;
+        }
+
+        let mut items = Vec::with_capacity(self.items.len());
+        for item in self.items.iter() {
+            let Item::Shape(sh) = item else {
+                items.push(item.clone());
+                continue;
+            };
+            if !flattened.contains(&sh.id) {
+                items.push(Item::Shape(detach(sh, &flattened, &arrows)));
+                continue;
+            }
+
+            let polylines: Vec<Vec<Point>> = if is_boxed_shape(sh.kind) {
+                vec![shape_outline(sh)]
+            } else if let Some(r) = arrows.iter().find(|r| r.shape_id == sh.id) {
+                let mut head = r.head_polygon();
+                head.push(r.end);
+                let mut lines = vec![r.shaft.flatten(r.start, r.shaft_end, CURVE_SEGMENTS), head];
+                lines.extend(r.tail.map(|t| t.outline()));
+                lines
+            } else {
+                // Too short to render: keep the raw segment.
+                vec![vec![sh.start, sh.end]]
+            };
+            for (i, points) in polylines.into_iter().enumerate() {
+                let id = if i == 0 {
+                    sh.id
+                } else {
+                    let id = self.next_id;
+                    self.next_id = self.next_id.saturating_add(1);
+                    id
+                };
+                items.push(Item::Stroke(Stroke {
+                    id,
+                    color: sh.style.stroke_color,
+                    width: sh.style.stroke_width,
+                    points,
+                    // Heads and tails keep the shaft's joins but not its taper.
+                    line: if i == 0 {
+                        sh.style.line
+                    } else {
+                        LineStyle {
+                            taper_start: 0.0,
+                            taper_end: 0.0,
+                            ..sh.style.line
+                        }
+                    },
+                    blend_mode: sh.blend_mode,
+                    color_ref: sh.style.stroke_color_ref,
+                    layer_id: sh.layer_id,
+                    tags: sh.tags.clone(),
+                    link: sh.link.clone(),
+                    created_ms: sh.created_ms,
+                }));
+            }
+        }
+
+        let edit = self.items_edit(items);
+        self.apply_all([edit])
+    }
+}
+
+// Bakes the rendered endpoint of an arrow attached to a flattened shape.
+fn detach(sh: &Shape, flattened: &[u64], arrows: &[ArrowRender]) -> Shape {
+    let mut sh = sh.clone();
+    if !is_arrow_like(sh.kind) {
+        return sh;
+    }
+    let render = arrows.iter().find(|r| r.shape_id == sh.id);
+    if sh.start_attach_id.is_some_and(|id| flattened.contains(&id)) {
+        if let Some(r) = render {
+            sh.start = r.start;
+        }
+        sh.start_attach_id = None;
+        sh.start_attach_uv = None;
+    }
+    if sh.end_attach_id.is_some_and(|id| flattened.contains(&id)) {
+        if let Some(r) = render {
+            sh.end = r.end;
+        }
+        sh.end_attach_id = None;
+        sh.end_attach_uv = None;
+    }
+    sh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ShapeKind, ShapeStyle};
+    use crate::test_util::style;
+
+    #[test]
+    fn flatten_replaces_shapes_with_strokes_in_place() {
+        let mut store = Store::new();
+        let style = ShapeStyle {
+            stroke_width: 3.0,
+            fill_enabled: true,
+            ..style()
+        };
+        let mut rect = store.begin_shape(ShapeKind::Rectangle, style, Point { x: 0.0, y: 0.0 });
+        rect.end = Point { x: 100.0, y: 50.0 };
+        let rect_id = rect.id;
+        store.commit_shape(rect).unwrap();
+        let mut arrow = store.begin_shape(ShapeKind::Arrow, style, Point { x: 50.0, y: 25.0 });
+        arrow.end = Point { x: 300.0, y: 25.0 };
+        arrow.start_attach_id = Some(rect_id);
+        let arrow_id = arrow.id;
+        store.commit_shape(arrow).unwrap();
+        let attached_start = render_arrows(store.items())[0].start;
+
+        assert!(store.flatten(&[rect_id]));
+        let Item::Stroke(outline) = &store.items()[0] else {
+            panic!("expected stroke")
+        };
+        assert_eq!(outline.id, rect_id);
+        assert_eq!(outline.points.len(), 5);
+        assert_eq!(outline.width, 3.0);
+        let Item::Shape(arrow) = &store.items()[1] else {
+            panic!("expected shape")
+        };
+        assert_eq!(arrow.start_attach_id, None);
+        assert_eq!(arrow.start, attached_start);
+
+        assert!(store.flatten(&[arrow_id]));
+        assert_eq!(store.items().len(), 3);
+        assert!(store.items().iter().all(|it| matches!(it, Item::Stroke(_))));
+
+        store.undo().unwrap();
+        store.undo().unwrap();
+        assert!(matches!(store.items()[0], Item::Shape(_)));
+    }
+}
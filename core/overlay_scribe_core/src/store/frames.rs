@@ -0,0 +1,94 @@
+use super::Store;
+use crate::export::{to_svg, SvgOptions};
+use crate::geometry::Rect;
+use crate::model::Frame;
+
+impl Store {
+    pub fn frames(&self) -> &[Frame] {
+        &self.meta.frames
+    }
+
+    pub fn frame(&self, name: &str) -> Option<&Frame> {
+        self.meta.frames.iter().find(|f| f.name == name)
+    }
+
+    /// Creates or moves the frame called `name`.
+    pub fn set_frame(&mut self, name: &str, rect: Rect) -> bool {
+        let mut meta = self.meta.clone();
+        match meta.frames.iter_mut().find(|f| f.name == name) {
+            Some(f) => f.rect = rect,
+            None => meta.frames.push(Frame {
+                name: name.to_string(),
+                rect,
+            }),
+        }
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+
+    /// Renames a frame; fails if `new_name` is already taken.
+    pub fn rename_frame(&mut self, name: &str, new_name: &str) -> bool {
+        if name != new_name && self.frame(new_name).is_some() {
+            return false;
+        }
+        let mut meta = self.meta.clone();
+        let Some(frame) = meta.frames.iter_mut().find(|f| f.name == name) else {
+            return false;
+        };
+        frame.name = new_name.to_string();
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+
+    pub fn remove_frame(&mut self, name: &str) -> bool {
+        let mut meta = self.meta.clone();
+        meta.frames.retain(|f| f.name != name);
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+
+    /// SVG export cropped to the frame called `name`.
+    pub fn export_frame(&self, name: &str) -> Option<String> {
+        let rect = self.frame(name)?.rect;
+        Some(to_svg(&self.document(), &SvgOptions { region: Some(rect) }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ColorRgba8, Point};
+
+    #[test]
+    fn export_frame_crops_to_frame() {
+        let mut store = Store::new();
+        let c = ColorRgba8 {
+            r: 255,
+            g: 0,
+            b: 0,
+            a: 255,
+        };
+        let mut s = store.begin_stroke(c, 2.0, Point { x: 10.0, y: 10.0 });
+        s.points.push(Point { x: 500.0, y: 500.0 });
+        store.commit_stroke(s).unwrap();
+
+        let rect = Rect {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: 200.0,
+            max_y: 100.0,
+        };
+        assert!(store.set_frame("Header", rect));
+        assert!(store.export_frame("Missing").is_none());
+        let svg = store.export_frame("Header").unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(r#"viewBox="0 0 200 100""#));
+        assert!(svg.contains(r#"<polyline points="10,10 500,500""#));
+
+        assert!(store.rename_frame("Header", "Intro"));
+        assert!(store.frame("Header").is_none());
+        let loaded = Store::from_json(&store.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.meta.frames, store.frames());
+        assert!(store.remove_frame("Intro"));
+    }
+}
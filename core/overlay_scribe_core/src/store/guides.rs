@@ -0,0 +1,100 @@
+use super::Store;
+use crate::model::{Guide, GuideOrientation, Point};
+use crate::snap::{snap_point, SnapConfig, SnapResult};
+
+impl Store {
+    pub fn guides(&self) -> &[Guide] {
+        &self.meta.guides
+    }
+
+    /// Adds a guide line and returns its id.
+    pub fn add_guide(&mut self, orientation: GuideOrientation, position: f32) -> u32 {
+        let id = self
+            .meta
+            .guides
+            .iter()
+            .map(|g| g.id)
+            .max()
+            .map_or(1, |max| max.saturating_add(1));
+        let mut meta = self.meta.clone();
+        meta.guides.push(Guide {
+            id,
+            orientation,
+            position,
+        });
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit]);
+        id
+    }
+
+    pub fn move_guide(&mut self, id: u32, position: f32) -> bool {
+        let mut meta = self.meta.clone();
+        let Some(guide) = meta.guides.iter_mut().find(|g| g.id == id) else {
+            return false;
+        };
+        guide.position = position;
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+
+    pub fn remove_guide(&mut self, id: u32) -> bool {
+        let mut meta = self.meta.clone();
+        meta.guides.retain(|g| g.id != id);
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+
+    pub fn snap_to_guides(&self) -> bool {
+        self.meta.snap_to_guides
+    }
+
+    pub fn set_snap_to_guides(&mut self, enabled: bool) -> bool {
+        let mut meta = self.meta.clone();
+        meta.snap_to_guides = enabled;
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+
+    /// [`snap_point`] against this document's items, adding its guides to
+    /// `config` when guide snapping is enabled.
+    pub fn snap_point(
+        &self,
+        point: Point,
+        anchor: Option<Point>,
+        mut config: SnapConfig,
+    ) -> SnapResult {
+        if self.meta.snap_to_guides {
+            config.guides.extend_from_slice(&self.meta.guides);
+        }
+        snap_point(point, anchor, &self.items, &config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snap::SnapTarget;
+
+    #[test]
+    fn guides_persist_and_snap_only_when_enabled() {
+        let mut store = Store::new();
+        let id = store.add_guide(GuideOrientation::Vertical, 40.0);
+        assert!(store.move_guide(id, 50.0));
+
+        let p = Point { x: 47.0, y: 10.0 };
+        let r = store.snap_point(p, None, SnapConfig::default());
+        assert_eq!(r.point, p);
+
+        assert!(store.set_snap_to_guides(true));
+        let r = store.snap_point(p, None, SnapConfig::default());
+        assert_eq!(r.point, Point { x: 50.0, y: 10.0 });
+        assert_eq!(r.x.unwrap().target, SnapTarget::Guide(id));
+
+        let loaded = Store::from_json(&store.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.meta.guides, store.guides());
+        assert!(loaded.meta.snap_to_guides);
+
+        assert!(store.remove_guide(id));
+        assert!(store.guides().is_empty());
+    }
+}
@@ -0,0 +1,73 @@
+use super::Store;
+use crate::model::Halo;
+
+impl Store {
+    pub fn halo(&self) -> Option<Halo> {
+        self.meta.halo
+    }
+
+    /// Sets (or with `None` removes) the casing drawn under every item's
+    /// ink, as one undo step. A width that is not a positive finite number
+    /// is refused.
+    pub fn set_halo(&mut self, halo: Option<Halo>) -> bool {
+        if halo.is_some_and(|h| !(h.width.is_finite() && h.width > 0.0)) {
+            return false;
+        }
+        let mut meta = self.meta.clone();
+        meta.halo = halo;
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{to_svg, SvgOptions};
+    use crate::geometry::Rect;
+    use crate::model::Point;
+    use crate::test_util::black;
+
+    #[test]
+    fn halo_cases_ink_in_svg_and_tiles() {
+        let mut store = Store::new();
+        let black = black();
+        let mut s = store.begin_stroke(black, 2.0, Point { x: 0.0, y: 8.0 });
+        s.points.push(Point { x: 16.0, y: 8.0 });
+        store.commit_stroke(s).unwrap();
+        let region = [Rect {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: 16.0,
+            max_y: 16.0,
+        }];
+        // Two units off the line: bare, then inside a 3-unit casing.
+        let near = |store: &Store| {
+            let tile = &store.render_tiles(16, &region)[0];
+            let at = (10 * 16 + 8) * 4;
+            tile.rgba[at..at + 4].to_vec()
+        };
+        assert_eq!(near(&store)[3], 0);
+
+        assert!(!store.set_halo(Some(Halo {
+            width: -1.0,
+            color: None,
+        })));
+        assert!(store.set_halo(Some(Halo {
+            width: 3.0,
+            color: None,
+        })));
+        assert_eq!(near(&store), vec![255, 255, 255, 255]);
+        let svg = to_svg(&store.document(), &SvgOptions::default());
+        let casing = svg.find(r#"<g id="halo">"#).unwrap();
+        assert!(svg[casing..].contains(r#"fill="rgb(255,255,255)""#));
+        assert!(casing < svg.find("<polyline").unwrap());
+        assert_eq!(
+            store.export_bounds(0.0).map(|r| r.min_y),
+            Some(8.0 - 1.0 - 3.0)
+        );
+
+        store.undo().unwrap();
+        assert_eq!(store.halo(), None);
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::{HashMap, HashSet};
+
+use super::limits::item_points;
+use super::{Document, Store};
+use crate::model::Item;
+
+impl Store {
+    /// Appends `items` as a single undo step, e.g. when pasting or importing
+    /// a template. Returns the ids the items ended up with, in order.
+    ///
+    /// An item keeps its id unless that id is already used (in the document
+    /// or earlier in `items`), in which case it gets a fresh one. Arrow
+    /// attachments to other pasted items follow the renumbering; attachments
+    /// to ids that exist in neither are dropped, as are unknown layers.
+    /// Items are checked and clamped like commits; nothing is inserted if
+    /// any of them is corrupt or they would break the [`DocumentLimits`].
+    ///
+    /// [`DocumentLimits`]: super::DocumentLimits
+    pub fn insert_items(&mut self, items: Vec<Item>) -> Vec<u64> {
+        self.insert_with_ids(items, false)
+    }
+
+    /// Stamps a copy of `template`'s items into the document, moved by
+    /// (`dx`, `dy`), as one undo step. Every copy gets a fresh id; arrows
+    /// between template items stay attached. Returns the new ids in order.
+    pub fn instantiate_template(&mut self, template: &Document, dx: f32, dy: f32) -> Vec<u64> {
+        let mut items = template.items.clone();
+        if dx.is_finite() && dy.is_finite() {
+            for item in &mut items {
+                item.translate(dx, dy);
+            }
+        }
+        self.insert_with_ids(items, true)
+    }
+
+    // With `fresh`, every item is renumbered, not just conflicting ones.
+    fn insert_with_ids(&mut self, mut items: Vec<Item>, fresh: bool) -> Vec<u64> {
+        let sane = items.iter_mut().all(|item| match item {
+            Item::Stroke(s) => Self::sanitize_stroke(s).is_ok(),
+            Item::Shape(sh) => Self::sanitize_shape(sh, true).is_ok(),
+        });
+        if !sane {
+            return Vec::new();
+        }
+        let points: Vec<usize> = items.iter().map(item_points).collect();
+        if items.is_empty() || self.check_limits(&points, &[]).is_err() {
+            return Vec::new();
+        }
+        let mut used: HashSet<u64> = self.items.iter().map(Item::id).collect();
+        let mut next_id = if fresh {
+            self.next_id
+        } else {
+            self.next_id.max(
+                items
+                    .iter()
+                    .map(Item::id)
+                    .max()
+                    .unwrap_or(0)
+                    .saturating_add(1),
+            )
+        };
+        let mut remap: HashMap<u64, u64> = HashMap::new();
+        let mut ids = Vec::with_capacity(items.len());
+        for item in &items {
+            let id = if fresh || used.contains(&item.id()) {
+                let id = next_id;
+                next_id = next_id.saturating_add(1);
+                id
+            } else {
+                item.id()
+            };
+            used.insert(id);
+            remap.entry(item.id()).or_insert(id);
+            ids.push(id);
+        }
+
+        let resolve = |attach: Option<u64>| {
+            let attach = attach?;
+            match remap.get(&attach) {
+                Some(&id) => Some(id),
+                None if !fresh && self.items.iter().any(|i| i.id() == attach) => Some(attach),
+                None => None,
+            }
+        };
+        let mut after = self.items.to_vec();
+        for (mut item, &id) in items.into_iter().zip(&ids) {
+            match &mut item {
+                Item::Stroke(s) => {
+                    s.id = id;
+                    if s.layer_id.is_some_and(|l| self.layer(l).is_none()) {
+                        s.layer_id = None;
+                    }
+                    self.resolve_stroke_palette(s);
+                }
+                Item::Shape(sh) => {
+                    sh.id = id;
+                    sh.start_attach_id = resolve(sh.start_attach_id);
+                    if sh.start_attach_id.is_none() {
+                        sh.start_attach_uv = None;
+                    }
+                    sh.end_attach_id = resolve(sh.end_attach_id);
+                    if sh.end_attach_id.is_none() {
+                        sh.end_attach_uv = None;
+                    }
+                    if sh.layer_id.is_some_and(|l| self.layer(l).is_none()) {
+                        sh.layer_id = None;
+                    }
+                    self.resolve_style_palette(&mut sh.style);
+                }
+            }
+            after.push(item);
+        }
+        self.next_id = next_id;
+        let edit = self.items_edit(after);
+        self.apply_all([edit]);
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Point, ShapeKind};
+    use crate::test_util::{black, style};
+
+    #[test]
+    fn insert_renumbers_conflicts_and_undoes_in_one_step() {
+        let mut store = Store::new();
+        let c = black();
+        let style = style();
+        let rect = store.begin_shape(ShapeKind::Rectangle, style, Point { x: 0.0, y: 0.0 });
+        let existing = rect.id;
+        store.commit_shape(rect).unwrap();
+
+        let mut target = store.begin_shape(ShapeKind::Rectangle, style, Point { x: 0.0, y: 0.0 });
+        target.id = existing;
+        let mut arrow = store.begin_shape(ShapeKind::Arrow, style, Point { x: 0.0, y: 0.0 });
+        arrow.start_attach_id = Some(existing);
+        arrow.end_attach_id = Some(999);
+        let arrow_id = arrow.id;
+
+        let ids = store.insert_items(vec![Item::Shape(target), Item::Shape(arrow)]);
+        assert_eq!(store.items().len(), 3);
+        assert_ne!(ids[0], existing);
+        assert_eq!(ids[1], arrow_id);
+        let Item::Shape(arrow) = &store.items()[2] else {
+            panic!("expected shape");
+        };
+        assert_eq!(arrow.start_attach_id, Some(ids[0]));
+        assert_eq!(arrow.end_attach_id, None);
+
+        let fresh = store.begin_stroke(c, 1.0, Point { x: 0.0, y: 0.0 });
+        assert!(!ids.contains(&fresh.id));
+
+        store.undo().unwrap();
+        assert_eq!(store.items().len(), 1);
+    }
+}
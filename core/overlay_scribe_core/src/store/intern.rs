@@ -0,0 +1,139 @@
+use super::Store;
+use crate::model::{Item, ShapeStyle};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::mem::size_of;
+
+/// The distinct shape styles in a document, each stored once, with every
+/// shape's index into them. Built on first use and dropped whenever the
+/// items change, like the chunk index.
+#[derive(Debug, Default)]
+pub(super) struct StyleTable {
+    styles: Vec<ShapeStyle>,
+    // Table indices keyed by `bucket_key`; styles sharing a key are told
+    // apart by `==`.
+    buckets: HashMap<u64, Vec<u32>>,
+    // Per item, in document order: its style's index, `None` for strokes.
+    of_item: Vec<Option<u32>>,
+}
+
+impl StyleTable {
+    fn build(items: &[Item]) -> Self {
+        let mut table = StyleTable::default();
+        table.of_item = items
+            .iter()
+            .map(|item| match item {
+                Item::Shape(sh) => Some(table.intern(&sh.style)),
+                Item::Stroke(_) => None,
+            })
+            .collect();
+        table
+    }
+
+    fn intern(&mut self, style: &ShapeStyle) -> u32 {
+        let bucket = self.buckets.entry(bucket_key(style)).or_default();
+        if let Some(&index) = bucket.iter().find(|&&i| self.styles[i as usize] == *style) {
+            return index;
+        }
+        let index = self.styles.len() as u32;
+        self.styles.push(*style);
+        bucket.push(index);
+        index
+    }
+
+    pub(super) fn heap_bytes(&self) -> usize {
+        let buckets: usize = self
+            .buckets
+            .values()
+            .map(|b| size_of::<(u64, Vec<u32>)>() + b.capacity() * size_of::<u32>())
+            .sum();
+        self.styles.capacity() * size_of::<ShapeStyle>()
+            + buckets
+            + self.of_item.capacity() * size_of::<Option<u32>>()
+    }
+}
+
+// Hashes the fields styles most often differ in.
+fn bucket_key(style: &ShapeStyle) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for color in [style.stroke_color, style.fill_color] {
+        [color.r, color.g, color.b, color.a].hash(&mut hasher);
+    }
+    style.stroke_width.to_bits().hash(&mut hasher);
+    style.corner_radius.to_bits().hash(&mut hasher);
+    (style.fill_enabled, style.hatch_enabled).hash(&mut hasher);
+    hasher.finish()
+}
+
+impl Store {
+    fn style_table(&self) -> &StyleTable {
+        self.styles.get_or_init(|| StyleTable::build(&self.items))
+    }
+
+    /// The distinct shape styles in the document, each listed once in order
+    /// of first use.
+    pub fn distinct_styles(&self) -> &[ShapeStyle] {
+        &self.style_table().styles
+    }
+
+    /// Ids of the shapes styled exactly like shape `id` (including `id`
+    /// itself), in document order. Empty if `id` is not a shape.
+    ///
+    /// Shapes are matched by their index in the style table, so this never
+    /// compares styles field by field.
+    pub fn select_same_style(&self, id: u64) -> Vec<u64> {
+        let table = self.style_table();
+        let Some(style) = self
+            .items
+            .iter()
+            .position(|it| it.id() == id)
+            .and_then(|i| table.of_item[i])
+        else {
+            return Vec::new();
+        };
+        self.items
+            .iter()
+            .zip(&table.of_item)
+            .filter(|(_, s)| **s == Some(style))
+            .map(|(it, _)| it.id())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Point, ShapeKind};
+    use crate::test_util::{black, style};
+
+    #[test]
+    fn equal_styles_are_listed_once() {
+        let mut store = Store::new();
+        let mut ids = Vec::new();
+        for x in [0.0, 50.0, 100.0] {
+            let mut arrow = store.begin_shape(ShapeKind::CurvedArrow, style(), Point { x, y: 0.0 });
+            arrow.end = Point {
+                x: x + 40.0,
+                y: 40.0,
+            };
+            ids.push(arrow.id);
+            store.commit_shape(arrow).unwrap();
+        }
+        let mut s = store.begin_stroke(black(), 2.0, Point { x: 0.0, y: 0.0 });
+        s.points.push(Point { x: 5.0, y: 5.0 });
+        store.commit_stroke(s).unwrap();
+        assert_eq!(store.distinct_styles(), &[style()]);
+        assert_eq!(store.select_same_style(ids[0]), ids);
+
+        // Restyling one shape adds a style; the others still match.
+        assert!(store.set_curvature(&[ids[1]], Some(0.5)));
+        assert_eq!(store.distinct_styles().len(), 2);
+        assert_eq!(store.select_same_style(ids[0]), vec![ids[0], ids[2]]);
+        assert_eq!(store.select_same_style(ids[1]), vec![ids[1]]);
+
+        store.undo().unwrap();
+        assert_eq!(store.select_same_style(ids[2]), ids);
+        assert!(store.select_same_style(u64::MAX).is_empty());
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use super::{DocumentMeta, Edit, Store};
+use crate::geometry::is_closed_shape;
+use crate::model::Item;
+
+/// Which history stack an [`InvariantViolation`] was found in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryStack {
+    Undo,
+    Redo,
+}
+
+/// A broken store invariant, as reported by [`Store::check_invariants`].
+/// None of these arise from the public API; each one is a bug.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvariantViolation {
+    DuplicateId(u64),
+    // An item id that a later `begin_stroke`/`begin_shape` could hand out again.
+    IdNotReserved(u64),
+    // A live stroke or shape that already has an item in the document.
+    LiveIdInDocument(u64),
+    // An arrow attached to itself, or to an item that isn't a closed shape.
+    // Attachments to missing ids are fine: undoing a delete reattaches them.
+    BadAttachment { arrow: u64, target: u64 },
+    // A history entry (`depth` 0 is the top) that refers to an item index or
+    // item its stack state doesn't have.
+    HistoryOutOfBounds { stack: HistoryStack, depth: usize },
+    // A history entry that undoing and then redoing doesn't restore.
+    HistoryNotInverse { stack: HistoryStack, depth: usize },
+}
+
+impl Store {
+    /// Every broken invariant, for shells to assert on in debug builds and
+    /// for property tests: unique and reserved ids, attachment targets,
+    /// live items, and that each undo and redo entry fits the state it
+    /// applies to and is undone exactly by its inverse. Walks a copy of the
+    /// history, so it costs about as much as undoing everything.
+    pub fn check_invariants(&self) -> Vec<InvariantViolation> {
+        let mut out = Vec::new();
+        let mut seen = HashSet::new();
+        for item in self.items.iter() {
+            let id = item.id();
+            if !seen.insert(id) {
+                out.push(InvariantViolation::DuplicateId(id));
+            }
+            if id >= self.next_id {
+                out.push(InvariantViolation::IdNotReserved(id));
+            }
+            let Item::Shape(sh) = item else { continue };
+            for target in [sh.start_attach_id, sh.end_attach_id].into_iter().flatten() {
+                let closed =
+                    |item: &Item| matches!(item, Item::Shape(t) if is_closed_shape(t.kind));
+                let bad = target == id || self.item(target).is_some_and(|t| !closed(t));
+                if bad {
+                    out.push(InvariantViolation::BadAttachment { arrow: id, target });
+                }
+            }
+        }
+        let live = self.live_strokes.iter().map(|s| s.id);
+        for id in live.chain(self.live_shapes.iter().map(|(_, sh)| sh.id)) {
+            if seen.contains(&id) {
+                out.push(InvariantViolation::LiveIdInDocument(id));
+            }
+        }
+        for (stack, entries) in [
+            (HistoryStack::Undo, &self.undo),
+            (HistoryStack::Redo, &self.redo),
+        ] {
+            let mut state = scratch(self.items.to_vec(), self.meta.clone());
+            for (depth, edit) in entries.iter().rev().enumerate() {
+                if !fits(&mut scratch(state.items.to_vec(), state.meta.clone()), edit) {
+                    out.push(InvariantViolation::HistoryOutOfBounds { stack, depth });
+                    break;
+                }
+                let (items, meta) = (state.items.clone(), state.meta.clone());
+                let inverse = state.unapply(edit);
+                let mut again = scratch(state.items.to_vec(), state.meta.clone());
+                again.unapply(&inverse);
+                if again.items != items || again.meta != meta {
+                    out.push(InvariantViolation::HistoryNotInverse { stack, depth });
+                    break;
+                }
+            }
+        }
+        out
+    }
+}
+
+fn scratch(items: Vec<Item>, meta: DocumentMeta) -> Store {
+    Store {
+        items: Arc::new(items),
+        meta,
+        ..Store::default()
+    }
+}
+
+// Whether unapplying `edit` to `state` only touches items it has; unapplies
+// it as it goes.
+fn fits(state: &mut Store, edit: &Edit) -> bool {
+    let ok = match edit {
+        Edit::AddItem(item) => state.items.contains(item),
+        Edit::RemoveItem { index, .. } => *index <= state.items.len(),
+        Edit::ReplaceItem { index, after, .. } => state.items.get(*index) == Some(after),
+        Edit::ReplaceAll { .. } | Edit::ReplaceMeta { .. } => true,
+        Edit::Batch(edits) => return edits.iter().rev().all(|e| fits(state, e)),
+    };
+    if ok {
+        state.unapply(edit);
+    }
+    ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Point, ShapeKind};
+    use crate::test_util::{black, style};
+
+    #[test]
+    fn a_busy_history_keeps_every_invariant() {
+        let mut store = Store::new();
+        let c = black();
+        let style = style();
+        let mut rect = store.begin_shape(ShapeKind::Rectangle, style, Point { x: 0.0, y: 0.0 });
+        rect.end = Point { x: 40.0, y: 20.0 };
+        let rect_id = rect.id;
+        store.commit_shape(rect.clone()).unwrap();
+        let mut arrow = store.begin_shape(ShapeKind::Arrow, style, Point { x: 100.0, y: 0.0 });
+        arrow.end = Point { x: 40.0, y: 10.0 };
+        arrow.end_attach_id = Some(rect_id);
+        store.commit_shape(arrow).unwrap();
+        rect.text = "label".into();
+        store.commit_shape(rect).unwrap();
+        let mut s = store.begin_stroke(c, 2.0, Point { x: 0.0, y: 50.0 });
+        s.points.push(Point { x: 60.0, y: 50.0 });
+        let stroke_id = s.id;
+        store.commit_stroke(s).unwrap();
+        store.split_stroke(stroke_id, crate::store::SplitAt::Fraction(0.5));
+        store.remove_items(&[rect_id]);
+        store.undo().unwrap();
+        store.undo().unwrap();
+        store.begin_live_stroke(c, 2.0, Point { x: 0.0, y: 0.0 });
+        assert_eq!(store.check_invariants(), []);
+
+        // Corrupt the document behind the history's back: the top undo entry
+        // added the stroke that's now gone.
+        Arc::make_mut(&mut store.items).pop();
+        let violations = store.check_invariants();
+        assert!(
+            violations.contains(&InvariantViolation::HistoryOutOfBounds {
+                stack: HistoryStack::Undo,
+                depth: 0
+            })
+        );
+        let first = store.items[0].clone();
+        Arc::make_mut(&mut store.items).push(first);
+        assert!(store
+            .check_invariants()
+            .contains(&InvariantViolation::DuplicateId(rect_id)));
+    }
+}
@@ -0,0 +1,142 @@
+use super::{dist2, Store};
+use crate::model::{Item, Point};
+
+impl Store {
+    /// Continues stroke `a` with stroke `b` when one of `b`'s ends lies
+    /// within `tolerance` of one of `a`'s, as one undo step, so an
+    /// interrupted line erases and undoes as one object.
+    ///
+    /// The joined stroke keeps `a`'s id, style and stacking position and
+    /// runs in `a`'s direction; `b` is reversed if needed and removed. When
+    /// several pairs of ends touch, `a`'s end meeting `b`'s start wins, then
+    /// the closest pair. Refused for strokes on different layers and when the
+    /// joined stroke would break the [`DocumentLimits`].
+    ///
+    /// [`DocumentLimits`]: super::DocumentLimits
+    pub fn join_strokes(&mut self, a: u64, b: u64, tolerance: f32) -> bool {
+        if a == b || !(tolerance.is_finite() && tolerance >= 0.0) {
+            return false;
+        }
+        let (Some(Item::Stroke(first)), Some(Item::Stroke(second))) = (self.item(a), self.item(b))
+        else {
+            return false;
+        };
+        if first.layer_id != second.layer_id {
+            return false;
+        }
+        let (Some(&a_start), Some(&a_end), Some(&b_start), Some(&b_end)) = (
+            first.points.first(),
+            first.points.last(),
+            second.points.first(),
+            second.points.last(),
+        ) else {
+            return false;
+        };
+
+        // (a's end is the joint, b's start is the joint, gap²)
+        let pairs = [
+            (true, true, dist2(a_end, b_start)),
+            (true, false, dist2(a_end, b_end)),
+            (false, false, dist2(a_start, b_end)),
+            (false, true, dist2(a_start, b_start)),
+        ];
+        let reach = tolerance * tolerance;
+        let joint = if pairs[0].2 <= reach {
+            Some(&pairs[0])
+        } else {
+            pairs
+                .iter()
+                .filter(|p| p.2 <= reach)
+                .min_by(|p, q| p.2.total_cmp(&q.2))
+        };
+        let Some(&(at_end, from_start, _)) = joint else {
+            return false;
+        };
+
+        // b's points in the order they continue a, joint first when appended.
+        let mut tail: Vec<Point> = second.points.clone();
+        if at_end != from_start {
+            tail.reverse();
+        }
+        let mut points = first.points.clone();
+        if at_end {
+            if tail.first() == points.last() {
+                tail.remove(0);
+            }
+            points.extend(tail);
+        } else {
+            if tail.last() == points.first() {
+                tail.pop();
+            }
+            tail.extend(points);
+            points = tail;
+        }
+        let mut tags = first.tags.clone();
+        tags.extend(second.tags.iter().cloned());
+        tags.sort();
+        tags.dedup();
+
+        if self.check_limits(&[points.len()], &[a, b]).is_err() {
+            return false;
+        }
+        let mut items = self.items.to_vec();
+        items.retain(|item| item.id() != b);
+        for item in &mut items {
+            if let Item::Stroke(s) = item {
+                if s.id == a {
+                    s.points = std::mem::take(&mut points);
+                    s.tags = std::mem::take(&mut tags);
+                }
+            }
+        }
+        let edit = self.items_edit(items);
+        self.apply_all([edit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::black;
+
+    #[test]
+    fn joins_touching_strokes_in_the_first_ones_direction() {
+        let mut store = Store::new();
+        let c = black();
+        let draw = |store: &mut Store, points: &[(f32, f32)]| {
+            let p = |&(x, y): &(f32, f32)| Point { x, y };
+            let mut s = store.begin_stroke(c, 2.0, p(&points[0]));
+            s.points.extend(points[1..].iter().map(p));
+            let id = s.id;
+            store.commit_stroke(s).unwrap();
+            id
+        };
+        let a = draw(&mut store, &[(0.0, 0.0), (10.0, 0.0)]);
+        // Drawn backwards, ending near a's end.
+        let b = draw(&mut store, &[(30.0, 0.0), (20.0, 0.0), (11.0, 1.0)]);
+        let far = draw(&mut store, &[(100.0, 100.0), (120.0, 100.0)]);
+        let xs = |store: &Store| match store.item(a) {
+            Some(Item::Stroke(s)) => s.points.iter().map(|p| p.x).collect::<Vec<_>>(),
+            _ => panic!("expected stroke"),
+        };
+
+        assert!(!store.join_strokes(a, far, 5.0));
+        assert!(!store.join_strokes(a, b, 1.0));
+        assert!(!store.join_strokes(a, a, 5.0));
+        assert!(store.join_strokes(a, b, 2.0));
+        assert_eq!(xs(&store), [0.0, 10.0, 11.0, 20.0, 30.0]);
+        assert!(store.item(b).is_none());
+        assert_eq!(store.items().len(), 2);
+
+        // Continuing from a's start prepends.
+        let lead = draw(&mut store, &[(-20.0, 0.0), (0.0, 0.0)]);
+        assert!(store.join_strokes(a, lead, 0.5));
+        assert_eq!(xs(&store), [-20.0, 0.0, 10.0, 11.0, 20.0, 30.0]);
+
+        for _ in 0..3 {
+            store.undo().unwrap();
+        }
+        assert_eq!(xs(&store), [0.0, 10.0]);
+        assert!(store.item(b).is_some());
+    }
+}
@@ -0,0 +1,132 @@
+use super::Store;
+use crate::geometry::Rect;
+use crate::model::{Item, Layer};
+
+impl Store {
+    pub fn layers(&self) -> &[Layer] {
+        &self.meta.layers
+    }
+
+    pub fn layer(&self, id: u32) -> Option<&Layer> {
+        self.meta.layers.iter().find(|l| l.id == id)
+    }
+
+    /// Adds an (unclipped) layer and returns its id.
+    pub fn add_layer(&mut self, name: &str) -> u32 {
+        let id = self
+            .meta
+            .layers
+            .iter()
+            .map(|l| l.id)
+            .max()
+            .map_or(1, |max| max.saturating_add(1));
+        let mut meta = self.meta.clone();
+        meta.layers.push(Layer {
+            id,
+            name: name.to_string(),
+            clip: None,
+        });
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit]);
+        id
+    }
+
+    pub fn rename_layer(&mut self, id: u32, name: &str) -> bool {
+        let mut meta = self.meta.clone();
+        let Some(layer) = meta.layers.iter_mut().find(|l| l.id == id) else {
+            return false;
+        };
+        layer.name = name.to_string();
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+
+    /// Sets or clears the clip rectangle of a layer.
+    pub fn set_layer_clip(&mut self, id: u32, clip: Option<Rect>) -> bool {
+        let mut meta = self.meta.clone();
+        let Some(layer) = meta.layers.iter_mut().find(|l| l.id == id) else {
+            return false;
+        };
+        layer.clip = clip;
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+
+    /// Removes a layer. Its items move to the base layer; one undo step.
+    pub fn remove_layer(&mut self, id: u32) -> bool {
+        let mut meta = self.meta.clone();
+        let before = meta.layers.len();
+        meta.layers.retain(|l| l.id != id);
+        if meta.layers.len() == before {
+            return false;
+        }
+        let mut items = self.items.to_vec();
+        for item in &mut items {
+            if item.layer_id() == Some(id) {
+                set_layer_id(item, None);
+            }
+        }
+        let edits = [self.meta_edit(meta), self.items_edit(items)];
+        self.apply_all(edits)
+    }
+
+    /// Moves items to `layer` (`None` for the base layer).
+    pub fn move_to_layer(&mut self, ids: &[u64], layer: Option<u32>) -> bool {
+        if layer.is_some_and(|id| self.layer(id).is_none()) {
+            return false;
+        }
+        let edit = self.map_items_edit(ids, |item| set_layer_id(item, layer));
+        self.apply_all([edit])
+    }
+
+    /// Clip rectangle that applies to `item`, from its layer.
+    pub fn clip_for(&self, item: &Item) -> Option<Rect> {
+        self.layer(item.layer_id()?)?.clip
+    }
+}
+
+fn set_layer_id(item: &mut Item, layer: Option<u32>) {
+    match item {
+        Item::Stroke(s) => s.layer_id = layer,
+        Item::Shape(sh) => sh.layer_id = layer,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{to_svg, SvgOptions};
+    use crate::model::Point;
+    use crate::test_util::black;
+
+    #[test]
+    fn layer_clip_applies_to_members_and_export() {
+        let mut store = Store::new();
+        let c = black();
+        let mut s = store.begin_stroke(c, 2.0, Point { x: 0.0, y: 0.0 });
+        s.points.push(Point { x: 100.0, y: 100.0 });
+        let id = s.id;
+        store.commit_stroke(s).unwrap();
+
+        let layer = store.add_layer("Dialog");
+        let clip = Rect {
+            min_x: 10.0,
+            min_y: 10.0,
+            max_x: 50.0,
+            max_y: 50.0,
+        };
+        assert!(store.set_layer_clip(layer, Some(clip)));
+        assert!(!store.move_to_layer(&[id], Some(99)));
+        assert!(store.move_to_layer(&[id], Some(layer)));
+        assert_eq!(store.clip_for(&store.items()[0]), Some(clip));
+
+        let svg = to_svg(&store.document(), &SvgOptions::default());
+        assert!(svg.contains(r#"<clipPath id="clip-layer-1">"#));
+        assert!(svg.contains(r#"clip-path="url(#clip-layer-1)""#));
+
+        assert!(store.remove_layer(layer));
+        assert_eq!(store.items()[0].layer_id(), None);
+        store.undo().unwrap();
+        assert_eq!(store.items()[0].layer_id(), Some(layer));
+    }
+}
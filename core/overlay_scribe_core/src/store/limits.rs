@@ -0,0 +1,158 @@
+use super::{Store, StoreError};
+use crate::model::Item;
+
+/// Caps on how large a document may grow, for shells embedded in
+/// memory-constrained hosts. `None` means unbounded, the default. Limits are
+/// configuration of the store, not part of the saved document.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DocumentLimits {
+    pub max_items: Option<usize>,
+    pub max_points_per_stroke: Option<usize>,
+    pub max_total_points: Option<usize>,
+}
+
+/// Which of the [`DocumentLimits`] an edit would break.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Items,
+    PointsPerStroke,
+    TotalPoints,
+}
+
+impl Store {
+    pub fn limits(&self) -> DocumentLimits {
+        self.limits
+    }
+
+    /// Sets the caps new ink is checked against. A document already past a
+    /// cap keeps its items; only further growth is refused.
+    pub fn set_limits(&mut self, limits: DocumentLimits) {
+        self.limits = limits;
+    }
+
+    // Whether new items with `points` points each can be added, in place of
+    // the items in `replacing`, without breaking a limit.
+    pub(super) fn check_limits(
+        &self,
+        points: &[usize],
+        replacing: &[u64],
+    ) -> Result<(), StoreError> {
+        let exceeded = |limit, max| Err(StoreError::LimitExceeded { limit, max });
+        let limits = &self.limits;
+        if let Some(max) = limits.max_points_per_stroke {
+            if points.iter().any(|&n| n > max) {
+                return exceeded(Limit::PointsPerStroke, max);
+            }
+        }
+        let replaced: Vec<&Item> = self
+            .items
+            .iter()
+            .filter(|item| replacing.contains(&item.id()))
+            .collect();
+        // Only growth is refused, so a document already past a cap can still shrink.
+        let grows = |now: usize, max: usize, added: usize, removed: usize| {
+            let after = (now + added).saturating_sub(removed);
+            after > max && after > now
+        };
+        if let Some(max) = limits.max_items {
+            let removed = replaced.len();
+            if grows(self.items.len(), max, points.len(), removed) {
+                return exceeded(Limit::Items, max);
+            }
+        }
+        if let Some(max) = limits.max_total_points {
+            let total = self.items.iter().map(item_points).sum();
+            let removed = replaced.iter().map(|item| item_points(item)).sum();
+            if grows(total, max, points.iter().sum(), removed) {
+                return exceeded(Limit::TotalPoints, max);
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(super) fn item_points(item: &Item) -> usize {
+    match item {
+        Item::Stroke(s) => s.points.len(),
+        Item::Shape(_) => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Point;
+    use crate::store::SplitAt;
+    use crate::test_util::black;
+
+    #[test]
+    fn refuses_growth_past_the_limits() {
+        let mut store = Store::new();
+        let c = black();
+        store.set_limits(DocumentLimits {
+            max_items: Some(2),
+            max_points_per_stroke: Some(3),
+            max_total_points: Some(5),
+        });
+        let stroke = |store: &mut Store, n: usize| {
+            let mut s = store.begin_stroke(c, 2.0, Point { x: 0.0, y: 0.0 });
+            for i in 1..n {
+                s.points.push(Point {
+                    x: i as f32,
+                    y: 0.0,
+                });
+            }
+            s
+        };
+
+        let long = stroke(&mut store, 4);
+        assert!(matches!(
+            store.commit_stroke(long),
+            Err(StoreError::LimitExceeded {
+                limit: Limit::PointsPerStroke,
+                max: 3
+            })
+        ));
+        let first = stroke(&mut store, 3);
+        store.commit_stroke(first).unwrap();
+        let second = stroke(&mut store, 3);
+        assert!(matches!(
+            store.commit_stroke(second),
+            Err(StoreError::LimitExceeded {
+                limit: Limit::TotalPoints,
+                ..
+            })
+        ));
+        let second = stroke(&mut store, 2);
+        store.commit_stroke(second).unwrap();
+        let third = stroke(&mut store, 1);
+        assert!(matches!(
+            store.commit_stroke(third),
+            Err(StoreError::LimitExceeded {
+                limit: Limit::Items,
+                ..
+            })
+        ));
+        assert_eq!(store.items().len(), 2);
+
+        let live = store.begin_live_stroke(c, 2.0, Point { x: 0.0, y: 0.0 });
+        assert!(store.append_stroke_point(live, Point { x: 1.0, y: 0.0 }));
+        assert!(store.append_stroke_point(live, Point { x: 2.0, y: 0.0 }));
+        assert!(!store.append_stroke_point(live, Point { x: 3.0, y: 0.0 }));
+        assert!(store
+            .insert_items(vec![store.items()[0].clone()])
+            .is_empty());
+
+        // Splitting adds an item and a point; joining makes a longer stroke.
+        let (first, second) = (store.items()[0].id(), store.items()[1].id());
+        assert_eq!(store.split_stroke(first, SplitAt::Fraction(0.5)), None);
+        store.set_limits(DocumentLimits {
+            max_items: None,
+            ..store.limits()
+        });
+        assert_eq!(store.split_stroke(first, SplitAt::Fraction(0.5)), None);
+        assert!(!store.join_strokes(first, second, 10.0));
+        store.set_limits(DocumentLimits::default());
+        assert!(store.join_strokes(first, second, 10.0));
+    }
+}
@@ -0,0 +1,39 @@
+use super::Store;
+
+impl Store {
+    /// Points each of `ids` at `link` (trimmed); `None` or a blank link clears it.
+    pub fn set_link(&mut self, ids: &[u64], link: Option<&str>) -> bool {
+        let link = link.map(str::trim).filter(|l| !l.is_empty());
+        let edit = self.map_items_edit(ids, |item| item.set_link(link.map(str::to_string)));
+        self.apply_all([edit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::SvgOptions;
+    use crate::model::Point;
+    use crate::test_util::black;
+
+    #[test]
+    fn links_round_trip_and_wrap_svg_items() {
+        let mut store = Store::new();
+        let c = black();
+        let mut s = store.begin_stroke(c, 2.0, Point { x: 0.0, y: 0.0 });
+        s.points.push(Point { x: 10.0, y: 10.0 });
+        let id = s.id;
+        store.commit_stroke(s).unwrap();
+
+        assert!(store.set_link(&[id], Some("https://example.com/t?a=1&b=2")));
+        let doc = Store::from_json(&store.to_json().unwrap()).unwrap();
+        assert_eq!(doc.items[0].link(), Some("https://example.com/t?a=1&b=2"));
+
+        let svg = store.export_svg(&SvgOptions::default());
+        assert!(svg.contains(r#"<a href="https://example.com/t?a=1&amp;b=2">"#));
+        assert!(svg.contains("</a>"));
+
+        assert!(store.set_link(&[id], Some(" ")));
+        assert_eq!(store.items()[0].link(), None);
+    }
+}
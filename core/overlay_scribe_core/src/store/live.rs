@@ -0,0 +1,175 @@
+use super::Store;
+use crate::geometry::{drag_corners, ShapeGesture};
+use crate::model::{ColorRgba8, Point, Shape, ShapeKind, ShapeStyle, Stroke};
+
+impl Store {
+    /// Starts a stroke that is built up inside the store, so a shell can send
+    /// one point per input event instead of the whole growing stroke. The
+    /// stroke isn't part of the document until [`Store::end_stroke`].
+    pub fn begin_live_stroke(&mut self, color: ColorRgba8, width: f32, start: Point) -> u64 {
+        let stroke = self.begin_stroke(color, width, start);
+        let id = stroke.id;
+        self.live_strokes.push(stroke);
+        id
+    }
+
+    /// Adds a point to a live stroke; refused for a non-finite point or once
+    /// the stroke holds [`super::DocumentLimits::max_points_per_stroke`]
+    /// points.
+    pub fn append_stroke_point(&mut self, stroke_id: u64, point: Point) -> bool {
+        let max = self.limits.max_points_per_stroke.unwrap_or(usize::MAX);
+        match self.live_strokes.iter_mut().find(|s| s.id == stroke_id) {
+            Some(stroke)
+                if stroke.points.len() < max && point.x.is_finite() && point.y.is_finite() =>
+            {
+                stroke.points.push(point);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The in-progress stroke, for drawing it before it is committed.
+    pub fn live_stroke(&self, stroke_id: u64) -> Option<&Stroke> {
+        self.live_strokes.iter().find(|s| s.id == stroke_id)
+    }
+
+    /// Commits a live stroke as one undo step; false if there is no such
+    /// stroke or the [`super::DocumentLimits`] refuse it.
+    pub fn end_stroke(&mut self, stroke_id: u64) -> bool {
+        match self.take_live_stroke(stroke_id) {
+            Some(stroke) => self.commit_stroke(stroke).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Discards a live stroke without touching the document.
+    pub fn cancel_stroke(&mut self, stroke_id: u64) -> bool {
+        self.take_live_stroke(stroke_id).is_some()
+    }
+
+    fn take_live_stroke(&mut self, stroke_id: u64) -> Option<Stroke> {
+        let index = self.live_strokes.iter().position(|s| s.id == stroke_id)?;
+        Some(self.live_strokes.remove(index))
+    }
+
+    /// Starts a shape dragged out from `press` inside the store; like live
+    /// strokes, it isn't part of the document until [`Store::end_shape`].
+    pub fn begin_live_shape(&mut self, kind: ShapeKind, style: ShapeStyle, press: Point) -> u64 {
+        let shape = self.begin_shape(kind, style, press);
+        let id = shape.id;
+        self.live_shapes.push((press, shape));
+        id
+    }
+
+    /// Moves the dragged corner to `pointer`, applying the held modifiers
+    /// (see [`drag_corners`]) from the press point.
+    pub fn drag_live_shape(
+        &mut self,
+        shape_id: u64,
+        pointer: Point,
+        gesture: ShapeGesture,
+    ) -> bool {
+        let Some((press, shape)) = self.live_shapes.iter_mut().find(|(_, s)| s.id == shape_id)
+        else {
+            return false;
+        };
+        (shape.start, shape.end) = drag_corners(shape.kind, *press, pointer, gesture);
+        true
+    }
+
+    /// The in-progress shape, for drawing it before it is committed.
+    pub fn live_shape(&self, shape_id: u64) -> Option<&Shape> {
+        self.live_shapes
+            .iter()
+            .map(|(_, s)| s)
+            .find(|s| s.id == shape_id)
+    }
+
+    /// Commits a live shape as one undo step; false if there is no such
+    /// shape or the [`super::DocumentLimits`] refuse it.
+    pub fn end_shape(&mut self, shape_id: u64) -> bool {
+        match self.take_live_shape(shape_id) {
+            Some(shape) => self.commit_shape(shape).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Discards a live shape without touching the document.
+    pub fn cancel_shape(&mut self, shape_id: u64) -> bool {
+        self.take_live_shape(shape_id).is_some()
+    }
+
+    fn take_live_shape(&mut self, shape_id: u64) -> Option<Shape> {
+        let index = self
+            .live_shapes
+            .iter()
+            .position(|(_, s)| s.id == shape_id)?;
+        Some(self.live_shapes.remove(index).1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Item;
+    use crate::test_util::{black, style};
+
+    #[test]
+    fn live_strokes_commit_on_end() {
+        let mut store = Store::new();
+        let c = black();
+        let id = store.begin_live_stroke(c, 2.0, Point { x: 0.0, y: 0.0 });
+        for i in 1..=3 {
+            assert!(store.append_stroke_point(
+                id,
+                Point {
+                    x: i as f32,
+                    y: 0.0
+                }
+            ));
+        }
+        assert!(store.items().is_empty());
+        assert_eq!(store.live_stroke(id).unwrap().points.len(), 4);
+
+        assert!(store.end_stroke(id));
+        assert!(!store.end_stroke(id));
+        assert!(!store.append_stroke_point(id, Point { x: 9.0, y: 9.0 }));
+        assert_eq!(store.items().len(), 1);
+        assert_eq!(store.items()[0].id(), id);
+
+        let other = store.begin_live_stroke(c, 2.0, Point { x: 0.0, y: 0.0 });
+        assert!(store.cancel_stroke(other));
+        assert_eq!(store.items().len(), 1);
+    }
+
+    #[test]
+    fn live_shapes_follow_modifiers_until_committed() {
+        let mut store = Store::new();
+        let style = style();
+        let id = store.begin_live_shape(ShapeKind::Ellipse, style, Point { x: 50.0, y: 50.0 });
+        let gesture = ShapeGesture {
+            constrain: true,
+            from_center: true,
+        };
+        assert!(store.drag_live_shape(id, Point { x: 80.0, y: 60.0 }, gesture));
+        let shape = store.live_shape(id).unwrap();
+        assert_eq!(
+            (shape.start, shape.end),
+            (Point { x: 20.0, y: 20.0 }, Point { x: 80.0, y: 80.0 })
+        );
+        assert!(store.items().is_empty());
+
+        assert!(store.drag_live_shape(id, Point { x: 80.0, y: 60.0 }, ShapeGesture::default()));
+        assert!(store.end_shape(id));
+        assert!(!store.drag_live_shape(id, Point { x: 0.0, y: 0.0 }, gesture));
+        let Item::Shape(committed) = &store.items()[0] else {
+            panic!("expected a shape");
+        };
+        assert_eq!(committed.end, Point { x: 80.0, y: 60.0 });
+
+        let other = store.begin_live_shape(ShapeKind::Arrow, style, Point { x: 0.0, y: 0.0 });
+        assert!(store.cancel_shape(other));
+        assert_eq!(store.items().len(), 1);
+    }
+}
@@ -0,0 +1,146 @@
+use super::{DocumentMeta, Edit, Store};
+use crate::model::{ImageSource, Item, Point, Revision, Slide};
+use std::mem::size_of;
+
+/// Estimated heap usage of a [`Store`], in bytes. Figures count allocated
+/// capacity, so they are approximate but track real growth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryFootprint {
+    pub items: usize,
+    pub meta: usize,
+    pub undo: usize,
+    pub redo: usize,
+    // Derived data that can be rebuilt, such as the spatial index.
+    pub caches: usize,
+}
+
+impl MemoryFootprint {
+    pub fn total(&self) -> usize {
+        self.items + self.meta + self.undo + self.redo + self.caches
+    }
+}
+
+impl Store {
+    /// Estimates memory held by items, history and caches, so shells can
+    /// decide when to trim history or drop caches.
+    pub fn memory_footprint(&self) -> MemoryFootprint {
+        MemoryFootprint {
+            items: items_bytes(&self.items),
+            meta: meta_bytes(&self.meta),
+            undo: edits_bytes(&self.undo),
+            redo: edits_bytes(&self.redo),
+            caches: self.chunks.get().map_or(0, |c| c.heap_bytes())
+                + self.styles.get().map_or(0, |t| t.heap_bytes()),
+        }
+    }
+}
+
+fn items_bytes(items: &[Item]) -> usize {
+    items.iter().map(item_bytes).sum()
+}
+
+fn item_bytes(item: &Item) -> usize {
+    let extra = match item {
+        Item::Stroke(s) => s.points.capacity() * size_of::<Point>(),
+        Item::Shape(sh) => sh.text.capacity(),
+    };
+    let tags: usize = item
+        .tags()
+        .iter()
+        .map(|t| size_of::<String>() + t.len())
+        .sum();
+    size_of::<Item>() + extra + tags + item.link().map_or(0, str::len)
+}
+
+fn edits_bytes(edits: &[Edit]) -> usize {
+    edits.iter().map(edit_bytes).sum()
+}
+
+fn edit_bytes(edit: &Edit) -> usize {
+    size_of::<Edit>()
+        + match edit {
+            Edit::AddItem(item) | Edit::RemoveItem { item, .. } => item_bytes(item),
+            Edit::ReplaceItem { before, after, .. } => item_bytes(before) + item_bytes(after),
+            Edit::ReplaceAll { before, after } => items_bytes(before) + items_bytes(after),
+            Edit::ReplaceMeta { before, after } => meta_bytes(before) + meta_bytes(after),
+            Edit::Batch(edits) => edits_bytes(edits),
+        }
+}
+
+fn meta_bytes(meta: &DocumentMeta) -> usize {
+    fn named<T>(list: &[T], name: impl Fn(&T) -> &str) -> usize {
+        list.iter().map(|x| size_of::<T>() + name(x).len()).sum()
+    }
+    let revisions: usize = meta.revisions.iter().map(revision_bytes).sum();
+    size_of::<DocumentMeta>()
+        + named(&meta.palette, |c| &c.name)
+        + named(&meta.presets, |p| &p.name)
+        + named(&meta.tool_profiles, |p| &p.name)
+        + named(&meta.frames, |f| &f.name)
+        + named(&meta.layers, |l| &l.name)
+        + named(&meta.views, |v| &v.name)
+        + std::mem::size_of_val(meta.guides.as_slice())
+        + std::mem::size_of_val(meta.shape_defaults.as_slice())
+        + meta
+            .background_image
+            .as_ref()
+            .map_or(0, |image| match &image.source {
+                ImageSource::Embedded { mime_type, data } => mime_type.len() + data.len(),
+                ImageSource::Uri { uri } => uri.len(),
+            })
+        + revisions
+        + meta
+            .slides
+            .iter()
+            .map(|slide| size_of::<Slide>() + items_bytes(&slide.items))
+            .sum::<usize>()
+}
+
+fn revision_bytes(revision: &Revision) -> usize {
+    size_of::<Revision>()
+        + revision.name.len()
+        + items_bytes(&revision.changed)
+        + (revision.removed.len() + revision.order.len()) * size_of::<u64>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Rect;
+    use crate::test_util::black;
+
+    #[test]
+    fn footprint_tracks_items_history_and_caches() {
+        let mut store = Store::new();
+        let empty = store.memory_footprint();
+        assert_eq!(empty.items, 0);
+        assert_eq!(empty.undo, 0);
+
+        let c = black();
+        let mut s = store.begin_stroke(c, 2.0, Point { x: 0.0, y: 0.0 });
+        s.points.extend((0..1000).map(|i| Point {
+            x: i as f32,
+            y: 0.0,
+        }));
+        store.commit_stroke(s).unwrap();
+
+        let after = store.memory_footprint();
+        assert!(after.items > 1000 * size_of::<Point>());
+        assert!(after.undo >= after.items);
+        assert_eq!(after.caches, 0);
+
+        store.items_in_region(Rect {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: 10.0,
+            max_y: 10.0,
+        });
+        assert!(store.memory_footprint().caches > 0);
+
+        store.undo().unwrap();
+        let undone = store.memory_footprint();
+        assert_eq!(undone.items, 0);
+        assert!(undone.redo > 0);
+        assert_eq!(undone.total(), undone.meta + undone.redo);
+    }
+}
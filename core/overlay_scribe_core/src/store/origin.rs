@@ -0,0 +1,269 @@
+use super::Store;
+use crate::geometry::union_bounds;
+use crate::model::{GuideOrientation, Point, WorldPoint};
+
+impl WorldPoint {
+    pub fn is_zero(&self) -> bool {
+        self.x == 0.0 && self.y == 0.0
+    }
+}
+
+impl Store {
+    pub fn origin(&self) -> WorldPoint {
+        self.meta.origin
+    }
+
+    /// Local position of the top-left corner of the sized canvas and the
+    /// background image: (0, 0) until a rebase moves it.
+    pub fn page_origin(&self) -> Point {
+        self.meta.page_origin.unwrap_or(Point { x: 0.0, y: 0.0 })
+    }
+
+    /// World position of a document-local point.
+    pub fn to_world(&self, p: Point) -> WorldPoint {
+        WorldPoint {
+            x: self.meta.origin.x + p.x as f64,
+            y: self.meta.origin.y + p.y as f64,
+        }
+    }
+
+    /// Document-local position of a world point.
+    pub fn to_local(&self, p: WorldPoint) -> Point {
+        Point {
+            x: (p.x - self.meta.origin.x) as f32,
+            y: (p.y - self.meta.origin.y) as f32,
+        }
+    }
+
+    /// Moves the local origin to `origin` without moving anything in world
+    /// space, as one undo step. Items (on every slide), guides, frames, layer
+    /// clips, views, revisions and the page origin (where the canvas and
+    /// every slide's background image sit) are shifted by the (f64-exact)
+    /// difference, so shells working far from (0, 0) can rebase to keep f32
+    /// coordinates small and precise.
+    pub fn rebase_origin(&mut self, origin: WorldPoint) -> bool {
+        if !(origin.x.is_finite() && origin.y.is_finite()) || origin == self.meta.origin {
+            return false;
+        }
+        let dx = (self.meta.origin.x - origin.x) as f32;
+        let dy = (self.meta.origin.y - origin.y) as f32;
+
+        let mut meta = self.meta.clone();
+        meta.origin = origin;
+        let page = self.page_origin();
+        let page = Point {
+            x: page.x + dx,
+            y: page.y + dy,
+        };
+        meta.page_origin = (page.x != 0.0 || page.y != 0.0).then_some(page);
+        for guide in &mut meta.guides {
+            guide.position += match guide.orientation {
+                GuideOrientation::Horizontal => dy,
+                GuideOrientation::Vertical => dx,
+            };
+        }
+        for frame in &mut meta.frames {
+            frame.rect.min_x += dx;
+            frame.rect.max_x += dx;
+            frame.rect.min_y += dy;
+            frame.rect.max_y += dy;
+        }
+        for clip in meta.layers.iter_mut().filter_map(|l| l.clip.as_mut()) {
+            clip.min_x += dx;
+            clip.max_x += dx;
+            clip.min_y += dy;
+            clip.max_y += dy;
+        }
+        for view in &mut meta.views {
+            view.center.x += dx;
+            view.center.y += dy;
+        }
+        let revisions = meta.revisions.iter_mut().flat_map(|r| &mut r.changed);
+        let slides = meta.slides.iter_mut().flat_map(|s| &mut s.items);
+        for item in revisions.chain(slides) {
+            item.translate(dx, dy);
+        }
+
+        let mut items = self.items.to_vec();
+        for item in &mut items {
+            item.translate(dx, dy);
+        }
+        let edits = [self.meta_edit(meta), self.items_edit(items)];
+        self.apply_all(edits)
+    }
+
+    /// Rebases so the content's center becomes the local origin.
+    pub fn recenter_origin(&mut self) -> bool {
+        let Some(bounds) = union_bounds(self.items.iter()) else {
+            return false;
+        };
+        let c = bounds.center();
+        let origin = self.to_world(c);
+        self.rebase_origin(origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::default_region;
+    use crate::geometry::Rect;
+    use crate::model::{BackgroundImage, Canvas, ImageSource, Item};
+    use crate::test_util::black;
+
+    #[test]
+    fn rebasing_keeps_world_positions() {
+        let mut store = Store::new();
+        let c = black();
+        let far = 4_000_000.0;
+        let mut s = store.begin_stroke(c, 1.0, Point { x: far, y: far });
+        s.points.push(Point {
+            x: far + 10.0,
+            y: far + 10.0,
+        });
+        store.commit_stroke(s).unwrap();
+        let layer = store.add_layer("callouts");
+        let clip = Rect {
+            min_x: far,
+            min_y: far,
+            max_x: far + 10.0,
+            max_y: far + 10.0,
+        };
+        assert!(store.set_layer_clip(layer, Some(clip)));
+
+        assert!(store.recenter_origin());
+        assert_eq!(
+            store.origin(),
+            WorldPoint {
+                x: far as f64 + 5.0,
+                y: far as f64 + 5.0
+            }
+        );
+        let Item::Stroke(s) = &store.items()[0] else {
+            panic!("expected stroke")
+        };
+        assert_eq!(s.points[0], Point { x: -5.0, y: -5.0 });
+        assert_eq!(
+            store.layers()[0].clip.map(|c| (c.min_x, c.max_y)),
+            Some((-5.0, 5.0))
+        );
+        // Sub-unit offsets are exact again near the new origin.
+        let p = store.to_local(WorldPoint {
+            x: far as f64 + 5.25,
+            y: far as f64,
+        });
+        assert_eq!(p, Point { x: 0.25, y: -5.0 });
+
+        store.undo().unwrap();
+        assert!(store.origin().is_zero());
+    }
+
+    #[test]
+    fn rebasing_moves_the_background_and_canvas_with_the_items() {
+        let mut store = Store::new();
+        let image = BackgroundImage {
+            source: ImageSource::Uri {
+                uri: "shot.png".to_string(),
+            },
+            width: 1920.0,
+            height: 1080.0,
+        };
+        assert!(store.set_background_image(Some(image.clone())));
+        let mut s = store.begin_stroke(black(), 1.0, Point { x: 100.0, y: 100.0 });
+        s.points.push(Point { x: 110.0, y: 100.0 });
+        store.commit_stroke(s).unwrap();
+        let corner = store.to_world(store.page_origin());
+
+        let origin = WorldPoint { x: 960.0, y: 540.0 };
+        assert!(store.rebase_origin(origin));
+        assert_eq!(
+            store.page_origin(),
+            Point {
+                x: -960.0,
+                y: -540.0
+            }
+        );
+        assert_eq!(store.to_world(store.page_origin()), corner);
+        let doc = store.document();
+        let region = default_region(&doc).unwrap();
+        assert_eq!((region.min_x, region.max_y), (-960.0, 540.0));
+        assert!(store
+            .export_svg(&Default::default())
+            .contains(r#"<image x="-960" y="-540" width="1920" height="1080""#));
+
+        // A sized canvas takes over the page and moves the same way.
+        assert!(store.set_canvas(Some(Canvas {
+            width: Some(800.0),
+            height: Some(600.0),
+            ..Default::default()
+        })));
+        assert!(store.rebase_origin(WorldPoint::default()));
+        assert_eq!(store.meta().page_origin, None);
+        let region = default_region(&store.document()).unwrap();
+        assert_eq!((region.min_x, region.max_y), (0.0, 600.0));
+        let Item::Stroke(s) = &store.items()[0] else {
+            panic!("expected stroke")
+        };
+        assert_eq!(s.points[0], Point { x: 100.0, y: 100.0 });
+
+        let loaded = Store::from_json(&store.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.meta.background_image, Some(image));
+        store.undo().unwrap();
+        store.undo().unwrap();
+        assert_eq!(
+            store.page_origin(),
+            Point {
+                x: -960.0,
+                y: -540.0
+            }
+        );
+    }
+
+    #[test]
+    fn rebasing_a_deck_moves_every_slide_and_its_background() {
+        let mut store = Store::new();
+        let screenshot = |uri: &str| BackgroundImage {
+            source: ImageSource::Uri {
+                uri: uri.to_string(),
+            },
+            width: 1280.0,
+            height: 720.0,
+        };
+        assert!(store.set_background_image(Some(screenshot("one.png"))));
+        let second = store.add_slide(Some(screenshot("two.png")));
+        assert!(store.go_to_slide(second));
+        let mut s = store.begin_stroke(black(), 1.0, Point { x: 40.0, y: 30.0 });
+        s.points.push(Point { x: 50.0, y: 30.0 });
+        store.commit_stroke(s).unwrap();
+        assert!(store.go_to_slide(0));
+
+        assert!(store.rebase_origin(WorldPoint { x: 640.0, y: 360.0 }));
+        let shifted = r#"<image x="-640" y="-360" width="1280" height="720""#;
+        let options = Default::default();
+        assert!(store.export_slide(0, &options).unwrap().contains(shifted));
+        assert!(store
+            .export_slide(second, &options)
+            .unwrap()
+            .contains(shifted));
+
+        assert!(store.go_to_slide(second));
+        let Item::Stroke(s) = &store.items()[0] else {
+            panic!("expected stroke")
+        };
+        assert_eq!(
+            s.points[0],
+            Point {
+                x: -600.0,
+                y: -330.0
+            }
+        );
+        assert_eq!(
+            store.page_origin(),
+            Point {
+                x: -640.0,
+                y: -360.0
+            }
+        );
+        assert_eq!(store.background_image(), Some(&screenshot("two.png")));
+    }
+}
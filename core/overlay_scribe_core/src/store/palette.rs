@@ -0,0 +1,179 @@
+use super::Store;
+use crate::model::{ColorRgba8, Item, PaletteColor, ShapeStyle, Stroke};
+
+impl Store {
+    pub fn palette(&self) -> &[PaletteColor] {
+        &self.meta.palette
+    }
+
+    pub fn palette_color(&self, id: u32) -> Option<&PaletteColor> {
+        self.meta.palette.iter().find(|c| c.id == id)
+    }
+
+    /// Adds a named color to the document palette and returns its id.
+    pub fn add_palette_color(&mut self, name: &str, color: ColorRgba8) -> u32 {
+        let id = self
+            .meta
+            .palette
+            .iter()
+            .map(|c| c.id)
+            .max()
+            .map_or(1, |max| max.saturating_add(1));
+        let mut meta = self.meta.clone();
+        meta.palette.push(PaletteColor {
+            id,
+            name: name.to_string(),
+            color,
+        });
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit]);
+        id
+    }
+
+    pub fn rename_palette_color(&mut self, id: u32, name: &str) -> bool {
+        let mut meta = self.meta.clone();
+        let Some(entry) = meta.palette.iter_mut().find(|c| c.id == id) else {
+            return false;
+        };
+        entry.name = name.to_string();
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+
+    /// Changes a palette color and recolors every item referencing it, as one undo step.
+    pub fn set_palette_color(&mut self, id: u32, color: ColorRgba8) -> bool {
+        let mut meta = self.meta.clone();
+        let Some(entry) = meta.palette.iter_mut().find(|c| c.id == id) else {
+            return false;
+        };
+        entry.color = color;
+
+        let mut items = self.items.to_vec();
+        for item in &mut items {
+            match item {
+                Item::Stroke(s) => sync_stroke(s, &meta.palette),
+                Item::Shape(sh) => sync_style(&mut sh.style, &meta.palette),
+            }
+        }
+
+        let edits = [self.meta_edit(meta), self.items_edit(items)];
+        self.apply_all(edits)
+    }
+
+    /// Removes a palette entry. Referencing items keep their current color but
+    /// lose the reference. Applied as one undo step.
+    pub fn remove_palette_color(&mut self, id: u32) -> bool {
+        let mut meta = self.meta.clone();
+        let before = meta.palette.len();
+        meta.palette.retain(|c| c.id != id);
+        if meta.palette.len() == before {
+            return false;
+        }
+
+        let mut items = self.items.to_vec();
+        for item in &mut items {
+            match item {
+                Item::Stroke(s) => {
+                    if s.color_ref == Some(id) {
+                        s.color_ref = None;
+                    }
+                }
+                Item::Shape(sh) => {
+                    if sh.style.stroke_color_ref == Some(id) {
+                        sh.style.stroke_color_ref = None;
+                    }
+                    if sh.style.fill_color_ref == Some(id) {
+                        sh.style.fill_color_ref = None;
+                    }
+                }
+            }
+        }
+
+        let edits = [self.meta_edit(meta), self.items_edit(items)];
+        self.apply_all(edits)
+    }
+
+    pub(super) fn resolve_stroke_palette(&self, stroke: &mut Stroke) {
+        sync_stroke(stroke, &self.meta.palette);
+    }
+
+    pub(super) fn resolve_style_palette(&self, style: &mut ShapeStyle) {
+        sync_style(style, &self.meta.palette);
+    }
+}
+
+fn lookup(palette: &[PaletteColor], id: Option<u32>) -> Option<ColorRgba8> {
+    let id = id?;
+    palette.iter().find(|c| c.id == id).map(|c| c.color)
+}
+
+fn sync_stroke(stroke: &mut Stroke, palette: &[PaletteColor]) {
+    if let Some(c) = lookup(palette, stroke.color_ref) {
+        stroke.color = c;
+    }
+}
+
+fn sync_style(style: &mut ShapeStyle, palette: &[PaletteColor]) {
+    if let Some(c) = lookup(palette, style.stroke_color_ref) {
+        style.stroke_color = c;
+    }
+    if let Some(c) = lookup(palette, style.fill_color_ref) {
+        style.fill_color = c;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Point;
+
+    #[test]
+    fn editing_palette_color_recolors_references_in_one_undo_step() {
+        let mut store = Store::new();
+        let red = ColorRgba8 {
+            r: 255,
+            g: 0,
+            b: 0,
+            a: 255,
+        };
+        let blue = ColorRgba8 {
+            r: 0,
+            g: 0,
+            b: 255,
+            a: 255,
+        };
+        let id = store.add_palette_color("Review", red);
+
+        let mut s = store.begin_stroke(blue, 2.0, Point { x: 0.0, y: 0.0 });
+        s.color_ref = Some(id);
+        store.commit_stroke(s).unwrap();
+        let plain = store.begin_stroke(blue, 2.0, Point { x: 5.0, y: 5.0 });
+        store.commit_stroke(plain).unwrap();
+
+        // Committing resolves the reference.
+        let Item::Stroke(s) = &store.items()[0] else {
+            panic!("expected stroke")
+        };
+        assert_eq!(s.color, red);
+
+        assert!(store.set_palette_color(id, blue));
+        let Item::Stroke(s) = &store.items()[0] else {
+            panic!("expected stroke")
+        };
+        assert_eq!(s.color, blue);
+        assert_eq!(store.palette_color(id).unwrap().color, blue);
+
+        store.undo().unwrap();
+        let Item::Stroke(s) = &store.items()[0] else {
+            panic!("expected stroke")
+        };
+        assert_eq!(s.color, red);
+        assert_eq!(store.palette_color(id).unwrap().color, red);
+
+        store.redo().unwrap();
+        assert_eq!(store.palette_color(id).unwrap().color, blue);
+
+        let doc = Store::from_json(&store.to_json().unwrap()).unwrap();
+        assert_eq!(doc.meta.palette.len(), 1);
+    }
+}
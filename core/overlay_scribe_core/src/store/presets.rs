@@ -0,0 +1,53 @@
+use super::Store;
+use crate::model::{Item, ShapeStyle, StylePreset};
+
+impl Store {
+    pub fn presets(&self) -> &[StylePreset] {
+        &self.meta.presets
+    }
+
+    pub fn preset(&self, name: &str) -> Option<&StylePreset> {
+        self.meta.presets.iter().find(|p| p.name == name)
+    }
+
+    /// Saves `style` under `name`, replacing any preset with the same name.
+    pub fn save_preset(&mut self, name: &str, style: ShapeStyle) {
+        let mut meta = self.meta.clone();
+        match meta.presets.iter_mut().find(|p| p.name == name) {
+            Some(p) => p.style = style,
+            None => meta.presets.push(StylePreset {
+                name: name.to_string(),
+                style,
+            }),
+        }
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit]);
+    }
+
+    pub fn remove_preset(&mut self, name: &str) -> bool {
+        let mut meta = self.meta.clone();
+        meta.presets.retain(|p| p.name != name);
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+
+    /// Applies a preset to the given items as one undo step. Shapes take the
+    /// whole style; strokes take its stroke color and width.
+    pub fn apply_preset(&mut self, name: &str, ids: &[u64]) -> bool {
+        let Some(preset) = self.preset(name) else {
+            return false;
+        };
+        let mut style = preset.style;
+        self.resolve_style_palette(&mut style);
+
+        let edit = self.map_items_edit(ids, |item| match item {
+            Item::Stroke(s) => {
+                s.color = style.stroke_color;
+                s.color_ref = style.stroke_color_ref;
+                s.width = style.stroke_width;
+            }
+            Item::Shape(sh) => sh.style = style,
+        });
+        self.apply_all([edit])
+    }
+}
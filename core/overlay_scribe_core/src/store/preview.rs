@@ -0,0 +1,128 @@
+use super::query::tight_bounds;
+use super::Store;
+use crate::geometry::{is_boxed_shape, shape_outline, Rect};
+use crate::model::{Item, Point};
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+
+// Items sketched in a preview, and points kept per sketched item.
+const SKETCH_ITEMS: usize = 64;
+const SKETCH_POINTS: usize = 16;
+
+/// Summary embedded at the start of serialized documents, enough for a file
+/// browser to draw a thumbnail.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentPreview {
+    pub item_count: usize,
+    pub bounds: Option<Rect>,
+    // Coarse polylines (whole units) for the first few items, in z-order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sketch: Vec<Vec<Point>>,
+}
+
+impl Store {
+    pub fn preview(&self) -> DocumentPreview {
+        DocumentPreview {
+            item_count: self.items.len(),
+            bounds: tight_bounds(&self.items, self.items.iter()),
+            sketch: self
+                .items
+                .iter()
+                .take(SKETCH_ITEMS)
+                .map(sketch_item)
+                .filter(|line| !line.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Reads the preview from the head of a serialized document without
+    /// parsing its items. Returns `None` for documents written without one.
+    pub fn read_preview(bytes: &[u8]) -> Option<DocumentPreview> {
+        let mut rest = skip_ws(bytes).strip_prefix(b"{")?;
+        loop {
+            let (key, after) = next_value::<String>(skip_ws(rest))?;
+            let after = skip_ws(after).strip_prefix(b":")?;
+            match key.as_str() {
+                "preview" => return next_value(after).map(|(preview, _)| preview),
+                // The preview is written ahead of the items; don't scan them.
+                "items" | "strokes" => return None,
+                _ => {
+                    let (_, after) = next_value::<IgnoredAny>(after)?;
+                    rest = skip_ws(after).strip_prefix(b",")?;
+                }
+            }
+        }
+    }
+}
+
+fn sketch_item(item: &Item) -> Vec<Point> {
+    let points = match item {
+        Item::Stroke(s) => s.points.clone(),
+        Item::Shape(sh) if is_boxed_shape(sh.kind) => shape_outline(sh),
+        Item::Shape(sh) => vec![sh.start, sh.end],
+    };
+    let stride = points.len().div_ceil(SKETCH_POINTS).max(1);
+    let mut kept: Vec<Point> = points.iter().step_by(stride).copied().collect();
+    if let (Some(last), Some(kept_last)) = (points.last(), kept.last()) {
+        if kept_last != last {
+            kept.push(*last);
+        }
+    }
+    kept.iter()
+        .map(|p| Point {
+            x: p.x.round(),
+            y: p.y.round(),
+        })
+        .collect()
+}
+
+fn skip_ws(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+// Parses one JSON value off the front of `bytes`, returning it and the rest.
+fn next_value<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Option<(T, &[u8])> {
+    let mut stream = serde_json::Deserializer::from_slice(bytes).into_iter::<T>();
+    let value = stream.next()?.ok()?;
+    Some((value, &bytes[stream.byte_offset()..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::black;
+
+    #[test]
+    fn preview_reads_from_head_of_document() {
+        let mut store = Store::new();
+        let c = black();
+        let mut s = store.begin_stroke(c, 2.0, Point { x: 0.0, y: 0.0 });
+        s.points.extend((1..=100).map(|i| Point {
+            x: i as f32,
+            y: 0.4,
+        }));
+        store.commit_stroke(s).unwrap();
+
+        let json = store.to_json().unwrap();
+        assert!(json.find("\"preview\"").unwrap() < json.find("\"items\"").unwrap());
+        let preview = Store::read_preview(json.as_bytes()).unwrap();
+        assert_eq!(preview.item_count, 1);
+        assert_eq!(
+            preview.bounds,
+            store.selection_bounds(&[store.items()[0].id()], true)
+        );
+        assert!(preview.sketch[0].len() <= SKETCH_POINTS + 1);
+        assert_eq!(preview.sketch[0].last(), Some(&Point { x: 100.0, y: 0.0 }));
+
+        // The items after the preview are never parsed.
+        let truncated = &json[..json.find("\"items\"").unwrap() + 10];
+        assert_eq!(Store::read_preview(truncated.as_bytes()), Some(preview));
+        let legacy = r#"{"version":2,"items":[]}"#;
+        assert_eq!(Store::read_preview(legacy.as_bytes()), None);
+        assert!(Store::from_json(&json).is_ok());
+    }
+}
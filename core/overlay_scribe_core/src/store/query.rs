@@ -0,0 +1,289 @@
+use super::Store;
+use crate::geometry::{
+    closed_shape_hit, closest_outline_point, item_bounds, rect_for_shape, rotated_rect_bounds,
+    union_bounds, ClosedShapeHit, Rect,
+};
+use crate::model::{Item, LineCap, LineJoin, LineStyle, Point};
+use crate::outline::MITER_LIMIT;
+use crate::render::{
+    anchor_point_uv, closest_boundary_point, closest_point_on_arrow, is_arrow_like, label_size,
+    render_arrows, ArrowRender,
+};
+use crate::units::document_radius;
+
+/// A closed shape a dragged arrow endpoint would attach to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AttachCandidate {
+    pub id: u64,
+    // Shape-local UV to store in `start_attach_uv`/`end_attach_uv`.
+    pub uv: Point,
+    // Where `render_arrows` will resolve the endpoint for this UV.
+    pub anchor: Point,
+}
+
+impl Store {
+    pub fn item(&self, id: u64) -> Option<&Item> {
+        self.items.iter().find(|it| it.id() == id)
+    }
+
+    pub fn contains(&self, id: u64) -> bool {
+        self.item(id).is_some()
+    }
+
+    /// Bounds of the items in `ids`, or `None` if none of them exist.
+    ///
+    /// Loose bounds cover the geometric points only. Tight bounds cover what is
+    /// actually drawn: half the stroke width on every side, routed arrow
+    /// curves including their extrema, and arrowheads. Used for export
+    /// cropping and zoom-to-selection.
+    pub fn selection_bounds(&self, ids: &[u64], tight: bool) -> Option<Rect> {
+        let selected = self.items.iter().filter(|it| ids.contains(&it.id()));
+        if !tight {
+            return union_bounds(selected);
+        }
+        tight_bounds(&self.items, selected)
+    }
+
+    /// Tight bounds of everything drawn (see [`Store::selection_bounds`])
+    /// and any halo casing, grown by `padding` on every side, for cropping
+    /// exports to the content instead of the whole canvas. `None` for an
+    /// empty document.
+    pub fn export_bounds(&self, padding: f32) -> Option<Rect> {
+        let halo = self.meta.halo.map_or(0.0, |h| h.width.max(0.0));
+        let padding = padding.max(0.0) + halo;
+        tight_bounds(&self.items, self.items.iter()).map(|r| r.inflate(padding, padding))
+    }
+
+    /// Ids of the items a marquee over `rect` selects, in z order: those
+    /// touching it, or with `contained` only those lying wholly inside.
+    pub fn select_in_rect(&self, rect: Rect, contained: bool) -> Vec<u64> {
+        self.items_in_region(rect)
+            .into_iter()
+            .filter(|it| {
+                !contained
+                    || item_bounds(it).is_some_and(|b| {
+                        b.min_x >= rect.min_x
+                            && b.min_y >= rect.min_y
+                            && b.max_x <= rect.max_x
+                            && b.max_y <= rect.max_y
+                    })
+            })
+            .map(Item::id)
+            .collect()
+    }
+
+    /// Closest point on the outline of item `id` (shapes only), using the
+    /// routed path for arrows so snapping matches what is drawn.
+    pub fn closest_boundary_point(&self, id: u64, p: Point) -> Option<(Point, f32)> {
+        let shape = self.items.iter().find_map(|it| match it {
+            Item::Shape(sh) if sh.id == id => Some(sh),
+            _ => None,
+        })?;
+        if is_arrow_like(shape.kind) {
+            if let Some(render) = render_arrows(&self.items)
+                .into_iter()
+                .find(|r| r.shape_id == id)
+            {
+                return Some(closest_point_on_arrow(&render, p));
+            }
+        }
+        Some(closest_boundary_point(shape, p))
+    }
+
+    /// Finds the closed shape a dragged arrow endpoint at `point` would attach
+    /// to: the topmost shape containing it, else the one whose outline is
+    /// nearest within `radius`.
+    ///
+    /// The returned UV and anchor are exactly what `render_arrows` resolves,
+    /// so a shell preview matches the committed arrow.
+    pub fn attach_candidate(&self, point: Point, radius: f32) -> Option<AttachCandidate> {
+        let mut best: Option<(ClosedShapeHit, f32)> = None;
+        for item in self.items.iter().rev() {
+            let Item::Shape(sh) = item else { continue };
+            let Some(hit) = closed_shape_hit(sh) else {
+                continue;
+            };
+            if hit.contains(point) {
+                best = Some((hit, 0.0));
+                break;
+            }
+            let Some((_, d)) = closest_outline_point(sh, point) else {
+                continue;
+            };
+            if d <= radius && best.is_none_or(|(_, bd)| d < bd) {
+                best = Some((hit, d));
+            }
+        }
+
+        let (hit, _) = best?;
+        let local = hit.to_local(point);
+        let r = hit.rect;
+        let uv = Point {
+            x: ((local.x - r.min_x) / r.width().max(1e-6)).clamp(0.0, 1.0),
+            y: ((local.y - r.min_y) / r.height().max(1e-6)).clamp(0.0, 1.0),
+        };
+        Some(AttachCandidate {
+            id: hit.id,
+            uv,
+            anchor: anchor_point_uv(&hit, uv),
+        })
+    }
+
+    /// Like [`Store::attach_candidate`] with `radius` in screen pixels at
+    /// zoom `view_scale`; see [`document_radius`].
+    pub fn attach_candidate_scaled(
+        &self,
+        point: Point,
+        radius: f32,
+        view_scale: f32,
+    ) -> Option<AttachCandidate> {
+        self.attach_candidate(point, document_radius(radius, Some(view_scale)))
+    }
+}
+
+/// Drawn bounds of `selected` (see [`Store::selection_bounds`]); `items` is the
+/// whole document, needed to route arrows.
+pub(crate) fn tight_bounds<'a>(
+    items: &[Item],
+    selected: impl IntoIterator<Item = &'a Item>,
+) -> Option<Rect> {
+    let arrows = render_arrows(items);
+    selected
+        .into_iter()
+        .filter_map(|item| drawn_bounds(item, &arrows))
+        .reduce(|a, b| a.union(b))
+}
+
+/// Drawn bounds of one item, given the document's rendered arrows.
+pub(crate) fn drawn_bounds(item: &Item, arrows: &[ArrowRender]) -> Option<Rect> {
+    match item {
+        Item::Stroke(s) => {
+            let half = ink_reach(s.width, &s.line);
+            item_bounds(item).map(|r| r.inflate(half, half))
+        }
+        Item::Shape(sh) if is_arrow_like(sh.kind) => {
+            let half = ink_reach(sh.style.stroke_width, &sh.style.line);
+            match arrows.iter().find(|r| r.shape_id == sh.id) {
+                Some(r) => {
+                    let mut bounds = r
+                        .path
+                        .bounds(r.start, r.end)
+                        .union(Rect::from_points(r.head_left, r.head_right));
+                    if let Some(tail) = r.tail {
+                        bounds = bounds.union(tail.bounds());
+                    }
+                    bounds = bounds.inflate(half, half);
+                    if let Some(c) = r.label {
+                        let (w, h) = label_size(sh.text.trim());
+                        let label = Rect::from_points(c, c).inflate(w * 0.5, h * 0.5);
+                        bounds = bounds.union(label);
+                    }
+                    Some(bounds)
+                }
+                // Too short to render; fall back to the raw segment.
+                None => item_bounds(item).map(|r| r.inflate(half, half)),
+            }
+        }
+        Item::Shape(sh) => {
+            let half = sh.style.stroke_width * 0.5;
+            Some(rotated_rect_bounds(
+                rect_for_shape(sh).inflate(half, half),
+                sh.rotation,
+            ))
+        }
+    }
+}
+
+// How far ink can reach past a line's points: half the width, further at
+// the corners of square caps and at the tips of miter joins.
+fn ink_reach(width: f32, line: &LineStyle) -> f32 {
+    let half = width * 0.5;
+    if line.join == LineJoin::Miter {
+        half * MITER_LIMIT
+    } else if line.cap == LineCap::Square {
+        half * std::f32::consts::SQRT_2
+    } else {
+        half
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ShapeKind;
+    use crate::test_util::{black, style};
+
+    #[test]
+    fn item_lookup_by_id() {
+        let mut store = Store::new();
+        let c = black();
+        let s = store.begin_stroke(c, 2.0, Point { x: 0.0, y: 0.0 });
+        let id = s.id;
+        store.commit_stroke(s).unwrap();
+        assert!(store.contains(id));
+        assert!(matches!(store.item(id), Some(Item::Stroke(s)) if s.id == id));
+        assert!(!store.contains(id + 1));
+    }
+
+    #[test]
+    fn attach_candidate_matches_rendered_endpoint() {
+        let mut store = Store::new();
+        let style = style();
+        let mut rect = store.begin_shape(ShapeKind::Rectangle, style, Point { x: 0.0, y: 0.0 });
+        rect.end = Point { x: 100.0, y: 100.0 };
+        store.commit_shape(rect).unwrap();
+
+        assert!(store
+            .attach_candidate(Point { x: 300.0, y: 300.0 }, 10.0)
+            .is_none());
+        let near = store
+            .attach_candidate(Point { x: 105.0, y: 50.0 }, 10.0)
+            .unwrap();
+        assert_eq!(near.uv, Point { x: 1.0, y: 0.5 });
+        // 2 screen pixels reach the outline 5 units away only when zoomed out.
+        let far = Point { x: 105.0, y: 50.0 };
+        assert!(store.attach_candidate_scaled(far, 2.0, 1.0).is_none());
+        assert_eq!(store.attach_candidate_scaled(far, 2.0, 0.25), Some(near));
+
+        let cand = store
+            .attach_candidate(Point { x: 80.0, y: 40.0 }, 10.0)
+            .unwrap();
+        let mut arrow = store.begin_shape(ShapeKind::Arrow, style, Point { x: 80.0, y: 40.0 });
+        arrow.end = Point { x: 300.0, y: 40.0 };
+        arrow.start_attach_id = Some(cand.id);
+        arrow.start_attach_uv = Some(cand.uv);
+        store.commit_shape(arrow).unwrap();
+
+        let render = render_arrows(store.items())[0];
+        assert_eq!(render.start, cand.anchor);
+    }
+
+    #[test]
+    fn tight_selection_bounds_include_stroke_width_and_arrowhead() {
+        let mut store = Store::new();
+        let c = black();
+        let mut s = store.begin_stroke(c, 4.0, Point { x: 0.0, y: 0.0 });
+        s.points.push(Point { x: 10.0, y: 10.0 });
+        let stroke_id = s.id;
+        store.commit_stroke(s).unwrap();
+        let style = style();
+        let mut arrow = store.begin_shape(ShapeKind::Arrow, style, Point { x: 0.0, y: 50.0 });
+        arrow.end = Point { x: 100.0, y: 50.0 };
+        let arrow_id = arrow.id;
+        store.commit_shape(arrow).unwrap();
+
+        let ids = [stroke_id, arrow_id];
+        let loose = store.selection_bounds(&ids, false).unwrap();
+        assert_eq!((loose.min_x, loose.max_y), (0.0, 50.0));
+        let tight = store.selection_bounds(&ids, true).unwrap();
+        assert_eq!((tight.min_x, tight.min_y), (-2.0, -2.0));
+        // Arrowhead half-width (4) plus half the stroke width.
+        assert!((tight.max_y - 55.0).abs() < 1e-4);
+        assert!(store.selection_bounds(&[999], true).is_none());
+
+        let crop = store.export_bounds(5.0).unwrap();
+        assert_eq!((crop.min_x, crop.min_y), (-7.0, -7.0));
+        assert!((crop.max_y - 60.0).abs() < 1e-4);
+        assert!(Store::new().export_bounds(5.0).is_none());
+    }
+}
@@ -0,0 +1,104 @@
+use super::Store;
+use crate::model::{ColorRgba8, RecentStyles, ShapeStyle};
+
+impl Store {
+    /// Stroke and fill colors of the items committed most recently, newest
+    /// first and without repeats (at most [`RecentStyles::LIMIT`]).
+    pub fn recent_colors(&self) -> &[ColorRgba8] {
+        &self.recent.colors
+    }
+
+    /// Styles of the shapes committed most recently, newest first and
+    /// without repeats (at most [`RecentStyles::LIMIT`]).
+    pub fn recent_styles(&self) -> &[ShapeStyle] {
+        &self.recent.styles
+    }
+
+    pub fn clear_recent(&mut self) {
+        self.recent = RecentStyles::default();
+    }
+
+    /// Whether the recent colors and styles are saved with the document.
+    /// Off by default; loading a document that carries them turns it on.
+    pub fn keeps_recent(&self) -> bool {
+        self.keep_recent
+    }
+
+    pub fn set_keep_recent(&mut self, keep: bool) {
+        self.keep_recent = keep;
+    }
+
+    pub(super) fn note_recent_color(&mut self, color: ColorRgba8) {
+        note(&mut self.recent.colors, color);
+    }
+
+    pub(super) fn note_recent_style(&mut self, style: ShapeStyle) {
+        if style.fill_enabled {
+            self.note_recent_color(style.fill_color);
+        }
+        self.note_recent_color(style.stroke_color);
+        note(&mut self.recent.styles, style);
+    }
+}
+
+// Moves `value` to the front of `list`, dropping the oldest entry past the
+// limit.
+fn note<T: PartialEq>(list: &mut Vec<T>, value: T) {
+    list.retain(|v| *v != value);
+    list.insert(0, value);
+    list.truncate(RecentStyles::LIMIT);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Point, ShapeKind};
+    use crate::test_util::style;
+
+    #[test]
+    fn recent_colors_follow_commits_and_optionally_persist() {
+        let mut store = Store::new();
+        let gray = |v: u8| ColorRgba8 {
+            r: v,
+            g: v,
+            b: v,
+            a: 255,
+        };
+        for v in 0..20 {
+            let s = store.begin_stroke(gray(v), 2.0, Point { x: 0.0, y: 0.0 });
+            store.commit_stroke(s).unwrap();
+        }
+        let s = store.begin_stroke(gray(15), 2.0, Point { x: 0.0, y: 0.0 });
+        store.commit_stroke(s).unwrap();
+        let colors = store.recent_colors();
+        assert_eq!(colors.len(), RecentStyles::LIMIT);
+        assert_eq!((colors[0], colors[1]), (gray(15), gray(19)));
+        assert_eq!(colors.iter().filter(|c| **c == gray(15)).count(), 1);
+
+        let style = ShapeStyle {
+            stroke_color: gray(200),
+            stroke_width: 3.0,
+            fill_enabled: true,
+            fill_color: gray(100),
+            ..style()
+        };
+        let shape = store.begin_shape(ShapeKind::Rectangle, style, Point { x: 0.0, y: 0.0 });
+        store.commit_shape(shape).unwrap();
+        assert_eq!(store.recent_styles(), &[style]);
+        assert_eq!(&store.recent_colors()[..2], &[gray(200), gray(100)]);
+
+        // Undo leaves the history alone, and it is only saved when asked.
+        store.undo().unwrap();
+        assert_eq!(store.recent_styles().len(), 1);
+        assert!(!store.to_json().unwrap().contains("recent"));
+        store.set_keep_recent(true);
+        let mut reloaded = Store::new();
+        reloaded.load_document(Store::from_json(&store.to_json().unwrap()).unwrap());
+        assert!(reloaded.keeps_recent());
+        assert_eq!(reloaded.recent_colors(), store.recent_colors());
+        assert_eq!(reloaded.document().meta.recent.unwrap().styles, vec![style]);
+
+        reloaded.clear_recent();
+        assert!(reloaded.recent_colors().is_empty());
+    }
+}
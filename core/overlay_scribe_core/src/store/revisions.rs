@@ -0,0 +1,125 @@
+use super::Store;
+use crate::model::{Item, Revision};
+use std::collections::{HashMap, HashSet};
+
+impl Store {
+    /// Snapshots the current items under `name`, storing only what changed
+    /// since the last revision. Fails if the name is empty or taken.
+    pub fn save_version(&mut self, name: &str) -> bool {
+        let name = name.trim();
+        if name.is_empty() || self.list_versions().contains(&name) {
+            return false;
+        }
+        let previous = self
+            .meta
+            .revisions
+            .last()
+            .and_then(|r| self.version_items(&r.name))
+            .unwrap_or_default();
+        let by_id: HashMap<u64, &Item> = previous.iter().map(|i| (i.id(), i)).collect();
+        let changed = self
+            .items
+            .iter()
+            .filter(|item| by_id.get(&item.id()) != Some(item))
+            .cloned()
+            .collect();
+        let order: Vec<u64> = self.items.iter().map(Item::id).collect();
+        let current: HashSet<u64> = order.iter().copied().collect();
+        let removed = previous
+            .iter()
+            .map(Item::id)
+            .filter(|id| !current.contains(id))
+            .collect();
+
+        let mut meta = self.meta.clone();
+        meta.revisions.push(Revision {
+            name: name.to_string(),
+            changed,
+            removed,
+            order,
+        });
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+
+    /// Revision names, oldest first.
+    pub fn list_versions(&self) -> Vec<&str> {
+        self.meta
+            .revisions
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect()
+    }
+
+    /// Restores the items saved under `name` as one undoable edit. The
+    /// revision list itself is left untouched.
+    pub fn checkout_version(&mut self, name: &str) -> bool {
+        let Some(items) = self.version_items(name) else {
+            return false;
+        };
+        let edit = self.items_edit(items);
+        self.apply_all([edit])
+    }
+
+    /// Rebuilds the items of revision `name` by replaying deltas.
+    pub fn version_items(&self, name: &str) -> Option<Vec<Item>> {
+        let end = self.meta.revisions.iter().position(|r| r.name == name)?;
+        let mut by_id: HashMap<u64, Item> = HashMap::new();
+        for revision in &self.meta.revisions[..=end] {
+            for id in &revision.removed {
+                by_id.remove(id);
+            }
+            for item in &revision.changed {
+                by_id.insert(item.id(), item.clone());
+            }
+        }
+        let order = &self.meta.revisions[end].order;
+        Some(order.iter().filter_map(|id| by_id.remove(id)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Point;
+    use crate::test_util::black;
+
+    #[test]
+    fn versions_store_deltas_and_checkout_is_undoable() {
+        let mut store = Store::new();
+        let c = black();
+        let mut ids = Vec::new();
+        for i in 0..3 {
+            let s = store.begin_stroke(
+                c,
+                2.0,
+                Point {
+                    x: i as f32,
+                    y: 0.0,
+                },
+            );
+            ids.push(s.id);
+            store.commit_stroke(s).unwrap();
+        }
+        assert!(store.save_version("before review"));
+        assert!(!store.save_version("before review"));
+
+        store.nudge(&ids[..1], 5.0, 0.0, 0);
+        store.erase_at(Point { x: 2.0, y: 0.0 }, 0.5);
+        assert!(store.save_version("after review"));
+        assert_eq!(store.list_versions(), ["before review", "after review"]);
+
+        let after = &store.meta().revisions[1];
+        assert_eq!(after.changed.len(), 1);
+        assert_eq!(after.removed, [ids[2]]);
+
+        let current = store.items().to_vec();
+        assert!(store.checkout_version("before review"));
+        assert_eq!(store.items().len(), 3);
+        assert_eq!(store.items()[0].id(), ids[0]);
+        store.undo().unwrap();
+        assert_eq!(store.items(), current.as_slice());
+
+        assert!(!store.checkout_version("missing"));
+    }
+}
@@ -0,0 +1,142 @@
+use super::{Store, StoreError};
+use crate::model::{CornerRadii, Shape, Stroke};
+use crate::render::is_arrow_like;
+use crate::validate::{shape_is_finite, stroke_is_finite};
+
+/// Widest stroke or outline a commit keeps; wider ones are clamped to it.
+pub const MAX_STROKE_WIDTH: f32 = 500.0;
+// Free arrows shorter than this have nothing to route or draw.
+const MIN_ARROW_LENGTH: f32 = 0.5;
+
+/// Why a commit refused its input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidInput {
+    // A coordinate, width, radius or angle is NaN or infinite.
+    NonFinite,
+    EmptyStroke,
+    // The stroke or outline width is zero or negative.
+    NonPositiveWidth,
+    // A new arrow attached at neither end whose ends (nearly) coincide.
+    DegenerateShape,
+}
+
+impl Store {
+    // Refuses a stroke corrupt input would make unusable and clamps an
+    // absurd width, so bad touch data never reaches geometry or routing.
+    pub(super) fn sanitize_stroke(stroke: &mut Stroke) -> Result<(), StoreError> {
+        if stroke.points.is_empty() {
+            return invalid(InvalidInput::EmptyStroke);
+        }
+        if !stroke_is_finite(stroke) {
+            return invalid(InvalidInput::NonFinite);
+        }
+        stroke.width = clamp_width(stroke.width)?;
+        Ok(())
+    }
+
+    // Like `sanitize_stroke`; an `is_new` free arrow must also have some
+    // length. Edits may keep a degenerate arrow loaded from an old file, and
+    // empty closed shapes are left alone: they still draw and can be resized.
+    pub(super) fn sanitize_shape(shape: &mut Shape, is_new: bool) -> Result<(), StoreError> {
+        if !shape_is_finite(shape) {
+            return invalid(InvalidInput::NonFinite);
+        }
+        let length = (shape.end.x - shape.start.x).hypot(shape.end.y - shape.start.y);
+        let free = shape.start_attach_id.is_none() && shape.end_attach_id.is_none();
+        if is_new && is_arrow_like(shape.kind) && free && length < MIN_ARROW_LENGTH {
+            return invalid(InvalidInput::DegenerateShape);
+        }
+        shape.style.stroke_width = clamp_width(shape.style.stroke_width)?;
+        shape.style.corner_radius = shape.style.corner_radius.max(0.0);
+        if let Some(radii) = &mut shape.style.corner_radii {
+            *radii = CornerRadii {
+                top_left: radii.top_left.max(0.0),
+                top_right: radii.top_right.max(0.0),
+                bottom_right: radii.bottom_right.max(0.0),
+                bottom_left: radii.bottom_left.max(0.0),
+            };
+        }
+        Ok(())
+    }
+}
+
+fn invalid(reason: InvalidInput) -> Result<(), StoreError> {
+    Err(StoreError::InvalidInput(reason))
+}
+
+fn clamp_width(width: f32) -> Result<f32, StoreError> {
+    if width <= 0.0 {
+        return Err(StoreError::InvalidInput(InvalidInput::NonPositiveWidth));
+    }
+    Ok(width.min(MAX_STROKE_WIDTH))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Item, Point, ShapeKind};
+    use crate::test_util::{black, style};
+
+    #[test]
+    fn commits_refuse_corrupt_input_and_clamp_widths() {
+        let mut store = Store::new();
+        let c = black();
+        let refused = |result: Result<(), StoreError>| match result {
+            Err(StoreError::InvalidInput(reason)) => reason,
+            other => panic!("expected invalid input, got {other:?}"),
+        };
+
+        let mut s = store.begin_stroke(c, 2.0, Point { x: 0.0, y: 0.0 });
+        s.points.push(Point {
+            x: f32::NAN,
+            y: 1.0,
+        });
+        assert_eq!(refused(store.commit_stroke(s)), InvalidInput::NonFinite);
+        let s = store.begin_stroke(c, 0.0, Point { x: 0.0, y: 0.0 });
+        assert_eq!(
+            refused(store.commit_stroke(s)),
+            InvalidInput::NonPositiveWidth
+        );
+        let mut s = store.begin_stroke(c, 1e9, Point { x: 0.0, y: 0.0 });
+        s.points.clear();
+        assert_eq!(refused(store.commit_stroke(s)), InvalidInput::EmptyStroke);
+        let s = store.begin_stroke(c, 1e9, Point { x: 0.0, y: 0.0 });
+        store.commit_stroke(s).unwrap();
+        assert!(matches!(&store.items()[0], Item::Stroke(s) if s.width == MAX_STROKE_WIDTH));
+
+        let style = style();
+        let arrow = store.begin_shape(ShapeKind::Arrow, style, Point { x: 5.0, y: 5.0 });
+        assert_eq!(
+            refused(store.commit_shape(arrow)),
+            InvalidInput::DegenerateShape
+        );
+        let mut rect = store.begin_shape(ShapeKind::Rectangle, style, Point { x: 0.0, y: 0.0 });
+        rect.end = Point {
+            x: f32::INFINITY,
+            y: 0.0,
+        };
+        assert_eq!(refused(store.commit_shape(rect)), InvalidInput::NonFinite);
+        assert_eq!(store.items().len(), 1);
+
+        let wide = store.begin_stroke(c, 1e9, Point { x: 0.0, y: 0.0 });
+        let mut corrupt = wide.clone();
+        corrupt.points.push(Point {
+            x: f32::NAN,
+            y: 0.0,
+        });
+        let batch = vec![Item::Stroke(wide.clone()), Item::Stroke(corrupt)];
+        assert!(store.insert_items(batch).is_empty());
+        assert_eq!(store.items().len(), 1);
+        assert_eq!(store.insert_items(vec![Item::Stroke(wide)]).len(), 1);
+        assert!(matches!(&store.items()[1], Item::Stroke(s) if s.width == MAX_STROKE_WIDTH));
+
+        let live = store.begin_live_stroke(c, 2.0, Point { x: 0.0, y: 0.0 });
+        assert!(!store.append_stroke_point(
+            live,
+            Point {
+                x: 0.0,
+                y: f32::NAN
+            }
+        ));
+    }
+}
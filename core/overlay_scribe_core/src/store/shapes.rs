@@ -0,0 +1,351 @@
+use super::Store;
+use crate::geometry::is_closed_shape;
+use crate::model::{ArcAngles, ArrowRoute, ArrowTail, Item, Point, ShapeKind};
+use crate::render::{is_arrow_like, render_arrows, ArrowPath};
+
+/// One end of an arrow-like shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrowEnd {
+    Start,
+    // The end with the head.
+    End,
+}
+
+impl Store {
+    /// Changes the kind of shape `id` within its family (arrows to arrows,
+    /// closed shapes to closed shapes), keeping geometry, attachments, text
+    /// and style, as one undo step. Converting across families would strand
+    /// the arrows attached to a closed shape, so it is refused.
+    pub fn convert_shape_kind(&mut self, id: u64, new_kind: ShapeKind) -> bool {
+        let Some(Item::Shape(sh)) = self.items.iter().find(|it| it.id() == id) else {
+            return false;
+        };
+        let same_family = (is_arrow_like(sh.kind) && is_arrow_like(new_kind))
+            || (is_closed_shape(sh.kind) && is_closed_shape(new_kind));
+        if !same_family {
+            return false;
+        }
+        let edit = self.map_items_edit(&[id], |item| {
+            if let Item::Shape(sh) = item {
+                sh.kind = new_kind;
+            }
+        });
+        self.apply_all([edit])
+    }
+
+    /// Sets (or with `None` removes) the tail decoration of the arrows among
+    /// `ids`, as one undo step.
+    pub fn set_arrow_tail(&mut self, ids: &[u64], tail: Option<ArrowTail>) -> bool {
+        let edit = self.map_items_edit(ids, |item| match item {
+            Item::Shape(sh) if is_arrow_like(sh.kind) => sh.tail = tail,
+            _ => {}
+        });
+        self.apply_all([edit])
+    }
+
+    /// Sets (or with `None` resets to the whole ellipse) the span of the arcs
+    /// and pies among `ids`, as one undo step. Non-finite angles are refused.
+    pub fn set_arc_angles(&mut self, ids: &[u64], angles: Option<ArcAngles>) -> bool {
+        if angles.is_some_and(|a| !(a.start.is_finite() && a.end.is_finite())) {
+            return false;
+        }
+        let edit = self.map_items_edit(ids, |item| match item {
+            Item::Shape(sh) if matches!(sh.kind, ShapeKind::Arc | ShapeKind::Pie) => {
+                sh.angles = angles
+            }
+            _ => {}
+        });
+        self.apply_all([edit])
+    }
+
+    /// Sets (or with `None` resets to the default) the bow of the S-curves
+    /// and curved arrows among `ids`, as one undo step; see
+    /// [`ShapeStyle::curvature`]. A non-finite curvature is refused.
+    ///
+    /// [`ShapeStyle::curvature`]: crate::model::ShapeStyle::curvature
+    pub fn set_curvature(&mut self, ids: &[u64], curvature: Option<f32>) -> bool {
+        if curvature.is_some_and(|c| !c.is_finite()) {
+            return false;
+        }
+        let edit = self.map_items_edit(ids, |item| match item {
+            Item::Shape(sh) if matches!(sh.kind, ShapeKind::SCurve | ShapeKind::CurvedArrow) => {
+                sh.style.curvature = curvature
+            }
+            _ => {}
+        });
+        self.apply_all([edit])
+    }
+
+    /// Sets (or with `None` removes) the notch cut into the back of the
+    /// arrowheads among `ids`, as one undo step; see
+    /// [`ShapeStyle::head_notch`]. A non-finite depth is refused.
+    ///
+    /// [`ShapeStyle::head_notch`]: crate::model::ShapeStyle::head_notch
+    pub fn set_head_notch(&mut self, ids: &[u64], notch: Option<f32>) -> bool {
+        if notch.is_some_and(|n| !n.is_finite()) {
+            return false;
+        }
+        let edit = self.map_items_edit(ids, |item| match item {
+            Item::Shape(sh) if is_arrow_like(sh.kind) => sh.style.head_notch = notch,
+            _ => {}
+        });
+        self.apply_all([edit])
+    }
+
+    /// Turns obstacle avoidance off (`ignore` true) or back on for the
+    /// arrows among `ids`, as one undo step. Arrows that ignore obstacles
+    /// always take their default path.
+    pub fn set_ignore_obstacles(&mut self, ids: &[u64], ignore: bool) -> bool {
+        let edit = self.map_items_edit(ids, |item| match item {
+            Item::Shape(sh) if is_arrow_like(sh.kind) => sh.ignore_obstacles = ignore,
+            _ => {}
+        });
+        self.apply_all([edit])
+    }
+
+    /// Routes every curved arrow again and keeps each one's detour on the
+    /// arrow (see `Shape::route`), as one undo step. With
+    /// `prefer_minimal_change` an arrow whose kept detour is still clear
+    /// holds on to it rather than jumping to a fresh route; without it every
+    /// arrow is routed from scratch. Returns whether any route changed.
+    pub fn reroute_arrows(&mut self, prefer_minimal_change: bool) -> bool {
+        let mut items = self.items.to_vec();
+        if !prefer_minimal_change {
+            for item in &mut items {
+                if let Item::Shape(sh) = item {
+                    sh.route = None;
+                }
+            }
+        }
+        let routes = render_arrows(&items);
+        for item in &mut items {
+            let Item::Shape(sh) = item else { continue };
+            if sh.kind != ShapeKind::CurvedArrow {
+                continue;
+            }
+            let routed = routes.iter().find(|r| r.shape_id == sh.id);
+            sh.route = routed.and_then(|r| match r.path {
+                ArrowPath::Cubic { c1, c2 } => ArrowRoute::from_controls(r.start, r.end, c1, c2),
+                _ => None,
+            });
+        }
+        if items == *self.items {
+            return false;
+        }
+        let edit = self.items_edit(items);
+        self.apply_all([edit])
+    }
+
+    /// Swaps the ends of arrow `id`, including attachment ids and UVs, so the
+    /// head points the other way. One undo step.
+    pub fn reverse_arrow(&mut self, id: u64) -> bool {
+        if !matches!(self.item(id), Some(Item::Shape(sh)) if is_arrow_like(sh.kind)) {
+            return false;
+        }
+        let edit = self.map_items_edit(&[id], |item| {
+            if let Item::Shape(sh) = item {
+                std::mem::swap(&mut sh.start, &mut sh.end);
+                std::mem::swap(&mut sh.start_attach_id, &mut sh.end_attach_id);
+                std::mem::swap(&mut sh.start_attach_uv, &mut sh.end_attach_uv);
+                // Keep an explicit bow on the same side of the arrow.
+                if sh.kind == ShapeKind::CurvedArrow {
+                    sh.style.curvature = sh.style.curvature.map(|c| -c);
+                    sh.route = sh.route.map(ArrowRoute::reversed);
+                }
+            }
+        });
+        self.apply_all([edit])
+    }
+
+    /// Moves one end of arrow `id` to `point` as one undo step. With
+    /// `attach` of `(shape id, uv)` the end attaches there (UV clamped to the
+    /// unit square, see `Shape::start_attach_uv`); with `None` it detaches.
+    /// Refused when `id` isn't an arrow or the target isn't another closed
+    /// shape in the document.
+    pub fn move_arrow_endpoint(
+        &mut self,
+        id: u64,
+        end: ArrowEnd,
+        point: Point,
+        attach: Option<(u64, Point)>,
+    ) -> bool {
+        if !matches!(self.item(id), Some(Item::Shape(sh)) if is_arrow_like(sh.kind)) {
+            return false;
+        }
+        if !(point.x.is_finite() && point.y.is_finite()) {
+            return false;
+        }
+        let attach = match attach {
+            None => None,
+            Some((target, uv)) => {
+                let closed = matches!(
+                    self.item(target),
+                    Some(Item::Shape(sh)) if is_closed_shape(sh.kind)
+                );
+                if !(closed && uv.x.is_finite() && uv.y.is_finite()) {
+                    return false;
+                }
+                let uv = Point {
+                    x: uv.x.clamp(0.0, 1.0),
+                    y: uv.y.clamp(0.0, 1.0),
+                };
+                Some((target, uv))
+            }
+        };
+        let edit = self.map_items_edit(&[id], |item| {
+            let Item::Shape(sh) = item else { return };
+            let (at, attach_id, attach_uv) = match end {
+                ArrowEnd::Start => (
+                    &mut sh.start,
+                    &mut sh.start_attach_id,
+                    &mut sh.start_attach_uv,
+                ),
+                ArrowEnd::End => (&mut sh.end, &mut sh.end_attach_id, &mut sh.end_attach_uv),
+            };
+            *at = point;
+            *attach_id = attach.map(|(target, _)| target);
+            *attach_uv = attach.map(|(_, uv)| uv);
+        });
+        self.apply_all([edit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Point;
+    use crate::test_util::style;
+
+    #[test]
+    fn converts_and_reverses_keeping_attachments() {
+        let mut store = Store::new();
+        let style = style();
+        let mut rect = store.begin_shape(ShapeKind::Rectangle, style, Point { x: 0.0, y: 0.0 });
+        rect.end = Point { x: 40.0, y: 20.0 };
+        rect.text = "box".to_string();
+        let rect_id = rect.id;
+        store.commit_shape(rect).unwrap();
+        let mut arrow = store.begin_shape(ShapeKind::Arrow, style, Point { x: 100.0, y: 0.0 });
+        arrow.end = Point { x: 40.0, y: 10.0 };
+        arrow.end_attach_id = Some(rect_id);
+        arrow.end_attach_uv = Some(Point { x: 1.0, y: 0.5 });
+        let arrow_id = arrow.id;
+        store.commit_shape(arrow).unwrap();
+
+        assert!(store.convert_shape_kind(arrow_id, ShapeKind::CurvedArrow));
+        assert!(store.convert_shape_kind(rect_id, ShapeKind::Ellipse));
+        assert!(!store.convert_shape_kind(rect_id, ShapeKind::Arrow));
+        assert!(!store.convert_shape_kind(rect_id, ShapeKind::Ellipse));
+        let Some(Item::Shape(arrow)) = store.item(arrow_id) else {
+            panic!("expected shape");
+        };
+        assert_eq!(arrow.kind, ShapeKind::CurvedArrow);
+        assert_eq!(arrow.end_attach_id, Some(rect_id));
+        let Some(Item::Shape(rect)) = store.item(rect_id) else {
+            panic!("expected shape");
+        };
+        assert_eq!((rect.kind, rect.text.as_str()), (ShapeKind::Ellipse, "box"));
+
+        assert!(store.reverse_arrow(arrow_id));
+        assert!(!store.reverse_arrow(rect_id));
+        let Some(Item::Shape(arrow)) = store.item(arrow_id) else {
+            panic!("expected shape");
+        };
+        assert_eq!(arrow.start, Point { x: 40.0, y: 10.0 });
+        assert_eq!(arrow.start_attach_id, Some(rect_id));
+        assert_eq!(arrow.start_attach_uv, Some(Point { x: 1.0, y: 0.5 }));
+        assert_eq!(arrow.end_attach_id, None);
+
+        store.undo().unwrap();
+        store.undo().unwrap();
+        assert!(
+            matches!(store.item(rect_id), Some(Item::Shape(sh)) if sh.kind == ShapeKind::Rectangle)
+        );
+    }
+
+    #[test]
+    fn arrow_endpoints_attach_and_detach() {
+        let mut store = Store::new();
+        let style = style();
+        let mut rect = store.begin_shape(ShapeKind::Rectangle, style, Point { x: 0.0, y: 0.0 });
+        rect.end = Point { x: 40.0, y: 20.0 };
+        let rect_id = rect.id;
+        store.commit_shape(rect).unwrap();
+        let mut arrow = store.begin_shape(ShapeKind::Arrow, style, Point { x: 100.0, y: 0.0 });
+        arrow.end = Point { x: 100.0, y: 50.0 };
+        let arrow_id = arrow.id;
+        store.commit_shape(arrow).unwrap();
+        let ends = |store: &Store| match store.item(arrow_id) {
+            Some(Item::Shape(sh)) => (sh.end, sh.end_attach_id, sh.end_attach_uv),
+            _ => panic!("expected shape"),
+        };
+
+        let drop = Point { x: 40.0, y: 10.0 };
+        let uv = Point { x: 1.5, y: 0.5 };
+        assert!(store.move_arrow_endpoint(arrow_id, ArrowEnd::End, drop, Some((rect_id, uv))));
+        assert_eq!(
+            ends(&store),
+            (drop, Some(rect_id), Some(Point { x: 1.0, y: 0.5 }))
+        );
+        assert!(!store.move_arrow_endpoint(arrow_id, ArrowEnd::End, drop, Some((arrow_id, uv))));
+        assert!(!store.move_arrow_endpoint(rect_id, ArrowEnd::End, drop, None));
+
+        let free = Point { x: 200.0, y: 80.0 };
+        assert!(store.move_arrow_endpoint(arrow_id, ArrowEnd::End, free, None));
+        assert_eq!(ends(&store), (free, None, None));
+        assert!(store.move_arrow_endpoint(arrow_id, ArrowEnd::Start, drop, None));
+
+        store.undo().unwrap();
+        store.undo().unwrap();
+        assert_eq!(ends(&store).1, Some(rect_id));
+    }
+
+    #[test]
+    fn minimal_change_rerouting_keeps_a_clear_detour() {
+        let mut store = Store::new();
+        let style = style();
+        // Two nodes with a box in the way.
+        for (x0, x1) in [(0.0, 100.0), (300.0, 400.0), (150.0, 250.0)] {
+            let mut sh = store.begin_shape(ShapeKind::Rectangle, style, Point { x: x0, y: 0.0 });
+            sh.end = Point { x: x1, y: 100.0 };
+            store.commit_shape(sh).unwrap();
+        }
+        let ids: Vec<u64> = store.items().iter().map(Item::id).collect();
+        let mut arrow =
+            store.begin_shape(ShapeKind::CurvedArrow, style, Point { x: 50.0, y: 50.0 });
+        arrow.end = Point { x: 350.0, y: 50.0 };
+        arrow.start_attach_id = Some(ids[0]);
+        arrow.end_attach_id = Some(ids[1]);
+        let arrow_id = arrow.id;
+        store.commit_shape(arrow).unwrap();
+        let route = |store: &Store| match store.item(arrow_id) {
+            Some(Item::Shape(sh)) => sh.route,
+            _ => None,
+        };
+
+        let detour = render_arrows(store.items())[0].path;
+        assert!(matches!(detour, ArrowPath::Cubic { .. }));
+        assert!(store.reroute_arrows(true));
+        assert!(route(&store).is_some());
+        assert!(!store.reroute_arrows(true), "nothing moved");
+
+        // With the box gone the detour is no longer needed, but still clear.
+        assert!(store.translate(&[ids[2]], 0.0, 400.0));
+        let kept = render_arrows(store.items())[0].path;
+        assert!(matches!(kept, ArrowPath::Cubic { .. }));
+        assert!(!store.reroute_arrows(true));
+        assert!(store.reroute_arrows(false));
+        assert_eq!(route(&store), None);
+        let fresh = render_arrows(store.items())[0].path;
+        assert!(matches!(fresh, ArrowPath::Quadratic { .. }));
+
+        // Reversing keeps the kept curve where it was.
+        store.undo().unwrap();
+        let before = render_arrows(store.items())[0];
+        assert!(store.reverse_arrow(arrow_id));
+        let after = render_arrows(store.items())[0];
+        let mid = |r: &crate::render::ArrowRender| r.path.point_at(r.start, r.end, 0.5);
+        assert!((mid(&before).x - mid(&after).x).abs() < 0.01);
+        assert!((mid(&before).y - mid(&after).y).abs() < 0.01);
+    }
+}
@@ -0,0 +1,20 @@
+use super::Store;
+use crate::model::Sketch;
+
+impl Store {
+    pub fn sketch(&self) -> Option<Sketch> {
+        self.meta.sketch
+    }
+
+    /// Turns the hand-drawn look on (or with `None` off) as one undo step.
+    /// A roughness that is not a positive finite number is refused.
+    pub fn set_sketch(&mut self, sketch: Option<Sketch>) -> bool {
+        if sketch.is_some_and(|s| !(s.roughness.is_finite() && s.roughness > 0.0)) {
+            return false;
+        }
+        let mut meta = self.meta.clone();
+        meta.sketch = sketch;
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+}
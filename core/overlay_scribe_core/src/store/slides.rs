@@ -0,0 +1,165 @@
+use super::{Document, DocumentMeta, Store};
+use crate::export::{to_svg, SvgOptions};
+use crate::model::{BackgroundImage, Item, Slide};
+
+impl Store {
+    /// Number of slides; a document without a deck is one slide.
+    pub fn slide_count(&self) -> usize {
+        self.meta.slides.len().max(1)
+    }
+
+    pub fn current_slide(&self) -> usize {
+        self.meta.current_slide
+    }
+
+    /// Appends an empty slide over `background` and returns its index, as
+    /// one undo step. The current slide stays shown.
+    pub fn add_slide(&mut self, background: Option<BackgroundImage>) -> usize {
+        let mut meta = self.meta.clone();
+        if meta.slides.is_empty() {
+            meta.slides.push(Slide::default());
+        }
+        meta.slides.push(Slide {
+            background,
+            items: Vec::new(),
+        });
+        let index = meta.slides.len() - 1;
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit]);
+        index
+    }
+
+    /// Shows slide `index`: the current slide's items and background are
+    /// put away in the deck and the target's are loaded, as one undo step.
+    pub fn go_to_slide(&mut self, index: usize) -> bool {
+        if index == self.meta.current_slide || index >= self.meta.slides.len() {
+            return false;
+        }
+        let (mut meta, _) = self.stowed();
+        let items = show(&mut meta, index);
+        let edits = [self.items_edit(items), self.meta_edit(meta)];
+        self.apply_all(edits)
+    }
+
+    /// Removes slide `index`, as one undo step. Removing the current slide
+    /// shows the one before it (or the next, for the first). The last
+    /// remaining slide can't be removed.
+    pub fn remove_slide(&mut self, index: usize) -> bool {
+        if self.meta.slides.len() < 2 || index >= self.meta.slides.len() {
+            return false;
+        }
+        let (mut meta, mut items) = self.stowed();
+        let current = meta.current_slide;
+        if index == current {
+            items = show(&mut meta, if index > 0 { index - 1 } else { 1 });
+        }
+        meta.slides.remove(index);
+        if meta.current_slide > index {
+            meta.current_slide -= 1;
+        }
+        let edits = [self.items_edit(items), self.meta_edit(meta)];
+        self.apply_all(edits)
+    }
+
+    /// SVG of slide `index` over its background, whether or not it is shown.
+    pub fn export_slide(&self, index: usize, options: &SvgOptions) -> Option<String> {
+        if index >= self.slide_count() {
+            return None;
+        }
+        let (mut meta, items) = self.stowed();
+        let slide = meta.slides.get_mut(index).map(std::mem::take);
+        let (background, items) = match slide {
+            Some(slide) if index != self.meta.current_slide => (slide.background, slide.items),
+            _ => (self.meta.background_image.clone(), items),
+        };
+        meta.slides.clear();
+        meta.current_slide = 0;
+        meta.background_image = background;
+        let doc = Document {
+            version: Document::CURRENT_VERSION,
+            preview: None,
+            items,
+            meta,
+        };
+        Some(to_svg(&doc, options))
+    }
+
+    // The meta with the current slide's content moved into its deck entry,
+    // and the current items.
+    fn stowed(&self) -> (DocumentMeta, Vec<Item>) {
+        let mut meta = self.meta.clone();
+        if let Some(slide) = meta.slides.get_mut(meta.current_slide) {
+            slide.background = meta.background_image.take();
+            slide.items = self.items.to_vec();
+        }
+        (meta, self.items.to_vec())
+    }
+}
+
+// Takes slide `index` out of the deck into the document's background and
+// returns its items.
+fn show(meta: &mut DocumentMeta, index: usize) -> Vec<Item> {
+    let slide = std::mem::take(&mut meta.slides[index]);
+    meta.background_image = slide.background;
+    meta.current_slide = index;
+    slide.items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ImageSource, Point};
+    use crate::test_util::black;
+
+    #[test]
+    fn slides_keep_their_own_items_and_backgrounds() {
+        let mut store = Store::new();
+        let c = black();
+        let screenshot = |uri: &str| BackgroundImage {
+            source: ImageSource::Uri {
+                uri: uri.to_string(),
+            },
+            width: 1280.0,
+            height: 720.0,
+        };
+        let draw = |store: &mut Store| {
+            let mut s = store.begin_stroke(c, 2.0, Point { x: 0.0, y: 0.0 });
+            s.points.push(Point { x: 10.0, y: 10.0 });
+            let id = s.id;
+            store.commit_stroke(s).unwrap();
+            id
+        };
+        assert_eq!(store.slide_count(), 1);
+        store.set_background_image(Some(screenshot("one.png")));
+        let first = draw(&mut store);
+
+        assert_eq!(store.add_slide(Some(screenshot("two.png"))), 1);
+        assert_eq!(store.slide_count(), 2);
+        assert!(store.go_to_slide(1));
+        assert!(store.items().is_empty());
+        assert_eq!(store.background_image(), Some(&screenshot("two.png")));
+        let second = draw(&mut store);
+        assert_ne!(first, second);
+
+        let loaded = Store::from_json(&store.to_json().unwrap()).unwrap();
+        let mut reloaded = Store::new();
+        reloaded.load_document(loaded);
+        assert_eq!(reloaded.current_slide(), 1);
+        assert!(reloaded.go_to_slide(0));
+        assert_eq!(reloaded.items()[0].id(), first);
+        assert!(draw(&mut reloaded) > second);
+
+        let svg = store.export_slide(0, &SvgOptions::default()).unwrap();
+        assert!(svg.contains(r#"href="one.png""#));
+        assert!(store.export_slide(2, &SvgOptions::default()).is_none());
+
+        assert!(store.remove_slide(1));
+        assert_eq!((store.slide_count(), store.current_slide()), (1, 0));
+        assert_eq!(store.items()[0].id(), first);
+        assert!(!store.remove_slide(0));
+
+        store.undo().unwrap();
+        assert_eq!(store.current_slide(), 1);
+        assert_eq!(store.items()[0].id(), second);
+    }
+}
@@ -0,0 +1,166 @@
+use super::Store;
+use crate::model::{Item, Point};
+
+/// Where [`Store::split_stroke`] cuts a stroke.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SplitAt {
+    // Share of the stroke's length from its first point, in (0, 1).
+    Fraction(f32),
+    // The point of the stroke closest to this one.
+    Nearest(Point),
+}
+
+impl Store {
+    /// Cuts stroke `id` in two at `at`, as one undo step, so part of a long
+    /// line can be erased on its own. Both halves keep the stroke's style
+    /// and meet at the cut; the first keeps the id and the stacking position
+    /// with the second directly above it. Tapers stay at the outer ends.
+    /// Returns the second half's id, or `None` when the cut falls on an end
+    /// of the stroke or the halves would break the [`DocumentLimits`].
+    ///
+    /// [`DocumentLimits`]: super::DocumentLimits
+    pub fn split_stroke(&mut self, id: u64, at: SplitAt) -> Option<u64> {
+        let Some(Item::Stroke(stroke)) = self.item(id) else {
+            return None;
+        };
+        let (segment, cut) = cut_point(&stroke.points, at)?;
+        let (mut head, mut tail) = (stroke.clone(), stroke.clone());
+        head.points.truncate(segment + 1);
+        if head.points.last() != Some(&cut) {
+            head.points.push(cut);
+        }
+        tail.points.drain(..=segment);
+        if tail.points.first() != Some(&cut) {
+            tail.points.insert(0, cut);
+        }
+        if head.points.len() < 2 || tail.points.len() < 2 {
+            return None;
+        }
+        let total = length(&stroke.points);
+        head.line.taper_start = rescale(stroke.line.taper_start, total, length(&head.points));
+        head.line.taper_end = 0.0;
+        tail.line.taper_start = 0.0;
+        tail.line.taper_end = rescale(stroke.line.taper_end, total, length(&tail.points));
+
+        self.check_limits(&[head.points.len(), tail.points.len()], &[id])
+            .ok()?;
+        tail.id = self.next_id;
+        let tail_id = tail.id;
+        let mut items = self.items.to_vec();
+        let index = items.iter().position(|item| item.id() == id)?;
+        items[index] = Item::Stroke(head);
+        items.insert(index + 1, Item::Stroke(tail));
+        let edit = self.items_edit(items);
+        if !self.apply_all([edit]) {
+            return None;
+        }
+        self.next_id = self.next_id.saturating_add(1);
+        Some(tail_id)
+    }
+}
+
+// The segment (index of its first point) the cut falls in and the cut point.
+fn cut_point(points: &[Point], at: SplitAt) -> Option<(usize, Point)> {
+    let lerp = |a: Point, b: Point, t: f32| Point {
+        x: a.x + (b.x - a.x) * t,
+        y: a.y + (b.y - a.y) * t,
+    };
+    match at {
+        SplitAt::Fraction(t) => {
+            if !(t > 0.0 && t < 1.0) {
+                return None;
+            }
+            let mut left = t * length(points);
+            for (i, pair) in points.windows(2).enumerate() {
+                let step = distance(pair[0], pair[1]);
+                if left <= step && step > 0.0 {
+                    return Some((i, lerp(pair[0], pair[1], left / step)));
+                }
+                left -= step;
+            }
+            None
+        }
+        SplitAt::Nearest(p) => {
+            if !(p.x.is_finite() && p.y.is_finite()) {
+                return None;
+            }
+            let mut best: Option<(f32, usize, Point)> = None;
+            for (i, pair) in points.windows(2).enumerate() {
+                let (a, b) = (pair[0], pair[1]);
+                let len2 = (b.x - a.x).powi(2) + (b.y - a.y).powi(2);
+                let t = if len2 > 0.0 {
+                    (((p.x - a.x) * (b.x - a.x) + (p.y - a.y) * (b.y - a.y)) / len2).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let q = lerp(a, b, t);
+                let d = distance(p, q);
+                if best.is_none_or(|(bd, _, _)| d < bd) {
+                    best = Some((d, i, q));
+                }
+            }
+            best.map(|(_, i, q)| (i, q))
+        }
+    }
+}
+
+fn distance(a: Point, b: Point) -> f32 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+fn length(points: &[Point]) -> f32 {
+    points.windows(2).map(|w| distance(w[0], w[1])).sum()
+}
+
+// A taper share of the whole stroke as a share of a piece of it.
+fn rescale(taper: f32, total: f32, piece: f32) -> f32 {
+    if piece > 0.0 {
+        (taper * total / piece).min(1.0)
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Stroke;
+    use crate::test_util::black;
+
+    #[test]
+    fn splits_a_stroke_into_two_undoable_halves() {
+        let mut store = Store::new();
+        let c = black();
+        let mut s = store.begin_stroke(c, 2.0, Point { x: 0.0, y: 0.0 });
+        s.points.push(Point { x: 10.0, y: 0.0 });
+        s.points.push(Point { x: 40.0, y: 0.0 });
+        s.line.taper_end = 0.5;
+        let id = s.id;
+        store.commit_stroke(s).unwrap();
+        let stroke = |store: &Store, id| match store.item(id) {
+            Some(Item::Stroke(s)) => s.clone(),
+            _ => panic!("expected stroke"),
+        };
+
+        let tail = store.split_stroke(id, SplitAt::Fraction(0.5)).unwrap();
+        let xs = |s: &Stroke| s.points.iter().map(|p| p.x).collect::<Vec<_>>();
+        assert_eq!(xs(&stroke(&store, id)), [0.0, 10.0, 20.0]);
+        assert_eq!(xs(&stroke(&store, tail)), [20.0, 40.0]);
+        assert_eq!(stroke(&store, id).line.taper_end, 0.0);
+        assert_eq!(stroke(&store, tail).line.taper_end, 1.0);
+        assert_eq!(store.items()[1].id(), tail);
+
+        let near = Point { x: 5.0, y: 3.0 };
+        let third = store.split_stroke(id, SplitAt::Nearest(near)).unwrap();
+        assert_eq!(xs(&stroke(&store, third)), [5.0, 10.0, 20.0]);
+        assert!(store
+            .split_stroke(id, SplitAt::Nearest(Point { x: -5.0, y: 0.0 }))
+            .is_none());
+        assert!(store.split_stroke(id, SplitAt::Fraction(1.0)).is_none());
+
+        store.undo().unwrap();
+        store.undo().unwrap();
+        assert_eq!(store.items().len(), 1);
+        assert_eq!(xs(&stroke(&store, id)), [0.0, 10.0, 40.0]);
+    }
+}
@@ -0,0 +1,213 @@
+use super::{Document, DocumentMeta, Store, StoreError};
+use crate::metrics::{Serialization, Timer};
+use crate::model::{Item, Stroke};
+use crate::trace::Span;
+use serde::de::{DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+use std::cell::Cell;
+use std::fmt;
+use std::io::Read;
+use std::rc::Rc;
+
+/// Progress of a streaming load, reported after every parsed item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadProgress {
+    pub items_loaded: usize,
+    pub bytes_read: usize,
+    // Size of the input when known (0 otherwise).
+    pub total_bytes: usize,
+}
+
+impl Store {
+    /// Like [`Store::from_json`], but hands every item to `on_item` as soon as
+    /// it is parsed, so a shell can start drawing a large document before the
+    /// whole file has been read.
+    pub fn from_json_streaming(
+        json: &str,
+        on_item: impl FnMut(&Item, LoadProgress),
+    ) -> Result<Document, StoreError> {
+        Self::from_reader_streaming(json.as_bytes(), json.len(), on_item)
+    }
+
+    /// Streaming load from any reader; `total_bytes` is only echoed back in
+    /// the progress reports (pass 0 if unknown).
+    pub fn from_reader_streaming<R: Read>(
+        reader: R,
+        total_bytes: usize,
+        mut on_item: impl FnMut(&Item, LoadProgress),
+    ) -> Result<Document, StoreError> {
+        let mut span = Span::enter("store.load_streaming");
+        let _timer = Timer::start(Serialization::Load);
+        let read = Rc::new(Cell::new(0));
+        let counting = CountingReader {
+            inner: reader,
+            read: Rc::clone(&read),
+        };
+        let mut de = serde_json::Deserializer::from_reader(counting);
+        let doc = DocumentSeed {
+            on_item: &mut on_item,
+            read: &read,
+            total_bytes,
+        }
+        .deserialize(&mut de)?;
+        de.end()?;
+        span.record("bytes", read.get() as u64);
+        span.record("items", doc.items.len() as u64);
+        Ok(doc)
+    }
+}
+
+struct CountingReader<R> {
+    inner: R,
+    read: Rc<Cell<usize>>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read.set(self.read.get() + n);
+        Ok(n)
+    }
+}
+
+struct DocumentSeed<'a, F> {
+    on_item: &'a mut F,
+    read: &'a Cell<usize>,
+    total_bytes: usize,
+}
+
+impl<'de, F: FnMut(&Item, LoadProgress)> DeserializeSeed<'de> for DocumentSeed<'_, F> {
+    type Value = Document;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Document, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, F: FnMut(&Item, LoadProgress)> Visitor<'de> for DocumentSeed<'_, F> {
+    type Value = Document;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an overlay document")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Document, A::Error> {
+        let mut items = Vec::new();
+        let mut rest = serde_json::Map::new();
+        let mut is_v1 = false;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                // v2 items, or v1 strokes which load as stroke items.
+                "items" | "strokes" => {
+                    is_v1 |= key == "strokes";
+                    map.next_value_seed(ItemsSeed {
+                        items: &mut items,
+                        strokes_only: key == "strokes",
+                        on_item: &mut *self.on_item,
+                        read: self.read,
+                        total_bytes: self.total_bytes,
+                    })?;
+                }
+                _ => {
+                    rest.insert(key, map.next_value()?);
+                }
+            }
+        }
+        rest.remove("version");
+        let meta = if is_v1 {
+            DocumentMeta::default()
+        } else {
+            DocumentMeta::deserialize(serde_json::Value::Object(rest))
+                .map_err(serde::de::Error::custom)?
+        };
+        Ok(Document {
+            version: Document::CURRENT_VERSION,
+            preview: None,
+            items,
+            meta,
+        })
+    }
+}
+
+struct ItemsSeed<'a, F> {
+    items: &'a mut Vec<Item>,
+    strokes_only: bool,
+    on_item: &'a mut F,
+    read: &'a Cell<usize>,
+    total_bytes: usize,
+}
+
+impl<'de, F: FnMut(&Item, LoadProgress)> DeserializeSeed<'de> for ItemsSeed<'_, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F: FnMut(&Item, LoadProgress)> Visitor<'de> for ItemsSeed<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list of items")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        loop {
+            let item = if self.strokes_only {
+                seq.next_element::<Stroke>()?.map(Item::Stroke)
+            } else {
+                seq.next_element::<Item>()?
+            };
+            let Some(item) = item else { break };
+            self.items.push(item);
+            let progress = LoadProgress {
+                items_loaded: self.items.len(),
+                bytes_read: self.read.get(),
+                total_bytes: self.total_bytes,
+            };
+            (self.on_item)(self.items.last().expect("just pushed"), progress);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Point;
+    use crate::test_util::black;
+
+    #[test]
+    fn streaming_load_matches_regular_load() {
+        let mut store = Store::new();
+        let c = black();
+        for i in 0..5 {
+            let s = store.begin_stroke(
+                c,
+                2.0,
+                Point {
+                    x: i as f32,
+                    y: 0.0,
+                },
+            );
+            store.commit_stroke(s).unwrap();
+        }
+        store.add_palette_color("Ink", c);
+        let json = store.to_json().unwrap();
+
+        let mut seen = Vec::new();
+        let doc = Store::from_json_streaming(&json, |item, p| seen.push((item.id(), p))).unwrap();
+        assert_eq!(seen.len(), 5);
+        assert!(seen
+            .windows(2)
+            .all(|w| w[0].1.bytes_read <= w[1].1.bytes_read));
+        assert_eq!(seen[4].1.total_bytes, json.len());
+        assert_eq!(doc.items, store.items());
+        assert_eq!(doc.meta, *store.meta());
+
+        let v1 = r#"{"version":1,"strokes":[{"id":7,"color":{"r":0,"g":0,"b":0,"a":255},"width":1.0,"points":[]}]}"#;
+        let doc = Store::from_json_streaming(v1, |_, _| {}).unwrap();
+        assert_eq!(doc.items[0].id(), 7);
+    }
+}
@@ -0,0 +1,138 @@
+use super::Store;
+use crate::model::{BlendMode, Item, ShapeStyle, TextAlignH, TextAlignV};
+
+/// Style captured by [`Store::copy_style_from`] for the format painter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CopiedStyle {
+    pub style: ShapeStyle,
+    pub blend_mode: BlendMode,
+    pub text_align_h: TextAlignH,
+    pub text_align_v: TextAlignV,
+    // False when copied from a stroke: only the stroke color/width and blend
+    // mode are meaningful then, so shape-only attributes are left untouched.
+    pub from_shape: bool,
+}
+
+impl Store {
+    /// Captures the style of `source_id` for a later [`Store::apply_copied_style`].
+    pub fn copy_style_from(&mut self, source_id: u64) -> bool {
+        let Some(item) = self.items.iter().find(|it| it.id() == source_id) else {
+            return false;
+        };
+        self.copied_style = Some(match item {
+            Item::Shape(sh) => CopiedStyle {
+                style: sh.style,
+                blend_mode: sh.blend_mode,
+                text_align_h: sh.text_align_h,
+                text_align_v: sh.text_align_v,
+                from_shape: true,
+            },
+            Item::Stroke(s) => CopiedStyle {
+                style: ShapeStyle {
+                    stroke_color: s.color,
+                    stroke_width: s.width,
+                    fill_enabled: false,
+                    fill_color: s.color,
+                    hatch_enabled: false,
+                    corner_radius: 0.0,
+                    stroke_color_ref: s.color_ref,
+                    fill_color_ref: None,
+                    line: s.line,
+                    corner_radii: None,
+                    curvature: None,
+                    head_notch: None,
+                },
+                blend_mode: s.blend_mode,
+                text_align_h: Default::default(),
+                text_align_v: Default::default(),
+                from_shape: false,
+            },
+        });
+        true
+    }
+
+    pub fn copied_style(&self) -> Option<&CopiedStyle> {
+        self.copied_style.as_ref()
+    }
+
+    /// Pastes the copied style onto the given items as one undo step.
+    pub fn apply_copied_style(&mut self, target_ids: &[u64]) -> bool {
+        let Some(copied) = self.copied_style else {
+            return false;
+        };
+        let edit = self.map_items_edit(target_ids, |item| match item {
+            Item::Stroke(s) => {
+                s.color = copied.style.stroke_color;
+                s.color_ref = copied.style.stroke_color_ref;
+                s.width = copied.style.stroke_width;
+                s.line = copied.style.line;
+                s.blend_mode = copied.blend_mode;
+            }
+            Item::Shape(sh) => {
+                sh.blend_mode = copied.blend_mode;
+                if copied.from_shape {
+                    sh.style = copied.style;
+                    sh.text_align_h = copied.text_align_h;
+                    sh.text_align_v = copied.text_align_v;
+                } else {
+                    sh.style.stroke_color = copied.style.stroke_color;
+                    sh.style.stroke_color_ref = copied.style.stroke_color_ref;
+                    sh.style.stroke_width = copied.style.stroke_width;
+                    sh.style.line = copied.style.line;
+                }
+            }
+        });
+        self.apply_all([edit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ColorRgba8, Point, ShapeKind};
+    use crate::test_util::style;
+
+    #[test]
+    fn stroke_style_only_touches_shape_outline() {
+        let mut store = Store::new();
+        let blue = ColorRgba8 {
+            r: 0,
+            g: 0,
+            b: 255,
+            a: 255,
+        };
+        let style = ShapeStyle {
+            stroke_color: ColorRgba8 {
+                r: 255,
+                g: 0,
+                b: 0,
+                a: 255,
+            },
+            fill_enabled: true,
+            fill_color: blue,
+            hatch_enabled: true,
+            corner_radius: 8.0,
+            ..style()
+        };
+        let sh = store.begin_shape(ShapeKind::RoundedRectangle, style, Point { x: 0.0, y: 0.0 });
+        let shape_id = sh.id;
+        store.commit_shape(sh).unwrap();
+        let mut s = store.begin_stroke(blue, 9.0, Point { x: 1.0, y: 1.0 });
+        s.blend_mode = BlendMode::Multiply;
+        let stroke_id = s.id;
+        store.commit_stroke(s).unwrap();
+
+        assert!(!store.apply_copied_style(&[shape_id]));
+        assert!(store.copy_style_from(stroke_id));
+        assert!(store.apply_copied_style(&[shape_id]));
+
+        let Item::Shape(sh) = &store.items()[0] else {
+            panic!("expected shape")
+        };
+        assert_eq!(sh.style.stroke_color, blue);
+        assert_eq!(sh.style.stroke_width, 9.0);
+        assert!(sh.style.hatch_enabled);
+        assert_eq!(sh.style.corner_radius, 8.0);
+        assert_eq!(sh.blend_mode, BlendMode::Multiply);
+    }
+}
@@ -0,0 +1,83 @@
+use super::Store;
+use crate::model::Item;
+
+impl Store {
+    /// Tags `ids` with `tag` (trimmed). Returns false if the tag is empty or
+    /// every item already had it.
+    pub fn add_tag(&mut self, ids: &[u64], tag: &str) -> bool {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return false;
+        }
+        let edit = self.map_items_edit(ids, |item| {
+            let tags = item.tags_mut();
+            if let Err(at) = tags.binary_search_by(|t| t.as_str().cmp(tag)) {
+                tags.insert(at, tag.to_string());
+            }
+        });
+        self.apply_all([edit])
+    }
+
+    pub fn remove_tag(&mut self, ids: &[u64], tag: &str) -> bool {
+        let tag = tag.trim();
+        let edit = self.map_items_edit(ids, |item| item.tags_mut().retain(|t| t != tag));
+        self.apply_all([edit])
+    }
+
+    /// Items carrying `tag`, in document order.
+    pub fn items_with_tag(&self, tag: &str) -> Vec<&Item> {
+        let tag = tag.trim();
+        self.items
+            .iter()
+            .filter(|item| item.tags().iter().any(|t| t == tag))
+            .collect()
+    }
+
+    /// Every tag used in the document, sorted.
+    pub fn all_tags(&self) -> Vec<&str> {
+        let mut tags: Vec<&str> = self
+            .items
+            .iter()
+            .flat_map(|item| item.tags().iter().map(String::as_str))
+            .collect();
+        tags.sort_unstable();
+        tags.dedup();
+        tags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Point;
+    use crate::test_util::black;
+
+    #[test]
+    fn tags_filter_items_and_round_trip() {
+        let mut store = Store::new();
+        let c = black();
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            let s = store.begin_stroke(c, 2.0, Point { x: 0.0, y: 0.0 });
+            ids.push(s.id);
+            store.commit_stroke(s).unwrap();
+        }
+
+        assert!(!store.add_tag(&ids, "  "));
+        assert!(store.add_tag(&ids, " todo "));
+        assert!(store.add_tag(&ids[1..], "bug"));
+        assert!(!store.add_tag(&ids, "todo"));
+        assert_eq!(store.items()[1].tags(), ["bug", "todo"]);
+        assert_eq!(store.all_tags(), ["bug", "todo"]);
+        let hits: Vec<u64> = store.items_with_tag("bug").iter().map(|i| i.id()).collect();
+        assert_eq!(hits, [ids[1]]);
+
+        let doc = Store::from_json(&store.to_json().unwrap()).unwrap();
+        assert_eq!(doc.items[1].tags(), ["bug", "todo"]);
+
+        assert!(store.remove_tag(&ids, "todo"));
+        assert!(store.items_with_tag("todo").is_empty());
+        store.undo().unwrap();
+        assert_eq!(store.items_with_tag("todo").len(), 2);
+    }
+}
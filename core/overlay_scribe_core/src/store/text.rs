@@ -0,0 +1,154 @@
+use super::{Store, StoreError};
+use crate::model::Item;
+use crate::search::{FindOptions, Pattern, TextMatches};
+
+/// Which items a find-and-replace may touch.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ReplaceScope {
+    #[default]
+    All,
+    Items(Vec<u64>),
+}
+
+impl ReplaceScope {
+    fn contains(&self, id: u64) -> bool {
+        match self {
+            ReplaceScope::All => true,
+            ReplaceScope::Items(ids) => ids.contains(&id),
+        }
+    }
+}
+
+impl Store {
+    /// Finds `query` in shape text, returning each matching item id with the
+    /// byte ranges of its matches, in document order.
+    pub fn find_text(&self, query: &str, options: FindOptions) -> Result<TextMatches, StoreError> {
+        let pattern = Pattern::new(query, options).map_err(StoreError::InvalidPattern)?;
+        Ok(self
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Shape(s) => {
+                    let ranges = pattern.find_all(&s.text);
+                    (!ranges.is_empty()).then_some((s.id, ranges))
+                }
+                Item::Stroke(_) => None,
+            })
+            .collect())
+    }
+
+    /// Replaces every match of `query` in shape text within `scope` by
+    /// `replacement` (inserted literally), as one undo step. Returns the
+    /// number of matches replaced.
+    pub fn replace_text(
+        &mut self,
+        query: &str,
+        replacement: &str,
+        options: FindOptions,
+        scope: &ReplaceScope,
+    ) -> Result<usize, StoreError> {
+        let matches: TextMatches = self
+            .find_text(query, options)?
+            .into_iter()
+            .filter(|(id, _)| scope.contains(*id))
+            .collect();
+        let count = matches.iter().map(|(_, ranges)| ranges.len()).sum();
+        let ids: Vec<u64> = matches.iter().map(|(id, _)| *id).collect();
+        let edit = self.map_items_edit(&ids, |item| {
+            let Item::Shape(s) = item else { return };
+            let Some((_, ranges)) = matches.iter().find(|(id, _)| *id == s.id) else {
+                return;
+            };
+            let mut text = String::with_capacity(s.text.len());
+            let mut last = 0;
+            for range in ranges {
+                text.push_str(&s.text[last..range.start]);
+                text.push_str(replacement);
+                last = range.end;
+            }
+            text.push_str(&s.text[last..]);
+            s.text = text;
+        });
+        self.apply_all([edit]);
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use crate::model::{Item, Point, ShapeKind};
+    use crate::search::FindOptions;
+    use crate::store::{ReplaceScope, Store, StoreError};
+    use crate::test_util::style;
+
+    fn store_with_texts(texts: &[&str]) -> (Store, Vec<u64>) {
+        let mut store = Store::new();
+        let style = style();
+        let mut ids = Vec::new();
+        for text in texts {
+            let mut shape =
+                store.begin_shape(ShapeKind::Rectangle, style, Point { x: 0.0, y: 0.0 });
+            shape.end = Point { x: 10.0, y: 10.0 };
+            shape.text = text.to_string();
+            ids.push(shape.id);
+            store.commit_shape(shape).unwrap();
+        }
+        (store, ids)
+    }
+
+    fn text_of(store: &Store, id: u64) -> &str {
+        store
+            .items()
+            .iter()
+            .find_map(|item| match item {
+                Item::Shape(s) if s.id == id => Some(s.text.as_str()),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn find_text_reports_ranges_per_item() {
+        let (store, ids) = store_with_texts(&["Fix login", "no match", "fix FIX"]);
+        let options = FindOptions {
+            case_insensitive: true,
+            wildcard: false,
+        };
+        let hits = store.find_text("fix", options).unwrap();
+        assert_eq!(hits, vec![(ids[0], vec![0..3]), (ids[2], vec![0..3, 4..7])]);
+
+        let wildcard = FindOptions {
+            case_insensitive: false,
+            wildcard: true,
+        };
+        let hits = store.find_text("n? m*h", wildcard).unwrap();
+        assert_eq!(hits, vec![(ids[1], vec![0..8])]);
+        assert!(matches!(
+            store.find_text("no\\", wildcard),
+            Err(StoreError::InvalidPattern(_))
+        ));
+    }
+
+    #[test]
+    fn replace_text_is_one_undo_step_within_scope() {
+        let (mut store, ids) = store_with_texts(&["old and old", "old", "keep"]);
+        let scope = ReplaceScope::Items(vec![ids[0], ids[2]]);
+        let count = store
+            .replace_text("old", "new", FindOptions::default(), &scope)
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(text_of(&store, ids[0]), "new and new");
+        assert_eq!(text_of(&store, ids[1]), "old");
+
+        let count = store
+            .replace_text("old", "new", FindOptions::default(), &ReplaceScope::All)
+            .unwrap();
+        assert_eq!(count, 1);
+        store.undo().unwrap();
+        assert_eq!(text_of(&store, ids[1]), "old");
+        assert_eq!(text_of(&store, ids[0]), "new and new");
+        store.undo().unwrap();
+        assert_eq!(text_of(&store, ids[0]), "old and old");
+    }
+}
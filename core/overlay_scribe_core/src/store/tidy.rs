@@ -0,0 +1,201 @@
+use super::Store;
+use crate::geometry::{is_boxed_shape, rect_for_shape};
+use crate::model::{Item, Point, Shape, ShapeKind};
+use crate::render::{render_arrows_for, RoutingConfig};
+
+/// Shape centers closer than this on an axis are lined up by [`Store::tidy`].
+pub const TIDY_ALIGN_TOLERANCE: f32 = 6.0;
+/// Grid that [`Store::tidy`] rounds shape sizes to.
+pub const TIDY_GRID: f32 = 8.0;
+/// Straight arrows within this many degrees of horizontal or vertical are
+/// straightened by [`Store::tidy`].
+pub const TIDY_STRAIGHTEN_DEGREES: f32 = 8.0;
+
+impl Store {
+    /// Cleans up a rough diagram, as one undo step: closed shapes among `ids`
+    /// get sizes rounded to [`TIDY_GRID`] and near-aligned centers moved onto
+    /// a shared axis; then attached arrow ends are re-anchored to where the
+    /// router puts them, and the free ends of nearly axis-aligned straight
+    /// arrows are snapped level or plumb.
+    pub fn tidy(&mut self, ids: &[u64]) -> bool {
+        let mut items = self.items.to_vec();
+        let selected = |item: &Item| ids.contains(&item.id());
+
+        let mut boxes: Vec<&mut Shape> = items
+            .iter_mut()
+            .filter(|it| selected(it))
+            .filter_map(|it| match it {
+                Item::Shape(sh) if is_boxed_shape(sh.kind) => Some(sh),
+                _ => None,
+            })
+            .collect();
+        for sh in boxes.iter_mut() {
+            let rect = rect_for_shape(sh);
+            let snap = |v: f32| ((v / TIDY_GRID).round() * TIDY_GRID).max(TIDY_GRID);
+            set_rect(sh, rect.center(), snap(rect.width()), snap(rect.height()));
+        }
+        let centers: Vec<Point> = boxes.iter().map(|sh| rect_for_shape(sh).center()).collect();
+        let xs = cluster_means(centers.iter().map(|c| c.x).collect());
+        let ys = cluster_means(centers.iter().map(|c| c.y).collect());
+        for (i, sh) in boxes.iter_mut().enumerate() {
+            let rect = rect_for_shape(sh);
+            let center = Point { x: xs[i], y: ys[i] };
+            set_rect(sh, center, rect.width(), rect.height());
+        }
+
+        reanchor(&mut items, ids);
+        for item in &mut items {
+            if let Item::Shape(sh) = item {
+                if sh.kind == ShapeKind::Arrow && ids.contains(&sh.id) {
+                    straighten(sh);
+                }
+            }
+        }
+        // Ends anchored toward the other end move when that end does.
+        reanchor(&mut items, ids);
+
+        let edit = self.items_edit(items);
+        self.apply_all([edit])
+    }
+}
+
+// Stores the routed position of every attached arrow end touched by `ids`.
+fn reanchor(items: &mut [Item], ids: &[u64]) {
+    let routes = render_arrows_for(items, ids, &RoutingConfig::default());
+    for item in items {
+        let Item::Shape(sh) = item else { continue };
+        let Some(route) = routes.iter().find(|r| r.shape_id == sh.id) else {
+            continue;
+        };
+        if sh.start_attach_id.is_some() {
+            sh.start = route.start;
+        }
+        if sh.end_attach_id.is_some() {
+            sh.end = route.end;
+        }
+    }
+}
+
+fn set_rect(sh: &mut Shape, center: Point, width: f32, height: f32) {
+    sh.start = Point {
+        x: center.x - width * 0.5,
+        y: center.y - height * 0.5,
+    };
+    sh.end = Point {
+        x: center.x + width * 0.5,
+        y: center.y + height * 0.5,
+    };
+}
+
+// Replaces each value by the mean of its cluster, where a cluster is a run of
+// sorted values each within the tolerance of the run's first value.
+fn cluster_means(values: Vec<f32>) -> Vec<f32> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|a, b| values[*a].total_cmp(&values[*b]));
+    let mut out = values.clone();
+    let mut run_start = 0;
+    for i in 1..=order.len() {
+        let ends =
+            i == order.len() || values[order[i]] - values[order[run_start]] > TIDY_ALIGN_TOLERANCE;
+        if ends {
+            let run = &order[run_start..i];
+            let mean = run.iter().map(|j| values[*j]).sum::<f32>() / run.len() as f32;
+            for j in run {
+                out[*j] = mean;
+            }
+            run_start = i;
+        }
+    }
+    out
+}
+
+// Levels or plumbs a nearly axis-aligned arrow by moving its free end.
+// Arrows attached at both ends are left to the router.
+fn straighten(sh: &mut Shape) {
+    let (dx, dy) = (sh.end.x - sh.start.x, sh.end.y - sh.start.y);
+    let limit = TIDY_STRAIGHTEN_DEGREES.to_radians().tan();
+    let level = dy.abs() <= dx.abs() * limit;
+    let plumb = dx.abs() <= dy.abs() * limit;
+    if !(level || plumb) || (dx == 0.0 && dy == 0.0) {
+        return;
+    }
+    let (anchor, free) = match (sh.start_attach_id, sh.end_attach_id) {
+        (_, None) => (sh.start, &mut sh.end),
+        (None, Some(_)) => (sh.end, &mut sh.start),
+        (Some(_), Some(_)) => return,
+    };
+    if level {
+        free.y = anchor.y;
+    } else {
+        free.x = anchor.x;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::render::render_arrows;
+    use crate::test_util::style;
+
+    #[test]
+    fn tidies_sizes_axes_and_arrows_in_one_step() {
+        let mut store = Store::new();
+        let style = style();
+        let mut add = |kind, start: Point, end: Point, attach: Option<u64>| {
+            let mut sh = store.begin_shape(kind, style, start);
+            sh.end = end;
+            sh.end_attach_id = attach;
+            let id = sh.id;
+            store.commit_shape(sh).unwrap();
+            id
+        };
+        // Centers at x 50 and 53, sizes 37x22 and 42x18.
+        let a = add(
+            ShapeKind::Rectangle,
+            Point { x: 31.5, y: 0.0 },
+            Point { x: 68.5, y: 22.0 },
+            None,
+        );
+        let b = add(
+            ShapeKind::Rectangle,
+            Point { x: 32.0, y: 100.0 },
+            Point { x: 74.0, y: 118.0 },
+            None,
+        );
+        let free = add(
+            ShapeKind::Arrow,
+            Point { x: 200.0, y: 0.0 },
+            Point { x: 300.0, y: 6.0 },
+            None,
+        );
+        let link = add(
+            ShapeKind::Arrow,
+            Point { x: 51.0, y: 60.0 },
+            Point { x: 0.0, y: 0.0 },
+            Some(b),
+        );
+
+        assert!(store.tidy(&[a, b, free, link]));
+        let shape = |store: &Store, id| match store.item(id) {
+            Some(Item::Shape(sh)) => sh.clone(),
+            _ => unreachable!(),
+        };
+        let (ra, rb) = (
+            rect_for_shape(&shape(&store, a)),
+            rect_for_shape(&shape(&store, b)),
+        );
+        assert_eq!((ra.width(), ra.height()), (40.0, 24.0));
+        assert_eq!((rb.width(), rb.height()), (40.0, 16.0));
+        assert_eq!(ra.center().x, 51.5);
+        assert_eq!(rb.center().x, 51.5);
+        assert_eq!(shape(&store, free).end.y, 0.0);
+        let routed = render_arrows(store.items());
+        let routed_end = routed.iter().find(|r| r.shape_id == link).unwrap().end;
+        assert_eq!(shape(&store, link).end, routed_end);
+        assert!((shape(&store, link).start.x - routed_end.x).abs() < 0.1);
+
+        store.undo().unwrap();
+        assert_eq!(shape(&store, free).end.y, 6.0);
+    }
+}
@@ -0,0 +1,61 @@
+use super::{Document, Store};
+
+impl Store {
+    /// Ids of the items whose drawing began in `[from_ms, to_ms)` on the
+    /// shell's clock, in stacking order. Items without a timestamp are never
+    /// in range.
+    pub fn items_in_time_range(&self, from_ms: u64, to_ms: u64) -> Vec<u64> {
+        self.items
+            .iter()
+            .filter(|item| in_range(item.created_ms(), from_ms, to_ms))
+            .map(|item| item.id())
+            .collect()
+    }
+
+    /// The document with only the items drawn in `[from_ms, to_ms)`, for
+    /// rendering or exporting one stretch of a session. Arrows attached to a
+    /// shape outside the range keep their stored endpoints.
+    pub fn document_in_time_range(&self, from_ms: u64, to_ms: u64) -> Document {
+        let mut doc = self.document();
+        doc.items
+            .retain(|item| in_range(item.created_ms(), from_ms, to_ms));
+        doc
+    }
+}
+
+fn in_range(created_ms: Option<u64>, from_ms: u64, to_ms: u64) -> bool {
+    created_ms.is_some_and(|t| from_ms <= t && t < to_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::SvgOptions;
+    use crate::model::Point;
+    use crate::test_util::black;
+
+    #[test]
+    fn filters_items_by_when_they_were_drawn() {
+        let mut store = Store::new();
+        let c = black();
+        let mut ids = Vec::new();
+        for created_ms in [None, Some(60_000), Some(200_000), Some(300_000)] {
+            let mut s = store.begin_stroke(c, 2.0, Point { x: 0.0, y: 0.0 });
+            s.points.push(Point { x: 10.0, y: 10.0 });
+            s.created_ms = created_ms;
+            ids.push(s.id);
+            store.commit_stroke(s).unwrap();
+        }
+
+        // Minutes 3 to 5.
+        assert_eq!(store.items_in_time_range(180_000, 300_000), [ids[2]]);
+        assert_eq!(store.items_in_time_range(0, u64::MAX), ids[1..]);
+        assert!(store.items_in_time_range(300_001, 300_000).is_empty());
+
+        let doc = store.document_in_time_range(60_000, 200_001);
+        assert_eq!(doc.items.len(), 2);
+        let json = Store::from_json(&store.to_json().unwrap()).unwrap();
+        assert_eq!(json.items[2].created_ms(), Some(200_000));
+        assert!(crate::export::to_svg(&doc, &SvgOptions::default()).contains("<polyline"));
+    }
+}
@@ -0,0 +1,171 @@
+use super::{Store, StoreError};
+use crate::model::{Point, Shape, ShapeStyle, Stroke, Tool, ToolProfile};
+
+impl Store {
+    pub fn tool_profiles(&self) -> &[ToolProfile] {
+        &self.meta.tool_profiles
+    }
+
+    pub fn tool_profile(&self, name: &str) -> Option<&ToolProfile> {
+        self.meta.tool_profiles.iter().find(|p| p.name == name)
+    }
+
+    /// Saves `profile`, replacing any profile with the same name, as one undo
+    /// step. An empty name or a width that is not a positive finite number
+    /// is refused.
+    pub fn save_tool_profile(&mut self, profile: ToolProfile) -> bool {
+        self.merge_tool_profiles(vec![profile]) == 1
+    }
+
+    pub fn remove_tool_profile(&mut self, name: &str) -> bool {
+        let mut meta = self.meta.clone();
+        meta.tool_profiles.retain(|p| p.name != name);
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+
+    /// The profiles as JSON, for keeping them in app-level settings.
+    pub fn export_tool_profiles(&self) -> Result<String, StoreError> {
+        Ok(serde_json::to_string(&self.meta.tool_profiles)?)
+    }
+
+    /// Saves every valid profile from `json` (as written by
+    /// [`Store::export_tool_profiles`]) over same-named ones, as one undo
+    /// step. Returns how many were saved.
+    pub fn import_tool_profiles(&mut self, json: &str) -> Result<usize, StoreError> {
+        let profiles: Vec<ToolProfile> = serde_json::from_str(json)?;
+        Ok(self.merge_tool_profiles(profiles))
+    }
+
+    /// Starts a stroke drawn with the pen, marker or highlighter profile
+    /// `name`. `None` for an unknown name or another kind of tool.
+    pub fn begin_profile_stroke(&mut self, name: &str, start: Point) -> Option<Stroke> {
+        let profile = self.tool_profile(name)?.clone();
+        if !matches!(profile.tool, Tool::Pen | Tool::Marker | Tool::Highlighter) {
+            return None;
+        }
+        let mut stroke = self.begin_stroke(profile.color, profile.width, start);
+        stroke.line = profile.line;
+        stroke.blend_mode = profile.blend_mode;
+        Some(stroke)
+    }
+
+    /// Starts a shape with the shape profile `name`: the kind's saved
+    /// defaults (see [`Store::begin_default_shape`]) with the profile's
+    /// stroke color, width, line and blend mode. `None` for an unknown name
+    /// or another kind of tool.
+    pub fn begin_profile_shape(&mut self, name: &str, start: Point) -> Option<Shape> {
+        let profile = self.tool_profile(name)?.clone();
+        let Tool::Shape { kind } = profile.tool else {
+            return None;
+        };
+        let plain = ShapeStyle {
+            stroke_color: profile.color,
+            stroke_width: profile.width,
+            fill_enabled: false,
+            fill_color: profile.color,
+            hatch_enabled: false,
+            corner_radius: 0.0,
+            stroke_color_ref: None,
+            fill_color_ref: None,
+            line: profile.line,
+            corner_radii: None,
+            curvature: None,
+            head_notch: None,
+        };
+        let mut shape = self.begin_default_shape(kind, plain, start);
+        shape.style.stroke_color = profile.color;
+        shape.style.stroke_color_ref = None;
+        shape.style.stroke_width = profile.width;
+        shape.style.line = profile.line;
+        shape.blend_mode = profile.blend_mode;
+        Some(shape)
+    }
+
+    // Saves the valid `profiles` as one undo step; returns how many.
+    fn merge_tool_profiles(&mut self, profiles: Vec<ToolProfile>) -> usize {
+        let mut meta = self.meta.clone();
+        let mut saved = 0;
+        for profile in profiles {
+            if profile.name.is_empty() || !(profile.width.is_finite() && profile.width > 0.0) {
+                continue;
+            }
+            match meta
+                .tool_profiles
+                .iter_mut()
+                .find(|p| p.name == profile.name)
+            {
+                Some(p) => *p = profile,
+                None => meta.tool_profiles.push(profile),
+            }
+            saved += 1;
+        }
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit]);
+        saved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{BlendMode, ColorRgba8, ShapeKind};
+
+    #[test]
+    fn tool_profiles_start_items_and_travel_as_json() {
+        let yellow = ColorRgba8 {
+            r: 255,
+            g: 220,
+            b: 0,
+            a: 128,
+        };
+        let profile = |name: &str, tool, width| ToolProfile {
+            name: name.to_string(),
+            tool,
+            color: yellow,
+            width,
+            line: Default::default(),
+            blend_mode: BlendMode::Multiply,
+        };
+        let mut store = Store::new();
+        assert!(!store.save_tool_profile(profile("", Tool::Pen, 2.0)));
+        assert!(!store.save_tool_profile(profile("bad", Tool::Pen, 0.0)));
+        assert!(store.save_tool_profile(profile("marker", Tool::Highlighter, 18.0)));
+        assert!(store.save_tool_profile(profile(
+            "box",
+            Tool::Shape {
+                kind: ShapeKind::Rectangle
+            },
+            3.0
+        )));
+        assert!(store.save_tool_profile(profile("eraser", Tool::Eraser, 30.0)));
+
+        let origin = Point { x: 0.0, y: 0.0 };
+        let stroke = store.begin_profile_stroke("marker", origin).unwrap();
+        assert_eq!(
+            (stroke.width, stroke.blend_mode),
+            (18.0, BlendMode::Multiply)
+        );
+        let shape = store.begin_profile_shape("box", origin).unwrap();
+        assert_eq!(
+            (shape.kind, shape.style.stroke_width),
+            (ShapeKind::Rectangle, 3.0)
+        );
+        assert!(store.begin_profile_stroke("eraser", origin).is_none());
+        assert!(store.begin_profile_shape("marker", origin).is_none());
+        assert!(store.begin_profile_stroke("missing", origin).is_none());
+
+        let json = store.export_tool_profiles().unwrap();
+        let mut other = Store::new();
+        assert_eq!(other.import_tool_profiles(&json).unwrap(), 3);
+        assert_eq!(other.tool_profiles(), store.tool_profiles());
+        assert!(other.import_tool_profiles("{").is_err());
+
+        let mut reloaded = Store::new();
+        reloaded.load_document(Store::from_json(&store.to_json().unwrap()).unwrap());
+        assert_eq!(reloaded.tool_profile("eraser").unwrap().width, 30.0);
+        assert!(reloaded.remove_tool_profile("eraser"));
+        reloaded.undo().unwrap();
+        assert_eq!(reloaded.tool_profiles().len(), 3);
+    }
+}
@@ -0,0 +1,404 @@
+use super::{Edit, Store};
+use crate::geometry::{
+    is_boxed_shape, rect_for_shape, rotate_point, union_bounds, FlipAxis, Transform2D,
+};
+use crate::model::{ArcAngles, ArrowRoute, CornerRadii, Item, Point, Shape, ShapeKind};
+use std::f32::consts::PI;
+
+/// Nudges closer together than this (in the shell's millisecond clock) on
+/// the same selection share one undo entry.
+pub const NUDGE_COALESCE_WINDOW_MS: u64 = 1000;
+
+impl Store {
+    /// Moves the given items by (dx, dy) as one undo step.
+    pub fn translate(&mut self, ids: &[u64], dx: f32, dy: f32) -> bool {
+        if (dx == 0.0 && dy == 0.0) || !dx.is_finite() || !dy.is_finite() {
+            return false;
+        }
+        let edit = self.map_items_edit(ids, |item| item.translate(dx, dy));
+        self.apply_all([edit])
+    }
+
+    /// Keyboard nudge. Like [`Store::translate`], but repeated nudges of the
+    /// same selection within [`NUDGE_COALESCE_WINDOW_MS`] of each other merge
+    /// into a single undo entry. `timestamp_ms` is any monotonic clock the
+    /// shell uses for input events.
+    pub fn nudge(&mut self, ids: &[u64], dx: f32, dy: f32, timestamp_ms: u64) -> bool {
+        let coalesce = self.last_nudge.as_ref().is_some_and(|(last_ids, last_ts)| {
+            last_ids == ids && timestamp_ms.saturating_sub(*last_ts) <= NUDGE_COALESCE_WINDOW_MS
+        });
+
+        // Replaces the previous nudge's entry with one spanning both moves,
+        // recorded through `apply` like any other edit.
+        let merges = matches!(self.undo.last(), Some(Edit::ReplaceAll { .. }));
+        if coalesce && merges && dx.is_finite() && dy.is_finite() {
+            let Some(Edit::ReplaceAll { after, .. }) =
+                self.map_items_edit(ids, |item| item.translate(dx, dy))
+            else {
+                return false;
+            };
+            let Some(Edit::ReplaceAll { before, .. }) = self.undo.pop() else {
+                return false;
+            };
+            self.apply(Edit::ReplaceAll { before, after });
+            self.last_nudge = Some((ids.to_vec(), timestamp_ms));
+            return true;
+        }
+
+        if !self.translate(ids, dx, dy) {
+            return false;
+        }
+        self.last_nudge = Some((ids.to_vec(), timestamp_ms));
+        true
+    }
+    /// Mirrors the given items about the center of their combined bounds, as
+    /// one undo step.
+    ///
+    /// Arrows attached to a flipped shape have their attachment UVs mirrored
+    /// too, so connectors stay on the same (mirrored) spot of the shape.
+    pub fn flip(&mut self, ids: &[u64], axis: FlipAxis) -> bool {
+        let Some(bounds) = union_bounds(self.items.iter().filter(|it| ids.contains(&it.id())))
+        else {
+            return false;
+        };
+        let c = bounds.center();
+        let mirror = |p: Point| match axis {
+            FlipAxis::Horizontal => Point {
+                x: 2.0 * c.x - p.x,
+                y: p.y,
+            },
+            FlipAxis::Vertical => Point {
+                x: p.x,
+                y: 2.0 * c.y - p.y,
+            },
+        };
+        let mirror_uv = |uv: Point| match axis {
+            FlipAxis::Horizontal => Point {
+                x: 1.0 - uv.x,
+                y: uv.y,
+            },
+            FlipAxis::Vertical => Point {
+                x: uv.x,
+                y: 1.0 - uv.y,
+            },
+        };
+
+        let mut items = self.items.to_vec();
+        for item in &mut items {
+            if ids.contains(&item.id()) {
+                match item {
+                    Item::Stroke(s) => {
+                        for p in &mut s.points {
+                            *p = mirror(*p);
+                        }
+                    }
+                    Item::Shape(sh) => {
+                        sh.start = mirror(sh.start);
+                        sh.end = mirror(sh.end);
+                        sh.rotation = -sh.rotation;
+                        sh.style.corner_radii = sh.style.corner_radii.map(|r| match axis {
+                            FlipAxis::Horizontal => CornerRadii {
+                                top_left: r.top_right,
+                                top_right: r.top_left,
+                                bottom_right: r.bottom_left,
+                                bottom_left: r.bottom_right,
+                            },
+                            FlipAxis::Vertical => CornerRadii {
+                                top_left: r.bottom_left,
+                                top_right: r.bottom_right,
+                                bottom_right: r.top_right,
+                                bottom_left: r.top_left,
+                            },
+                        });
+                        sh.angles = sh.angles.map(|a| mirror_angles(a, axis));
+                        mirror_curvature(sh);
+                    }
+                }
+            }
+            if let Item::Shape(sh) = item {
+                if sh.start_attach_id.is_some_and(|id| ids.contains(&id)) {
+                    sh.start_attach_uv = sh.start_attach_uv.map(mirror_uv);
+                }
+                if sh.end_attach_id.is_some_and(|id| ids.contains(&id)) {
+                    sh.end_attach_uv = sh.end_attach_uv.map(mirror_uv);
+                }
+            }
+        }
+        let edit = self.items_edit(items);
+        self.apply_all([edit])
+    }
+
+    /// Rotates the given items by `angle` radians about `pivot`, as one undo step.
+    ///
+    /// Strokes and arrow endpoints are rotated point-wise; closed shapes move
+    /// their center and accumulate `rotation`. Attachment UVs are shape-local,
+    /// so arrows attached to a rotated shape follow it without changes.
+    pub fn rotate(&mut self, ids: &[u64], angle: f32, pivot: Point) -> bool {
+        if angle == 0.0 || !angle.is_finite() {
+            return false;
+        }
+        let edit = self.map_items_edit(ids, |item| match item {
+            Item::Stroke(s) => {
+                for p in &mut s.points {
+                    *p = rotate_point(*p, pivot, angle);
+                }
+            }
+            Item::Shape(sh) if is_boxed_shape(sh.kind) => {
+                let rect = rect_for_shape(sh);
+                let c = rect.center();
+                let nc = rotate_point(c, pivot, angle);
+                let (dx, dy) = (nc.x - c.x, nc.y - c.y);
+                sh.start = Point {
+                    x: sh.start.x + dx,
+                    y: sh.start.y + dy,
+                };
+                sh.end = Point {
+                    x: sh.end.x + dx,
+                    y: sh.end.y + dy,
+                };
+                sh.rotation = normalize_angle(sh.rotation + angle);
+            }
+            Item::Shape(sh) => {
+                sh.start = rotate_point(sh.start, pivot, angle);
+                sh.end = rotate_point(sh.end, pivot, angle);
+            }
+        });
+        self.apply_all([edit])
+    }
+
+    /// Applies an affine transform to the given items as one undo step.
+    ///
+    /// Strokes and arrow endpoints are mapped point-wise. Closed shapes map
+    /// their center and local axes; shear is approximated away since shapes
+    /// are stored as rotated rects. Stroke widths are kept as is. When the
+    /// transform mirrors (negative determinant), attachment UVs on
+    /// transformed shapes are mirrored to stay on the same spot.
+    pub fn transform_items(&mut self, ids: &[u64], transform: Transform2D) -> bool {
+        if !transform.is_finite() || transform.determinant().abs() <= f32::EPSILON {
+            return false;
+        }
+        let mirrored = transform.determinant() < 0.0;
+
+        let mut items = self.items.to_vec();
+        for item in &mut items {
+            if ids.contains(&item.id()) {
+                match item {
+                    Item::Stroke(s) => {
+                        for p in &mut s.points {
+                            *p = transform.apply(*p);
+                        }
+                    }
+                    Item::Shape(sh) if is_boxed_shape(sh.kind) => {
+                        let rect = rect_for_shape(sh);
+                        let c = transform.apply(rect.center());
+                        let (sin, cos) = sh.rotation.sin_cos();
+                        let (ux, uy) = transform.apply_vector(cos, sin);
+                        let (vx, vy) = transform.apply_vector(-sin, cos);
+                        let hw = rect.width() * 0.5 * ux.hypot(uy);
+                        let hh = rect.height() * 0.5 * vx.hypot(vy);
+                        sh.start = Point {
+                            x: c.x - hw,
+                            y: c.y - hh,
+                        };
+                        sh.end = Point {
+                            x: c.x + hw,
+                            y: c.y + hh,
+                        };
+                        sh.rotation = normalize_angle(uy.atan2(ux));
+                        if mirrored {
+                            // The local y axis flipped with the transform.
+                            sh.angles = sh.angles.map(|a| mirror_angles(a, FlipAxis::Vertical));
+                        }
+                    }
+                    Item::Shape(sh) => {
+                        sh.start = transform.apply(sh.start);
+                        sh.end = transform.apply(sh.end);
+                        if mirrored {
+                            mirror_curvature(sh);
+                        }
+                    }
+                }
+            }
+            if mirrored {
+                if let Item::Shape(sh) = item {
+                    let mirror_v = |uv: Point| Point {
+                        x: uv.x,
+                        y: 1.0 - uv.y,
+                    };
+                    if sh.start_attach_id.is_some_and(|id| ids.contains(&id)) {
+                        sh.start_attach_uv = sh.start_attach_uv.map(mirror_v);
+                    }
+                    if sh.end_attach_id.is_some_and(|id| ids.contains(&id)) {
+                        sh.end_attach_uv = sh.end_attach_uv.map(mirror_v);
+                    }
+                }
+            }
+        }
+        let edit = self.items_edit(items);
+        self.apply_all([edit])
+    }
+}
+
+// `angles` of a shape mirrored across `axis` in its own frame; the arc keeps
+// running clockwise, so its ends swap.
+fn mirror_angles(angles: ArcAngles, axis: FlipAxis) -> ArcAngles {
+    match axis {
+        FlipAxis::Horizontal => ArcAngles {
+            start: PI - angles.end,
+            end: PI - angles.start,
+        },
+        FlipAxis::Vertical => ArcAngles {
+            start: -angles.end,
+            end: -angles.start,
+        },
+    }
+}
+
+// A mirrored curve bows to the other side of its direction of travel, and
+// so does a kept detour. Curved arrows without an explicit curvature pick
+// their side from the new layout.
+fn mirror_curvature(sh: &mut Shape) {
+    match sh.kind {
+        ShapeKind::SCurve => sh.style.curvature = Some(-sh.style.curvature()),
+        ShapeKind::CurvedArrow => {
+            sh.style.curvature = sh.style.curvature.map(|c| -c);
+            sh.route = sh.route.map(ArrowRoute::mirrored);
+        }
+        _ => {}
+    }
+}
+
+// Wraps an angle into (-PI, PI].
+fn normalize_angle(a: f32) -> f32 {
+    let mut a = a.rem_euclid(2.0 * PI);
+    if a > PI {
+        a -= 2.0 * PI;
+    }
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::style;
+
+    #[test]
+    fn flip_mirrors_points_and_attached_uvs() {
+        let mut store = Store::new();
+        let mut rect = store.begin_shape(ShapeKind::Rectangle, style(), Point { x: 0.0, y: 0.0 });
+        rect.end = Point { x: 100.0, y: 50.0 };
+        let rect_id = rect.id;
+        store.commit_shape(rect).unwrap();
+
+        let mut arrow = store.begin_shape(ShapeKind::Arrow, style(), Point { x: 100.0, y: 10.0 });
+        arrow.end = Point { x: 200.0, y: 10.0 };
+        arrow.start_attach_id = Some(rect_id);
+        arrow.start_attach_uv = Some(Point { x: 1.0, y: 0.2 });
+        store.commit_shape(arrow).unwrap();
+
+        let mut s = store.begin_stroke(style().stroke_color, 2.0, Point { x: 10.0, y: 0.0 });
+        s.points.push(Point { x: 30.0, y: 0.0 });
+        let stroke_id = s.id;
+        store.commit_stroke(s).unwrap();
+
+        assert!(store.flip(&[rect_id, stroke_id], FlipAxis::Horizontal));
+        let Item::Shape(arrow) = &store.items()[1] else {
+            panic!("expected arrow")
+        };
+        assert_eq!(arrow.start_attach_uv, Some(Point { x: 0.0, y: 0.2 }));
+        let Item::Stroke(s) = &store.items()[2] else {
+            panic!("expected stroke")
+        };
+        assert_eq!(s.points[0], Point { x: 90.0, y: 0.0 });
+        assert_eq!(s.points[1], Point { x: 70.0, y: 0.0 });
+
+        store.undo().unwrap();
+        let Item::Stroke(s) = &store.items()[2] else {
+            panic!("expected stroke")
+        };
+        assert_eq!(s.points[0], Point { x: 10.0, y: 0.0 });
+    }
+
+    #[test]
+    fn rotate_moves_attached_arrow_with_shape() {
+        let mut store = Store::new();
+        let mut rect =
+            store.begin_shape(ShapeKind::Rectangle, style(), Point { x: -50.0, y: -10.0 });
+        rect.end = Point { x: 50.0, y: 10.0 };
+        let rect_id = rect.id;
+        store.commit_shape(rect).unwrap();
+
+        let mut arrow = store.begin_shape(ShapeKind::Arrow, style(), Point { x: 0.0, y: 0.0 });
+        arrow.end = Point { x: 0.0, y: 200.0 };
+        arrow.start_attach_id = Some(rect_id);
+        // Middle of the right edge.
+        arrow.start_attach_uv = Some(Point { x: 1.0, y: 0.5 });
+        store.commit_shape(arrow).unwrap();
+
+        assert!(store.rotate(&[rect_id], PI / 2.0, Point { x: 0.0, y: 0.0 }));
+        let Item::Shape(sh) = &store.items()[0] else {
+            panic!("expected rect")
+        };
+        assert!((sh.rotation - PI / 2.0).abs() < 1e-5);
+
+        // The right edge midpoint now faces down.
+        let renders = crate::render::render_arrows(store.items());
+        assert!((renders[0].start.x - 0.0).abs() < 1e-3);
+        assert!((renders[0].start.y - 50.0).abs() < 1e-3);
+
+        store.undo().unwrap();
+        let Item::Shape(sh) = &store.items()[0] else {
+            panic!("expected rect")
+        };
+        assert_eq!(sh.rotation, 0.0);
+    }
+
+    #[test]
+    fn nudges_within_window_share_one_undo_entry() {
+        let mut store = Store::new();
+        let s = store.begin_stroke(style().stroke_color, 2.0, Point { x: 0.0, y: 0.0 });
+        let id = s.id;
+        store.commit_stroke(s).unwrap();
+
+        for i in 0..40 {
+            assert!(store.nudge(&[id], 1.0, 0.0, i * 100));
+        }
+        // A pause starts a new entry.
+        assert!(store.nudge(&[id], 0.0, 1.0, 10_000));
+
+        let Item::Stroke(s) = &store.items()[0] else {
+            panic!("expected stroke")
+        };
+        assert_eq!(s.points[0], Point { x: 40.0, y: 1.0 });
+
+        store.undo().unwrap();
+        store.undo().unwrap();
+        let Item::Stroke(s) = &store.items()[0] else {
+            panic!("expected stroke")
+        };
+        assert_eq!(s.points[0], Point { x: 0.0, y: 0.0 });
+        assert_eq!(store.items().len(), 1);
+    }
+
+    #[test]
+    fn transform_scales_about_pivot() {
+        let mut store = Store::new();
+        let mut rect = store.begin_shape(ShapeKind::Rectangle, style(), Point { x: 10.0, y: 10.0 });
+        rect.end = Point { x: 30.0, y: 20.0 };
+        let rect_id = rect.id;
+        store.commit_shape(rect).unwrap();
+
+        let t = Transform2D::scale_about(2.0, 3.0, Point { x: 10.0, y: 10.0 });
+        assert!(store.transform_items(&[rect_id], t));
+        let Item::Shape(sh) = &store.items()[0] else {
+            panic!("expected rect")
+        };
+        let r = rect_for_shape(sh);
+        assert!((r.min_x - 10.0).abs() < 1e-4 && (r.min_y - 10.0).abs() < 1e-4);
+        assert!((r.max_x - 50.0).abs() < 1e-4 && (r.max_y - 40.0).abs() < 1e-4);
+        assert_eq!(sh.rotation, 0.0);
+
+        assert!(t.inverse().is_some());
+        assert!(!store.transform_items(&[rect_id], Transform2D::scale(0.0, 1.0)));
+    }
+}
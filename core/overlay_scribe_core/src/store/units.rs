@@ -0,0 +1,59 @@
+use super::Store;
+use crate::model::DocumentUnits;
+
+impl Store {
+    pub fn units(&self) -> DocumentUnits {
+        self.meta.units
+    }
+
+    /// Sets the physical size of a document unit as one undo step. A scale
+    /// that is not a positive finite number is refused.
+    pub fn set_units(&mut self, units: DocumentUnits) -> bool {
+        if !(units.scale.is_finite() && units.scale > 0.0) {
+            return false;
+        }
+        let mut meta = self.meta.clone();
+        meta.units = units;
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Rect;
+    use crate::model::LengthUnit;
+    use crate::SvgOptions;
+
+    #[test]
+    fn units_round_trip_and_size_the_svg() {
+        let mut store = Store::new();
+        let units = DocumentUnits {
+            unit: LengthUnit::Mm,
+            scale: 4.0,
+        };
+        assert!(!store.set_units(DocumentUnits {
+            scale: 0.0,
+            ..units
+        }));
+        assert!(store.set_units(units));
+        assert!(!store.set_units(units));
+
+        let loaded = Store::from_json(&store.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.meta.units, units);
+
+        let svg = store.export_svg(&SvgOptions {
+            region: Some(Rect {
+                min_x: 0.0,
+                min_y: 0.0,
+                max_x: 840.0,
+                max_y: 1188.0,
+            }),
+        });
+        assert!(svg.contains(r#"width="210mm" height="297mm" viewBox="0 0 840 1188""#));
+
+        store.undo().unwrap();
+        assert!(store.units().is_default());
+    }
+}
@@ -0,0 +1,95 @@
+use super::Store;
+use crate::model::{NamedView, Point};
+
+impl Store {
+    pub fn views(&self) -> &[NamedView] {
+        &self.meta.views
+    }
+
+    pub fn view(&self, name: &str) -> Option<&NamedView> {
+        self.meta.views.iter().find(|v| v.name == name)
+    }
+
+    /// Saves a viewport bookmark, replacing any view with the same name.
+    /// Refused for a non-finite center or a zoom that isn't positive.
+    pub fn save_view(&mut self, name: &str, center: Point, zoom: f32) -> bool {
+        if !(center.x.is_finite() && center.y.is_finite() && zoom.is_finite() && zoom > 0.0) {
+            return false;
+        }
+        let mut meta = self.meta.clone();
+        match meta.views.iter_mut().find(|v| v.name == name) {
+            Some(v) => {
+                v.center = center;
+                v.zoom = zoom;
+            }
+            None => meta.views.push(NamedView {
+                name: name.to_string(),
+                center,
+                zoom,
+            }),
+        }
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+
+    /// Renames a view; fails if `new_name` is already taken.
+    pub fn rename_view(&mut self, name: &str, new_name: &str) -> bool {
+        if name != new_name && self.view(new_name).is_some() {
+            return false;
+        }
+        let mut meta = self.meta.clone();
+        let Some(view) = meta.views.iter_mut().find(|v| v.name == name) else {
+            return false;
+        };
+        view.name = new_name.to_string();
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+
+    pub fn remove_view(&mut self, name: &str) -> bool {
+        let mut meta = self.meta.clone();
+        meta.views.retain(|v| v.name != name);
+        let edit = self.meta_edit(meta);
+        self.apply_all([edit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn views_save_rename_remove_and_undo() {
+        let mut store = Store::new();
+        let center = Point { x: 40.0, y: 30.0 };
+        assert!(store.save_view("Overview", center, 1.0));
+        assert!(store.save_view("Detail", Point { x: 0.0, y: 0.0 }, 4.0));
+        assert!(store.save_view("Overview", center, 0.5));
+        assert_eq!(store.views().len(), 2);
+        assert_eq!(store.view("Overview").unwrap().zoom, 0.5);
+
+        let nan = Point {
+            x: f32::NAN,
+            y: 0.0,
+        };
+        assert!(!store.save_view("Broken", nan, 1.0));
+        assert!(!store.save_view("Broken", center, 0.0));
+        assert!(!store.save_view("Broken", center, f32::INFINITY));
+
+        assert!(!store.rename_view("Detail", "Overview"));
+        assert!(!store.rename_view("Missing", "Other"));
+        assert!(store.rename_view("Detail", "Close-up"));
+        assert!(store.view("Detail").is_none());
+
+        let loaded = Store::from_json(&store.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.meta.views, store.views());
+
+        assert!(store.remove_view("Overview"));
+        assert_eq!(store.views().len(), 1);
+        store.undo().unwrap();
+        assert_eq!(store.view("Overview").unwrap().center, center);
+        store.undo().unwrap();
+        assert!(store.view("Detail").is_some());
+        assert!(store.view("Close-up").is_none());
+    }
+}
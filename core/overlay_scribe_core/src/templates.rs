@@ -0,0 +1,92 @@
+//! Named template documents ("code review markers", "UX heuristics", ...)
+//! that shells stamp into the current board with
+//! [`Store::instantiate_template`].
+
+use crate::store::{Document, Store, StoreError};
+
+/// Templates by name, in insertion order. Lives beside the store rather than
+/// in it: templates are app assets, not part of any one document.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateLibrary {
+    templates: Vec<(String, Document)>,
+}
+
+impl TemplateLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `template` under `name`, replacing any template of that name.
+    pub fn insert(&mut self, name: &str, template: Document) {
+        match self.templates.iter_mut().find(|(n, _)| n == name) {
+            Some((_, existing)) => *existing = template,
+            None => self.templates.push((name.to_string(), template)),
+        }
+    }
+
+    /// Parses `json` (any version [`Store::from_json`] reads) and adds it.
+    pub fn insert_json(&mut self, name: &str, json: &str) -> Result<(), StoreError> {
+        self.insert(name, Store::from_json(json)?);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.templates.len();
+        self.templates.retain(|(n, _)| n != name);
+        self.templates.len() != before
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Document> {
+        self.templates
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, doc)| doc)
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.templates.iter().map(|(n, _)| n.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Item, Point, ShapeKind};
+    use crate::test_util::style;
+
+    #[test]
+    fn instantiates_with_fresh_ids_offset_and_attachments() {
+        let style = style();
+        let mut source = Store::new();
+        let mut rect = source.begin_shape(ShapeKind::Rectangle, style, Point { x: 0.0, y: 0.0 });
+        rect.end = Point { x: 20.0, y: 20.0 };
+        let rect_id = rect.id;
+        source.commit_shape(rect).unwrap();
+        let mut arrow = source.begin_shape(ShapeKind::Arrow, style, Point { x: 50.0, y: 50.0 });
+        arrow.start_attach_id = Some(rect_id);
+        source.commit_shape(arrow).unwrap();
+
+        let mut library = TemplateLibrary::new();
+        library
+            .insert_json("marker", &source.to_json().unwrap())
+            .unwrap();
+        assert_eq!(library.names(), ["marker"]);
+
+        let mut store = Store::new();
+        let first = store.instantiate_template(library.get("marker").unwrap(), 100.0, 0.0);
+        let second = store.instantiate_template(library.get("marker").unwrap(), 0.0, 100.0);
+        assert_eq!(store.items().len(), 4);
+        assert!(first.iter().all(|id| !second.contains(id)));
+
+        let Some(Item::Shape(copy)) = store.item(second[1]) else {
+            panic!("expected the arrow");
+        };
+        assert_eq!(copy.start_attach_id, Some(second[0]));
+        assert_eq!(copy.start, Point { x: 50.0, y: 150.0 });
+
+        store.undo().unwrap();
+        assert_eq!(store.items().len(), 2);
+        assert!(library.remove("marker"));
+        assert!(library.get("marker").is_none());
+    }
+}
@@ -0,0 +1,31 @@
+//! Fixtures shared by the unit tests.
+
+use crate::model::{ColorRgba8, ShapeStyle};
+
+pub(crate) fn black() -> ColorRgba8 {
+    ColorRgba8 {
+        r: 0,
+        g: 0,
+        b: 0,
+        a: 255,
+    }
+}
+
+/// A plain black outline two units wide, with every optional attribute
+/// unset. Tests adjust it with struct update syntax.
+pub(crate) fn style() -> ShapeStyle {
+    ShapeStyle {
+        stroke_color: black(),
+        stroke_width: 2.0,
+        fill_enabled: false,
+        fill_color: black(),
+        hatch_enabled: false,
+        corner_radius: 0.0,
+        stroke_color_ref: None,
+        fill_color_ref: None,
+        line: Default::default(),
+        corner_radii: None,
+        curvature: None,
+        head_notch: None,
+    }
+}
@@ -0,0 +1,119 @@
+//! Opt-in timing spans for diagnosing slow operations on large documents.
+//!
+//! With the `trace` feature, spans around arrow routing, serialization and
+//! edits are reported to the sink installed with [`set_span_sink`], with
+//! size fields such as item and byte counts. Without the feature, spans
+//! compile to nothing. Records never leave the process unless the sink sends
+//! them somewhere.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A finished span.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanRecord {
+    pub name: &'static str,
+    pub duration: Duration,
+    pub fields: Vec<(&'static str, u64)>,
+}
+
+/// Shared so a span can call the sink without holding the lock, which lets
+/// the sink itself replace or remove the sink.
+pub type SpanSink = Arc<dyn Fn(&SpanRecord) + Send + Sync>;
+
+#[cfg(feature = "trace")]
+mod imp {
+    use super::{SpanRecord, SpanSink};
+    use std::sync::RwLock;
+    use std::time::Instant;
+
+    static SINK: RwLock<Option<SpanSink>> = RwLock::new(None);
+
+    pub fn set_span_sink(sink: Option<SpanSink>) {
+        *SINK.write().unwrap_or_else(|e| e.into_inner()) = sink;
+    }
+
+    pub(crate) struct Span {
+        name: &'static str,
+        start: Instant,
+        fields: Vec<(&'static str, u64)>,
+    }
+
+    impl Span {
+        pub(crate) fn enter(name: &'static str) -> Self {
+            Self {
+                name,
+                start: Instant::now(),
+                fields: Vec::new(),
+            }
+        }
+
+        pub(crate) fn record(&mut self, key: &'static str, value: u64) {
+            self.fields.push((key, value));
+        }
+    }
+
+    impl Drop for Span {
+        fn drop(&mut self) {
+            let sink = SINK.read().unwrap_or_else(|e| e.into_inner()).clone();
+            if let Some(sink) = sink {
+                sink(&SpanRecord {
+                    name: self.name,
+                    duration: self.start.elapsed(),
+                    fields: std::mem::take(&mut self.fields),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "trace"))]
+mod imp {
+    use super::SpanSink;
+
+    /// Has no effect without the `trace` feature.
+    pub fn set_span_sink(_sink: Option<SpanSink>) {}
+
+    pub(crate) struct Span;
+
+    impl Span {
+        #[inline(always)]
+        pub(crate) fn enter(_name: &'static str) -> Self {
+            Span
+        }
+
+        #[inline(always)]
+        pub(crate) fn record(&mut self, _key: &'static str, _value: u64) {}
+    }
+}
+
+/// Installs (or with `None`, removes) the process-wide span sink.
+pub use imp::set_span_sink;
+pub(crate) use imp::Span;
+
+#[cfg(all(test, feature = "trace"))]
+mod tests {
+    use super::*;
+    use crate::store::Store;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn spans_reach_the_sink_with_fields() {
+        let seen: Arc<Mutex<Vec<SpanRecord>>> = Arc::default();
+        let sink = seen.clone();
+        set_span_sink(Some(Arc::new(move |r| {
+            sink.lock().unwrap().push(r.clone())
+        })));
+        let json = Store::new().to_json().unwrap();
+        set_span_sink(None);
+
+        let seen = seen.lock().unwrap();
+        let span = seen.iter().find(|r| r.name == "store.to_json").unwrap();
+        assert!(span.fields.contains(&("bytes", json.len() as u64)));
+
+        // The lock is released before the sink runs, so it may replace itself.
+        set_span_sink(Some(Arc::new(|_| set_span_sink(None))));
+        Store::new().to_json().unwrap();
+        Store::new().to_json().unwrap();
+    }
+}
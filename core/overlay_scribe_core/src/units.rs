@@ -0,0 +1,85 @@
+//! Reading and writing measurements in a document's [`DocumentUnits`], so a
+//! "2cm" label means the same length on every platform, and converting
+//! on-screen sizes back to document units.
+
+use crate::model::{DocumentUnits, LengthUnit};
+
+// Decimals kept when a length is formatted.
+const FORMAT_DECIMALS: usize = 2;
+
+/// `length` (document units) labelled in the document's unit, e.g. `"20 mm"`,
+/// with at most two decimals and no trailing zeros.
+pub fn format_length(units: &DocumentUnits, length: f32) -> String {
+    let value = units.to_unit(length, units.unit);
+    let mut text = format!("{value:.FORMAT_DECIMALS$}");
+    if text.contains('.') {
+        text.truncate(text.trim_end_matches('0').trim_end_matches('.').len());
+    }
+    if text == "-0" {
+        text = "0".to_string();
+    }
+    format!("{text} {}", units.unit.suffix())
+}
+
+/// Parses a measurement such as `"2cm"`, `"12 pt"` or `"1.5in"` into document
+/// units. A bare number is in the document's unit. `None` if the text is not
+/// a finite number followed by px, pt, mm, cm or in.
+pub fn parse_length(units: &DocumentUnits, text: &str) -> Option<f32> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(text.len());
+    let (number, suffix) = text.split_at(split);
+    let value: f32 = number.trim().parse().ok()?;
+    if !value.is_finite() {
+        return None;
+    }
+    let inches = match suffix.to_ascii_lowercase().as_str() {
+        "" => units.unit.inches(),
+        "px" => LengthUnit::Px.inches(),
+        "pt" => LengthUnit::Pt.inches(),
+        "mm" => LengthUnit::Mm.inches(),
+        "cm" => LengthUnit::Mm.inches() * 10.0,
+        "in" => 1.0,
+        _ => return None,
+    };
+    Some(value * inches / units.unit.inches() * units.scale)
+}
+
+/// A hit-test or eraser `radius` in document units. With a `view_scale`
+/// (screen pixels per document unit, i.e. the zoom) `radius` is in screen
+/// pixels, so it covers the same patch of screen at any zoom; a scale that
+/// isn't positive and finite is ignored.
+pub fn document_radius(radius: f32, view_scale: Option<f32>) -> f32 {
+    match view_scale {
+        Some(scale) if scale.is_finite() && scale > 0.0 => radius / scale,
+        _ => radius,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lengths_convert_between_units_and_labels() {
+        // A screenshot at 4 document units per millimetre.
+        let units = DocumentUnits {
+            unit: LengthUnit::Mm,
+            scale: 4.0,
+        };
+        assert_eq!(parse_length(&units, "2cm"), Some(80.0));
+        assert_eq!(parse_length(&units, " 5 "), Some(20.0));
+        assert!((parse_length(&units, "1in").unwrap() - 101.6).abs() < 1e-3);
+        assert_eq!(parse_length(&units, "2 furlongs"), None);
+        assert_eq!(parse_length(&units, "cm"), None);
+        assert_eq!(format_length(&units, 80.0), "20 mm");
+        assert_eq!(format_length(&units, 10.0), "2.5 mm");
+        assert!((units.to_unit(101.6, LengthUnit::Pt) - 72.0).abs() < 1e-3);
+        assert!((units.from_unit(72.0, LengthUnit::Pt) - 101.6).abs() < 1e-3);
+
+        let px = DocumentUnits::default();
+        assert_eq!(format_length(&px, 12.0), "12 px");
+        assert_eq!(parse_length(&px, "72pt"), Some(96.0));
+    }
+}
@@ -0,0 +1,216 @@
+//! Consistency checks for loaded documents, and a repair pass for files
+//! written by older or buggy clients.
+
+use crate::model::{Item, Point, Shape, Stroke};
+use crate::store::Document;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Problem {
+    // A later item reuses an earlier item's id.
+    DuplicateId(u64),
+    // A coordinate, width or angle is NaN or infinite.
+    NonFinite(u64),
+    EmptyStroke(u64),
+    DanglingAttachment { arrow: u64, target: u64 },
+    UnknownLayer { item: u64, layer: u32 },
+    UnknownPaletteColor { item: u64, color: u32 },
+}
+
+/// Every problem in `doc`, in item order.
+pub fn validate(doc: &Document) -> Vec<Problem> {
+    let ids: HashSet<u64> = doc.items.iter().map(Item::id).collect();
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+    for item in &doc.items {
+        let id = item.id();
+        if !seen.insert(id) {
+            out.push(Problem::DuplicateId(id));
+        }
+        if !is_finite(item) {
+            out.push(Problem::NonFinite(id));
+        }
+        if let Some(layer) = item.layer_id() {
+            if !doc.meta.layers.iter().any(|l| l.id == layer) {
+                out.push(Problem::UnknownLayer { item: id, layer });
+            }
+        }
+        for color in color_refs(item).into_iter().flatten() {
+            if !doc.meta.palette.iter().any(|c| c.id == color) {
+                out.push(Problem::UnknownPaletteColor { item: id, color });
+            }
+        }
+        match item {
+            Item::Stroke(s) if s.points.is_empty() => out.push(Problem::EmptyStroke(id)),
+            Item::Stroke(_) => {}
+            Item::Shape(sh) => {
+                for target in [sh.start_attach_id, sh.end_attach_id].into_iter().flatten() {
+                    if !ids.contains(&target) {
+                        out.push(Problem::DanglingAttachment { arrow: id, target });
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Fixes what [`validate`] reports: drops non-finite items and empty
+/// strokes, renumbers duplicate ids and clears references to missing
+/// attachments, layers and palette colors. Returns the number of fixes.
+pub fn repair(doc: &mut Document) -> usize {
+    let before = doc.items.len();
+    doc.items
+        .retain(|item| is_finite(item) && !matches!(item, Item::Stroke(s) if s.points.is_empty()));
+    let mut fixes = before - doc.items.len();
+
+    let mut next_id = doc.items.iter().map(Item::id).max().unwrap_or(0);
+    let mut seen = HashSet::new();
+    for item in &mut doc.items {
+        if !seen.insert(item.id()) {
+            next_id = next_id.saturating_add(1);
+            match item {
+                Item::Stroke(s) => s.id = next_id,
+                Item::Shape(sh) => sh.id = next_id,
+            }
+            seen.insert(next_id);
+            fixes += 1;
+        }
+    }
+
+    let meta = &doc.meta;
+    let layer_ok = |layer: Option<u32>| layer.is_none_or(|l| meta.layers.iter().any(|x| x.id == l));
+    let color_ok =
+        |color: Option<u32>| color.is_none_or(|c| meta.palette.iter().any(|x| x.id == c));
+    let mut fix = |ok: bool, clear: &mut dyn FnMut()| {
+        if !ok {
+            clear();
+            fixes += 1;
+        }
+    };
+    for item in &mut doc.items {
+        match item {
+            Item::Stroke(s) => {
+                fix(layer_ok(s.layer_id), &mut || s.layer_id = None);
+                fix(color_ok(s.color_ref), &mut || s.color_ref = None);
+            }
+            Item::Shape(sh) => {
+                fix(layer_ok(sh.layer_id), &mut || sh.layer_id = None);
+                fix(color_ok(sh.style.stroke_color_ref), &mut || {
+                    sh.style.stroke_color_ref = None
+                });
+                fix(color_ok(sh.style.fill_color_ref), &mut || {
+                    sh.style.fill_color_ref = None
+                });
+                let start_ok = sh.start_attach_id.is_none_or(|id| seen.contains(&id));
+                fix(start_ok, &mut || {
+                    sh.start_attach_id = None;
+                    sh.start_attach_uv = None;
+                });
+                let end_ok = sh.end_attach_id.is_none_or(|id| seen.contains(&id));
+                fix(end_ok, &mut || {
+                    sh.end_attach_id = None;
+                    sh.end_attach_uv = None;
+                });
+            }
+        }
+    }
+    fixes
+}
+
+fn color_refs(item: &Item) -> [Option<u32>; 2] {
+    match item {
+        Item::Stroke(s) => [s.color_ref, None],
+        Item::Shape(sh) => [sh.style.stroke_color_ref, sh.style.fill_color_ref],
+    }
+}
+
+fn is_finite(item: &Item) -> bool {
+    match item {
+        Item::Stroke(s) => stroke_is_finite(s),
+        Item::Shape(sh) => shape_is_finite(sh),
+    }
+}
+
+pub(crate) fn stroke_is_finite(s: &Stroke) -> bool {
+    s.width.is_finite() && s.points.iter().all(point_is_finite)
+}
+
+pub(crate) fn shape_is_finite(sh: &Shape) -> bool {
+    point_is_finite(&sh.start)
+        && point_is_finite(&sh.end)
+        && sh.rotation.is_finite()
+        && sh.style.stroke_width.is_finite()
+        && sh.style.corner_radius.is_finite()
+        && sh.style.corner_radii.is_none_or(|r| r.is_finite())
+        && sh.style.curvature.is_none_or(f32::is_finite)
+        && sh.style.head_notch.is_none_or(f32::is_finite)
+        && sh
+            .angles
+            .is_none_or(|a| a.start.is_finite() && a.end.is_finite())
+        && sh.route.is_none_or(|r| r.is_finite())
+        && [sh.start_attach_uv, sh.end_attach_uv]
+            .iter()
+            .flatten()
+            .all(point_is_finite)
+}
+
+fn point_is_finite(p: &Point) -> bool {
+    p.x.is_finite() && p.y.is_finite()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ShapeKind, ShapeStyle};
+    use crate::store::Store;
+    use crate::test_util::{black, style};
+
+    #[test]
+    fn repair_fixes_everything_validate_reports() {
+        let mut store = Store::new();
+        let c = black();
+        let style = ShapeStyle {
+            stroke_color_ref: Some(7),
+            ..style()
+        };
+        let s = store.begin_stroke(c, 2.0, Point { x: 0.0, y: 0.0 });
+        let stroke_id = s.id;
+        store.commit_stroke(s).unwrap();
+        let mut arrow = store.begin_shape(ShapeKind::Arrow, style, Point { x: 0.0, y: 0.0 });
+        arrow.end_attach_id = Some(99);
+        let arrow_id = arrow.id;
+        let mut doc = store.document();
+        doc.items.push(Item::Shape(arrow));
+        let mut dup = doc.items[0].clone();
+        dup.set_link(Some("dup".into()));
+        doc.items.push(dup);
+        let mut bad = store.begin_stroke(c, f32::NAN, Point { x: 0.0, y: 0.0 });
+        bad.layer_id = Some(3);
+        let bad_id = bad.id;
+        doc.items.push(Item::Stroke(bad));
+
+        assert_eq!(
+            validate(&doc),
+            [
+                Problem::UnknownPaletteColor {
+                    item: arrow_id,
+                    color: 7
+                },
+                Problem::DanglingAttachment {
+                    arrow: arrow_id,
+                    target: 99
+                },
+                Problem::DuplicateId(stroke_id),
+                Problem::NonFinite(bad_id),
+                Problem::UnknownLayer {
+                    item: bad_id,
+                    layer: 3
+                },
+            ]
+        );
+        assert_eq!(repair(&mut doc), 4);
+        assert!(validate(&doc).is_empty());
+        assert_eq!(doc.items.len(), 3);
+    }
+}
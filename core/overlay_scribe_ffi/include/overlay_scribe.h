@@ -0,0 +1,278 @@
+/* Generated by cbindgen from core/overlay_scribe_ffi/src/capi.rs.
+ * Regenerate with scripts/gen-c-header.sh; do not edit by hand. */
+
+#ifndef OVERLAY_SCRIBE_H
+#define OVERLAY_SCRIBE_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Result of a fallible C API call.
+ */
+typedef enum OscStatus {
+  OscStatus_Ok = 0,
+  /**
+   * A null handle or buffer, non-UTF-8 input, or a too-small buffer.
+   */
+  OscStatus_InvalidArgument = 1,
+  OscStatus_CannotUndo = 2,
+  OscStatus_CannotRedo = 3,
+  OscStatus_ParseError = 4,
+  OscStatus_SerializationError = 5,
+  OscStatus_InvalidPattern = 6,
+  OscStatus_Cancelled = 7,
+  /**
+   * The document limits refuse the new item.
+   */
+  OscStatus_LimitExceeded = 8,
+  /**
+   * Corrupt item data: NaN coordinates, a non-positive width, ...
+   */
+  OscStatus_InvalidInput = 9,
+} OscStatus;
+
+typedef enum OscShapeKind {
+  OscShapeKind_Rectangle = 0,
+  OscShapeKind_RoundedRectangle = 1,
+  OscShapeKind_Ellipse = 2,
+  OscShapeKind_Arrow = 3,
+  OscShapeKind_CurvedArrow = 4,
+  OscShapeKind_Arc = 5,
+  OscShapeKind_Pie = 6,
+  OscShapeKind_SCurve = 7,
+} OscShapeKind;
+
+typedef enum OscPathKind {
+  OscPathKind_Line = 0,
+  OscPathKind_Quadratic = 1,
+  OscPathKind_Cubic = 2,
+} OscPathKind;
+
+typedef struct CoreDocument CoreDocument;
+
+typedef struct OscColor {
+  uint8_t r;
+  uint8_t g;
+  uint8_t b;
+  uint8_t a;
+} OscColor;
+
+typedef struct OscPoint {
+  float x;
+  float y;
+} OscPoint;
+
+/**
+ * The plain part of a shape style; line caps, dashes, per-corner radii,
+ * curvature and notches keep their defaults.
+ */
+typedef struct OscShapeStyle {
+  struct OscColor stroke_color;
+  float stroke_width;
+  bool fill_enabled;
+  struct OscColor fill_color;
+  bool hatch_enabled;
+  float corner_radius;
+} OscShapeStyle;
+
+/**
+ * A curve from `start` to `end`. A quadratic's control point is `c1`;
+ * controls a kind doesn't use are zero.
+ */
+typedef struct OscPath {
+  enum OscPathKind kind;
+  struct OscPoint start;
+  struct OscPoint c1;
+  struct OscPoint c2;
+  struct OscPoint end;
+} OscPath;
+
+/**
+ * How to draw one arrow: stroke `shaft`, then fill the first `head_len`
+ * points of `head`. Tails and labels are only available through UniFFI.
+ */
+typedef struct OscArrowRender {
+  uint64_t shape_id;
+  struct OscColor stroke_color;
+  float stroke_width;
+  /**
+   * The whole route, ending at the arrowhead's tip.
+   */
+  struct OscPath path;
+  /**
+   * The stroked part of `path`, ending at the head's base.
+   */
+  struct OscPath shaft;
+  /**
+   * Closed head outline: tip, left barb, notch if any, right barb.
+   */
+  struct OscPoint head[4];
+  uint32_t head_len;
+  bool head_even_odd;
+} OscArrowRender;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates an empty document. Release it with [`osc_document_free`].
+ */
+struct CoreDocument *osc_document_new(void);
+
+/**
+ * # Safety
+ * `doc` must be null or a handle from [`osc_document_new`] that has not
+ * been freed yet.
+ */
+void osc_document_free(struct CoreDocument *doc);
+
+/**
+ * Releases a string returned by this API.
+ *
+ * # Safety
+ * `s` must be null or a string returned by this API that has not been
+ * freed yet.
+ */
+void osc_string_free(char *s);
+
+/**
+ * Replaces the document with the given JSON.
+ *
+ * # Safety
+ * `doc` must be a live handle and `json` a NUL-terminated string.
+ */
+enum OscStatus osc_document_load_json(const struct CoreDocument *doc, const char *json);
+
+/**
+ * The document as JSON, or null on failure.
+ *
+ * # Safety
+ * `doc` must be a live handle.
+ */
+char *osc_document_to_json(const struct CoreDocument *doc);
+
+/**
+ * # Safety
+ * `doc` must be a live handle.
+ */
+uint64_t osc_document_item_count(const struct CoreDocument *doc);
+
+/**
+ * # Safety
+ * `doc` must be a live handle.
+ */
+enum OscStatus osc_document_undo(const struct CoreDocument *doc);
+
+/**
+ * # Safety
+ * `doc` must be a live handle.
+ */
+enum OscStatus osc_document_redo(const struct CoreDocument *doc);
+
+/**
+ * # Safety
+ * `doc` must be a live handle.
+ */
+void osc_document_clear(const struct CoreDocument *doc);
+
+/**
+ * Moves the `len` items in `ids` by (dx, dy) as one undo step. Returns
+ * false if nothing moved.
+ *
+ * # Safety
+ * `doc` must be a live handle and `ids` point to `len` ids (or be null
+ * when `len` is 0).
+ */
+bool osc_document_translate(const struct CoreDocument *doc,
+                            const uint64_t *ids,
+                            uintptr_t len,
+                            float dx,
+                            float dy);
+
+/**
+ * Adds a stroke through the `len` points in `points` as one undo step and
+ * writes its id to `out_id` (if not null). Nothing is added unless the
+ * result is `Ok`.
+ *
+ * # Safety
+ * `doc` must be a live handle, `points` point to `len` points and `out_id`
+ * be null or writable.
+ */
+enum OscStatus osc_document_commit_stroke(const struct CoreDocument *doc,
+                                          struct OscColor color,
+                                          float width,
+                                          const struct OscPoint *points,
+                                          uintptr_t len,
+                                          uint64_t *out_id);
+
+/**
+ * Adds a `kind` shape from `start` to `end` as one undo step and writes its
+ * id to `out_id` (if not null). Nothing is added unless the result is `Ok`.
+ *
+ * # Safety
+ * `doc` must be a live handle, `kind` one of the [`OscShapeKind`] values
+ * and `out_id` be null or writable.
+ */
+enum OscStatus osc_document_commit_shape(const struct CoreDocument *doc,
+                                         enum OscShapeKind kind,
+                                         struct OscShapeStyle style,
+                                         struct OscPoint start,
+                                         struct OscPoint end,
+                                         uint64_t *out_id);
+
+/**
+ * Deletes the `len` items in `ids` as one undo step. Returns false if
+ * nothing was deleted.
+ *
+ * # Safety
+ * `doc` must be a live handle and `ids` point to `len` ids (or be null
+ * when `len` is 0).
+ */
+bool osc_document_remove_items(const struct CoreDocument *doc, const uint64_t *ids, uintptr_t len);
+
+/**
+ * Routes every arrow in the document into `out`, which holds `capacity`
+ * renders, and writes how many there are to `out_count`. With too small a
+ * buffer (e.g. null and 0, to ask for the count) nothing is written to
+ * `out` and the result is `InvalidArgument`.
+ *
+ * # Safety
+ * `doc` must be a live handle, `out` point to `capacity` writable renders
+ * (or be null when `capacity` is 0) and `out_count` be writable.
+ */
+enum OscStatus osc_document_render_arrows(const struct CoreDocument *doc,
+                                          struct OscArrowRender *out,
+                                          uintptr_t capacity,
+                                          uintptr_t *out_count);
+
+/**
+ * SVG of the whole document, or null on failure.
+ *
+ * # Safety
+ * `doc` must be a live handle.
+ */
+char *osc_document_export_svg(const struct CoreDocument *doc);
+
+/**
+ * Rasterizes tile (`col`, `row`) of `size` pixels into `out` as straight
+ * alpha RGBA8, row-major. `out_len` must be at least `size * size * 4`.
+ *
+ * # Safety
+ * `doc` must be a live handle and `out` point to `out_len` writable bytes.
+ */
+enum OscStatus osc_document_render_tile(const struct CoreDocument *doc,
+                                        int32_t col,
+                                        int32_t row,
+                                        uint32_t size,
+                                        uint8_t *out,
+                                        uintptr_t out_len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* OVERLAY_SCRIBE_H */
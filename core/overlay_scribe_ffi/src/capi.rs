@@ -0,0 +1,654 @@
+//! Plain C ABI over [`CoreDocument`], for hosts without UniFFI support (game
+//! engines, C++ capture tools). `include/overlay_scribe.h` declares these.
+//!
+//! Documents are opaque heap handles. Strings going in are NUL-terminated
+//! UTF-8; strings coming out are owned by the caller and released with
+//! [`osc_string_free`]. Fallible calls return an [`OscStatus`].
+
+use crate::types::{
+    CoreDocument, FfiArrowPath, FfiArrowPathKind, FfiArrowRender, FfiColorRgba8, FfiError,
+    FfiFillRule, FfiPoint, FfiRect, FfiShapeKind, FfiShapeStyle,
+};
+use std::ffi::{c_char, CStr, CString};
+
+/// Result of a fallible C API call.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OscStatus {
+    Ok = 0,
+    /// A null handle or buffer, non-UTF-8 input, or a too-small buffer.
+    InvalidArgument = 1,
+    CannotUndo = 2,
+    CannotRedo = 3,
+    ParseError = 4,
+    SerializationError = 5,
+    InvalidPattern = 6,
+    Cancelled = 7,
+    /// The document limits refuse the new item.
+    LimitExceeded = 8,
+    /// Corrupt item data: NaN coordinates, a non-positive width, ...
+    InvalidInput = 9,
+}
+
+impl From<FfiError> for OscStatus {
+    fn from(value: FfiError) -> Self {
+        match value {
+            FfiError::CannotUndo => OscStatus::CannotUndo,
+            FfiError::CannotRedo => OscStatus::CannotRedo,
+            FfiError::ParseError { .. } => OscStatus::ParseError,
+            FfiError::SerializationError { .. } => OscStatus::SerializationError,
+            FfiError::InvalidPattern { .. } => OscStatus::InvalidPattern,
+            FfiError::Cancelled => OscStatus::Cancelled,
+            FfiError::LimitExceeded { .. } => OscStatus::LimitExceeded,
+            FfiError::InvalidInput { .. } => OscStatus::InvalidInput,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct OscPoint {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl From<OscPoint> for FfiPoint {
+    fn from(value: OscPoint) -> Self {
+        Self {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
+impl From<FfiPoint> for OscPoint {
+    fn from(value: FfiPoint) -> Self {
+        Self {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OscColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl From<OscColor> for FfiColorRgba8 {
+    fn from(value: OscColor) -> Self {
+        Self {
+            r: value.r,
+            g: value.g,
+            b: value.b,
+            a: value.a,
+        }
+    }
+}
+
+impl From<FfiColorRgba8> for OscColor {
+    fn from(value: FfiColorRgba8) -> Self {
+        Self {
+            r: value.r,
+            g: value.g,
+            b: value.b,
+            a: value.a,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OscShapeKind {
+    Rectangle = 0,
+    RoundedRectangle = 1,
+    Ellipse = 2,
+    Arrow = 3,
+    CurvedArrow = 4,
+    Arc = 5,
+    Pie = 6,
+    SCurve = 7,
+}
+
+impl From<OscShapeKind> for FfiShapeKind {
+    fn from(value: OscShapeKind) -> Self {
+        match value {
+            OscShapeKind::Rectangle => FfiShapeKind::Rectangle,
+            OscShapeKind::RoundedRectangle => FfiShapeKind::RoundedRectangle,
+            OscShapeKind::Ellipse => FfiShapeKind::Ellipse,
+            OscShapeKind::Arrow => FfiShapeKind::Arrow,
+            OscShapeKind::CurvedArrow => FfiShapeKind::CurvedArrow,
+            OscShapeKind::Arc => FfiShapeKind::Arc,
+            OscShapeKind::Pie => FfiShapeKind::Pie,
+            OscShapeKind::SCurve => FfiShapeKind::SCurve,
+        }
+    }
+}
+
+/// The plain part of a shape style; line caps, dashes, per-corner radii,
+/// curvature and notches keep their defaults.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OscShapeStyle {
+    pub stroke_color: OscColor,
+    pub stroke_width: f32,
+    pub fill_enabled: bool,
+    pub fill_color: OscColor,
+    pub hatch_enabled: bool,
+    pub corner_radius: f32,
+}
+
+impl From<OscShapeStyle> for FfiShapeStyle {
+    fn from(value: OscShapeStyle) -> Self {
+        Self {
+            stroke_color: value.stroke_color.into(),
+            stroke_width: value.stroke_width,
+            fill_enabled: value.fill_enabled,
+            fill_color: value.fill_color.into(),
+            hatch_enabled: value.hatch_enabled,
+            corner_radius: value.corner_radius,
+            stroke_color_ref: None,
+            fill_color_ref: None,
+            line: overlay_scribe_core::LineStyle::default().into(),
+            corner_radii: None,
+            curvature: None,
+            head_notch: None,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OscPathKind {
+    #[default]
+    Line = 0,
+    Quadratic = 1,
+    Cubic = 2,
+}
+
+/// A curve from `start` to `end`. A quadratic's control point is `c1`;
+/// controls a kind doesn't use are zero.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct OscPath {
+    pub kind: OscPathKind,
+    pub start: OscPoint,
+    pub c1: OscPoint,
+    pub c2: OscPoint,
+    pub end: OscPoint,
+}
+
+impl OscPath {
+    fn new(path: FfiArrowPath, start: FfiPoint, end: FfiPoint) -> Self {
+        Self {
+            kind: match path.kind {
+                FfiArrowPathKind::Line => OscPathKind::Line,
+                FfiArrowPathKind::Quadratic => OscPathKind::Quadratic,
+                FfiArrowPathKind::Cubic => OscPathKind::Cubic,
+            },
+            start: start.into(),
+            c1: path.c1.map(Into::into).unwrap_or_default(),
+            c2: path.c2.map(Into::into).unwrap_or_default(),
+            end: end.into(),
+        }
+    }
+}
+
+/// How to draw one arrow: stroke `shaft`, then fill the first `head_len`
+/// points of `head`. Tails and labels are only available through UniFFI.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct OscArrowRender {
+    pub shape_id: u64,
+    pub stroke_color: OscColor,
+    pub stroke_width: f32,
+    /// The whole route, ending at the arrowhead's tip.
+    pub path: OscPath,
+    /// The stroked part of `path`, ending at the head's base.
+    pub shaft: OscPath,
+    /// Closed head outline: tip, left barb, notch if any, right barb.
+    pub head: [OscPoint; 4],
+    pub head_len: u32,
+    pub head_even_odd: bool,
+}
+
+impl From<FfiArrowRender> for OscArrowRender {
+    fn from(value: FfiArrowRender) -> Self {
+        let mut head = [OscPoint::default(); 4];
+        for (slot, p) in head.iter_mut().zip(value.head_polygon.iter().cloned()) {
+            *slot = p.into();
+        }
+        Self {
+            shape_id: value.shape_id,
+            stroke_color: value.style.stroke_color.into(),
+            stroke_width: value.style.stroke_width,
+            path: OscPath::new(value.path, value.start.clone(), value.end),
+            shaft: OscPath::new(value.shaft, value.start, value.shaft_end),
+            head,
+            head_len: value.head_polygon.len().min(head.len()) as u32,
+            head_even_odd: matches!(value.head_fill_rule, FfiFillRule::EvenOdd),
+        }
+    }
+}
+
+fn status(result: Result<(), FfiError>) -> OscStatus {
+    result.map_or_else(Into::into, |()| OscStatus::Ok)
+}
+
+// Hands `s` to the caller; null if it contains a NUL byte.
+fn into_c_string(s: String) -> *mut c_char {
+    CString::new(s).map_or(std::ptr::null_mut(), CString::into_raw)
+}
+
+/// Creates an empty document. Release it with [`osc_document_free`].
+#[no_mangle]
+pub extern "C" fn osc_document_new() -> *mut CoreDocument {
+    Box::into_raw(Box::new(CoreDocument::new()))
+}
+
+/// # Safety
+/// `doc` must be null or a handle from [`osc_document_new`] that has not
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn osc_document_free(doc: *mut CoreDocument) {
+    if !doc.is_null() {
+        drop(Box::from_raw(doc));
+    }
+}
+
+/// Releases a string returned by this API.
+///
+/// # Safety
+/// `s` must be null or a string returned by this API that has not been
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn osc_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Replaces the document with the given JSON.
+///
+/// # Safety
+/// `doc` must be a live handle and `json` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn osc_document_load_json(
+    doc: *const CoreDocument,
+    json: *const c_char,
+) -> OscStatus {
+    let (Some(doc), false) = (doc.as_ref(), json.is_null()) else {
+        return OscStatus::InvalidArgument;
+    };
+    let Ok(json) = CStr::from_ptr(json).to_str() else {
+        return OscStatus::InvalidArgument;
+    };
+    status(doc.load_json(json.to_string()))
+}
+
+/// The document as JSON, or null on failure.
+///
+/// # Safety
+/// `doc` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn osc_document_to_json(doc: *const CoreDocument) -> *mut c_char {
+    match doc.as_ref().map(CoreDocument::to_json) {
+        Some(Ok(json)) => into_c_string(json),
+        _ => std::ptr::null_mut(),
+    }
+}
+
+/// # Safety
+/// `doc` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn osc_document_item_count(doc: *const CoreDocument) -> u64 {
+    doc.as_ref().map_or(0, |doc| doc.items().len() as u64)
+}
+
+/// # Safety
+/// `doc` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn osc_document_undo(doc: *const CoreDocument) -> OscStatus {
+    doc.as_ref()
+        .map_or(OscStatus::InvalidArgument, |doc| status(doc.undo()))
+}
+
+/// # Safety
+/// `doc` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn osc_document_redo(doc: *const CoreDocument) -> OscStatus {
+    doc.as_ref()
+        .map_or(OscStatus::InvalidArgument, |doc| status(doc.redo()))
+}
+
+/// # Safety
+/// `doc` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn osc_document_clear(doc: *const CoreDocument) {
+    if let Some(doc) = doc.as_ref() {
+        doc.clear_all();
+    }
+}
+
+/// Moves the `len` items in `ids` by (dx, dy) as one undo step. Returns
+/// false if nothing moved.
+///
+/// # Safety
+/// `doc` must be a live handle and `ids` point to `len` ids (or be null
+/// when `len` is 0).
+#[no_mangle]
+pub unsafe extern "C" fn osc_document_translate(
+    doc: *const CoreDocument,
+    ids: *const u64,
+    len: usize,
+    dx: f32,
+    dy: f32,
+) -> bool {
+    let Some(doc) = doc.as_ref() else {
+        return false;
+    };
+    if len == 0 || ids.is_null() {
+        return false;
+    }
+    let ids = std::slice::from_raw_parts(ids, len).to_vec();
+    doc.translate(ids, dx, dy)
+}
+
+/// Adds a stroke through the `len` points in `points` as one undo step and
+/// writes its id to `out_id` (if not null). Nothing is added unless the
+/// result is `Ok`.
+///
+/// # Safety
+/// `doc` must be a live handle, `points` point to `len` points and `out_id`
+/// be null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn osc_document_commit_stroke(
+    doc: *const CoreDocument,
+    color: OscColor,
+    width: f32,
+    points: *const OscPoint,
+    len: usize,
+    out_id: *mut u64,
+) -> OscStatus {
+    let Some(doc) = doc.as_ref() else {
+        return OscStatus::InvalidArgument;
+    };
+    if len == 0 || points.is_null() {
+        return OscStatus::InvalidArgument;
+    }
+    let points = std::slice::from_raw_parts(points, len);
+    let mut stroke = doc.begin_stroke(color.into(), width, points[0].into());
+    stroke
+        .points
+        .extend(points[1..].iter().map(|&p| FfiPoint::from(p)));
+    let id = stroke.id;
+    let result = status(doc.commit_stroke(stroke));
+    if result == OscStatus::Ok && !out_id.is_null() {
+        *out_id = id;
+    }
+    result
+}
+
+/// Adds a `kind` shape from `start` to `end` as one undo step and writes its
+/// id to `out_id` (if not null). Nothing is added unless the result is `Ok`.
+///
+/// # Safety
+/// `doc` must be a live handle, `kind` one of the [`OscShapeKind`] values
+/// and `out_id` be null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn osc_document_commit_shape(
+    doc: *const CoreDocument,
+    kind: OscShapeKind,
+    style: OscShapeStyle,
+    start: OscPoint,
+    end: OscPoint,
+    out_id: *mut u64,
+) -> OscStatus {
+    let Some(doc) = doc.as_ref() else {
+        return OscStatus::InvalidArgument;
+    };
+    let mut shape = doc.begin_shape(kind.into(), style.into(), start.into());
+    shape.end = end.into();
+    let id = shape.id;
+    let result = status(doc.commit_shape(shape));
+    if result == OscStatus::Ok && !out_id.is_null() {
+        *out_id = id;
+    }
+    result
+}
+
+/// Deletes the `len` items in `ids` as one undo step. Returns false if
+/// nothing was deleted.
+///
+/// # Safety
+/// `doc` must be a live handle and `ids` point to `len` ids (or be null
+/// when `len` is 0).
+#[no_mangle]
+pub unsafe extern "C" fn osc_document_remove_items(
+    doc: *const CoreDocument,
+    ids: *const u64,
+    len: usize,
+) -> bool {
+    let Some(doc) = doc.as_ref() else {
+        return false;
+    };
+    if len == 0 || ids.is_null() {
+        return false;
+    }
+    doc.remove_items(std::slice::from_raw_parts(ids, len).to_vec())
+}
+
+/// Routes every arrow in the document into `out`, which holds `capacity`
+/// renders, and writes how many there are to `out_count`. With too small a
+/// buffer (e.g. null and 0, to ask for the count) nothing is written to
+/// `out` and the result is `InvalidArgument`.
+///
+/// # Safety
+/// `doc` must be a live handle, `out` point to `capacity` writable renders
+/// (or be null when `capacity` is 0) and `out_count` be writable.
+#[no_mangle]
+pub unsafe extern "C" fn osc_document_render_arrows(
+    doc: *const CoreDocument,
+    out: *mut OscArrowRender,
+    capacity: usize,
+    out_count: *mut usize,
+) -> OscStatus {
+    let (Some(doc), false) = (doc.as_ref(), out_count.is_null()) else {
+        return OscStatus::InvalidArgument;
+    };
+    let renders = doc.arrow_renders();
+    *out_count = renders.len();
+    if renders.is_empty() {
+        return OscStatus::Ok;
+    }
+    if out.is_null() || capacity < renders.len() {
+        return OscStatus::InvalidArgument;
+    }
+    let out = std::slice::from_raw_parts_mut(out, renders.len());
+    for (slot, render) in out.iter_mut().zip(renders) {
+        *slot = render.into();
+    }
+    OscStatus::Ok
+}
+
+/// SVG of the whole document, or null on failure.
+///
+/// # Safety
+/// `doc` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn osc_document_export_svg(doc: *const CoreDocument) -> *mut c_char {
+    doc.as_ref().map_or(std::ptr::null_mut(), |doc| {
+        into_c_string(doc.export_svg(None))
+    })
+}
+
+/// Rasterizes tile (`col`, `row`) of `size` pixels into `out` as straight
+/// alpha RGBA8, row-major. `out_len` must be at least `size * size * 4`.
+///
+/// # Safety
+/// `doc` must be a live handle and `out` point to `out_len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn osc_document_render_tile(
+    doc: *const CoreDocument,
+    col: i32,
+    row: i32,
+    size: u32,
+    out: *mut u8,
+    out_len: usize,
+) -> OscStatus {
+    let Some(doc) = doc.as_ref() else {
+        return OscStatus::InvalidArgument;
+    };
+    let needed = (size as usize)
+        .saturating_mul(size as usize)
+        .saturating_mul(4);
+    if size == 0 || out.is_null() || out_len < needed {
+        return OscStatus::InvalidArgument;
+    }
+    let s = size as f32;
+    let rect = FfiRect {
+        min_x: col as f32 * s,
+        min_y: row as f32 * s,
+        max_x: (col as f32 + 1.0) * s,
+        max_y: (row as f32 + 1.0) * s,
+    };
+    let Some(tile) = doc.render_tiles(size, vec![rect]).into_iter().next() else {
+        return OscStatus::InvalidArgument;
+    };
+    std::ptr::copy_nonoverlapping(tile.rgba.as_ptr(), out, needed.min(tile.rgba.len()));
+    OscStatus::Ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{FfiDocumentLimits, FfiInvalidInput, FfiLimit};
+    use std::ptr::{null, null_mut};
+
+    const BLACK: OscColor = OscColor {
+        r: 0,
+        g: 0,
+        b: 0,
+        a: 255,
+    };
+
+    fn style() -> OscShapeStyle {
+        OscShapeStyle {
+            stroke_color: BLACK,
+            stroke_width: 2.0,
+            fill_enabled: false,
+            fill_color: BLACK,
+            hatch_enabled: false,
+            corner_radius: 0.0,
+        }
+    }
+
+    fn point(x: f32, y: f32) -> OscPoint {
+        OscPoint { x, y }
+    }
+
+    #[test]
+    fn errors_map_to_their_own_status() {
+        let cases = [
+            (FfiError::CannotUndo, OscStatus::CannotUndo),
+            (
+                FfiError::LimitExceeded {
+                    limit: FfiLimit::Items,
+                    max: 1,
+                },
+                OscStatus::LimitExceeded,
+            ),
+            (
+                FfiError::InvalidInput {
+                    reason: FfiInvalidInput::NonFinite,
+                },
+                OscStatus::InvalidInput,
+            ),
+        ];
+        for (error, expected) in cases {
+            assert_eq!(OscStatus::from(error), expected);
+        }
+    }
+
+    #[test]
+    fn commit_render_and_json_round_trip() {
+        unsafe {
+            let doc = osc_document_new();
+            let line = [point(0.0, 0.0), point(10.0, 10.0)];
+            let mut stroke_id = 0;
+            assert_eq!(
+                osc_document_commit_stroke(doc, BLACK, 2.0, line.as_ptr(), 2, &mut stroke_id),
+                OscStatus::Ok
+            );
+            let bad = [point(f32::NAN, 0.0)];
+            assert_eq!(
+                osc_document_commit_stroke(doc, BLACK, 2.0, bad.as_ptr(), 1, null_mut()),
+                OscStatus::InvalidInput
+            );
+            assert_eq!(
+                osc_document_commit_stroke(doc, BLACK, 2.0, null(), 0, null_mut()),
+                OscStatus::InvalidArgument
+            );
+
+            let mut arrow_id = 0;
+            assert_eq!(
+                osc_document_commit_shape(
+                    doc,
+                    OscShapeKind::CurvedArrow,
+                    style(),
+                    point(0.0, 100.0),
+                    point(200.0, 100.0),
+                    &mut arrow_id,
+                ),
+                OscStatus::Ok
+            );
+            assert_eq!(osc_document_item_count(doc), 2);
+
+            let mut count = 0;
+            assert_eq!(
+                osc_document_render_arrows(doc, null_mut(), 0, &mut count),
+                OscStatus::InvalidArgument
+            );
+            assert_eq!(count, 1);
+            let mut renders = [OscArrowRender::default(); 1];
+            assert_eq!(
+                osc_document_render_arrows(doc, renders.as_mut_ptr(), 1, &mut count),
+                OscStatus::Ok
+            );
+            let r = renders[0];
+            assert_eq!(r.shape_id, arrow_id);
+            assert_eq!(r.path.start, point(0.0, 100.0));
+            assert_eq!((r.head_len, r.head[0]), (3, r.path.end));
+            assert_eq!(r.shaft.start, r.path.start);
+
+            (*doc).set_limits(FfiDocumentLimits {
+                max_items: Some(2),
+                max_points_per_stroke: None,
+                max_total_points: None,
+            });
+            assert_eq!(
+                osc_document_commit_stroke(doc, BLACK, 2.0, line.as_ptr(), 2, null_mut()),
+                OscStatus::LimitExceeded
+            );
+
+            let json = osc_document_to_json(doc);
+            let copy = osc_document_new();
+            assert_eq!(osc_document_load_json(copy, json), OscStatus::Ok);
+            osc_string_free(json);
+            assert_eq!(osc_document_item_count(copy), 2);
+
+            assert!(osc_document_remove_items(copy, &stroke_id, 1));
+            assert_eq!(osc_document_item_count(copy), 1);
+            assert!(!osc_document_remove_items(copy, &stroke_id, 1));
+            assert_eq!(osc_document_undo(copy), OscStatus::Ok);
+            assert_eq!(osc_document_item_count(copy), 2);
+
+            osc_document_free(copy);
+            osc_document_free(doc);
+        }
+    }
+}
@@ -1,8 +1,31 @@
 uniffi::setup_scaffolding!();
 
+pub mod capi;
 mod types;
 
 pub use types::{
-    CoreDocument, FfiArrowPath, FfiArrowPathKind, FfiArrowRender, FfiColorRgba8, FfiItem, FfiPoint,
-    FfiShape, FfiShapeKind, FfiShapeStyle, FfiStroke,
+    color_from_hex, color_from_hsl, color_from_hsv, color_lighten, color_premultiplied,
+    color_to_hex, color_to_hsl, color_to_hsv, color_unpremultiplied, convex_hull, read_preview,
+    rect_intersection, rects_intersect, reset_session_metrics, segment_intersects_rect,
+    segment_segment_intersection, session_metrics, set_metrics_enabled, set_span_observer,
+    stroke_outline,
+};
+pub use types::{
+    CoreDocument, FfiAlignEdge, FfiAlignmentGuide, FfiAlignmentResult, FfiArcAngles, FfiArrowEnd,
+    FfiArrowPath, FfiArrowPathKind, FfiArrowRender, FfiArrowRoute, FfiArrowTail,
+    FfiAttachCandidate, FfiBackgroundImage, FfiBackgroundPattern, FfiBadgeOrder, FfiBlendMode,
+    FfiCanvas, FfiClosestPoint, FfiColorRgba8, FfiCornerRadii, FfiDocumentLimits,
+    FfiDocumentPreview, FfiDocumentUnits, FfiEndpointAllowance, FfiError, FfiExportObserver,
+    FfiFillRule, FfiFindOptions, FfiFlipAxis, FfiFrame, FfiGuide, FfiGuideOrientation, FfiHalo,
+    FfiHistoryStack, FfiHsl, FfiHsv, FfiImageSource, FfiInvalidInput, FfiInvariantViolation,
+    FfiItem, FfiItemCluster, FfiItemHalo, FfiItemKind, FfiItemSketch, FfiLayer, FfiLengthUnit,
+    FfiLimit, FfiLineCap, FfiLineJoin, FfiLineStyle, FfiLoadObserver, FfiMemoryFootprint,
+    FfiNamedView, FfiNearDuplicate, FfiObstacleHits, FfiObstacleScope, FfiOnionSkin,
+    FfiOnionSkinOptions, FfiPaletteColor, FfiPoint, FfiRect, FfiReplaceScope, FfiReplayOptions,
+    FfiReplayStep, FfiRoutingCandidate, FfiRoutingConfig, FfiRoutingDebug, FfiSessionMetrics,
+    FfiShape, FfiShapeDefaults, FfiShapeGesture, FfiShapeKind, FfiShapeStyle, FfiSketch,
+    FfiSnapConfig, FfiSnapHit, FfiSnapResult, FfiSnapTarget, FfiSnapshot, FfiSpacingAxis,
+    FfiSpanField, FfiSpanObserver, FfiSplitAt, FfiStroke, FfiStylePreset, FfiTailRender,
+    FfiTemplateLibrary, FfiTextMatch, FfiTextRange, FfiTile, FfiTool, FfiToolProfile,
+    FfiTransform2D, FfiWorldPoint,
 };
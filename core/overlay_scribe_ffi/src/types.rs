@@ -1,8 +1,85 @@
+use overlay_scribe_core::analysis::{self, ItemCluster, NearDuplicate};
+use overlay_scribe_core::animate;
+use overlay_scribe_core::compare::{onion_skin, OnionSkin, OnionSkinOptions};
+use overlay_scribe_core::export;
+use overlay_scribe_core::geometry;
+use overlay_scribe_core::geometry::{FlipAxis, Rect, ShapeGesture, Transform2D};
+use overlay_scribe_core::halo::{self, ItemHalo};
+use overlay_scribe_core::metrics::{self, SessionMetrics};
+use overlay_scribe_core::outline;
+use overlay_scribe_core::raster::{self, Tile};
+use overlay_scribe_core::render::{arrow_routing_debug, RoutingDebug};
+use overlay_scribe_core::replay::{self, ReplayOptions, ReplayStep};
+use overlay_scribe_core::sketch::{self, ItemSketch};
+use overlay_scribe_core::snap::{
+    AlignmentGuide, AlignmentResult, GuideOrientation, SnapConfig, SnapHit, SnapResult, SnapTarget,
+};
+use overlay_scribe_core::trace;
+use overlay_scribe_core::units;
 use overlay_scribe_core::{
-    ArrowPath, ArrowRender, ColorRgba8, Document, Item, Point, Shape, ShapeKind, ShapeStyle, Store,
-    Stroke, TextAlignH, TextAlignV,
+    AlignEdge, ArcAngles, ArrowEnd, ArrowPath, ArrowRender, ArrowRoute, ArrowTail, AttachCandidate,
+    BackgroundImage, BackgroundPattern, BadgeOrder, BlendMode, Canvas, ColorRgba8, CornerRadii,
+    DocumentLimits, DocumentPreview, DocumentUnits, EndpointAllowance, FillRule, FindOptions,
+    Frame, Guide, Halo, HistoryStack, ImageSource, InvalidInput, InvariantViolation, Item, Layer,
+    LengthUnit, Limit, LineCap, LineJoin, LineStyle, MemoryFootprint, NamedView, ObstacleScope,
+    PaletteColor, Point, ReplaceScope, RoutingConfig, Shape, ShapeDefaults, ShapeKind, ShapeStyle,
+    Sketch, SpacingAxis, SplitAt, Store, StoreError, Stroke, StylePreset, SvgOptions, TailRender,
+    TemplateLibrary, TextAlignH, TextAlignV, ThemeMap, Tool, ToolProfile, WorldPoint,
 };
-use std::sync::Mutex;
+use std::sync::{Arc, RwLock};
+
+/// Why a document operation failed.
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum FfiError {
+    #[error("nothing to undo")]
+    CannotUndo,
+    #[error("nothing to redo")]
+    CannotRedo,
+    #[error("could not parse document: {message}")]
+    ParseError { message: String },
+    #[error("could not serialize document: {message}")]
+    SerializationError { message: String },
+    #[error("invalid search pattern: {message}")]
+    InvalidPattern { message: String },
+    #[error("cancelled")]
+    Cancelled,
+    #[error("document limit reached: {limit:?} is capped at {max}")]
+    LimitExceeded { limit: FfiLimit, max: u64 },
+    #[error("invalid input: {reason:?}")]
+    InvalidInput { reason: FfiInvalidInput },
+}
+
+impl From<StoreError> for FfiError {
+    fn from(value: StoreError) -> Self {
+        match value {
+            StoreError::CannotUndo => FfiError::CannotUndo,
+            StoreError::CannotRedo => FfiError::CannotRedo,
+            StoreError::Serialization(e) => FfiError::SerializationError {
+                message: e.to_string(),
+            },
+            StoreError::InvalidPattern(message) => FfiError::InvalidPattern { message },
+            StoreError::LimitExceeded { limit, max } => FfiError::LimitExceeded {
+                limit: limit.into(),
+                max: max as u64,
+            },
+            StoreError::InvalidInput(reason) => FfiError::InvalidInput {
+                reason: reason.into(),
+            },
+        }
+    }
+}
+
+impl FfiError {
+    // Loading reports serde failures as parse errors rather than serialization ones.
+    fn parse(error: StoreError) -> Self {
+        match error {
+            StoreError::Serialization(e) => FfiError::ParseError {
+                message: e.to_string(),
+            },
+            other => other.into(),
+        }
+    }
+}
 
 #[derive(Debug, Clone, uniffi::Record)]
 pub struct FfiColorRgba8 {
@@ -58,12 +135,164 @@ impl From<Point> for FfiPoint {
     }
 }
 
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiBlendMode {
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+}
+
+impl From<FfiBlendMode> for BlendMode {
+    fn from(value: FfiBlendMode) -> Self {
+        match value {
+            FfiBlendMode::Normal => BlendMode::Normal,
+            FfiBlendMode::Multiply => BlendMode::Multiply,
+            FfiBlendMode::Screen => BlendMode::Screen,
+            FfiBlendMode::Overlay => BlendMode::Overlay,
+        }
+    }
+}
+
+impl From<BlendMode> for FfiBlendMode {
+    fn from(value: BlendMode) -> Self {
+        match value {
+            BlendMode::Normal => FfiBlendMode::Normal,
+            BlendMode::Multiply => FfiBlendMode::Multiply,
+            BlendMode::Screen => FfiBlendMode::Screen,
+            BlendMode::Overlay => FfiBlendMode::Overlay,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiRect {
+    pub min_x: f32,
+    pub min_y: f32,
+    pub max_x: f32,
+    pub max_y: f32,
+}
+
+impl From<FfiRect> for Rect {
+    fn from(value: FfiRect) -> Self {
+        Self {
+            min_x: value.min_x,
+            min_y: value.min_y,
+            max_x: value.max_x,
+            max_y: value.max_y,
+        }
+    }
+}
+
+impl From<Rect> for FfiRect {
+    fn from(value: Rect) -> Self {
+        Self {
+            min_x: value.min_x,
+            min_y: value.min_y,
+            max_x: value.max_x,
+            max_y: value.max_y,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiWorldPoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl From<FfiWorldPoint> for WorldPoint {
+    fn from(value: FfiWorldPoint) -> Self {
+        Self {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
+impl From<WorldPoint> for FfiWorldPoint {
+    fn from(value: WorldPoint) -> Self {
+        Self {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiLineCap {
+    Butt,
+    Round,
+    Square,
+}
+
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiLineJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiLineStyle {
+    pub cap: FfiLineCap,
+    pub join: FfiLineJoin,
+    // Share of the length tapered from zero width at each end (0 = none).
+    pub taper_start: f32,
+    pub taper_end: f32,
+}
+
+impl From<FfiLineStyle> for LineStyle {
+    fn from(value: FfiLineStyle) -> Self {
+        Self {
+            cap: match value.cap {
+                FfiLineCap::Butt => LineCap::Butt,
+                FfiLineCap::Round => LineCap::Round,
+                FfiLineCap::Square => LineCap::Square,
+            },
+            join: match value.join {
+                FfiLineJoin::Miter => LineJoin::Miter,
+                FfiLineJoin::Round => LineJoin::Round,
+                FfiLineJoin::Bevel => LineJoin::Bevel,
+            },
+            taper_start: value.taper_start,
+            taper_end: value.taper_end,
+        }
+    }
+}
+
+impl From<LineStyle> for FfiLineStyle {
+    fn from(value: LineStyle) -> Self {
+        Self {
+            cap: match value.cap {
+                LineCap::Butt => FfiLineCap::Butt,
+                LineCap::Round => FfiLineCap::Round,
+                LineCap::Square => FfiLineCap::Square,
+            },
+            join: match value.join {
+                LineJoin::Miter => FfiLineJoin::Miter,
+                LineJoin::Round => FfiLineJoin::Round,
+                LineJoin::Bevel => FfiLineJoin::Bevel,
+            },
+            taper_start: value.taper_start,
+            taper_end: value.taper_end,
+        }
+    }
+}
+
 #[derive(Debug, Clone, uniffi::Record)]
 pub struct FfiStroke {
     pub id: u64,
     pub color: FfiColorRgba8,
     pub width: f32,
     pub points: Vec<FfiPoint>,
+    pub line: FfiLineStyle,
+    pub blend_mode: FfiBlendMode,
+    pub color_ref: Option<u32>,
+    pub layer_id: Option<u32>,
+    pub tags: Vec<String>,
+    pub link: Option<String>,
+    pub created_ms: Option<u64>,
 }
 
 impl From<FfiStroke> for Stroke {
@@ -73,6 +302,13 @@ impl From<FfiStroke> for Stroke {
             color: value.color.into(),
             width: value.width,
             points: value.points.into_iter().map(Into::into).collect(),
+            line: value.line.into(),
+            blend_mode: value.blend_mode.into(),
+            color_ref: value.color_ref,
+            layer_id: value.layer_id,
+            tags: value.tags,
+            link: value.link,
+            created_ms: value.created_ms,
         }
     }
 }
@@ -84,6 +320,13 @@ impl From<Stroke> for FfiStroke {
             color: value.color.into(),
             width: value.width,
             points: value.points.into_iter().map(Into::into).collect(),
+            line: value.line.into(),
+            blend_mode: value.blend_mode.into(),
+            color_ref: value.color_ref,
+            layer_id: value.layer_id,
+            tags: value.tags,
+            link: value.link,
+            created_ms: value.created_ms,
         }
     }
 }
@@ -95,6 +338,9 @@ pub enum FfiShapeKind {
     Ellipse,
     Arrow,
     CurvedArrow,
+    Arc,
+    Pie,
+    SCurve,
 }
 
 impl From<FfiShapeKind> for ShapeKind {
@@ -105,6 +351,9 @@ impl From<FfiShapeKind> for ShapeKind {
             FfiShapeKind::Ellipse => ShapeKind::Ellipse,
             FfiShapeKind::Arrow => ShapeKind::Arrow,
             FfiShapeKind::CurvedArrow => ShapeKind::CurvedArrow,
+            FfiShapeKind::Arc => ShapeKind::Arc,
+            FfiShapeKind::Pie => ShapeKind::Pie,
+            FfiShapeKind::SCurve => ShapeKind::SCurve,
         }
     }
 }
@@ -117,6 +366,86 @@ impl From<ShapeKind> for FfiShapeKind {
             ShapeKind::Ellipse => FfiShapeKind::Ellipse,
             ShapeKind::Arrow => FfiShapeKind::Arrow,
             ShapeKind::CurvedArrow => FfiShapeKind::CurvedArrow,
+            ShapeKind::Arc => FfiShapeKind::Arc,
+            ShapeKind::Pie => FfiShapeKind::Pie,
+            ShapeKind::SCurve => FfiShapeKind::SCurve,
+        }
+    }
+}
+
+// Radians clockwise from +x; see `ArcAngles`.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiArcAngles {
+    pub start: f32,
+    pub end: f32,
+}
+
+impl From<FfiArcAngles> for ArcAngles {
+    fn from(value: FfiArcAngles) -> Self {
+        Self {
+            start: value.start,
+            end: value.end,
+        }
+    }
+}
+
+impl From<ArcAngles> for FfiArcAngles {
+    fn from(value: ArcAngles) -> Self {
+        Self {
+            start: value.start,
+            end: value.end,
+        }
+    }
+}
+
+// A curved arrow's kept detour, in the frame of its chord.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiArrowRoute {
+    pub c1: FfiPoint,
+    pub c2: FfiPoint,
+}
+
+impl From<FfiArrowRoute> for ArrowRoute {
+    fn from(value: FfiArrowRoute) -> Self {
+        Self {
+            c1: value.c1.into(),
+            c2: value.c2.into(),
+        }
+    }
+}
+
+impl From<ArrowRoute> for FfiArrowRoute {
+    fn from(value: ArrowRoute) -> Self {
+        Self {
+            c1: value.c1.into(),
+            c2: value.c2.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiArrowTail {
+    Circle,
+    Diamond,
+    Bar,
+}
+
+impl From<FfiArrowTail> for ArrowTail {
+    fn from(value: FfiArrowTail) -> Self {
+        match value {
+            FfiArrowTail::Circle => ArrowTail::Circle,
+            FfiArrowTail::Diamond => ArrowTail::Diamond,
+            FfiArrowTail::Bar => ArrowTail::Bar,
+        }
+    }
+}
+
+impl From<ArrowTail> for FfiArrowTail {
+    fn from(value: ArrowTail) -> Self {
+        match value {
+            ArrowTail::Circle => FfiArrowTail::Circle,
+            ArrowTail::Diamond => FfiArrowTail::Diamond,
+            ArrowTail::Bar => FfiArrowTail::Bar,
         }
     }
 }
@@ -129,6 +458,45 @@ pub struct FfiShapeStyle {
     pub fill_color: FfiColorRgba8,
     pub hatch_enabled: bool,
     pub corner_radius: f32,
+    pub stroke_color_ref: Option<u32>,
+    pub fill_color_ref: Option<u32>,
+    pub line: FfiLineStyle,
+    // Per-corner radii; `None` rounds every corner by `corner_radius`.
+    pub corner_radii: Option<FfiCornerRadii>,
+    // Signed bow of S-curves and curved arrows; `None` is the default.
+    pub curvature: Option<f32>,
+    // Depth of the notch in the back of arrowheads; `None` is a triangle.
+    pub head_notch: Option<f32>,
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiCornerRadii {
+    pub top_left: f32,
+    pub top_right: f32,
+    pub bottom_right: f32,
+    pub bottom_left: f32,
+}
+
+impl From<FfiCornerRadii> for CornerRadii {
+    fn from(value: FfiCornerRadii) -> Self {
+        Self {
+            top_left: value.top_left,
+            top_right: value.top_right,
+            bottom_right: value.bottom_right,
+            bottom_left: value.bottom_left,
+        }
+    }
+}
+
+impl From<CornerRadii> for FfiCornerRadii {
+    fn from(value: CornerRadii) -> Self {
+        Self {
+            top_left: value.top_left,
+            top_right: value.top_right,
+            bottom_right: value.bottom_right,
+            bottom_left: value.bottom_left,
+        }
+    }
 }
 
 #[derive(Debug, Clone, uniffi::Enum)]
@@ -194,6 +562,12 @@ impl From<FfiShapeStyle> for ShapeStyle {
             fill_color: value.fill_color.into(),
             hatch_enabled: value.hatch_enabled,
             corner_radius: value.corner_radius,
+            stroke_color_ref: value.stroke_color_ref,
+            fill_color_ref: value.fill_color_ref,
+            line: value.line.into(),
+            corner_radii: value.corner_radii.map(Into::into),
+            curvature: value.curvature,
+            head_notch: value.head_notch,
         }
     }
 }
@@ -207,6 +581,12 @@ impl From<ShapeStyle> for FfiShapeStyle {
             fill_color: value.fill_color.into(),
             hatch_enabled: value.hatch_enabled,
             corner_radius: value.corner_radius,
+            stroke_color_ref: value.stroke_color_ref,
+            fill_color_ref: value.fill_color_ref,
+            line: value.line.into(),
+            corner_radii: value.corner_radii.map(Into::into),
+            curvature: value.curvature,
+            head_notch: value.head_notch,
         }
     }
 }
@@ -222,9 +602,22 @@ pub struct FfiShape {
     pub end_attach_id: Option<u64>,
     pub start_attach_uv: Option<FfiPoint>,
     pub end_attach_uv: Option<FfiPoint>,
+    pub tail: Option<FfiArrowTail>,
+    // Arrows only: skip detours around other shapes.
+    pub ignore_obstacles: bool,
+    // Curved arrows only: the detour kept by the last reroute.
+    pub route: Option<FfiArrowRoute>,
+    // Span of arcs and pies; `None` is the whole ellipse.
+    pub angles: Option<FfiArcAngles>,
     pub text: String,
     pub text_align_h: FfiTextAlignH,
     pub text_align_v: FfiTextAlignV,
+    pub blend_mode: FfiBlendMode,
+    pub rotation: f32,
+    pub layer_id: Option<u32>,
+    pub tags: Vec<String>,
+    pub link: Option<String>,
+    pub created_ms: Option<u64>,
 }
 
 impl From<FfiShape> for Shape {
@@ -239,9 +632,19 @@ impl From<FfiShape> for Shape {
             end_attach_id: value.end_attach_id,
             start_attach_uv: value.start_attach_uv.map(Into::into),
             end_attach_uv: value.end_attach_uv.map(Into::into),
+            tail: value.tail.map(Into::into),
+            ignore_obstacles: value.ignore_obstacles,
+            route: value.route.map(Into::into),
+            angles: value.angles.map(Into::into),
             text: value.text,
             text_align_h: value.text_align_h.into(),
             text_align_v: value.text_align_v.into(),
+            blend_mode: value.blend_mode.into(),
+            rotation: value.rotation,
+            layer_id: value.layer_id,
+            tags: value.tags,
+            link: value.link,
+            created_ms: value.created_ms,
         }
     }
 }
@@ -258,9 +661,19 @@ impl From<Shape> for FfiShape {
             end_attach_id: value.end_attach_id,
             start_attach_uv: value.start_attach_uv.map(Into::into),
             end_attach_uv: value.end_attach_uv.map(Into::into),
+            tail: value.tail.map(Into::into),
+            ignore_obstacles: value.ignore_obstacles,
+            route: value.route.map(Into::into),
+            angles: value.angles.map(Into::into),
             text: value.text,
             text_align_h: value.text_align_h.into(),
             text_align_v: value.text_align_v.into(),
+            blend_mode: value.blend_mode.into(),
+            rotation: value.rotation,
+            layer_id: value.layer_id,
+            tags: value.tags,
+            link: value.link,
+            created_ms: value.created_ms,
         }
     }
 }
@@ -303,15 +716,109 @@ impl From<ArrowPath> for FfiArrowPath {
     }
 }
 
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiTailRender {
+    Circle { center: FfiPoint, radius: f32 },
+    // Corners: at the arrow start, left, far tip, right.
+    Diamond { points: Vec<FfiPoint> },
+    Bar { a: FfiPoint, b: FfiPoint },
+}
+
+impl From<TailRender> for FfiTailRender {
+    fn from(value: TailRender) -> Self {
+        match value {
+            TailRender::Circle { center, radius } => Self::Circle {
+                center: center.into(),
+                radius,
+            },
+            TailRender::Diamond { points } => Self::Diamond {
+                points: points.into_iter().map(Into::into).collect(),
+            },
+            TailRender::Bar { a, b } => Self::Bar {
+                a: a.into(),
+                b: b.into(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiRoutingConfig {
+    // Stable, fixed-point routing so every platform picks the same path.
+    pub deterministic: bool,
+    // Which closed shapes each arrow routes around.
+    pub obstacles: FfiObstacleScope,
+    // Steer curved arrows off the arrows routed before them.
+    pub avoid_arrows: bool,
+    // How far into attached shapes a path may run; `None` is the default.
+    pub endpoint_allowance: Option<FfiEndpointAllowance>,
+}
+
+impl From<FfiRoutingConfig> for RoutingConfig {
+    fn from(value: FfiRoutingConfig) -> Self {
+        Self {
+            deterministic: value.deterministic,
+            obstacles: value.obstacles.into(),
+            avoid_arrows: value.avoid_arrows,
+            endpoint_allowance: value.endpoint_allowance.map(Into::into).unwrap_or_default(),
+        }
+    }
+}
+
+// See `EndpointAllowance`.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiEndpointAllowance {
+    pub fraction: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl From<FfiEndpointAllowance> for EndpointAllowance {
+    fn from(value: FfiEndpointAllowance) -> Self {
+        Self {
+            fraction: value.fraction,
+            min: value.min,
+            max: value.max,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiObstacleScope {
+    All,
+    SameLayer,
+    SharedTag,
+}
+
+impl From<FfiObstacleScope> for ObstacleScope {
+    fn from(value: FfiObstacleScope) -> Self {
+        match value {
+            FfiObstacleScope::All => ObstacleScope::All,
+            FfiObstacleScope::SameLayer => ObstacleScope::SameLayer,
+            FfiObstacleScope::SharedTag => ObstacleScope::SharedTag,
+        }
+    }
+}
+
 #[derive(Debug, Clone, uniffi::Record)]
 pub struct FfiArrowRender {
     pub shape_id: u64,
     pub style: FfiShapeStyle,
+    pub blend_mode: FfiBlendMode,
     pub start: FfiPoint,
     pub end: FfiPoint,
     pub path: FfiArrowPath,
+    // The stroked part of `path`, ending at the head's base `shaft_end`.
+    pub shaft: FfiArrowPath,
+    pub shaft_end: FfiPoint,
     pub head_left: FfiPoint,
     pub head_right: FfiPoint,
+    // Closed head outline to fill: tip, left barb, notch if any, right barb.
+    pub head_polygon: Vec<FfiPoint>,
+    pub head_fill_rule: FfiFillRule,
+    pub tail: Option<FfiTailRender>,
+    // Center of the arrow's text, kept clear of shape labels.
+    pub label: Option<FfiPoint>,
 }
 
 impl From<ArrowRender> for FfiArrowRender {
@@ -319,158 +826,3194 @@ impl From<ArrowRender> for FfiArrowRender {
         Self {
             shape_id: value.shape_id,
             style: value.style.into(),
+            blend_mode: value.blend_mode.into(),
             start: value.start.into(),
             end: value.end.into(),
             path: value.path.into(),
+            shaft: value.shaft.into(),
+            shaft_end: value.shaft_end.into(),
             head_left: value.head_left.into(),
             head_right: value.head_right.into(),
+            head_polygon: value.head_polygon().into_iter().map(Into::into).collect(),
+            head_fill_rule: value.head_fill_rule.into(),
+            tail: value.tail.map(Into::into),
+            label: value.label.map(Into::into),
         }
     }
 }
 
 #[derive(Debug, Clone, uniffi::Enum)]
-pub enum FfiItem {
-    Stroke(FfiStroke),
-    Shape(FfiShape),
+pub enum FfiFillRule {
+    NonZero,
+    EvenOdd,
 }
 
-impl From<FfiItem> for Item {
-    fn from(value: FfiItem) -> Self {
+impl From<FillRule> for FfiFillRule {
+    fn from(value: FillRule) -> Self {
         match value {
-            FfiItem::Stroke(s) => Item::Stroke(s.into()),
-            FfiItem::Shape(sh) => Item::Shape(sh.into()),
+            FillRule::NonZero => Self::NonZero,
+            FillRule::EvenOdd => Self::EvenOdd,
         }
     }
 }
 
-impl From<Item> for FfiItem {
-    fn from(value: Item) -> Self {
-        match value {
-            Item::Stroke(s) => FfiItem::Stroke(s.into()),
-            Item::Shape(sh) => FfiItem::Shape(sh.into()),
-        }
-    }
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiReplayOptions {
+    // Document units of ink drawn per second.
+    pub speed: f32,
+    // Seconds between items.
+    pub pause: f32,
 }
 
-#[derive(uniffi::Object)]
-pub struct CoreDocument {
-    store: Mutex<Store>,
+impl From<FfiReplayOptions> for ReplayOptions {
+    fn from(value: FfiReplayOptions) -> Self {
+        Self {
+            speed: value.speed,
+            pause: value.pause,
+        }
+    }
 }
 
-impl Default for CoreDocument {
-    fn default() -> Self {
-        Self::new()
-    }
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiReplayStep {
+    pub item_id: u64,
+    pub start: f32,
+    pub duration: f32,
 }
 
-#[uniffi::export]
-impl CoreDocument {
-    #[uniffi::constructor]
-    pub fn new() -> Self {
+impl From<ReplayStep> for FfiReplayStep {
+    fn from(value: ReplayStep) -> Self {
         Self {
-            store: Mutex::new(Store::new()),
+            item_id: value.item_id,
+            start: value.start,
+            duration: value.duration,
         }
     }
+}
 
-    pub fn items(&self) -> Vec<FfiItem> {
-        self.store
-            .lock()
-            .expect("mutex poisoned")
-            .items()
-            .iter()
-            .cloned()
-            .map(Into::into)
-            .collect()
-    }
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiPaletteColor {
+    pub id: u32,
+    pub name: String,
+    pub color: FfiColorRgba8,
+}
 
-    pub fn arrow_renders(&self) -> Vec<FfiArrowRender> {
-        let store = self.store.lock().expect("mutex poisoned");
-        overlay_scribe_core::render::render_arrows(store.items())
-            .into_iter()
-            .map(Into::into)
-            .collect()
+impl From<PaletteColor> for FfiPaletteColor {
+    fn from(value: PaletteColor) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            color: value.color.into(),
+        }
     }
+}
 
-    pub fn begin_stroke(&self, color: FfiColorRgba8, width: f32, start: FfiPoint) -> FfiStroke {
-        self.store
-            .lock()
-            .expect("mutex poisoned")
-            .begin_stroke(color.into(), width, start.into())
-            .into()
-    }
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiFlipAxis {
+    Horizontal,
+    Vertical,
+}
 
-    pub fn commit_stroke(&self, stroke: FfiStroke) {
-        self.store
-            .lock()
-            .expect("mutex poisoned")
-            .commit_stroke(stroke.into());
+impl From<FfiFlipAxis> for FlipAxis {
+    fn from(value: FfiFlipAxis) -> Self {
+        match value {
+            FfiFlipAxis::Horizontal => FlipAxis::Horizontal,
+            FfiFlipAxis::Vertical => FlipAxis::Vertical,
+        }
     }
+}
 
-    pub fn begin_shape(
-        &self,
-        kind: FfiShapeKind,
-        style: FfiShapeStyle,
-        start: FfiPoint,
-    ) -> FfiShape {
-        self.store
-            .lock()
-            .expect("mutex poisoned")
-            .begin_shape(kind.into(), style.into(), start.into())
-            .into()
-    }
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiSplitAt {
+    Fraction { t: f32 },
+    Nearest { point: FfiPoint },
+}
 
-    pub fn commit_shape(&self, shape: FfiShape) {
-        self.store
-            .lock()
-            .expect("mutex poisoned")
-            .commit_shape(shape.into());
+impl From<FfiSplitAt> for SplitAt {
+    fn from(value: FfiSplitAt) -> Self {
+        match value {
+            FfiSplitAt::Fraction { t } => SplitAt::Fraction(t),
+            FfiSplitAt::Nearest { point } => SplitAt::Nearest(point.into()),
+        }
     }
+}
 
-    pub fn erase_at(&self, point: FfiPoint, radius: f32) -> bool {
-        self.store
-            .lock()
-            .expect("mutex poisoned")
-            .erase_at(point.into(), radius)
-    }
+#[derive(Debug, Clone, Copy, uniffi::Enum)]
+pub enum FfiArrowEnd {
+    Start,
+    End,
+}
 
-    pub fn clear_all(&self) {
-        self.store.lock().expect("mutex poisoned").clear_all();
+impl From<FfiArrowEnd> for ArrowEnd {
+    fn from(value: FfiArrowEnd) -> Self {
+        match value {
+            FfiArrowEnd::Start => ArrowEnd::Start,
+            FfiArrowEnd::End => ArrowEnd::End,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiAlignEdge {
+    Left,
+    CenterX,
+    Right,
+    Top,
+    CenterY,
+    Bottom,
+}
+
+impl From<FfiAlignEdge> for AlignEdge {
+    fn from(value: FfiAlignEdge) -> Self {
+        match value {
+            FfiAlignEdge::Left => AlignEdge::Left,
+            FfiAlignEdge::CenterX => AlignEdge::CenterX,
+            FfiAlignEdge::Right => AlignEdge::Right,
+            FfiAlignEdge::Top => AlignEdge::Top,
+            FfiAlignEdge::CenterY => AlignEdge::CenterY,
+            FfiAlignEdge::Bottom => AlignEdge::Bottom,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiSpacingAxis {
+    Horizontal,
+    Vertical,
+}
+
+impl From<FfiSpacingAxis> for SpacingAxis {
+    fn from(value: FfiSpacingAxis) -> Self {
+        match value {
+            FfiSpacingAxis::Horizontal => SpacingAxis::Horizontal,
+            FfiSpacingAxis::Vertical => SpacingAxis::Vertical,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiBadgeOrder {
+    Creation,
+    Spatial,
+    Manual { ids: Vec<u64> },
+}
+
+impl From<FfiBadgeOrder> for BadgeOrder {
+    fn from(value: FfiBadgeOrder) -> Self {
+        match value {
+            FfiBadgeOrder::Creation => BadgeOrder::Creation,
+            FfiBadgeOrder::Spatial => BadgeOrder::Spatial,
+            FfiBadgeOrder::Manual { ids } => BadgeOrder::Manual(ids),
+        }
+    }
+}
+
+/// Affine transform `x' = a·x + c·y + tx`, `y' = b·x + d·y + ty`.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiTransform2D {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub tx: f32,
+    pub ty: f32,
+}
+
+impl From<FfiTransform2D> for Transform2D {
+    fn from(value: FfiTransform2D) -> Self {
+        Self {
+            a: value.a,
+            b: value.b,
+            c: value.c,
+            d: value.d,
+            tx: value.tx,
+            ty: value.ty,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiClosestPoint {
+    pub point: FfiPoint,
+    pub distance: f32,
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiAttachCandidate {
+    pub id: u64,
+    pub uv: FfiPoint,
+    pub anchor: FfiPoint,
+}
+
+impl From<AttachCandidate> for FfiAttachCandidate {
+    fn from(value: AttachCandidate) -> Self {
+        Self {
+            id: value.id,
+            uv: value.uv.into(),
+            anchor: value.anchor.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiSnapConfig {
+    pub grid: Option<f32>,
+    pub items: bool,
+    // Radians.
+    pub angle_step: Option<f32>,
+    pub threshold: f32,
+    pub exclude_ids: Vec<u64>,
+}
+
+impl From<FfiSnapConfig> for SnapConfig {
+    fn from(value: FfiSnapConfig) -> Self {
+        Self {
+            grid: value.grid,
+            items: value.items,
+            angle_step: value.angle_step,
+            threshold: value.threshold,
+            exclude_ids: value.exclude_ids,
+            guides: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiSnapTarget {
+    Grid,
+    Guide { id: u32 },
+    ItemEdge { id: u64 },
+    ItemCenter { id: u64 },
+}
+
+impl From<SnapTarget> for FfiSnapTarget {
+    fn from(value: SnapTarget) -> Self {
+        match value {
+            SnapTarget::Grid => FfiSnapTarget::Grid,
+            SnapTarget::Guide(id) => FfiSnapTarget::Guide { id },
+            SnapTarget::ItemEdge(id) => FfiSnapTarget::ItemEdge { id },
+            SnapTarget::ItemCenter(id) => FfiSnapTarget::ItemCenter { id },
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiGuide {
+    pub id: u32,
+    pub orientation: FfiGuideOrientation,
+    pub position: f32,
+}
+
+impl From<Guide> for FfiGuide {
+    fn from(value: Guide) -> Self {
+        Self {
+            id: value.id,
+            orientation: value.orientation.into(),
+            position: value.position,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiSnapHit {
+    pub value: f32,
+    pub target: FfiSnapTarget,
+}
+
+impl From<SnapHit> for FfiSnapHit {
+    fn from(value: SnapHit) -> Self {
+        Self {
+            value: value.value,
+            target: value.target.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiSnapResult {
+    pub point: FfiPoint,
+    pub x: Option<FfiSnapHit>,
+    pub y: Option<FfiSnapHit>,
+    pub angle: Option<f32>,
+}
+
+impl From<SnapResult> for FfiSnapResult {
+    fn from(value: SnapResult) -> Self {
+        Self {
+            point: value.point.into(),
+            x: value.x.map(Into::into),
+            y: value.y.map(Into::into),
+            angle: value.angle,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiGuideOrientation {
+    Horizontal,
+    Vertical,
+}
+
+impl From<FfiGuideOrientation> for GuideOrientation {
+    fn from(value: FfiGuideOrientation) -> Self {
+        match value {
+            FfiGuideOrientation::Horizontal => GuideOrientation::Horizontal,
+            FfiGuideOrientation::Vertical => GuideOrientation::Vertical,
+        }
+    }
+}
+
+impl From<GuideOrientation> for FfiGuideOrientation {
+    fn from(value: GuideOrientation) -> Self {
+        match value {
+            GuideOrientation::Horizontal => FfiGuideOrientation::Horizontal,
+            GuideOrientation::Vertical => FfiGuideOrientation::Vertical,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiAlignmentGuide {
+    pub orientation: FfiGuideOrientation,
+    pub position: f32,
+    pub start: f32,
+    pub end: f32,
+    pub item_id: u64,
+}
+
+impl From<AlignmentGuide> for FfiAlignmentGuide {
+    fn from(value: AlignmentGuide) -> Self {
+        Self {
+            orientation: value.orientation.into(),
+            position: value.position,
+            start: value.start,
+            end: value.end,
+            item_id: value.item_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiAlignmentResult {
+    pub delta: FfiPoint,
+    pub guides: Vec<FfiAlignmentGuide>,
+}
+
+impl From<AlignmentResult> for FfiAlignmentResult {
+    fn from(value: AlignmentResult) -> Self {
+        Self {
+            delta: value.delta.into(),
+            guides: value.guides.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiStylePreset {
+    pub name: String,
+    pub style: FfiShapeStyle,
+}
+
+impl From<StylePreset> for FfiStylePreset {
+    fn from(value: StylePreset) -> Self {
+        Self {
+            name: value.name,
+            style: value.style.into(),
+        }
+    }
+}
+
+/// Modifiers held while dragging out a shape: `constrain` (shift) keeps
+/// boxes square and arrows at 45-degree steps; `from_center` (alt) grows the
+/// shape around the press point.
+#[derive(Debug, Clone, Copy, uniffi::Record)]
+pub struct FfiShapeGesture {
+    pub constrain: bool,
+    pub from_center: bool,
+}
+
+impl From<FfiShapeGesture> for ShapeGesture {
+    fn from(value: FfiShapeGesture) -> Self {
+        Self {
+            constrain: value.constrain,
+            from_center: value.from_center,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiShapeDefaults {
+    pub kind: FfiShapeKind,
+    pub style: FfiShapeStyle,
+    pub text_align_h: FfiTextAlignH,
+    pub text_align_v: FfiTextAlignV,
+    pub tail: Option<FfiArrowTail>,
+}
+
+impl From<FfiShapeDefaults> for ShapeDefaults {
+    fn from(value: FfiShapeDefaults) -> Self {
+        Self {
+            kind: value.kind.into(),
+            style: value.style.into(),
+            text_align_h: value.text_align_h.into(),
+            text_align_v: value.text_align_v.into(),
+            tail: value.tail.map(Into::into),
+        }
+    }
+}
+
+impl From<ShapeDefaults> for FfiShapeDefaults {
+    fn from(value: ShapeDefaults) -> Self {
+        Self {
+            kind: value.kind.into(),
+            style: value.style.into(),
+            text_align_h: value.text_align_h.into(),
+            text_align_v: value.text_align_v.into(),
+            tail: value.tail.map(Into::into),
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiTool {
+    Pen,
+    Marker,
+    Highlighter,
+    Eraser,
+    Shape { kind: FfiShapeKind },
+}
+
+impl From<FfiTool> for Tool {
+    fn from(value: FfiTool) -> Self {
+        match value {
+            FfiTool::Pen => Tool::Pen,
+            FfiTool::Marker => Tool::Marker,
+            FfiTool::Highlighter => Tool::Highlighter,
+            FfiTool::Eraser => Tool::Eraser,
+            FfiTool::Shape { kind } => Tool::Shape { kind: kind.into() },
+        }
+    }
+}
+
+impl From<Tool> for FfiTool {
+    fn from(value: Tool) -> Self {
+        match value {
+            Tool::Pen => FfiTool::Pen,
+            Tool::Marker => FfiTool::Marker,
+            Tool::Highlighter => FfiTool::Highlighter,
+            Tool::Eraser => FfiTool::Eraser,
+            Tool::Shape { kind } => FfiTool::Shape { kind: kind.into() },
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiToolProfile {
+    pub name: String,
+    pub tool: FfiTool,
+    pub color: FfiColorRgba8,
+    // Line width; the eraser's diameter for `Eraser`.
+    pub width: f32,
+    pub line: FfiLineStyle,
+    pub blend_mode: FfiBlendMode,
+}
+
+impl From<FfiToolProfile> for ToolProfile {
+    fn from(value: FfiToolProfile) -> Self {
+        Self {
+            name: value.name,
+            tool: value.tool.into(),
+            color: value.color.into(),
+            width: value.width,
+            line: value.line.into(),
+            blend_mode: value.blend_mode.into(),
+        }
+    }
+}
+
+impl From<ToolProfile> for FfiToolProfile {
+    fn from(value: ToolProfile) -> Self {
+        Self {
+            name: value.name,
+            tool: value.tool.into(),
+            color: value.color.into(),
+            width: value.width,
+            line: value.line.into(),
+            blend_mode: value.blend_mode.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiBackgroundPattern {
+    None,
+    Grid { spacing: f32 },
+    Dots { spacing: f32 },
+    Lines { spacing: f32 },
+}
+
+impl From<FfiBackgroundPattern> for BackgroundPattern {
+    fn from(value: FfiBackgroundPattern) -> Self {
+        match value {
+            FfiBackgroundPattern::None => BackgroundPattern::None,
+            FfiBackgroundPattern::Grid { spacing } => BackgroundPattern::Grid { spacing },
+            FfiBackgroundPattern::Dots { spacing } => BackgroundPattern::Dots { spacing },
+            FfiBackgroundPattern::Lines { spacing } => BackgroundPattern::Lines { spacing },
+        }
+    }
+}
+
+impl From<BackgroundPattern> for FfiBackgroundPattern {
+    fn from(value: BackgroundPattern) -> Self {
+        match value {
+            BackgroundPattern::None => FfiBackgroundPattern::None,
+            BackgroundPattern::Grid { spacing } => FfiBackgroundPattern::Grid { spacing },
+            BackgroundPattern::Dots { spacing } => FfiBackgroundPattern::Dots { spacing },
+            BackgroundPattern::Lines { spacing } => FfiBackgroundPattern::Lines { spacing },
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiCanvas {
+    pub width: Option<f32>,
+    pub height: Option<f32>,
+    pub background: Option<FfiColorRgba8>,
+    pub pattern: FfiBackgroundPattern,
+}
+
+impl From<FfiCanvas> for Canvas {
+    fn from(value: FfiCanvas) -> Self {
+        Self {
+            width: value.width,
+            height: value.height,
+            background: value.background.map(Into::into),
+            pattern: value.pattern.into(),
+        }
+    }
+}
+
+impl From<Canvas> for FfiCanvas {
+    fn from(value: Canvas) -> Self {
+        Self {
+            width: value.width,
+            height: value.height,
+            background: value.background.map(Into::into),
+            pattern: value.pattern.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiItemCluster {
+    pub bounds: FfiRect,
+    pub ids: Vec<u64>,
+}
+
+impl From<ItemCluster> for FfiItemCluster {
+    fn from(value: ItemCluster) -> Self {
+        Self {
+            bounds: value.bounds.into(),
+            ids: value.ids,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiNearDuplicate {
+    pub original: u64,
+    pub duplicate: u64,
+}
+
+impl From<NearDuplicate> for FfiNearDuplicate {
+    fn from(value: NearDuplicate) -> Self {
+        Self {
+            original: value.original,
+            duplicate: value.duplicate,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiImageSource {
+    Embedded { mime_type: String, data: Vec<u8> },
+    Uri { uri: String },
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiBackgroundImage {
+    pub source: FfiImageSource,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl From<FfiBackgroundImage> for BackgroundImage {
+    fn from(value: FfiBackgroundImage) -> Self {
+        Self {
+            source: match value.source {
+                FfiImageSource::Embedded { mime_type, data } => {
+                    ImageSource::Embedded { mime_type, data }
+                }
+                FfiImageSource::Uri { uri } => ImageSource::Uri { uri },
+            },
+            width: value.width,
+            height: value.height,
+        }
+    }
+}
+
+impl From<BackgroundImage> for FfiBackgroundImage {
+    fn from(value: BackgroundImage) -> Self {
+        Self {
+            source: match value.source {
+                ImageSource::Embedded { mime_type, data } => {
+                    FfiImageSource::Embedded { mime_type, data }
+                }
+                ImageSource::Uri { uri } => FfiImageSource::Uri { uri },
+            },
+            width: value.width,
+            height: value.height,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiLengthUnit {
+    Px,
+    Pt,
+    Mm,
+}
+
+impl From<FfiLengthUnit> for LengthUnit {
+    fn from(value: FfiLengthUnit) -> Self {
+        match value {
+            FfiLengthUnit::Px => Self::Px,
+            FfiLengthUnit::Pt => Self::Pt,
+            FfiLengthUnit::Mm => Self::Mm,
+        }
+    }
+}
+
+impl From<LengthUnit> for FfiLengthUnit {
+    fn from(value: LengthUnit) -> Self {
+        match value {
+            LengthUnit::Px => Self::Px,
+            LengthUnit::Pt => Self::Pt,
+            LengthUnit::Mm => Self::Mm,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiDocumentUnits {
+    pub unit: FfiLengthUnit,
+    // Document units per one `unit`.
+    pub scale: f32,
+}
+
+impl From<FfiDocumentUnits> for DocumentUnits {
+    fn from(value: FfiDocumentUnits) -> Self {
+        Self {
+            unit: value.unit.into(),
+            scale: value.scale,
+        }
+    }
+}
+
+impl From<DocumentUnits> for FfiDocumentUnits {
+    fn from(value: DocumentUnits) -> Self {
+        Self {
+            unit: value.unit.into(),
+            scale: value.scale,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiHalo {
+    pub width: f32,
+    // `None` picks black or white per item, against its ink.
+    pub color: Option<FfiColorRgba8>,
+}
+
+impl From<FfiHalo> for Halo {
+    fn from(value: FfiHalo) -> Self {
+        Self {
+            width: value.width,
+            color: value.color.map(Into::into),
+        }
+    }
+}
+
+impl From<Halo> for FfiHalo {
+    fn from(value: Halo) -> Self {
+        Self {
+            width: value.width,
+            color: value.color.map(Into::into),
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiItemHalo {
+    pub item_id: u64,
+    pub layer_id: Option<u32>,
+    pub color: FfiColorRgba8,
+    pub polygons: Vec<Vec<FfiPoint>>,
+}
+
+impl From<ItemHalo> for FfiItemHalo {
+    fn from(value: ItemHalo) -> Self {
+        Self {
+            item_id: value.item_id,
+            layer_id: value.layer_id,
+            color: value.color.into(),
+            polygons: value
+                .polygons
+                .into_iter()
+                .map(|poly| poly.into_iter().map(Into::into).collect())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiSketch {
+    // How far outline points wander, in document units.
+    pub roughness: f32,
+    pub seed: u64,
+}
+
+impl From<FfiSketch> for Sketch {
+    fn from(value: FfiSketch) -> Self {
+        Self {
+            roughness: value.roughness,
+            seed: value.seed,
+        }
+    }
+}
+
+impl From<Sketch> for FfiSketch {
+    fn from(value: Sketch) -> Self {
+        Self {
+            roughness: value.roughness,
+            seed: value.seed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiItemSketch {
+    pub item_id: u64,
+    pub lines: Vec<Vec<FfiPoint>>,
+}
+
+impl From<ItemSketch> for FfiItemSketch {
+    fn from(value: ItemSketch) -> Self {
+        Self {
+            item_id: value.item_id,
+            lines: value
+                .lines
+                .into_iter()
+                .map(|line| line.into_iter().map(Into::into).collect())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiLayer {
+    pub id: u32,
+    pub name: String,
+    pub clip: Option<FfiRect>,
+}
+
+impl From<Layer> for FfiLayer {
+    fn from(value: Layer) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            clip: value.clip.map(Into::into),
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiFrame {
+    pub name: String,
+    pub rect: FfiRect,
+}
+
+impl From<Frame> for FfiFrame {
+    fn from(value: Frame) -> Self {
+        Self {
+            name: value.name,
+            rect: value.rect.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiNamedView {
+    pub name: String,
+    pub center: FfiPoint,
+    pub zoom: f32,
+}
+
+impl From<NamedView> for FfiNamedView {
+    fn from(value: NamedView) -> Self {
+        Self {
+            name: value.name,
+            center: value.center.into(),
+            zoom: value.zoom,
+        }
+    }
+}
+
+/// `size * size` straight-alpha RGBA8 pixels covering document units
+/// `[col * size, (col + 1) * size)` by `[row * size, (row + 1) * size)`.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiTile {
+    pub col: i32,
+    pub row: i32,
+    pub size: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl From<Tile> for FfiTile {
+    fn from(value: Tile) -> Self {
+        Self {
+            col: value.col,
+            row: value.row,
+            size: value.size,
+            rgba: value.rgba,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiOnionSkinOptions {
+    pub tint: FfiColorRgba8,
+    pub tint_amount: f32,
+    pub ghost_opacity: f32,
+    pub hide_unchanged: bool,
+}
+
+impl From<FfiOnionSkinOptions> for OnionSkinOptions {
+    fn from(value: FfiOnionSkinOptions) -> Self {
+        Self {
+            tint: value.tint.into(),
+            tint_amount: value.tint_amount,
+            ghost_opacity: value.ghost_opacity,
+            hide_unchanged: value.hide_unchanged,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiOnionSkin {
+    pub ghost: Vec<FfiItem>,
+    pub ghost_arrows: Vec<FfiArrowRender>,
+    pub current: Vec<FfiItem>,
+    pub current_arrows: Vec<FfiArrowRender>,
+}
+
+impl From<OnionSkin> for FfiOnionSkin {
+    fn from(value: OnionSkin) -> Self {
+        Self {
+            ghost: value.ghost.into_iter().map(Into::into).collect(),
+            ghost_arrows: value.ghost_arrows.into_iter().map(Into::into).collect(),
+            current: value.current.into_iter().map(Into::into).collect(),
+            current_arrows: value.current_arrows.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiObstacleHits {
+    pub id: u64,
+    pub hits: i32,
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiRoutingCandidate {
+    pub waypoint: FfiPoint,
+    pub path: FfiArrowPath,
+    pub hits: i32,
+    pub arrow_hits: i32,
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiRoutingDebug {
+    pub start: FfiPoint,
+    pub end: FfiPoint,
+    pub quad_control: Option<FfiPoint>,
+    pub quad_hits: i32,
+    pub quad_arrow_hits: i32,
+    pub obstacle_hits: Vec<FfiObstacleHits>,
+    pub candidate_waypoints: Vec<FfiPoint>,
+    pub per_candidate_hits: Vec<FfiRoutingCandidate>,
+    pub chosen: Option<FfiArrowPath>,
+}
+
+impl From<RoutingDebug> for FfiRoutingDebug {
+    fn from(value: RoutingDebug) -> Self {
+        Self {
+            start: value.start.into(),
+            end: value.end.into(),
+            quad_control: value.quad_control.map(Into::into),
+            quad_hits: value.quad_hits,
+            quad_arrow_hits: value.quad_arrow_hits,
+            obstacle_hits: value
+                .obstacle_hits
+                .into_iter()
+                .map(|(id, hits)| FfiObstacleHits { id, hits })
+                .collect(),
+            candidate_waypoints: value
+                .candidate_waypoints
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            per_candidate_hits: value
+                .per_candidate_hits
+                .into_iter()
+                .map(|c| FfiRoutingCandidate {
+                    waypoint: c.waypoint.into(),
+                    path: c.path.into(),
+                    hits: c.hits,
+                    arrow_hits: c.arrow_hits,
+                })
+                .collect(),
+            chosen: value.chosen.map(Into::into),
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiDocumentPreview {
+    pub item_count: u64,
+    pub bounds: Option<FfiRect>,
+    pub sketch: Vec<Vec<FfiPoint>>,
+}
+
+impl From<DocumentPreview> for FfiDocumentPreview {
+    fn from(value: DocumentPreview) -> Self {
+        Self {
+            item_count: value.item_count as u64,
+            bounds: value.bounds.map(Into::into),
+            sketch: value
+                .sketch
+                .into_iter()
+                .map(|line| line.into_iter().map(Into::into).collect())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiMemoryFootprint {
+    pub items: u64,
+    pub meta: u64,
+    pub undo: u64,
+    pub redo: u64,
+    pub caches: u64,
+    pub total: u64,
+}
+
+impl From<MemoryFootprint> for FfiMemoryFootprint {
+    fn from(value: MemoryFootprint) -> Self {
+        Self {
+            items: value.items as u64,
+            meta: value.meta as u64,
+            undo: value.undo as u64,
+            redo: value.redo as u64,
+            caches: value.caches as u64,
+            total: value.total() as u64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiFindOptions {
+    pub case_insensitive: bool,
+    pub wildcard: bool,
+}
+
+impl From<FfiFindOptions> for FindOptions {
+    fn from(value: FfiFindOptions) -> Self {
+        Self {
+            case_insensitive: value.case_insensitive,
+            wildcard: value.wildcard,
+        }
+    }
+}
+
+/// A match range in UTF-16 code units, the indexing Swift and Kotlin strings use.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiTextRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiTextMatch {
+    pub id: u64,
+    pub ranges: Vec<FfiTextRange>,
+}
+
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiReplaceScope {
+    All,
+    Items { ids: Vec<u64> },
+}
+
+impl From<FfiReplaceScope> for ReplaceScope {
+    fn from(value: FfiReplaceScope) -> Self {
+        match value {
+            FfiReplaceScope::All => ReplaceScope::All,
+            FfiReplaceScope::Items { ids } => ReplaceScope::Items(ids),
+        }
+    }
+}
+
+fn utf16_offset(text: &str, byte: usize) -> u64 {
+    text[..byte].encode_utf16().count() as u64
+}
+
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiItemKind {
+    Stroke,
+    Shape { kind: FfiShapeKind },
+}
+
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum FfiItem {
+    Stroke(FfiStroke),
+    Shape(FfiShape),
+}
+
+impl From<FfiItem> for Item {
+    fn from(value: FfiItem) -> Self {
+        match value {
+            FfiItem::Stroke(s) => Item::Stroke(s.into()),
+            FfiItem::Shape(sh) => Item::Shape(sh.into()),
+        }
+    }
+}
+
+impl From<Item> for FfiItem {
+    fn from(value: Item) -> Self {
+        match value {
+            Item::Stroke(s) => FfiItem::Stroke(s.into()),
+            Item::Shape(sh) => FfiItem::Shape(sh.into()),
+        }
+    }
+}
+
+/// Polygons whose union is the ink of a line drawn with `line`; see
+/// `overlay_scribe_core::outline::stroke_outline`.
+#[uniffi::export]
+pub fn stroke_outline(points: Vec<FfiPoint>, width: f32, line: FfiLineStyle) -> Vec<Vec<FfiPoint>> {
+    let points: Vec<Point> = points.into_iter().map(Into::into).collect();
+    outline::stroke_outline(&points, width, &line.into())
+        .into_iter()
+        .map(|poly| poly.into_iter().map(Into::into).collect())
+        .collect()
+}
+
+#[uniffi::export]
+pub fn convex_hull(points: Vec<FfiPoint>) -> Vec<FfiPoint> {
+    let points: Vec<Point> = points.into_iter().map(Into::into).collect();
+    geometry::convex_hull(&points)
+        .into_iter()
+        .map(Into::into)
+        .collect()
+}
+
+#[uniffi::export]
+pub fn rects_intersect(a: FfiRect, b: FfiRect) -> bool {
+    Rect::from(a).intersects(b.into())
+}
+
+#[uniffi::export]
+pub fn rect_intersection(a: FfiRect, b: FfiRect) -> Option<FfiRect> {
+    geometry::rect_intersection(a.into(), b.into()).map(Into::into)
+}
+
+#[uniffi::export]
+pub fn segment_intersects_rect(a: FfiPoint, b: FfiPoint, rect: FfiRect) -> bool {
+    geometry::segment_intersects_rect(a.into(), b.into(), rect.into())
+}
+
+#[uniffi::export]
+pub fn segment_segment_intersection(
+    a1: FfiPoint,
+    a2: FfiPoint,
+    b1: FfiPoint,
+    b2: FfiPoint,
+) -> Option<FfiPoint> {
+    geometry::segment_segment_intersection(a1.into(), a2.into(), b1.into(), b2.into())
+        .map(Into::into)
+}
+
+/// Hue in degrees `[0, 360)`; saturation and lightness in `[0, 1]`.
+#[derive(Debug, Clone, Copy, uniffi::Record)]
+pub struct FfiHsl {
+    pub hue: f32,
+    pub saturation: f32,
+    pub lightness: f32,
+}
+
+/// Hue in degrees `[0, 360)`; saturation and value in `[0, 1]`.
+#[derive(Debug, Clone, Copy, uniffi::Record)]
+pub struct FfiHsv {
+    pub hue: f32,
+    pub saturation: f32,
+    pub value: f32,
+}
+
+/// Parses `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa` (`#` optional).
+#[uniffi::export]
+pub fn color_from_hex(text: String) -> Option<FfiColorRgba8> {
+    ColorRgba8::from_hex(&text).map(Into::into)
+}
+
+/// `#rrggbb`, or `#rrggbbaa` when not opaque.
+#[uniffi::export]
+pub fn color_to_hex(color: FfiColorRgba8) -> String {
+    ColorRgba8::from(color).to_hex()
+}
+
+#[uniffi::export]
+pub fn color_to_hsl(color: FfiColorRgba8) -> FfiHsl {
+    let (hue, saturation, lightness) = ColorRgba8::from(color).to_hsl();
+    FfiHsl {
+        hue,
+        saturation,
+        lightness,
+    }
+}
+
+#[uniffi::export]
+pub fn color_from_hsl(hsl: FfiHsl, alpha: u8) -> FfiColorRgba8 {
+    ColorRgba8::from_hsl(hsl.hue, hsl.saturation, hsl.lightness, alpha).into()
+}
+
+#[uniffi::export]
+pub fn color_to_hsv(color: FfiColorRgba8) -> FfiHsv {
+    let (hue, saturation, value) = ColorRgba8::from(color).to_hsv();
+    FfiHsv {
+        hue,
+        saturation,
+        value,
+    }
+}
+
+#[uniffi::export]
+pub fn color_from_hsv(hsv: FfiHsv, alpha: u8) -> FfiColorRgba8 {
+    ColorRgba8::from_hsv(hsv.hue, hsv.saturation, hsv.value, alpha).into()
+}
+
+/// Raises HSL lightness by `amount`; negative amounts darken.
+#[uniffi::export]
+pub fn color_lighten(color: FfiColorRgba8, amount: f32) -> FfiColorRgba8 {
+    ColorRgba8::from(color).lighten(amount).into()
+}
+
+#[uniffi::export]
+pub fn color_premultiplied(color: FfiColorRgba8) -> FfiColorRgba8 {
+    ColorRgba8::from(color).premultiplied().into()
+}
+
+/// Straight-alpha color of a premultiplied pixel, e.g. from an eyedropper.
+#[uniffi::export]
+pub fn color_unpremultiplied(color: FfiColorRgba8) -> FfiColorRgba8 {
+    ColorRgba8::from(color).unpremultiplied().into()
+}
+
+/// Reads the thumbnail summary from the head of a saved document without
+/// parsing its items.
+#[uniffi::export]
+pub fn read_preview(bytes: Vec<u8>) -> Option<FfiDocumentPreview> {
+    Store::read_preview(&bytes).map(Into::into)
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiSpanField {
+    pub key: String,
+    pub value: u64,
+}
+
+/// Receives timing spans from the core (only when built with `trace`).
+#[uniffi::export(callback_interface)]
+pub trait FfiSpanObserver: Send + Sync {
+    fn on_span(&self, name: String, duration_micros: u64, fields: Vec<FfiSpanField>);
+}
+
+/// Installs (or with `None`, removes) the span observer. Has no effect
+/// unless the library was built with the `trace` feature.
+#[uniffi::export]
+pub fn set_span_observer(observer: Option<Box<dyn FfiSpanObserver>>) {
+    trace::set_span_sink(observer.map(|observer| -> trace::SpanSink {
+        Arc::new(move |span: &trace::SpanRecord| {
+            observer.on_span(
+                span.name.to_string(),
+                span.duration.as_micros() as u64,
+                span.fields
+                    .iter()
+                    .map(|&(key, value)| FfiSpanField {
+                        key: key.to_string(),
+                        value,
+                    })
+                    .collect(),
+            )
+        })
+    }));
+}
+
+#[derive(Debug, Clone, Copy, uniffi::Enum)]
+pub enum FfiLimit {
+    Items,
+    PointsPerStroke,
+    TotalPoints,
+}
+
+impl From<Limit> for FfiLimit {
+    fn from(value: Limit) -> Self {
+        match value {
+            Limit::Items => FfiLimit::Items,
+            Limit::PointsPerStroke => FfiLimit::PointsPerStroke,
+            Limit::TotalPoints => FfiLimit::TotalPoints,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, uniffi::Enum)]
+pub enum FfiInvalidInput {
+    NonFinite,
+    EmptyStroke,
+    NonPositiveWidth,
+    DegenerateShape,
+}
+
+impl From<InvalidInput> for FfiInvalidInput {
+    fn from(value: InvalidInput) -> Self {
+        match value {
+            InvalidInput::NonFinite => FfiInvalidInput::NonFinite,
+            InvalidInput::EmptyStroke => FfiInvalidInput::EmptyStroke,
+            InvalidInput::NonPositiveWidth => FfiInvalidInput::NonPositiveWidth,
+            InvalidInput::DegenerateShape => FfiInvalidInput::DegenerateShape,
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiDocumentLimits {
+    pub max_items: Option<u64>,
+    pub max_points_per_stroke: Option<u64>,
+    pub max_total_points: Option<u64>,
+}
+
+impl From<FfiDocumentLimits> for DocumentLimits {
+    fn from(value: FfiDocumentLimits) -> Self {
+        let size = |v: Option<u64>| v.map(|v| usize::try_from(v).unwrap_or(usize::MAX));
+        Self {
+            max_items: size(value.max_items),
+            max_points_per_stroke: size(value.max_points_per_stroke),
+            max_total_points: size(value.max_total_points),
+        }
+    }
+}
+
+impl From<DocumentLimits> for FfiDocumentLimits {
+    fn from(value: DocumentLimits) -> Self {
+        Self {
+            max_items: value.max_items.map(|v| v as u64),
+            max_points_per_stroke: value.max_points_per_stroke.map(|v| v as u64),
+            max_total_points: value.max_total_points.map(|v| v as u64),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, uniffi::Enum)]
+pub enum FfiHistoryStack {
+    Undo,
+    Redo,
+}
+
+impl From<HistoryStack> for FfiHistoryStack {
+    fn from(value: HistoryStack) -> Self {
+        match value {
+            HistoryStack::Undo => FfiHistoryStack::Undo,
+            HistoryStack::Redo => FfiHistoryStack::Redo,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, uniffi::Enum)]
+pub enum FfiInvariantViolation {
+    DuplicateId { id: u64 },
+    IdNotReserved { id: u64 },
+    LiveIdInDocument { id: u64 },
+    BadAttachment { arrow: u64, target: u64 },
+    HistoryOutOfBounds { stack: FfiHistoryStack, depth: u64 },
+    HistoryNotInverse { stack: FfiHistoryStack, depth: u64 },
+}
+
+impl From<InvariantViolation> for FfiInvariantViolation {
+    fn from(value: InvariantViolation) -> Self {
+        match value {
+            InvariantViolation::DuplicateId(id) => FfiInvariantViolation::DuplicateId { id },
+            InvariantViolation::IdNotReserved(id) => FfiInvariantViolation::IdNotReserved { id },
+            InvariantViolation::LiveIdInDocument(id) => {
+                FfiInvariantViolation::LiveIdInDocument { id }
+            }
+            InvariantViolation::BadAttachment { arrow, target } => {
+                FfiInvariantViolation::BadAttachment { arrow, target }
+            }
+            InvariantViolation::HistoryOutOfBounds { stack, depth } => {
+                FfiInvariantViolation::HistoryOutOfBounds {
+                    stack: stack.into(),
+                    depth: depth as u64,
+                }
+            }
+            InvariantViolation::HistoryNotInverse { stack, depth } => {
+                FfiInvariantViolation::HistoryNotInverse {
+                    stack: stack.into(),
+                    depth: depth as u64,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiSessionMetrics {
+    pub items_added: u64,
+    pub items_removed: u64,
+    pub items_replaced: u64,
+    pub item_lists_replaced: u64,
+    pub meta_edits: u64,
+    pub undos: u64,
+    pub redos: u64,
+    pub routing_fallbacks: u64,
+    pub saves: u64,
+    pub save_micros: u64,
+    pub loads: u64,
+    pub load_micros: u64,
+}
+
+impl From<SessionMetrics> for FfiSessionMetrics {
+    fn from(value: SessionMetrics) -> Self {
+        Self {
+            items_added: value.items_added,
+            items_removed: value.items_removed,
+            items_replaced: value.items_replaced,
+            item_lists_replaced: value.item_lists_replaced,
+            meta_edits: value.meta_edits,
+            undos: value.undos,
+            redos: value.redos,
+            routing_fallbacks: value.routing_fallbacks,
+            saves: value.saves,
+            save_micros: value.save_time.as_micros() as u64,
+            loads: value.loads,
+            load_micros: value.load_time.as_micros() as u64,
+        }
+    }
+}
+
+/// Turns the in-process usage counters on or off; they start off and are
+/// never sent anywhere.
+#[uniffi::export]
+pub fn set_metrics_enabled(enabled: bool) {
+    metrics::set_metrics_enabled(enabled);
+}
+
+/// The usage counters since they were enabled or last reset.
+#[uniffi::export]
+pub fn session_metrics() -> FfiSessionMetrics {
+    metrics::session_metrics().into()
+}
+
+#[uniffi::export]
+pub fn reset_session_metrics() {
+    metrics::reset_session_metrics();
+}
+
+/// Receives items while [`CoreDocument::load_json_streaming`] parses them.
+#[uniffi::export(callback_interface)]
+pub trait FfiLoadObserver: Send + Sync {
+    /// A batch of newly parsed items, in document order.
+    fn on_items(&self, items: Vec<FfiItem>);
+    fn on_progress(&self, items_loaded: u64, bytes_read: u64, total_bytes: u64);
+}
+
+/// Follows a long-running export. Called from the exporting thread, so
+/// shells should run the export off the UI thread.
+#[uniffi::export(callback_interface)]
+pub trait FfiExportObserver: Send + Sync {
+    /// `done` of `total` units (items or tiles) finished. Return false to cancel.
+    fn on_progress(&self, done: u64, total: u64) -> bool;
+}
+
+// Items handed to the observer per `on_items` call.
+const STREAM_BATCH: usize = 256;
+
+#[derive(uniffi::Object)]
+pub struct CoreDocument {
+    // Read-only calls (rendering, queries, export) share the lock so a render
+    // thread doesn't stall the input thread; edits take it exclusively.
+    store: RwLock<Store>,
+}
+
+impl Default for CoreDocument {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[uniffi::export]
+impl CoreDocument {
+    #[uniffi::constructor]
+    pub fn new() -> Self {
+        Self {
+            store: RwLock::new(Store::new()),
+        }
+    }
+
+    /// Captures the current items so a background thread can render them
+    /// without holding the document lock. The items are shared, not copied;
+    /// later edits leave the snapshot as it was.
+    pub fn snapshot(&self) -> Arc<FfiSnapshot> {
+        let items = self.store.read().expect("lock poisoned").shared_items();
+        Arc::new(FfiSnapshot { items })
+    }
+
+    pub fn items(&self) -> Vec<FfiItem> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .items()
+            .iter()
+            .cloned()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// One item, for refreshing it after a change without pulling the list.
+    pub fn get_item(&self, id: u64) -> Option<FfiItem> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .item(id)
+            .cloned()
+            .map(Into::into)
+    }
+
+    pub fn contains(&self, id: u64) -> bool {
+        self.store.read().expect("lock poisoned").contains(id)
+    }
+
+    /// Stamps the named template into the document moved by (`dx`, `dy`),
+    /// as one undo step. Returns the new ids, or `None` for an unknown name.
+    pub fn instantiate_template(
+        &self,
+        library: Arc<FfiTemplateLibrary>,
+        name: String,
+        dx: f32,
+        dy: f32,
+    ) -> Option<Vec<u64>> {
+        let library = library.library.read().expect("lock poisoned");
+        let template = library.get(&name)?;
+        Some(
+            self.store
+                .write()
+                .expect("lock poisoned")
+                .instantiate_template(template, dx, dy),
+        )
+    }
+
+    /// Pastes or imports many items as one undo step. Returns their final
+    /// ids, which differ from the given ones where those were already taken.
+    pub fn insert_items(&self, items: Vec<FfiItem>) -> Vec<u64> {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .insert_items(items.into_iter().map(Into::into).collect())
+    }
+
+    pub fn item_kind(&self, id: u64) -> Option<FfiItemKind> {
+        let store = self.store.read().expect("lock poisoned");
+        Some(match store.item(id)? {
+            Item::Stroke(_) => FfiItemKind::Stroke,
+            Item::Shape(sh) => FfiItemKind::Shape {
+                kind: sh.kind.into(),
+            },
+        })
+    }
+
+    pub fn arrow_renders(&self) -> Vec<FfiArrowRender> {
+        let store = self.store.read().expect("lock poisoned");
+        overlay_scribe_core::render::render_arrows(store.items())
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    pub fn arrow_renders_with(&self, config: FfiRoutingConfig) -> Vec<FfiArrowRender> {
+        let store = self.store.read().expect("lock poisoned");
+        overlay_scribe_core::render::render_arrows_with(store.items(), &config.into())
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Quick route for an arrow still being dragged, around the document's
+    /// shapes. `quality` in [0, 1] trades speed for accuracy; 1 matches the
+    /// route the arrow gets once committed.
+    pub fn preview_arrow(&self, shape: FfiShape, quality: f32) -> Option<FfiArrowRender> {
+        let store = self.store.read().expect("lock poisoned");
+        overlay_scribe_core::render::preview_arrow(&shape.into(), store.items(), quality)
+            .map(Into::into)
+    }
+
+    /// Casings to fill under all ink when the document sets a halo, in
+    /// drawing order; empty otherwise.
+    pub fn item_halos(&self) -> Vec<FfiItemHalo> {
+        let store = self.store.read().expect("lock poisoned");
+        let Some(halo) = store.halo() else {
+            return Vec::new();
+        };
+        let arrows = overlay_scribe_core::render::render_arrows(store.items());
+        halo::item_halos(store.items(), &arrows, &halo)
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Lines to stroke instead of each shape outline and arrow shaft when
+    /// the document sets a sketch style, in drawing order; empty otherwise.
+    pub fn item_sketches(&self) -> Vec<FfiItemSketch> {
+        let store = self.store.read().expect("lock poisoned");
+        let Some(style) = store.sketch() else {
+            return Vec::new();
+        };
+        let arrows = overlay_scribe_core::render::render_arrows(store.items());
+        sketch::item_sketches(store.items(), &arrows, &style)
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Re-routes only the arrows in `ids` or attached to a shape in `ids`.
+    pub fn arrow_renders_for(
+        &self,
+        ids: Vec<u64>,
+        config: FfiRoutingConfig,
+    ) -> Vec<FfiArrowRender> {
+        let store = self.store.read().expect("lock poisoned");
+        overlay_scribe_core::render::render_arrows_for(store.items(), &ids, &config.into())
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// The router's intermediate choices for one arrow, for debug overlays.
+    pub fn arrow_routing_debug(
+        &self,
+        arrow_id: u64,
+        config: FfiRoutingConfig,
+    ) -> Option<FfiRoutingDebug> {
+        let store = self.store.read().expect("lock poisoned");
+        arrow_routing_debug(store.items(), arrow_id, &config.into()).map(Into::into)
+    }
+
+    /// Snaps an in-progress point against the document's items (and its
+    /// guides when guide snapping is enabled).
+    pub fn snap_point(
+        &self,
+        point: FfiPoint,
+        anchor: Option<FfiPoint>,
+        config: FfiSnapConfig,
+    ) -> FfiSnapResult {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .snap_point(point.into(), anchor.map(Into::into), config.into())
+            .into()
+    }
+
+    pub fn guides(&self) -> Vec<FfiGuide> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .guides()
+            .iter()
+            .copied()
+            .map(Into::into)
+            .collect()
+    }
+
+    pub fn add_guide(&self, orientation: FfiGuideOrientation, position: f32) -> u32 {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .add_guide(orientation.into(), position)
+    }
+
+    pub fn move_guide(&self, id: u32, position: f32) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .move_guide(id, position)
+    }
+
+    pub fn remove_guide(&self, id: u32) -> bool {
+        self.store.write().expect("lock poisoned").remove_guide(id)
+    }
+
+    pub fn snap_to_guides(&self) -> bool {
+        self.store.read().expect("lock poisoned").snap_to_guides()
+    }
+
+    pub fn set_snap_to_guides(&self, enabled: bool) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .set_snap_to_guides(enabled)
+    }
+
+    /// Closest point on the outline of shape `id` (routed path for arrows).
+    pub fn closest_boundary_point(&self, id: u64, point: FfiPoint) -> Option<FfiClosestPoint> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .closest_boundary_point(id, point.into())
+            .map(|(p, distance)| FfiClosestPoint {
+                point: p.into(),
+                distance,
+            })
+    }
+
+    /// The closed shape (and exact UV/anchor) a dragged arrow endpoint would attach to.
+    pub fn attach_candidate(&self, point: FfiPoint, radius: f32) -> Option<FfiAttachCandidate> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .attach_candidate(point.into(), radius)
+            .map(Into::into)
+    }
+
+    /// `attach_candidate` with `radius` in screen pixels at zoom `view_scale`.
+    pub fn attach_candidate_scaled(
+        &self,
+        point: FfiPoint,
+        radius: f32,
+        view_scale: f32,
+    ) -> Option<FfiAttachCandidate> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .attach_candidate_scaled(point.into(), radius, view_scale)
+            .map(Into::into)
+    }
+
+    /// Items intersecting `rect`, for shells that load a large board lazily.
+    pub fn load_region(&self, rect: FfiRect) -> Vec<FfiItem> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .load_region(rect.into())
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Bounds of the given items; `tight` includes stroke widths and arrowheads.
+    pub fn selection_bounds(&self, ids: Vec<u64>, tight: bool) -> Option<FfiRect> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .selection_bounds(&ids, tight)
+            .map(Into::into)
+    }
+
+    /// Tight bounds of all content plus `padding`, to pass as an export
+    /// `region` when cropping to the drawing; `None` for an empty document.
+    pub fn export_bounds(&self, padding: f32) -> Option<FfiRect> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .export_bounds(padding)
+            .map(Into::into)
+    }
+
+    /// Groups of items drawn within `gap` of each other, e.g. to export each
+    /// annotation group as its own image.
+    pub fn cluster_items(&self, gap: f32) -> Vec<FfiItemCluster> {
+        let store = self.store.read().expect("lock poisoned");
+        analysis::cluster_items(store.items(), gap)
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Later items that nearly repeat an earlier one; see
+    /// `overlay_scribe_core::analysis::find_near_duplicates`.
+    pub fn find_near_duplicates(&self, tolerance: f32) -> Vec<FfiNearDuplicate> {
+        let doc = self.store.read().expect("lock poisoned").document();
+        analysis::find_near_duplicates(&doc, tolerance)
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Deletes the given items as one undo step.
+    pub fn remove_items(&self, ids: Vec<u64>) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .remove_items(&ids)
+    }
+
+    pub fn alignment_guides(
+        &self,
+        moving_ids: Vec<u64>,
+        proposed_delta: FfiPoint,
+        threshold: f32,
+    ) -> FfiAlignmentResult {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .alignment_guides(&moving_ids, proposed_delta.into(), threshold)
+            .into()
+    }
+
+    pub fn begin_stroke(&self, color: FfiColorRgba8, width: f32, start: FfiPoint) -> FfiStroke {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .begin_stroke(color.into(), width, start.into())
+            .into()
+    }
+
+    /// Fails with `InvalidInput` for corrupt input (NaN coordinates, a
+    /// non-positive width, ...) and `LimitExceeded` when the document limits
+    /// refuse the stroke.
+    pub fn commit_stroke(&self, stroke: FfiStroke) -> Result<(), FfiError> {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .commit_stroke(stroke.into())?;
+        Ok(())
+    }
+
+    /// Starts a stroke kept inside the core; feed it with
+    /// `append_stroke_point` and commit it with `end_stroke`.
+    pub fn begin_live_stroke(&self, color: FfiColorRgba8, width: f32, start: FfiPoint) -> u64 {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .begin_live_stroke(color.into(), width, start.into())
+    }
+
+    pub fn append_stroke_point(&self, stroke_id: u64, point: FfiPoint) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .append_stroke_point(stroke_id, point.into())
+    }
+
+    pub fn live_stroke(&self, stroke_id: u64) -> Option<FfiStroke> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .live_stroke(stroke_id)
+            .cloned()
+            .map(Into::into)
+    }
+
+    pub fn end_stroke(&self, stroke_id: u64) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .end_stroke(stroke_id)
+    }
+
+    pub fn cancel_stroke(&self, stroke_id: u64) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .cancel_stroke(stroke_id)
+    }
+
+    /// Starts a shape dragged out from `press` inside the core, so modifier
+    /// handling matches across shells.
+    pub fn begin_live_shape(
+        &self,
+        kind: FfiShapeKind,
+        style: FfiShapeStyle,
+        press: FfiPoint,
+    ) -> u64 {
+        self.store.write().expect("lock poisoned").begin_live_shape(
+            kind.into(),
+            style.into(),
+            press.into(),
+        )
+    }
+
+    pub fn drag_live_shape(
+        &self,
+        shape_id: u64,
+        pointer: FfiPoint,
+        gesture: FfiShapeGesture,
+    ) -> bool {
+        self.store.write().expect("lock poisoned").drag_live_shape(
+            shape_id,
+            pointer.into(),
+            gesture.into(),
+        )
+    }
+
+    pub fn live_shape(&self, shape_id: u64) -> Option<FfiShape> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .live_shape(shape_id)
+            .cloned()
+            .map(Into::into)
+    }
+
+    pub fn end_shape(&self, shape_id: u64) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .end_shape(shape_id)
+    }
+
+    pub fn cancel_shape(&self, shape_id: u64) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .cancel_shape(shape_id)
+    }
+
+    pub fn begin_shape(
+        &self,
+        kind: FfiShapeKind,
+        style: FfiShapeStyle,
+        start: FfiPoint,
+    ) -> FfiShape {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .begin_shape(kind.into(), style.into(), start.into())
+            .into()
+    }
+
+    /// Starts a shape from the document's saved defaults for `kind`, or from
+    /// `fallback` when none are saved.
+    pub fn begin_default_shape(
+        &self,
+        kind: FfiShapeKind,
+        fallback: FfiShapeStyle,
+        start: FfiPoint,
+    ) -> FfiShape {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .begin_default_shape(kind.into(), fallback.into(), start.into())
+            .into()
+    }
+
+    /// Fails with `InvalidInput` for corrupt input (NaN coordinates, a
+    /// non-positive width, ...) and `LimitExceeded` when the document limits
+    /// refuse the shape.
+    pub fn commit_shape(&self, shape: FfiShape) -> Result<(), FfiError> {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .commit_shape(shape.into())?;
+        Ok(())
+    }
+
+    pub fn erase_at(&self, point: FfiPoint, radius: f32) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .erase_at(point.into(), radius)
+    }
+
+    /// `erase_at` with `radius` in screen pixels at zoom `view_scale`.
+    pub fn erase_at_scaled(&self, point: FfiPoint, radius: f32, view_scale: f32) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .erase_at_scaled(point.into(), radius, view_scale)
+    }
+
+    pub fn clear_all(&self) {
+        self.store.write().expect("lock poisoned").clear_all();
     }
 
     pub fn can_undo(&self) -> bool {
-        self.store.lock().expect("mutex poisoned").can_undo()
+        self.store.read().expect("lock poisoned").can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.store.read().expect("lock poisoned").can_redo()
+    }
+
+    pub fn undo(&self) -> Result<(), FfiError> {
+        Ok(self.store.write().expect("lock poisoned").undo()?)
+    }
+
+    pub fn redo(&self) -> Result<(), FfiError> {
+        Ok(self.store.write().expect("lock poisoned").redo()?)
+    }
+
+    pub fn palette(&self) -> Vec<FfiPaletteColor> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .palette()
+            .iter()
+            .cloned()
+            .map(Into::into)
+            .collect()
+    }
+
+    pub fn add_palette_color(&self, name: String, color: FfiColorRgba8) -> u32 {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .add_palette_color(&name, color.into())
+    }
+
+    pub fn rename_palette_color(&self, id: u32, name: String) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .rename_palette_color(id, &name)
+    }
+
+    pub fn set_palette_color(&self, id: u32, color: FfiColorRgba8) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .set_palette_color(id, color.into())
+    }
+
+    pub fn remove_palette_color(&self, id: u32) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .remove_palette_color(id)
+    }
+
+    pub fn canvas(&self) -> Option<FfiCanvas> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .canvas()
+            .copied()
+            .map(Into::into)
+    }
+
+    pub fn set_canvas(&self, canvas: Option<FfiCanvas>) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .set_canvas(canvas.map(Into::into))
+    }
+
+    pub fn background_image(&self) -> Option<FfiBackgroundImage> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .background_image()
+            .cloned()
+            .map(Into::into)
+    }
+
+    /// Sets or clears the screenshot being annotated; refuses an image
+    /// without a positive size or without data.
+    pub fn set_background_image(&self, image: Option<FfiBackgroundImage>) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .set_background_image(image.map(Into::into))
+    }
+
+    pub fn slide_count(&self) -> u32 {
+        self.store.read().expect("lock poisoned").slide_count() as u32
+    }
+
+    pub fn current_slide(&self) -> u32 {
+        self.store.read().expect("lock poisoned").current_slide() as u32
+    }
+
+    /// Appends an empty slide over `background`; returns its index.
+    pub fn add_slide(&self, background: Option<FfiBackgroundImage>) -> u32 {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .add_slide(background.map(Into::into)) as u32
+    }
+
+    /// Shows slide `index`, putting the current slide's items away.
+    pub fn go_to_slide(&self, index: u32) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .go_to_slide(index as usize)
+    }
+
+    pub fn remove_slide(&self, index: u32) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .remove_slide(index as usize)
+    }
+
+    /// SVG of slide `index` over its background, shown or not.
+    pub fn export_slide(&self, index: u32) -> Option<String> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .export_slide(index as usize, &SvgOptions::default())
+    }
+
+    pub fn units(&self) -> FfiDocumentUnits {
+        self.store.read().expect("lock poisoned").units().into()
+    }
+
+    /// Sets the physical size of a document unit; refuses a scale that is
+    /// not positive.
+    pub fn set_units(&self, units: FfiDocumentUnits) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .set_units(units.into())
+    }
+
+    /// `length` (document units) labelled in the document's unit, e.g. "20 mm".
+    pub fn format_length(&self, length: f32) -> String {
+        units::format_length(&self.store.read().expect("lock poisoned").units(), length)
+    }
+
+    /// Document units for a measurement like "2cm"; `None` if it can't be read.
+    pub fn parse_length(&self, text: String) -> Option<f32> {
+        units::parse_length(&self.store.read().expect("lock poisoned").units(), &text)
+    }
+
+    pub fn halo(&self) -> Option<FfiHalo> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .halo()
+            .map(Into::into)
+    }
+
+    /// Sets or removes the casing under every item's ink; refuses a width
+    /// that is not positive.
+    pub fn set_halo(&self, halo: Option<FfiHalo>) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .set_halo(halo.map(Into::into))
+    }
+
+    pub fn sketch(&self) -> Option<FfiSketch> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .sketch()
+            .map(Into::into)
+    }
+
+    /// Turns the hand-drawn look on or off; refuses a roughness that is not
+    /// positive.
+    pub fn set_sketch(&self, sketch: Option<FfiSketch>) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .set_sketch(sketch.map(Into::into))
+    }
+
+    /// Colors of the latest committed items, newest first, for a "recent
+    /// colors" row.
+    pub fn recent_colors(&self) -> Vec<FfiColorRgba8> {
+        let store = self.store.read().expect("lock poisoned");
+        store
+            .recent_colors()
+            .iter()
+            .copied()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Styles of the latest committed shapes, newest first.
+    pub fn recent_styles(&self) -> Vec<FfiShapeStyle> {
+        let store = self.store.read().expect("lock poisoned");
+        store
+            .recent_styles()
+            .iter()
+            .copied()
+            .map(Into::into)
+            .collect()
+    }
+
+    pub fn clear_recent(&self) {
+        self.store.write().expect("lock poisoned").clear_recent();
+    }
+
+    pub fn keeps_recent(&self) -> bool {
+        self.store.read().expect("lock poisoned").keeps_recent()
+    }
+
+    /// Whether the recent colors and styles are saved with the document.
+    pub fn set_keep_recent(&self, keep: bool) {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .set_keep_recent(keep);
+    }
+
+    pub fn layers(&self) -> Vec<FfiLayer> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .layers()
+            .iter()
+            .cloned()
+            .map(Into::into)
+            .collect()
+    }
+
+    pub fn add_layer(&self, name: String) -> u32 {
+        self.store.write().expect("lock poisoned").add_layer(&name)
+    }
+
+    pub fn rename_layer(&self, id: u32, name: String) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .rename_layer(id, &name)
+    }
+
+    /// Masks everything on the layer to `clip` (`None` to unclip).
+    pub fn set_layer_clip(&self, id: u32, clip: Option<FfiRect>) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .set_layer_clip(id, clip.map(Into::into))
+    }
+
+    pub fn remove_layer(&self, id: u32) -> bool {
+        self.store.write().expect("lock poisoned").remove_layer(id)
+    }
+
+    pub fn move_to_layer(&self, ids: Vec<u64>, layer: Option<u32>) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .move_to_layer(&ids, layer)
+    }
+
+    pub fn frames(&self) -> Vec<FfiFrame> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .frames()
+            .iter()
+            .cloned()
+            .map(Into::into)
+            .collect()
+    }
+
+    pub fn set_frame(&self, name: String, rect: FfiRect) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .set_frame(&name, rect.into())
+    }
+
+    pub fn rename_frame(&self, name: String, new_name: String) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .rename_frame(&name, &new_name)
+    }
+
+    pub fn remove_frame(&self, name: String) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .remove_frame(&name)
+    }
+
+    /// SVG of the document; `region` defaults to the canvas or the content bounds.
+    pub fn export_svg(&self, region: Option<FfiRect>) -> String {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .export_svg(&SvgOptions {
+                region: region.map(Into::into),
+            })
+    }
+
+    /// Like `export_svg`, reporting progress to `observer` and failing with
+    /// `Cancelled` if it asks to stop. The document lock is only held while
+    /// copying the items, so edits can continue during the export.
+    pub fn export_svg_with_progress(
+        &self,
+        region: Option<FfiRect>,
+        observer: Box<dyn FfiExportObserver>,
+    ) -> Result<String, FfiError> {
+        let doc = self.store.read().expect("lock poisoned").document();
+        let options = SvgOptions {
+            region: region.map(Into::into),
+        };
+        export::to_svg_with_progress(&doc, &options, |done, total| {
+            observer.on_progress(done as u64, total as u64)
+        })
+        .ok_or(FfiError::Cancelled)
+    }
+
+    /// Ids of the items drawn in `[from_ms, to_ms)`, in stacking order.
+    pub fn items_in_time_range(&self, from_ms: u64, to_ms: u64) -> Vec<u64> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .items_in_time_range(from_ms, to_ms)
+    }
+
+    /// SVG of only the items drawn in `[from_ms, to_ms)`.
+    pub fn export_time_range_svg(
+        &self,
+        region: Option<FfiRect>,
+        from_ms: u64,
+        to_ms: u64,
+    ) -> String {
+        let doc = self
+            .store
+            .read()
+            .expect("lock poisoned")
+            .document_in_time_range(from_ms, to_ms);
+        export::to_svg(
+            &doc,
+            &SvgOptions {
+                region: region.map(Into::into),
+            },
+        )
+    }
+
+    /// SVG that replays the drawing, each item appearing at its turn.
+    pub fn export_animated_svg(
+        &self,
+        region: Option<FfiRect>,
+        options: FfiReplayOptions,
+    ) -> String {
+        let doc = self.store.read().expect("lock poisoned").document();
+        let options = options.into();
+        export::to_animated_svg(
+            &doc,
+            &SvgOptions {
+                region: region.map(Into::into),
+            },
+            &options,
+        )
+    }
+
+    /// When each item is drawn in the replay, in drawing order.
+    pub fn replay_schedule(&self, options: FfiReplayOptions) -> Vec<FfiReplayStep> {
+        let doc = self.store.read().expect("lock poisoned").document();
+        replay::replay_schedule(&doc, &options.into())
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// The items shown `time` seconds into the replay, for shells that render
+    /// it frame by frame.
+    pub fn replay_frame(&self, options: FfiReplayOptions, time: f32) -> Vec<FfiItem> {
+        let doc = self.store.read().expect("lock poisoned").document();
+        let steps = replay::replay_schedule(&doc, &options.into());
+        replay::replay_frame(&doc, &steps, time)
+            .items
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// SVG cropped to the named frame, or `None` if no such frame exists.
+    pub fn export_frame(&self, name: String) -> Option<String> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .export_frame(&name)
+    }
+
+    pub fn views(&self) -> Vec<FfiNamedView> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .views()
+            .iter()
+            .cloned()
+            .map(Into::into)
+            .collect()
+    }
+
+    pub fn save_view(&self, name: String, center: FfiPoint, zoom: f32) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .save_view(&name, center.into(), zoom)
+    }
+
+    pub fn rename_view(&self, name: String, new_name: String) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .rename_view(&name, &new_name)
+    }
+
+    pub fn remove_view(&self, name: String) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .remove_view(&name)
+    }
+
+    /// World position of the document's local (0, 0).
+    pub fn origin(&self) -> FfiWorldPoint {
+        self.store.read().expect("lock poisoned").origin().into()
+    }
+
+    /// Local position where the sized canvas and background image start;
+    /// draw the screenshot here. (0, 0) until a rebase moves it.
+    pub fn page_origin(&self) -> FfiPoint {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .page_origin()
+            .into()
+    }
+
+    pub fn to_world(&self, point: FfiPoint) -> FfiWorldPoint {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .to_world(point.into())
+            .into()
+    }
+
+    pub fn to_local(&self, point: FfiWorldPoint) -> FfiPoint {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .to_local(point.into())
+            .into()
+    }
+
+    /// Moves the local origin without moving content in world space.
+    pub fn rebase_origin(&self, origin: FfiWorldPoint) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .rebase_origin(origin.into())
+    }
+
+    pub fn recenter_origin(&self) -> bool {
+        self.store.write().expect("lock poisoned").recenter_origin()
+    }
+
+    pub fn presets(&self) -> Vec<FfiStylePreset> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .presets()
+            .iter()
+            .cloned()
+            .map(Into::into)
+            .collect()
+    }
+
+    pub fn save_preset(&self, name: String, style: FfiShapeStyle) {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .save_preset(&name, style.into());
     }
 
-    pub fn can_redo(&self) -> bool {
-        self.store.lock().expect("mutex poisoned").can_redo()
+    pub fn remove_preset(&self, name: String) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .remove_preset(&name)
+    }
+
+    pub fn shape_defaults(&self) -> Vec<FfiShapeDefaults> {
+        let store = self.store.read().expect("lock poisoned");
+        store
+            .shape_defaults()
+            .iter()
+            .copied()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Saves the defaults for one shape kind with the document; refuses a
+    /// negative stroke width.
+    pub fn set_shape_defaults(&self, defaults: FfiShapeDefaults) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .set_shape_defaults(defaults.into())
+    }
+
+    pub fn remove_shape_defaults(&self, kind: FfiShapeKind) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .remove_shape_defaults(kind.into())
+    }
+
+    pub fn tool_profiles(&self) -> Vec<FfiToolProfile> {
+        let store = self.store.read().expect("lock poisoned");
+        store
+            .tool_profiles()
+            .iter()
+            .cloned()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Saves a named tool setting with the document, replacing one with the
+    /// same name; refuses an empty name or a width that is not positive.
+    pub fn save_tool_profile(&self, profile: FfiToolProfile) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .save_tool_profile(profile.into())
+    }
+
+    pub fn remove_tool_profile(&self, name: String) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .remove_tool_profile(&name)
+    }
+
+    /// The tool profiles as JSON, for app-level settings.
+    pub fn export_tool_profiles(&self) -> Result<String, FfiError> {
+        Ok(self
+            .store
+            .read()
+            .expect("lock poisoned")
+            .export_tool_profiles()?)
+    }
+
+    /// Merges profiles written by `export_tool_profiles`; returns how many
+    /// were saved.
+    pub fn import_tool_profiles(&self, json: String) -> Result<u32, FfiError> {
+        let saved = self
+            .store
+            .write()
+            .expect("lock poisoned")
+            .import_tool_profiles(&json)?;
+        Ok(saved as u32)
+    }
+
+    /// Starts a stroke with the pen, marker or highlighter profile `name`.
+    pub fn begin_profile_stroke(&self, name: String, start: FfiPoint) -> Option<FfiStroke> {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .begin_profile_stroke(&name, start.into())
+            .map(Into::into)
+    }
+
+    /// Starts a shape with the shape profile `name`.
+    pub fn begin_profile_shape(&self, name: String, start: FfiPoint) -> Option<FfiShape> {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .begin_profile_shape(&name, start.into())
+            .map(Into::into)
+    }
+
+    pub fn apply_preset(&self, name: String, ids: Vec<u64>) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .apply_preset(&name, &ids)
+    }
+
+    /// Captures the style of `source_id` for the format painter.
+    pub fn copy_style_from(&self, source_id: u64) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .copy_style_from(source_id)
+    }
+
+    pub fn apply_copied_style(&self, target_ids: Vec<u64>) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .apply_copied_style(&target_ids)
+    }
+
+    pub fn translate(&self, ids: Vec<u64>, dx: f32, dy: f32) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .translate(&ids, dx, dy)
+    }
+
+    /// Keyboard nudge; repeated nudges of the same selection coalesce into one
+    /// undo entry. `timestamp_ms` is the shell's monotonic event time.
+    pub fn nudge(&self, ids: Vec<u64>, dx: f32, dy: f32, timestamp_ms: u64) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .nudge(&ids, dx, dy, timestamp_ms)
+    }
+
+    pub fn flip(&self, ids: Vec<u64>, axis: FfiFlipAxis) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .flip(&ids, axis.into())
+    }
+
+    pub fn rotate(&self, ids: Vec<u64>, angle: f32, pivot: FfiPoint) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .rotate(&ids, angle, pivot.into())
+    }
+
+    pub fn transform_items(&self, ids: Vec<u64>, transform: FfiTransform2D) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .transform_items(&ids, transform.into())
+    }
+
+    /// Ids a marquee over `rect` selects; see `Store::select_in_rect`.
+    pub fn select_in_rect(&self, rect: FfiRect, contained: bool) -> Vec<u64> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .select_in_rect(rect.into(), contained)
+    }
+
+    pub fn align(&self, ids: Vec<u64>, edge: FfiAlignEdge) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .align(&ids, edge.into())
+    }
+
+    /// Evens out the gaps between the selection's bounds along `axis` (one undo step).
+    pub fn equalize_spacing(&self, ids: Vec<u64>, axis: FfiSpacingAxis) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .equalize_spacing(&ids, axis.into())
+    }
+
+    /// Rounds shape sizes, lines up near-aligned shapes and straightens and
+    /// re-anchors arrows among `ids` (one undo step).
+    pub fn tidy(&self, ids: Vec<u64>) -> bool {
+        self.store.write().expect("lock poisoned").tidy(&ids)
+    }
+
+    /// Switches shape `id` to another kind of the same family (arrow or
+    /// closed shape), keeping attachments, text and style.
+    pub fn convert_shape_kind(&self, id: u64, new_kind: FfiShapeKind) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .convert_shape_kind(id, new_kind.into())
+    }
+
+    /// Sets or clears the tail decoration of the arrows among `ids`.
+    pub fn set_arrow_tail(&self, ids: Vec<u64>, tail: Option<FfiArrowTail>) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .set_arrow_tail(&ids, tail.map(Into::into))
+    }
+
+    /// Sets the span of the arcs and pies among `ids`; `None` is the whole
+    /// ellipse.
+    pub fn set_arc_angles(&self, ids: Vec<u64>, angles: Option<FfiArcAngles>) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .set_arc_angles(&ids, angles.map(Into::into))
+    }
+
+    /// Sets the bow of the S-curves and curved arrows among `ids`; `None` is
+    /// the default.
+    pub fn set_curvature(&self, ids: Vec<u64>, curvature: Option<f32>) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .set_curvature(&ids, curvature)
+    }
+
+    /// Sets the notch depth of the arrowheads among `ids`; `None` is a plain
+    /// triangle.
+    pub fn set_head_notch(&self, ids: Vec<u64>, notch: Option<f32>) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .set_head_notch(&ids, notch)
+    }
+
+    /// Turns obstacle avoidance off (or back on) for the arrows among `ids`.
+    pub fn set_ignore_obstacles(&self, ids: Vec<u64>, ignore: bool) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .set_ignore_obstacles(&ids, ignore)
+    }
+
+    /// Routes every curved arrow again; with `prefer_minimal_change` arrows
+    /// keep their current detour while it stays clear.
+    pub fn reroute_arrows(&self, prefer_minimal_change: bool) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .reroute_arrows(prefer_minimal_change)
+    }
+
+    /// Flips arrow `id` end for end, attachments included.
+    pub fn reverse_arrow(&self, id: u64) -> bool {
+        self.store.write().expect("lock poisoned").reverse_arrow(id)
+    }
+
+    /// Continues stroke `a` with stroke `b` when their ends are within
+    /// `tolerance` (one undo step); see `Store::join_strokes`.
+    pub fn join_strokes(&self, a: u64, b: u64, tolerance: f32) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .join_strokes(a, b, tolerance)
+    }
+
+    /// Cuts stroke `id` in two at `at` (one undo step) and returns the
+    /// second half's id; `None` when the cut falls on an end.
+    pub fn split_stroke(&self, id: u64, at: FfiSplitAt) -> Option<u64> {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .split_stroke(id, at.into())
+    }
+
+    /// Drags one end of arrow `id` to `point` (one undo step), attaching it at
+    /// `attach` (from `attach_candidate`) or detaching it when `None`.
+    pub fn move_arrow_endpoint(
+        &self,
+        id: u64,
+        end: FfiArrowEnd,
+        point: FfiPoint,
+        attach: Option<FfiAttachCandidate>,
+    ) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .move_arrow_endpoint(
+                id,
+                end.into(),
+                point.into(),
+                attach.map(|c| (c.id, c.uv.into())),
+            )
+    }
+
+    /// Relabels numbered badges 1, 2, 3, ... in `order` (one undo step).
+    pub fn renumber_badges(&self, order: FfiBadgeOrder) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .renumber_badges(&order.into())
+    }
+
+    /// Scales the selection so its bounds fill `target` (one undo step).
+    pub fn resize(&self, ids: Vec<u64>, target: FfiRect) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .resize(&ids, target.into())
+    }
+
+    pub fn bring_to_front(&self, ids: Vec<u64>) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .bring_to_front(&ids)
+    }
+
+    pub fn send_to_back(&self, ids: Vec<u64>) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .send_to_back(&ids)
+    }
+
+    pub fn bring_forward(&self, ids: Vec<u64>) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .bring_forward(&ids)
+    }
+
+    pub fn send_backward(&self, ids: Vec<u64>) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .send_backward(&ids)
+    }
+
+    pub fn add_tag(&self, ids: Vec<u64>, tag: String) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .add_tag(&ids, &tag)
     }
 
-    pub fn undo(&self) -> bool {
-        self.store.lock().expect("mutex poisoned").undo().is_ok()
+    pub fn remove_tag(&self, ids: Vec<u64>, tag: String) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .remove_tag(&ids, &tag)
+    }
+
+    pub fn items_with_tag(&self, tag: String) -> Vec<FfiItem> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .items_with_tag(&tag)
+            .into_iter()
+            .cloned()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Sets (or with `None`, clears) the link on each of `ids`.
+    pub fn set_link(&self, ids: Vec<u64>, link: Option<String>) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .set_link(&ids, link.as_deref())
+    }
+
+    pub fn all_tags(&self) -> Vec<String> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .all_tags()
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Items of the document `t` of the way from this one (t = 0) to `other`
+    /// (t = 1), for animating between versions.
+    pub fn interpolate_to(&self, other: Arc<CoreDocument>, t: f32) -> Vec<FfiItem> {
+        // Snapshot each side separately so `other` may be `self`.
+        let a = self.store.read().expect("lock poisoned").document();
+        let b = other.store.read().expect("lock poisoned").document();
+        animate::interpolate(&a, &b, t)
+            .items
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// This document drawn over a ghosted, tinted `older` version.
+    pub fn onion_skin(
+        &self,
+        older: Arc<CoreDocument>,
+        options: FfiOnionSkinOptions,
+    ) -> FfiOnionSkin {
+        let old = older.store.read().expect("lock poisoned").document();
+        let new = self.store.read().expect("lock poisoned").document();
+        onion_skin(&old, &new, &options.into()).into()
+    }
+
+    /// Rasterizes only the tiles touching `dirty_rects`.
+    pub fn render_tiles(&self, tile_size: u32, dirty_rects: Vec<FfiRect>) -> Vec<FfiTile> {
+        let rects: Vec<Rect> = dirty_rects.into_iter().map(Into::into).collect();
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .render_tiles(tile_size, &rects)
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Like `render_tiles`, reporting progress per tile to `observer` and
+    /// failing with `Cancelled` if it asks to stop. Renders without holding
+    /// the document lock.
+    pub fn render_tiles_with_progress(
+        &self,
+        tile_size: u32,
+        dirty_rects: Vec<FfiRect>,
+        observer: Box<dyn FfiExportObserver>,
+    ) -> Result<Vec<FfiTile>, FfiError> {
+        let rects: Vec<Rect> = dirty_rects.into_iter().map(Into::into).collect();
+        let doc = self.store.read().expect("lock poisoned").document();
+        let tiles = raster::render_tiles_with_progress(&doc, tile_size, &rects, |done, total| {
+            observer.on_progress(done as u64, total as u64)
+        })
+        .ok_or(FfiError::Cancelled)?;
+        Ok(tiles.into_iter().map(Into::into).collect())
+    }
+
+    pub fn limits(&self) -> FfiDocumentLimits {
+        self.store.read().expect("lock poisoned").limits().into()
+    }
+
+    /// Caps document growth; edits past a cap fail instead of growing it.
+    pub fn set_limits(&self, limits: FfiDocumentLimits) {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .set_limits(limits.into());
+    }
+
+    /// Broken store invariants, empty when healthy; for debug-build asserts.
+    pub fn check_invariants(&self) -> Vec<FfiInvariantViolation> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .check_invariants()
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Estimated bytes held by items, history and caches.
+    pub fn memory_footprint(&self) -> FfiMemoryFootprint {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .memory_footprint()
+            .into()
+    }
+
+    /// Saves the current items as a named revision; fails if the name is taken.
+    pub fn save_version(&self, name: String) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .save_version(&name)
+    }
+
+    pub fn list_versions(&self) -> Vec<String> {
+        self.store
+            .read()
+            .expect("lock poisoned")
+            .list_versions()
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Restores a revision's items as an undoable edit.
+    pub fn checkout_version(&self, name: String) -> bool {
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .checkout_version(&name)
+    }
+
+    /// Searches shape text.
+    pub fn find_text(
+        &self,
+        query: String,
+        options: FfiFindOptions,
+    ) -> Result<Vec<FfiTextMatch>, FfiError> {
+        let store = self.store.read().expect("lock poisoned");
+        let matches = store.find_text(&query, options.into())?;
+        let text_of = |id: u64| {
+            store.items().iter().find_map(|item| match item {
+                Item::Shape(s) if s.id == id => Some(s.text.as_str()),
+                _ => None,
+            })
+        };
+        Ok(matches
+            .into_iter()
+            .map(|(id, ranges)| {
+                let text = text_of(id).unwrap_or_default();
+                FfiTextMatch {
+                    id,
+                    ranges: ranges
+                        .into_iter()
+                        .map(|r| FfiTextRange {
+                            start: utf16_offset(text, r.start),
+                            end: utf16_offset(text, r.end),
+                        })
+                        .collect(),
+                }
+            })
+            .collect())
+    }
+
+    /// Replaces matches as one undo step and returns how many were replaced.
+    pub fn replace_text(
+        &self,
+        query: String,
+        replacement: String,
+        options: FfiFindOptions,
+        scope: FfiReplaceScope,
+    ) -> Result<u64, FfiError> {
+        let count = self.store.write().expect("lock poisoned").replace_text(
+            &query,
+            &replacement,
+            options.into(),
+            &scope.into(),
+        )?;
+        Ok(count as u64)
     }
 
-    pub fn redo(&self) -> bool {
-        self.store.lock().expect("mutex poisoned").redo().is_ok()
+    /// Converts shapes and arrows into plain strokes (one undo step).
+    pub fn flatten(&self, ids: Vec<u64>) -> bool {
+        self.store.write().expect("lock poisoned").flatten(&ids)
     }
 
-    pub fn to_json(&self) -> String {
+    /// Applies the built-in dark/light transform to every color in the document.
+    pub fn invert_theme_colors(&self) -> bool {
         self.store
-            .lock()
-            .expect("mutex poisoned")
-            .to_json()
-            .unwrap_or_else(|_| serde_json::to_string(&Document::empty()).unwrap())
+            .write()
+            .expect("lock poisoned")
+            .remap_colors(&ThemeMap::InvertLightness)
+    }
+
+    pub fn to_json(&self) -> Result<String, FfiError> {
+        Ok(self.store.read().expect("lock poisoned").to_json()?)
     }
 
-    pub fn load_json(&self, json: String) -> bool {
-        match Store::from_json(&json) {
-            Ok(doc) => {
-                self.store
-                    .lock()
-                    .expect("mutex poisoned")
-                    .load_document(doc);
-                true
+    /// Serializes with quantized, delta-encoded stroke points; see
+    /// `Store::to_json_compact`. `load_json` reads both forms.
+    ///
+    /// There is no binary document format: this is the smallest encoding,
+    /// and what shells should pass for large documents.
+    pub fn to_json_compact(&self) -> Result<String, FfiError> {
+        Ok(self
+            .store
+            .read()
+            .expect("lock poisoned")
+            .to_json_compact()?)
+    }
+
+    /// Loads a document while reporting parsed items in batches, so the shell
+    /// can draw a large file before parsing finishes. The document replaces
+    /// the current one only once the whole input parsed successfully.
+    pub fn load_json_streaming(
+        &self,
+        json: String,
+        observer: Box<dyn FfiLoadObserver>,
+    ) -> Result<(), FfiError> {
+        let mut batch = Vec::with_capacity(STREAM_BATCH);
+        let mut last = None;
+        let parsed = Store::from_json_streaming(&json, |item, progress| {
+            batch.push(item.clone().into());
+            last = Some(progress);
+            if batch.len() >= STREAM_BATCH {
+                observer.on_items(std::mem::take(&mut batch));
+                observer.on_progress(
+                    progress.items_loaded as u64,
+                    progress.bytes_read as u64,
+                    progress.total_bytes as u64,
+                );
             }
-            Err(_) => false,
+        });
+        let doc = parsed.map_err(FfiError::parse)?;
+        if !batch.is_empty() {
+            observer.on_items(batch);
+        }
+        observer.on_progress(
+            last.map_or(0, |p| p.items_loaded as u64),
+            json.len() as u64,
+            json.len() as u64,
+        );
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .load_document(doc);
+        Ok(())
+    }
+
+    pub fn load_json(&self, json: String) -> Result<(), FfiError> {
+        let doc = Store::from_json(&json).map_err(FfiError::parse)?;
+        self.store
+            .write()
+            .expect("lock poisoned")
+            .load_document(doc);
+        Ok(())
+    }
+}
+
+/// The document's items as of one `CoreDocument::snapshot` call. Later edits
+/// to the document don't show up here.
+#[derive(uniffi::Object)]
+pub struct FfiSnapshot {
+    items: Arc<Vec<Item>>,
+}
+
+#[uniffi::export]
+impl FfiSnapshot {
+    pub fn items(&self) -> Vec<FfiItem> {
+        self.items.iter().cloned().map(Into::into).collect()
+    }
+
+    pub fn item_count(&self) -> u64 {
+        self.items.len() as u64
+    }
+
+    pub fn arrow_renders(&self) -> Vec<FfiArrowRender> {
+        overlay_scribe_core::render::render_arrows(&self.items)
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    pub fn arrow_renders_with(&self, config: FfiRoutingConfig) -> Vec<FfiArrowRender> {
+        overlay_scribe_core::render::render_arrows_with(&self.items, &config.into())
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+}
+
+/// Named template documents shared by every `CoreDocument`.
+#[derive(uniffi::Object, Default)]
+pub struct FfiTemplateLibrary {
+    library: RwLock<TemplateLibrary>,
+}
+
+#[uniffi::export]
+impl FfiTemplateLibrary {
+    #[uniffi::constructor]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds (or replaces) the template `name` from document JSON.
+    pub fn insert_json(&self, name: String, json: String) -> Result<(), FfiError> {
+        self.library
+            .write()
+            .expect("lock poisoned")
+            .insert_json(&name, &json)
+            .map_err(FfiError::parse)
+    }
+
+    pub fn remove(&self, name: String) -> bool {
+        self.library.write().expect("lock poisoned").remove(&name)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.library
+            .read()
+            .expect("lock poisoned")
+            .names()
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn black() -> FfiColorRgba8 {
+        FfiColorRgba8 {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
         }
     }
+
+    // Commits a two-point stroke from `(x, y)` and returns its id.
+    fn add_stroke(doc: &CoreDocument, x: f32, y: f32) -> u64 {
+        let mut stroke = doc.begin_stroke(black(), 2.0, FfiPoint { x, y });
+        stroke.points.push(FfiPoint {
+            x: x + 10.0,
+            y: y + 10.0,
+        });
+        let id = stroke.id;
+        doc.commit_stroke(stroke).unwrap();
+        id
+    }
+
+    #[test]
+    fn snapshot_keeps_the_items_it_was_taken_with() {
+        let doc = CoreDocument::new();
+        let id = add_stroke(&doc, 0.0, 0.0);
+        let snapshot = doc.snapshot();
+
+        assert!(doc.translate(vec![id], 5.0, 5.0));
+        add_stroke(&doc, 50.0, 50.0);
+        assert_eq!(snapshot.item_count(), 1);
+        let FfiItem::Stroke(s) = &snapshot.items()[0] else {
+            panic!("expected a stroke");
+        };
+        assert_eq!((s.points[0].x, s.points[0].y), (0.0, 0.0));
+        assert_eq!(doc.snapshot().item_count(), 2);
+    }
+
+    #[test]
+    fn compact_json_round_trips() {
+        let doc = CoreDocument::new();
+        add_stroke(&doc, 0.0, 0.0);
+        add_stroke(&doc, 20.0, 5.0);
+        let compact = doc.to_json_compact().unwrap();
+
+        let loaded = CoreDocument::new();
+        loaded.load_json(compact).unwrap();
+        assert_eq!(loaded.to_json().unwrap(), doc.to_json().unwrap());
+        assert!(matches!(
+            loaded.load_json("{".to_string()),
+            Err(FfiError::ParseError { .. })
+        ));
+    }
+
+    #[test]
+    fn load_region_returns_the_items_in_view_unchanged() {
+        let doc = CoreDocument::new();
+        let near = add_stroke(&doc, 0.0, 0.0);
+        add_stroke(&doc, 5_000.0, 5_000.0);
+        let view = FfiRect {
+            min_x: -10.0,
+            min_y: -10.0,
+            max_x: 100.0,
+            max_y: 100.0,
+        };
+
+        let region = doc.load_region(view);
+        assert_eq!(region.len(), 1);
+        let FfiItem::Stroke(s) = &region[0] else {
+            panic!("expected a stroke");
+        };
+        assert_eq!(s.id, near);
+        // Items cross the boundary losslessly: inserting them into an empty
+        // document gives the same JSON as deleting everything else.
+        let copy = CoreDocument::new();
+        assert_eq!(copy.insert_items(region), vec![near]);
+        let expected = CoreDocument::new();
+        expected.load_json(doc.to_json().unwrap()).unwrap();
+        assert!(expected.remove_items(vec![near + 1]));
+        assert_eq!(copy.to_json().unwrap(), expected.to_json().unwrap());
+    }
 }
@@ -0,0 +1,65 @@
+/* Drives the C ABI the way a C host would: commit items, route arrows,
+ * round-trip through JSON. Built and run by scripts/test-c-abi.sh. */
+
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "overlay_scribe.h"
+
+#define CHECK(cond)                                                   \
+  do {                                                                \
+    if (!(cond)) {                                                    \
+      fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, \
+              #cond);                                                 \
+      exit(1);                                                        \
+    }                                                                 \
+  } while (0)
+
+int main(void) {
+  const OscColor black = {0, 0, 0, 255};
+  const OscShapeStyle style = {black, 2.0f, false, black, false, 0.0f};
+  CoreDocument *doc = osc_document_new();
+
+  OscPoint line[] = {{0.0f, 0.0f}, {10.0f, 10.0f}};
+  uint64_t stroke_id = 0;
+  CHECK(osc_document_commit_stroke(doc, black, 2.0f, line, 2, &stroke_id) ==
+        OscStatus_Ok);
+  CHECK(osc_document_commit_stroke(doc, black, -1.0f, line, 2, NULL) ==
+        OscStatus_InvalidInput);
+
+  uint64_t arrow_id = 0;
+  OscPoint start = {0.0f, 100.0f};
+  OscPoint end = {200.0f, 100.0f};
+  CHECK(osc_document_commit_shape(doc, OscShapeKind_CurvedArrow, style, start,
+                                  end, &arrow_id) == OscStatus_Ok);
+  CHECK(osc_document_item_count(doc) == 2);
+
+  uintptr_t count = 0;
+  CHECK(osc_document_render_arrows(doc, NULL, 0, &count) ==
+        OscStatus_InvalidArgument);
+  CHECK(count == 1);
+  OscArrowRender render;
+  CHECK(osc_document_render_arrows(doc, &render, 1, &count) == OscStatus_Ok);
+  CHECK(render.shape_id == arrow_id);
+  CHECK(render.path.start.x == 0.0f && render.path.start.y == 100.0f);
+  CHECK(render.head_len == 3);
+  CHECK(render.head[0].x == render.path.end.x);
+
+  char *json = osc_document_to_json(doc);
+  CHECK(json != NULL);
+  CoreDocument *copy = osc_document_new();
+  CHECK(osc_document_load_json(copy, json) == OscStatus_Ok);
+  osc_string_free(json);
+  CHECK(osc_document_item_count(copy) == 2);
+  CHECK(osc_document_load_json(copy, "{") == OscStatus_ParseError);
+
+  CHECK(osc_document_remove_items(copy, &stroke_id, 1));
+  CHECK(osc_document_item_count(copy) == 1);
+  CHECK(osc_document_undo(copy) == OscStatus_Ok);
+  CHECK(osc_document_item_count(copy) == 2);
+
+  osc_document_free(copy);
+  osc_document_free(doc);
+  puts("C ABI round trip: ok");
+  return 0;
+}
@@ -0,0 +1,19 @@
+#!/usr/bin/env bash
+set -euo pipefail
+
+root_dir="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+crate_dir="$root_dir/core/overlay_scribe_ffi"
+
+if ! command -v cbindgen >/dev/null 2>&1; then
+  echo "cbindgen not found; install it with: cargo install cbindgen" >&2
+  exit 1
+fi
+
+echo "==> Generating C header via cbindgen"
+cbindgen \
+  --config "$crate_dir/cbindgen.toml" \
+  --crate overlay_scribe_ffi \
+  --output "$crate_dir/include/overlay_scribe.h" \
+  "$crate_dir"
+
+echo "==> Done. Header in: $crate_dir/include/overlay_scribe.h"
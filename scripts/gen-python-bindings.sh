@@ -0,0 +1,33 @@
+#!/usr/bin/env bash
+set -euo pipefail
+
+root_dir="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+cd "$root_dir"
+
+out_dir="$root_dir/bindings/python/overlay_scribe"
+
+echo "==> Building UniFFI library (release)"
+cargo build -p overlay_scribe_ffi --release
+
+case "$(uname -s)" in
+  Darwin) lib_name="liboverlay_scribe_ffi.dylib" ;;
+  MINGW* | MSYS* | CYGWIN*) lib_name="overlay_scribe_ffi.dll" ;;
+  *) lib_name="liboverlay_scribe_ffi.so" ;;
+esac
+lib_path="$root_dir/target/release/$lib_name"
+if [[ ! -f "$lib_path" ]]; then
+  echo "Expected library at: $lib_path" >&2
+  exit 1
+fi
+
+echo "==> Generating Python bindings via uniffi-bindgen"
+cargo run -p overlay_scribe_ffi --release --bin uniffi-bindgen -- \
+  generate \
+  --library "$lib_path" \
+  --language python \
+  --out-dir "$out_dir"
+
+echo "==> Copying library next to the bindings"
+cp -f "$lib_path" "$out_dir/"
+
+echo "==> Done. Python package in: $root_dir/bindings/python"
@@ -0,0 +1,21 @@
+#!/usr/bin/env bash
+set -euo pipefail
+
+root_dir="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+crate_dir="$root_dir/core/overlay_scribe_ffi"
+out_dir="$root_dir/target/c-abi"
+
+echo "==> Building overlay_scribe_ffi (staticlib)"
+cargo build -p overlay_scribe_ffi --manifest-path "$root_dir/Cargo.toml"
+
+echo "==> Compiling the C round-trip test"
+mkdir -p "$out_dir"
+cc -std=c99 -Wall -Wextra -Werror \
+  -I "$crate_dir/include" \
+  "$crate_dir/tests/c/roundtrip.c" \
+  "$root_dir/target/debug/liboverlay_scribe_ffi.a" \
+  -lpthread -ldl -lm \
+  -o "$out_dir/roundtrip"
+
+echo "==> Running"
+"$out_dir/roundtrip"